@@ -0,0 +1,538 @@
+//! Repository decorator that batches access-count writes
+
+use crate::domain::{OriginalUrl, ShortCode, ShortenedUrl};
+use crate::error::{Result, UrlShortenerError};
+use crate::ports::UrlRepository;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard};
+use std::time::SystemTime;
+
+/// Repository decorator that buffers access-count increments in memory
+///
+/// Resolving a short code rewrites the whole entry just to bump its access
+/// count, which is expensive for database backends. This decorator detects
+/// updates that only record accesses (a higher access count, per-day counts
+/// and a new last access time) and buffers them instead of writing them
+/// through; `increment_access_at` buffers its access directly, checking the
+/// access limit against the stored and buffered counts. Buffered accesses
+/// are written to the inner repository by `flush`, or automatically once
+/// `flush_threshold` increments are pending. Any other update is written
+/// through immediately.
+///
+/// Reads (`find_by_short_code`, `list_all`) merge buffered increments with
+/// the inner repository, so callers always see up-to-date counts and access
+/// times.
+///
+/// # Durability
+///
+/// Buffered increments live only in process memory: up to `flush_threshold`
+/// increments can be lost if the process exits without calling `flush`.
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::adapters::{BatchedCounterRepository, InMemoryUrlRepository};
+///
+/// let repo = BatchedCounterRepository::new(InMemoryUrlRepository::new());
+/// repo.flush().unwrap();
+/// ```
+pub struct BatchedCounterRepository<R: UrlRepository> {
+    inner: R,
+    flush_threshold: u64,
+    pending: Mutex<HashMap<String, PendingAccess>>,
+}
+
+/// Accesses recorded for one short code since the last flush
+#[derive(Debug, Clone, Default)]
+struct PendingAccess {
+    increments: u64,
+    last_accessed_at: Option<SystemTime>,
+    daily_increments: BTreeMap<i64, u64>,
+}
+
+impl PendingAccess {
+    /// Accesses recorded in `updated` on top of `current`
+    fn between(current: &ShortenedUrl, updated: &ShortenedUrl) -> Self {
+        let current_days = current.access_counts_by_day();
+        let daily_increments = updated.access_counts_by_day()
+            .iter()
+            .filter_map(|(day, &count)| {
+                let delta = count.saturating_sub(current_days.get(day).copied().unwrap_or(0));
+                (delta > 0).then_some((*day, delta))
+            })
+            .collect();
+
+        Self {
+            increments: updated.access_count().saturating_sub(current.access_count()),
+            last_accessed_at: updated.last_accessed_at(),
+            daily_increments,
+        }
+    }
+
+    fn add(&mut self, other: PendingAccess) {
+        self.increments = self.increments.saturating_add(other.increments);
+        self.last_accessed_at = self.last_accessed_at.max(other.last_accessed_at);
+        for (day, delta) in other.daily_increments {
+            let count = self.daily_increments.entry(day).or_insert(0);
+            *count = count.saturating_add(delta);
+        }
+    }
+
+    /// Apply these accesses on top of a stored entry
+    fn apply(&self, url: ShortenedUrl) -> ShortenedUrl {
+        let count = url.access_count().saturating_add(self.increments);
+
+        let mut days = url.access_counts_by_day().clone();
+        for (day, delta) in &self.daily_increments {
+            let count = days.entry(*day).or_insert(0);
+            *count = count.saturating_add(*delta);
+        }
+
+        let url = url.with_access_count(count).with_access_counts_by_day(days);
+
+        match self.last_accessed_at {
+            Some(at) if url.last_accessed_at() < Some(at) => url.with_last_accessed_at(at),
+            _ => url,
+        }
+    }
+}
+
+impl<R: UrlRepository> BatchedCounterRepository<R> {
+    /// Default number of pending increments that triggers a flush
+    pub const DEFAULT_FLUSH_THRESHOLD: u64 = 100;
+
+    /// Wrap a repository with the default flush threshold
+    pub fn new(inner: R) -> Self {
+        Self::with_flush_threshold(inner, Self::DEFAULT_FLUSH_THRESHOLD)
+    }
+
+    /// Wrap a repository, flushing once `threshold` increments are pending
+    pub fn with_flush_threshold(inner: R, threshold: u64) -> Self {
+        Self {
+            inner,
+            flush_threshold: threshold,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the wrapped repository
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Number of buffered increments not yet written to the inner repository
+    pub fn pending_increments(&self) -> Result<u64> {
+        Ok(self.lock_pending()?.values().map(|p| p.increments).sum())
+    }
+
+    /// Write all buffered increments to the inner repository
+    ///
+    /// Increments for codes deleted in the meantime are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the inner repository fails; increments that were
+    /// not written stay buffered.
+    pub fn flush(&self) -> Result<()> {
+        let drained: Vec<(String, PendingAccess)> = self.lock_pending()?.drain().collect();
+
+        let mut remaining = drained.into_iter();
+        while let Some((key, access)) = remaining.next() {
+            if let Err(e) = self.write_increment(&key, &access) {
+                let mut pending = self.lock_pending()?;
+                for (key, access) in std::iter::once((key, access)).chain(remaining) {
+                    pending.entry(key).or_default().add(access);
+                }
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_increment(&self, key: &str, access: &PendingAccess) -> Result<()> {
+        let code = ShortCode::new(key.to_string())?;
+        let stored = match self.inner.find_by_short_code(&code) {
+            Ok(stored) => stored,
+            Err(UrlShortenerError::ShortCodeNotFound(_)) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        self.inner.update(access.apply(stored))
+    }
+
+    /// Release the pending lock, flushing if enough increments are buffered
+    fn flush_if_due(&self, pending: MutexGuard<'_, HashMap<String, PendingAccess>>) -> Result<()> {
+        let total: u64 = pending.values().map(|p| p.increments).sum();
+        drop(pending);
+
+        if total >= self.flush_threshold {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn lock_pending(&self) -> Result<MutexGuard<'_, HashMap<String, PendingAccess>>> {
+        self.pending.lock().map_err(|e| UrlShortenerError::StorageUnavailable(
+            format!("Failed to acquire pending counter lock: {}", e)
+        ))
+    }
+
+    /// Apply buffered increments to an entry read from the inner repository
+    fn merge(&self, url: ShortenedUrl) -> Result<ShortenedUrl> {
+        let pending = self.lock_pending()?
+            .get(url.short_code().as_str())
+            .cloned();
+
+        Ok(match pending {
+            Some(access) => access.apply(url),
+            None => url,
+        })
+    }
+}
+
+impl<R: UrlRepository> UrlRepository for BatchedCounterRepository<R> {
+    fn save(&self, url: ShortenedUrl) -> Result<()> {
+        self.inner.save(url)
+    }
+
+    fn save_batch(&self, urls: Vec<ShortenedUrl>) -> Vec<Result<()>> {
+        self.inner.save_batch(urls)
+    }
+
+    fn find_by_short_code(&self, code: &ShortCode) -> Result<ShortenedUrl> {
+        let stored = self.inner.find_by_short_code(code)?;
+        self.merge(stored)
+    }
+
+    fn update(&self, url: ShortenedUrl) -> Result<()> {
+        let current = self.find_by_short_code(url.short_code())?;
+        let access = PendingAccess::between(&current, &url);
+        let only_accesses = access.increments > 0 && url == access.apply(current);
+
+        if !only_accesses {
+            // The entry carries the merged accesses, so buffered ones for it
+            // are written through as part of this update
+            self.inner.update(url.clone())?;
+            self.lock_pending()?.remove(url.short_code().as_str());
+            return Ok(());
+        }
+
+        let mut pending = self.lock_pending()?;
+        pending.entry(url.short_code().as_str().to_string()).or_default().add(access);
+        self.flush_if_due(pending)
+    }
+
+    fn increment_access_at(&self, code: &ShortCode, at: SystemTime) -> Result<u64> {
+        let stored = self.inner.find_by_short_code(code)?;
+
+        // The limit is checked against the stored and buffered accesses
+        // under the same lock the increment is buffered with
+        let mut pending = self.lock_pending()?;
+        let current = match pending.get(code.as_str()) {
+            Some(access) => access.apply(stored),
+            None => stored,
+        };
+        if current.is_access_limit_reached() {
+            return Err(UrlShortenerError::AccessLimitReached(code.as_str().to_string()));
+        }
+
+        let mut updated = current.clone();
+        updated.record_access_at(at);
+        let access = PendingAccess::between(&current, &updated);
+        pending.entry(code.as_str().to_string()).or_default().add(access);
+
+        self.flush_if_due(pending)?;
+        Ok(updated.access_count())
+    }
+
+    fn record_referrer(&self, code: &ShortCode, referrer: Option<String>) -> Result<()> {
+        // Referrers are written through; buffered increments are applied on
+        // top of them when flushed
+        self.inner.record_referrer(code, referrer)
+    }
+
+    fn exists(&self, code: &ShortCode) -> Result<bool> {
+        self.inner.exists(code)
+    }
+
+    fn delete(&self, code: &ShortCode) -> Result<()> {
+        self.inner.delete(code)?;
+        self.lock_pending()?.remove(code.as_str());
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<()> {
+        self.inner.clear()?;
+        self.lock_pending()?.clear();
+        Ok(())
+    }
+
+    fn list_all(&self) -> Result<Vec<ShortenedUrl>> {
+        self.inner.list_all()?
+            .into_iter()
+            .map(|url| self.merge(url))
+            .collect()
+    }
+
+    fn list_paginated(&self, offset: usize, limit: usize) -> Result<Vec<ShortenedUrl>> {
+        self.inner.list_paginated(offset, limit)?
+            .into_iter()
+            .map(|url| self.merge(url))
+            .collect()
+    }
+
+    fn count(&self) -> Result<usize> {
+        self.inner.count()
+    }
+
+    fn created_between(&self, start: SystemTime, end: SystemTime) -> Result<Vec<ShortenedUrl>> {
+        self.inner.created_between(start, end)?
+            .into_iter()
+            .map(|url| self.merge(url))
+            .collect()
+    }
+
+    fn find_by_original_url(&self, url: &OriginalUrl) -> Result<Option<ShortenedUrl>> {
+        self.inner.find_by_original_url(url)?
+            .map(|url| self.merge(url))
+            .transpose()
+    }
+
+    fn find_by_tag(&self, tag: &str) -> Result<Vec<ShortenedUrl>> {
+        self.inner.find_by_tag(tag)?
+            .into_iter()
+            .map(|url| self.merge(url))
+            .collect()
+    }
+
+    fn find_by_domain(&self, domain: &str) -> Result<Vec<ShortenedUrl>> {
+        self.inner.find_by_domain(domain)?
+            .into_iter()
+            .map(|url| self.merge(url))
+            .collect()
+    }
+
+    fn find_by_access_count_range(&self, min: u64, max: u64) -> Result<Vec<ShortenedUrl>> {
+        // Buffered increments change the counts, so filter the merged entries
+        // instead of asking the inner repository
+        let mut urls: Vec<ShortenedUrl> = self.list_all()?
+            .into_iter()
+            .filter(|entry| (min..=max).contains(&entry.access_count()))
+            .collect();
+        urls.sort_by(|a, b| a.short_code().cmp(b.short_code()));
+        Ok(urls)
+    }
+
+    fn swap_original_urls(&self, a: &ShortCode, b: &ShortCode) -> Result<()> {
+        // Counts stay with their codes, so buffered increments are unaffected
+        self.inner.swap_original_urls(a, b)
+    }
+
+    fn reserve(&self, code: &ShortCode, now: SystemTime, until: SystemTime) -> Result<()> {
+        self.inner.reserve(code, now, until)
+    }
+
+    fn is_reserved(&self, code: &ShortCode, now: SystemTime) -> Result<bool> {
+        self.inner.is_reserved(code, now)
+    }
+
+    fn release_reservation(&self, code: &ShortCode) -> Result<()> {
+        self.inner.release_reservation(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+    use crate::domain::{OriginalUrl, UrlId};
+    use crate::service::UrlShortenerService;
+    use std::sync::Arc;
+
+    fn create_test_url(code: &str) -> ShortenedUrl {
+        let id = UrlId::new(format!("id-{}", code));
+        let short_code = ShortCode::new(code.to_string()).unwrap();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        ShortenedUrl::new(id, short_code, url)
+    }
+
+    #[test]
+    fn test_increments_buffered_until_flush() {
+        let backend = InMemoryUrlRepository::new();
+        let repository = Arc::new(BatchedCounterRepository::new(backend.clone()));
+        let id_generator = Arc::new(RandomIdGenerator::new());
+        let service = UrlShortenerService::new(repository.clone(), id_generator);
+
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let shortened = service.shorten_url(url).unwrap();
+        let code = shortened.short_code();
+
+        for _ in 0..3 {
+            service.resolve_short_code(code).unwrap();
+        }
+
+        // Visible through the decorator, not yet written to the backend
+        assert_eq!(service.get_statistics(code).unwrap().access_count(), 3);
+        assert_eq!(backend.find_by_short_code(code).unwrap().access_count(), 0);
+        assert_eq!(repository.pending_increments().unwrap(), 3);
+
+        repository.flush().unwrap();
+
+        assert_eq!(backend.find_by_short_code(code).unwrap().access_count(), 3);
+        assert_eq!(service.get_statistics(code).unwrap().access_count(), 3);
+        assert_eq!(repository.pending_increments().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_last_access_time_buffered() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let backend = InMemoryUrlRepository::new();
+        let repo = BatchedCounterRepository::new(backend.clone());
+        repo.save(create_test_url("last1234")).unwrap();
+        let code = ShortCode::new("last1234".to_string()).unwrap();
+        let at = UNIX_EPOCH + Duration::from_secs(1_000);
+
+        let mut url = repo.find_by_short_code(&code).unwrap();
+        url.record_access_at(at);
+        repo.update(url).unwrap();
+
+        assert_eq!(repo.pending_increments().unwrap(), 1);
+        assert_eq!(repo.find_by_short_code(&code).unwrap().last_accessed_at(), Some(at));
+        assert_eq!(backend.find_by_short_code(&code).unwrap().last_accessed_at(), None);
+
+        repo.flush().unwrap();
+        assert_eq!(backend.find_by_short_code(&code).unwrap().last_accessed_at(), Some(at));
+    }
+
+    #[test]
+    fn test_daily_counts_buffered() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let backend = InMemoryUrlRepository::new();
+        let repo = BatchedCounterRepository::new(backend.clone());
+        repo.save(create_test_url("days1234")).unwrap();
+        let code = ShortCode::new("days1234".to_string()).unwrap();
+
+        for day in [5, 5, 6] {
+            let mut url = repo.find_by_short_code(&code).unwrap();
+            url.record_access_at(UNIX_EPOCH + Duration::from_secs(day * 86_400));
+            repo.update(url).unwrap();
+        }
+
+        let expected = BTreeMap::from([(5, 2), (6, 1)]);
+        assert_eq!(repo.pending_increments().unwrap(), 3);
+        assert_eq!(repo.find_by_short_code(&code).unwrap().access_counts_by_day(), &expected);
+        assert!(backend.find_by_short_code(&code).unwrap().access_counts_by_day().is_empty());
+
+        repo.flush().unwrap();
+        assert_eq!(backend.find_by_short_code(&code).unwrap().access_counts_by_day(), &expected);
+    }
+
+    #[test]
+    fn test_increment_access_checks_buffered_accesses() {
+        let backend = InMemoryUrlRepository::new();
+        let repo = Arc::new(BatchedCounterRepository::new(backend.clone()));
+        repo.save(create_test_url("limit123").with_max_accesses(30)).unwrap();
+        let code = ShortCode::new("limit123".to_string()).unwrap();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let repo = repo.clone();
+                let code = code.clone();
+                std::thread::spawn(move || {
+                    (0..10).filter(|_| repo.increment_access(&code).is_ok()).count()
+                })
+            })
+            .collect();
+        let granted: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+
+        assert_eq!(granted, 30);
+        assert_eq!(repo.pending_increments().unwrap(), 30);
+        assert_eq!(backend.find_by_short_code(&code).unwrap().access_count(), 0);
+        let result = repo.increment_access(&code);
+        assert!(matches!(result, Err(UrlShortenerError::AccessLimitReached(_))));
+
+        repo.flush().unwrap();
+        let stored = backend.find_by_short_code(&code).unwrap();
+        assert_eq!(stored.access_count(), 30);
+        assert_eq!(stored.access_counts_by_day().values().sum::<u64>(), 30);
+        assert!(stored.last_accessed_at().is_some());
+    }
+
+    #[test]
+    fn test_flush_threshold() {
+        let backend = InMemoryUrlRepository::new();
+        let repo = BatchedCounterRepository::with_flush_threshold(backend.clone(), 2);
+        repo.save(create_test_url("batch123")).unwrap();
+        let code = ShortCode::new("batch123".to_string()).unwrap();
+
+        let mut url = repo.find_by_short_code(&code).unwrap();
+        url.record_access();
+        repo.update(url.clone()).unwrap();
+        assert_eq!(backend.find_by_short_code(&code).unwrap().access_count(), 0);
+
+        url.record_access();
+        repo.update(url).unwrap();
+        assert_eq!(backend.find_by_short_code(&code).unwrap().access_count(), 2);
+    }
+
+    #[test]
+    fn test_other_updates_write_through() {
+        let backend = InMemoryUrlRepository::new();
+        let repo = BatchedCounterRepository::new(backend.clone());
+        repo.save(create_test_url("write123")).unwrap();
+        let code = ShortCode::new("write123".to_string()).unwrap();
+
+        let mut url = repo.find_by_short_code(&code).unwrap();
+        url.record_access();
+        repo.update(url).unwrap();
+
+        let mut url = repo.find_by_short_code(&code).unwrap();
+        url.retarget(OriginalUrl::new("https://other.com".to_string()).unwrap());
+        repo.update(url).unwrap();
+
+        let stored = backend.find_by_short_code(&code).unwrap();
+        assert_eq!(stored.original_url().as_str(), "https://other.com/");
+        assert_eq!(stored.access_count(), 1);
+        assert_eq!(repo.pending_increments().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_flush_drops_deleted_codes() {
+        let backend = InMemoryUrlRepository::new();
+        let repo = BatchedCounterRepository::new(backend.clone());
+        repo.save(create_test_url("gone1234")).unwrap();
+        let code = ShortCode::new("gone1234".to_string()).unwrap();
+
+        let mut url = repo.find_by_short_code(&code).unwrap();
+        url.record_access();
+        repo.update(url).unwrap();
+        backend.delete(&code).unwrap();
+
+        repo.flush().unwrap();
+        assert_eq!(repo.pending_increments().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_delete_expired_drops_pending_increments() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let backend = InMemoryUrlRepository::new();
+        let repo = BatchedCounterRepository::new(backend.clone());
+        let deadline = UNIX_EPOCH + Duration::from_secs(1_000);
+        repo.save(create_test_url("expired1").with_expires_at(deadline)).unwrap();
+        repo.save(create_test_url("forever1")).unwrap();
+
+        let code = ShortCode::new("expired1".to_string()).unwrap();
+        let mut url = repo.find_by_short_code(&code).unwrap();
+        url.record_access();
+        repo.update(url).unwrap();
+
+        assert_eq!(repo.delete_expired(deadline).unwrap(), 1);
+        assert_eq!(backend.len(), 1);
+        assert_eq!(repo.pending_increments().unwrap(), 0);
+    }
+}
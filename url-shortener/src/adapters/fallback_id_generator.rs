@@ -0,0 +1,157 @@
+//! Fallback ID generator combinator
+
+use crate::domain::{OriginalUrl, ShortCode, UrlId};
+use crate::error::Result;
+use crate::ports::IdGenerator;
+
+/// ID generator that falls back to a second generator when the first fails
+///
+/// Short codes come from `primary` unless it returns an error, in which case
+/// `fallback` is asked instead. URL ids always come from `primary`.
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::adapters::{FallbackIdGenerator, RandomIdGenerator, SequentialIdGenerator};
+/// use url_shortener::ports::IdGenerator;
+///
+/// // Sequential codes until the counter runs out, random ones afterwards
+/// let generator = FallbackIdGenerator::new(
+///     SequentialIdGenerator::with_start(u64::MAX),
+///     RandomIdGenerator::new(),
+/// );
+/// assert!(generator.generate_short_code().is_ok());
+/// ```
+pub struct FallbackIdGenerator<A, B> {
+    primary: A,
+    fallback: B,
+}
+
+impl<A, B> FallbackIdGenerator<A, B>
+where
+    A: IdGenerator,
+    B: IdGenerator,
+{
+    /// Create a generator preferring `primary` over `fallback`
+    pub fn new(primary: A, fallback: B) -> Self {
+        Self { primary, fallback }
+    }
+
+    /// Get the preferred generator
+    pub fn primary(&self) -> &A {
+        &self.primary
+    }
+
+    /// Get the generator used when `primary` fails
+    pub fn fallback(&self) -> &B {
+        &self.fallback
+    }
+}
+
+impl<A, B> IdGenerator for FallbackIdGenerator<A, B>
+where
+    A: IdGenerator,
+    B: IdGenerator,
+{
+    fn generate_id(&self) -> UrlId {
+        self.primary.generate_id()
+    }
+
+    fn generate_short_code(&self) -> Result<ShortCode> {
+        self.primary
+            .generate_short_code()
+            .or_else(|_| self.fallback.generate_short_code())
+    }
+
+    fn generate_short_code_for(&self, url: &OriginalUrl) -> Result<ShortCode> {
+        self.primary
+            .generate_short_code_for(url)
+            .or_else(|_| self.fallback.generate_short_code_for(url))
+    }
+
+    fn generate_short_code_with_length(&self, length: usize) -> Result<ShortCode> {
+        self.primary
+            .generate_short_code_with_length(length)
+            .or_else(|_| self.fallback.generate_short_code_with_length(length))
+    }
+
+    /// Combined keyspace of both generators
+    ///
+    /// Returns `None` unless both generators report their keyspace. The two
+    /// may overlap, so this is an upper bound.
+    fn keyspace(&self) -> Option<u64> {
+        Some(self.primary.keyspace()?.saturating_add(self.fallback.keyspace()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::{RandomIdGenerator, SequentialIdGenerator};
+    use crate::error::UrlShortenerError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Generator whose short codes always fail
+    struct ExhaustedGenerator;
+
+    impl IdGenerator for ExhaustedGenerator {
+        fn generate_id(&self) -> UrlId {
+            UrlId::new("exhausted".to_string())
+        }
+
+        fn generate_short_code(&self) -> Result<ShortCode> {
+            Err(UrlShortenerError::IdGenerationFailed(1))
+        }
+    }
+
+    /// Generator that counts how often it is asked for a short code
+    #[derive(Default)]
+    struct CountingGenerator {
+        calls: AtomicUsize,
+    }
+
+    impl IdGenerator for CountingGenerator {
+        fn generate_id(&self) -> UrlId {
+            UrlId::new("counting".to_string())
+        }
+
+        fn generate_short_code(&self) -> Result<ShortCode> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            ShortCode::new("fallback".to_string())
+        }
+    }
+
+    #[test]
+    fn test_falls_back_when_primary_fails() {
+        let generator = FallbackIdGenerator::new(ExhaustedGenerator, CountingGenerator::default());
+
+        assert_eq!(generator.generate_short_code().unwrap().as_str(), "fallback");
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        assert_eq!(generator.generate_short_code_for(&url).unwrap().as_str(), "fallback");
+        assert_eq!(generator.fallback().calls.load(Ordering::SeqCst), 2);
+
+        // Ids always come from the primary generator
+        assert_eq!(generator.generate_id().as_str(), "exhausted");
+    }
+
+    #[test]
+    fn test_primary_success_skips_fallback() {
+        let generator =
+            FallbackIdGenerator::new(SequentialIdGenerator::new(), CountingGenerator::default());
+
+        assert_eq!(generator.generate_short_code().unwrap().as_str(), "aaaa");
+        assert_eq!(generator.generate_short_code().unwrap().as_str(), "aaab");
+        assert_eq!(generator.fallback().calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_keyspace_needs_both_generators() {
+        let generator = FallbackIdGenerator::new(ExhaustedGenerator, RandomIdGenerator::new());
+        assert_eq!(generator.keyspace(), None);
+
+        let random = RandomIdGenerator::new();
+        let expected = random.keyspace().unwrap().saturating_mul(2);
+        let generator = FallbackIdGenerator::new(random, RandomIdGenerator::new());
+        assert_eq!(generator.keyspace(), Some(expected));
+    }
+}
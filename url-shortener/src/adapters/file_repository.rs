@@ -0,0 +1,499 @@
+//! JSON file implementation of UrlRepository
+//!
+//! Keeps entries in memory and mirrors them to a JSON file. Available with
+//! the `serde` feature.
+
+use crate::domain::{ShortCode, ShortenedUrl};
+use crate::error::{Result, UrlShortenerError};
+use crate::ports::UrlRepository;
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// URL repository persisted to a JSON file
+///
+/// The file holds a JSON object mapping short codes to entries. It is loaded
+/// once by `new`; reads are then served from an in-memory cache, and every
+/// mutating operation rewrites the whole file. Writes go to a temporary file
+/// that is renamed over the original, so a crash never leaves a truncated
+/// file behind. If writing fails, the in-memory change is rolled back.
+///
+/// Suited to small, single-process deployments: the file is not locked, so
+/// only one repository should use a given path at a time.
+///
+/// # Examples
+///
+/// ```no_run
+/// use url_shortener::adapters::FileUrlRepository;
+/// use url_shortener::ports::UrlRepository;
+///
+/// let repo = FileUrlRepository::new("urls.json").unwrap();
+/// println!("{} entries", repo.list_all().unwrap().len());
+/// ```
+pub struct FileUrlRepository {
+    path: PathBuf,
+    cache: RwLock<HashMap<String, ShortenedUrl>>,
+}
+
+impl FileUrlRepository {
+    /// Open a repository backed by the file at `path`
+    ///
+    /// A missing file is treated as an empty repository; it is created on
+    /// the first write.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::RepositoryError` if the file can't be read
+    /// or doesn't contain valid entries
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        let entries = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                UrlShortenerError::repository_with_source(
+                    format!("Corrupt repository file {}", path.display()),
+                    e,
+                )
+            })?,
+            Err(e) if e.kind() == ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                return Err(UrlShortenerError::repository_with_source(
+                    format!("Failed to read repository file {}", path.display()),
+                    e,
+                ));
+            }
+        };
+
+        Ok(Self {
+            path,
+            cache: RwLock::new(entries),
+        })
+    }
+
+    /// Get the path of the backing file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn read(&self) -> Result<RwLockReadGuard<'_, HashMap<String, ShortenedUrl>>> {
+        self.cache.read().map_err(|e| UrlShortenerError::StorageUnavailable(
+            format!("Failed to acquire read lock: {}", e)
+        ))
+    }
+
+    fn write(&self) -> Result<RwLockWriteGuard<'_, HashMap<String, ShortenedUrl>>> {
+        self.cache.write().map_err(|e| UrlShortenerError::StorageUnavailable(
+            format!("Failed to acquire write lock: {}", e)
+        ))
+    }
+
+    /// Write all entries to the backing file
+    fn persist(&self, entries: &HashMap<String, ShortenedUrl>) -> Result<()> {
+        let bytes = serde_json::to_vec(entries).map_err(|e| {
+            UrlShortenerError::repository_with_source("Failed to serialize entries", e)
+        })?;
+
+        let mut temp = self.path.clone().into_os_string();
+        temp.push(".tmp");
+
+        fs::write(&temp, bytes)
+            .and_then(|()| fs::rename(&temp, &self.path))
+            .map_err(|e| UrlShortenerError::repository_with_source(
+                format!("Failed to write repository file {}", self.path.display()),
+                e,
+            ))
+    }
+}
+
+impl UrlRepository for FileUrlRepository {
+    fn save(&self, url: ShortenedUrl) -> Result<()> {
+        let mut cache = self.write()?;
+
+        let key = url.short_code().as_str().to_string();
+
+        if cache.contains_key(&key) {
+            return Err(UrlShortenerError::ShortCodeAlreadyExists(key));
+        }
+
+        cache.insert(key.clone(), url);
+        self.persist(&cache).inspect_err(|_| {
+            cache.remove(&key);
+        })
+    }
+
+    fn find_by_short_code(&self, code: &ShortCode) -> Result<ShortenedUrl> {
+        self.read()?
+            .get(code.as_str())
+            .cloned()
+            .ok_or_else(|| UrlShortenerError::ShortCodeNotFound(code.as_str().to_string()))
+    }
+
+    fn update(&self, url: ShortenedUrl) -> Result<()> {
+        let mut cache = self.write()?;
+
+        let key = url.short_code().as_str().to_string();
+
+        let Some(previous) = cache.get(&key).cloned() else {
+            return Err(UrlShortenerError::ShortCodeNotFound(key));
+        };
+
+        cache.insert(key.clone(), url);
+        self.persist(&cache).inspect_err(|_| {
+            cache.insert(key, previous);
+        })
+    }
+
+    fn increment_access_at(&self, code: &ShortCode, at: SystemTime) -> Result<u64> {
+        // The write lock is held from the limit check until the file is written
+        let mut cache = self.write()?;
+
+        let key = code.as_str().to_string();
+
+        let Some(previous) = cache.get(&key).cloned() else {
+            return Err(UrlShortenerError::ShortCodeNotFound(key));
+        };
+        if previous.is_access_limit_reached() {
+            return Err(UrlShortenerError::AccessLimitReached(key));
+        }
+
+        let mut url = previous.clone();
+        url.record_access_at(at);
+        let count = url.access_count();
+
+        cache.insert(key.clone(), url);
+        self.persist(&cache).inspect_err(|_| {
+            cache.insert(key, previous);
+        })?;
+
+        Ok(count)
+    }
+
+    fn record_referrer(&self, code: &ShortCode, referrer: Option<String>) -> Result<()> {
+        // Like increment_access_at, so neither undoes the other
+        let mut cache = self.write()?;
+
+        let key = code.as_str().to_string();
+
+        let Some(previous) = cache.get(&key).cloned() else {
+            return Err(UrlShortenerError::ShortCodeNotFound(key));
+        };
+
+        let mut url = previous.clone();
+        url.record_referrer(referrer);
+
+        cache.insert(key.clone(), url);
+        self.persist(&cache).inspect_err(|_| {
+            cache.insert(key, previous);
+        })
+    }
+
+    fn exists(&self, code: &ShortCode) -> Result<bool> {
+        Ok(self.read()?.contains_key(code.as_str()))
+    }
+
+    fn delete(&self, code: &ShortCode) -> Result<()> {
+        let mut cache = self.write()?;
+
+        let key = code.as_str().to_string();
+
+        let Some(previous) = cache.remove(&key) else {
+            return Err(UrlShortenerError::ShortCodeNotFound(key));
+        };
+
+        self.persist(&cache).inspect_err(|_| {
+            cache.insert(key, previous);
+        })
+    }
+
+    fn clear(&self) -> Result<()> {
+        let mut cache = self.write()?;
+
+        let previous = std::mem::take(&mut *cache);
+        self.persist(&cache).inspect_err(|_| {
+            *cache = previous;
+        })
+    }
+
+    fn list_all(&self) -> Result<Vec<ShortenedUrl>> {
+        Ok(self.read()?.values().cloned().collect())
+    }
+
+    fn count(&self) -> Result<usize> {
+        Ok(self.read()?.len())
+    }
+
+    fn swap_original_urls(&self, a: &ShortCode, b: &ShortCode) -> Result<()> {
+        let mut cache = self.write()?;
+
+        let (Some(first), Some(second)) = (cache.get(a.as_str()), cache.get(b.as_str())) else {
+            let missing = if cache.contains_key(a.as_str()) { b } else { a };
+            return Err(UrlShortenerError::ShortCodeNotFound(missing.as_str().to_string()));
+        };
+        let (previous_first, previous_second) = (first.clone(), second.clone());
+
+        let mut first = previous_first.clone();
+        let mut second = previous_second.clone();
+        first.retarget(previous_second.original_url().clone());
+        second.retarget(previous_first.original_url().clone());
+
+        cache.insert(a.as_str().to_string(), first);
+        cache.insert(b.as_str().to_string(), second);
+
+        self.persist(&cache).inspect_err(|_| {
+            cache.insert(a.as_str().to_string(), previous_first);
+            cache.insert(b.as_str().to_string(), previous_second);
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{OriginalUrl, UrlId};
+
+    /// Temporary directory removed on drop
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir()
+                .join(format!("url_shortener_{}_{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn file(&self, name: &str) -> PathBuf {
+            self.0.join(name)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn create_test_url_to(code: &str, target: &str) -> ShortenedUrl {
+        let id = UrlId::new(format!("id-{}", code));
+        let short_code = ShortCode::new(code.to_string()).unwrap();
+        let url = OriginalUrl::new(target.to_string()).unwrap();
+        ShortenedUrl::new(id, short_code, url)
+    }
+
+    fn create_test_url(code: &str) -> ShortenedUrl {
+        create_test_url_to(code, "https://example.com")
+    }
+
+    #[test]
+    fn test_missing_file_is_empty() {
+        let dir = TempDir::new("file_missing");
+        let repo = FileUrlRepository::new(dir.file("urls.json")).unwrap();
+
+        assert!(repo.list_all().unwrap().is_empty());
+        assert!(!dir.file("urls.json").exists());
+    }
+
+    #[test]
+    fn test_data_survives_reopen() {
+        let dir = TempDir::new("file_reopen");
+        let path = dir.file("urls.json");
+        let code = ShortCode::new("keep1234".to_string()).unwrap();
+
+        {
+            let repo = FileUrlRepository::new(&path).unwrap();
+            repo.save(create_test_url("keep1234")).unwrap();
+            repo.save(create_test_url("gone1234")).unwrap();
+
+            let mut url = repo.find_by_short_code(&code).unwrap();
+            url.record_access();
+            repo.update(url).unwrap();
+
+            repo.delete(&ShortCode::new("gone1234".to_string()).unwrap()).unwrap();
+        }
+
+        let repo = FileUrlRepository::new(&path).unwrap();
+        assert_eq!(repo.list_all().unwrap().len(), 1);
+
+        let found = repo.find_by_short_code(&code).unwrap();
+        assert_eq!(found.id().as_str(), "id-keep1234");
+        assert_eq!(found.original_url().as_str(), "https://example.com/");
+        assert_eq!(found.access_count(), 1);
+    }
+
+    #[test]
+    fn test_urls_outside_default_limits_read_back() {
+        let dir = TempDir::new("file_wide");
+        let path = dir.file("urls.json");
+        let targets = [
+            OriginalUrl::new_with_schemes("mailto:team@example.com".to_string(), &["mailto"]),
+            OriginalUrl::new_with_schemes("ftp://example.com/file".to_string(), &["ftp"]),
+            OriginalUrl::new_with_max_len(
+                format!("https://example.com/{}", "a".repeat(3000)),
+                4096,
+            ),
+        ];
+
+        let mut saved = Vec::new();
+        {
+            let repo = FileUrlRepository::new(&path).unwrap();
+            for (i, target) in targets.into_iter().enumerate() {
+                let code = ShortCode::new(format!("wide{:04}", i)).unwrap();
+                let url = ShortenedUrl::new(UrlId::new(code.to_string()), code, target.unwrap());
+                repo.save(url.clone()).unwrap();
+                saved.push(url);
+            }
+        }
+
+        let repo = FileUrlRepository::new(&path).unwrap();
+        for url in saved {
+            let found = repo.find_by_short_code(url.short_code()).unwrap();
+            assert_eq!(found.original_url(), url.original_url());
+        }
+    }
+
+    #[test]
+    fn test_corrupt_file_is_repository_error() {
+        let dir = TempDir::new("file_corrupt");
+        let path = dir.file("urls.json");
+        fs::write(&path, "{ not json").unwrap();
+
+        let result = FileUrlRepository::new(&path);
+        assert!(matches!(result, Err(UrlShortenerError::RepositoryError { .. })));
+    }
+
+    #[test]
+    fn test_errors_match_in_memory_repository() {
+        let dir = TempDir::new("file_errors");
+        let repo = FileUrlRepository::new(dir.file("urls.json")).unwrap();
+        let missing = ShortCode::new("missing1".to_string()).unwrap();
+
+        repo.save(create_test_url("dupe1234")).unwrap();
+        let result = repo.save(create_test_url("dupe1234"));
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeAlreadyExists(_))));
+
+        let result = repo.update(create_test_url("missing1"));
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+        assert!(!repo.exists(&missing).unwrap());
+
+        let result = repo.delete(&missing);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+
+        let result = repo.increment_access(&missing);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+    }
+
+    #[test]
+    fn test_concurrent_increments_stay_within_limit() {
+        let dir = TempDir::new("file_increment");
+        let path = dir.file("urls.json");
+        let code = ShortCode::new("incr1234".to_string()).unwrap();
+
+        {
+            let repo = FileUrlRepository::new(&path).unwrap();
+            repo.save(create_test_url("incr1234").with_max_accesses(30)).unwrap();
+
+            let granted = std::thread::scope(|scope| {
+                let handles: Vec<_> = (0..4)
+                    .map(|_| scope.spawn(|| {
+                        (0..10).filter(|_| repo.increment_access(&code).is_ok()).count()
+                    }))
+                    .collect();
+                handles.into_iter().map(|h| h.join().unwrap()).sum::<usize>()
+            });
+            assert_eq!(granted, 30);
+
+            let result = repo.increment_access(&code);
+            assert!(matches!(result, Err(UrlShortenerError::AccessLimitReached(_))));
+        }
+
+        let repo = FileUrlRepository::new(&path).unwrap();
+        let found = repo.find_by_short_code(&code).unwrap();
+        assert_eq!(found.access_count(), 30);
+        assert_eq!(found.access_counts_by_day().values().sum::<u64>(), 30);
+    }
+
+    #[test]
+    fn test_referrers_keep_concurrent_increments() {
+        let dir = TempDir::new("file_referrer");
+        let path = dir.file("urls.json");
+        let code = ShortCode::new("refr1234".to_string()).unwrap();
+
+        {
+            let repo = FileUrlRepository::new(&path).unwrap();
+            repo.save(create_test_url("refr1234")).unwrap();
+
+            std::thread::scope(|scope| {
+                for _ in 0..4 {
+                    scope.spawn(|| {
+                        for _ in 0..50 {
+                            repo.increment_access(&code).unwrap();
+                        }
+                    });
+                    scope.spawn(|| {
+                        for _ in 0..50 {
+                            repo.record_referrer(&code, Some("newsletter".to_string())).unwrap();
+                        }
+                    });
+                }
+            });
+
+            let missing = ShortCode::new("missing1".to_string()).unwrap();
+            let result = repo.record_referrer(&missing, None);
+            assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+        }
+
+        let repo = FileUrlRepository::new(&path).unwrap();
+        let found = repo.find_by_short_code(&code).unwrap();
+        assert_eq!(found.access_count(), 200);
+        assert_eq!(found.referrer_counts().get("newsletter"), Some(&200));
+    }
+
+    #[test]
+    fn test_swap_original_urls_persists() {
+        let dir = TempDir::new("file_swap");
+        let path = dir.file("urls.json");
+        let a = ShortCode::new("swapaaaa".to_string()).unwrap();
+        let b = ShortCode::new("swapbbbb".to_string()).unwrap();
+
+        {
+            let repo = FileUrlRepository::new(&path).unwrap();
+            repo.save(create_test_url_to("swapaaaa", "https://a.example.com")).unwrap();
+            repo.save(create_test_url_to("swapbbbb", "https://b.example.com")).unwrap();
+            repo.swap_original_urls(&a, &b).unwrap();
+        }
+
+        let repo = FileUrlRepository::new(&path).unwrap();
+        let found_a = repo.find_by_short_code(&a).unwrap();
+        assert_eq!(found_a.original_url().as_str(), "https://b.example.com/");
+    }
+
+    #[test]
+    fn test_clear_persists() {
+        let dir = TempDir::new("file_clear");
+        let path = dir.file("urls.json");
+
+        {
+            let repo = FileUrlRepository::new(&path).unwrap();
+            repo.save(create_test_url("clear123")).unwrap();
+            repo.clear().unwrap();
+        }
+
+        let repo = FileUrlRepository::new(&path).unwrap();
+        assert_eq!(repo.count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_failed_write_rolls_back() {
+        let dir = TempDir::new("file_rollback");
+        // The parent directory doesn't exist, so every write fails
+        let repo = FileUrlRepository::new(dir.file("missing/urls.json")).unwrap();
+
+        let result = repo.save(create_test_url("fail1234"));
+        assert!(matches!(result, Err(UrlShortenerError::RepositoryError { .. })));
+        assert!(repo.list_all().unwrap().is_empty());
+    }
+}
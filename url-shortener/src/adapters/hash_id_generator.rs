@@ -0,0 +1,157 @@
+//! Hash-based ID generator implementation
+
+use super::RandomIdGenerator;
+use crate::domain::{OriginalUrl, ShortCode, UrlId};
+use crate::encoding::BASE62_CHARSET;
+use crate::error::Result;
+use crate::ports::IdGenerator;
+use sha2::{Digest, Sha256};
+
+/// Deterministic ID generator deriving short codes from the URL
+///
+/// `generate_short_code_for` hashes the URL with SHA-256 and base62-encodes
+/// the leading bytes of the digest, so the same URL always maps to the same
+/// code without a repository lookup. Codes use the same alphanumeric charset
+/// as `RandomIdGenerator`.
+///
+/// Since equal URLs get equal codes, shortening a URL that is already stored
+/// fails with a collision; pair this generator with
+/// `UrlShortenerService::shorten_url_idempotent`. `generate_short_code`,
+/// which has no URL to hash, and `generate_id` fall back to random values.
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::adapters::HashIdGenerator;
+/// use url_shortener::domain::OriginalUrl;
+/// use url_shortener::ports::IdGenerator;
+///
+/// let generator = HashIdGenerator::new();
+/// let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+///
+/// let first = generator.generate_short_code_for(&url).unwrap();
+/// let second = generator.generate_short_code_for(&url).unwrap();
+/// assert_eq!(first, second);
+/// ```
+pub struct HashIdGenerator {
+    short_code_length: usize,
+    fallback: RandomIdGenerator,
+}
+
+impl HashIdGenerator {
+    /// Default length for generated short codes
+    pub const DEFAULT_SHORT_CODE_LENGTH: usize = 8;
+
+    /// Create a hash ID generator with default settings
+    pub fn new() -> Self {
+        Self::with_length(Self::DEFAULT_SHORT_CODE_LENGTH)
+    }
+
+    /// Create a hash ID generator with a specific short code length
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length is outside the valid range for ShortCode
+    pub fn with_length(length: usize) -> Self {
+        Self {
+            short_code_length: length,
+            fallback: RandomIdGenerator::with_length(length),
+        }
+    }
+
+    /// Base62-encode the first 16 bytes of the URL's SHA-256 digest
+    fn hash_code(&self, url: &OriginalUrl) -> String {
+        let digest = Sha256::digest(url.as_str().as_bytes());
+        let mut leading = [0u8; 16];
+        leading.copy_from_slice(&digest[..16]);
+
+        // 62^12 < 2^128, so every digit carries hash entropy
+        let base = BASE62_CHARSET.len() as u128;
+        let mut value = u128::from_be_bytes(leading);
+        (0..self.short_code_length)
+            .map(|_| {
+                let digit = BASE62_CHARSET[(value % base) as usize] as char;
+                value /= base;
+                digit
+            })
+            .collect()
+    }
+}
+
+impl Default for HashIdGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdGenerator for HashIdGenerator {
+    fn generate_id(&self) -> UrlId {
+        self.fallback.generate_id()
+    }
+
+    fn generate_short_code(&self) -> Result<ShortCode> {
+        self.fallback.generate_short_code()
+    }
+
+    fn generate_short_code_for(&self, url: &OriginalUrl) -> Result<ShortCode> {
+        ShortCode::new(self.hash_code(url))
+    }
+
+    fn keyspace(&self) -> Option<u64> {
+        self.fallback.keyspace()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn url(s: &str) -> OriginalUrl {
+        OriginalUrl::new(s.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let target = url("https://example.com/some/path?q=1");
+
+        let first = HashIdGenerator::new().generate_short_code_for(&target).unwrap();
+        let second = HashIdGenerator::new().generate_short_code_for(&target).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(first.as_str().len(), HashIdGenerator::DEFAULT_SHORT_CODE_LENGTH);
+        assert!(first.as_str().chars().all(|c| c.is_ascii_alphanumeric()));
+    }
+
+    #[test]
+    fn test_length_truncates_same_hash() {
+        let target = url("https://example.com");
+        let short = HashIdGenerator::with_length(4).generate_short_code_for(&target).unwrap();
+        let long = HashIdGenerator::with_length(12).generate_short_code_for(&target).unwrap();
+
+        assert_eq!(short.as_str().len(), 4);
+        assert_eq!(long.as_str().len(), 12);
+        assert!(long.as_str().starts_with(short.as_str()));
+    }
+
+    #[test]
+    fn test_distinct_urls_rarely_collide() {
+        let generator = HashIdGenerator::new();
+        let codes: HashSet<ShortCode> = (0..10_000)
+            .map(|i| {
+                let target = url(&format!("https://example.com/page/{}", i));
+                generator.generate_short_code_for(&target).unwrap()
+            })
+            .collect();
+
+        // 62^8 possible codes make a collision among 10,000 URLs very unlikely
+        assert_eq!(codes.len(), 10_000);
+    }
+
+    #[test]
+    fn test_generate_short_code_without_url() {
+        let generator = HashIdGenerator::with_length(6);
+        let code = generator.generate_short_code().unwrap();
+        assert_eq!(code.as_str().len(), 6);
+    }
+}
@@ -0,0 +1,312 @@
+//! HTTP fallback implementation of AsyncUrlRepository
+//!
+//! Serves short codes missing from a primary repository from a legacy HTTP
+//! shortener. Available with the `http-fallback` feature.
+
+use crate::domain::{OriginalUrl, ShortCode, ShortenedUrl, UrlId};
+use crate::error::{Result, UrlShortenerError};
+use crate::ports::AsyncUrlRepository;
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Async repository decorator that falls back to a legacy HTTP shortener
+///
+/// Meant for gradual migrations: `find_by_short_code` looks in `primary`
+/// first and, on a miss, asks the legacy service with
+/// `GET {fallback_base_url}/{code}`. The legacy service answers 404 for
+/// unknown codes and otherwise a JSON object like
+///
+/// ```json
+/// {"original_url": "https://example.com", "created_at": 1700000000, "access_count": 42}
+/// ```
+///
+/// where `created_at` (Unix seconds) and `access_count` are optional. Entries
+/// built from it get the id `legacy-{code}`.
+///
+/// Writes, `exists` and `list_all` only use `primary`, so a code saved there
+/// shadows the legacy one. Accesses to codes served by the legacy service
+/// aren't recorded.
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::adapters::{HttpFallbackUrlRepository, InMemoryUrlRepository};
+///
+/// let repo = HttpFallbackUrlRepository::new(
+///     InMemoryUrlRepository::new(),
+///     "https://legacy.example.com/api/links",
+/// );
+/// assert_eq!(repo.fallback_base_url(), "https://legacy.example.com/api/links");
+/// ```
+pub struct HttpFallbackUrlRepository<R: AsyncUrlRepository> {
+    primary: R,
+    fallback_base_url: String,
+    timeout: Duration,
+    client: Client,
+}
+
+/// Response body of the legacy service
+#[derive(serde::Deserialize)]
+struct LegacyEntry {
+    original_url: String,
+    #[serde(default)]
+    created_at: Option<u64>,
+    #[serde(default)]
+    access_count: u64,
+}
+
+impl<R: AsyncUrlRepository> HttpFallbackUrlRepository<R> {
+    /// Default timeout for requests to the legacy service
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Wrap `primary`, falling back to the legacy service at `fallback_base_url`
+    ///
+    /// A trailing slash on the base URL is ignored.
+    pub fn new(primary: R, fallback_base_url: impl Into<String>) -> Self {
+        let fallback_base_url = fallback_base_url.into().trim_end_matches('/').to_string();
+
+        Self {
+            primary,
+            fallback_base_url,
+            timeout: Self::DEFAULT_TIMEOUT,
+            client: Client::new(),
+        }
+    }
+
+    /// Set the timeout for requests to the legacy service
+    ///
+    /// Defaults to `DEFAULT_TIMEOUT`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Get the primary repository
+    pub fn primary(&self) -> &R {
+        &self.primary
+    }
+
+    /// Get the base URL of the legacy service
+    pub fn fallback_base_url(&self) -> &str {
+        &self.fallback_base_url
+    }
+
+    /// Look up a short code in the legacy service
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::ShortCodeNotFound` if the legacy service
+    /// answers 404, or `UrlShortenerError::RepositoryError` if the request
+    /// fails, another error status comes back or the response can't be read
+    async fn fetch_legacy(&self, code: &ShortCode) -> Result<ShortenedUrl> {
+        let url = format!("{}/{}", self.fallback_base_url, code);
+        let failed = |e: reqwest::Error| {
+            UrlShortenerError::repository_with_source(format!("Legacy lookup of {} failed", url), e)
+        };
+
+        let response = self.client
+            .get(&url)
+            .timeout(self.timeout)
+            .send()
+            .await
+            .map_err(failed)?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(UrlShortenerError::ShortCodeNotFound(code.as_str().to_string()));
+        }
+        let body = response
+            .error_for_status()
+            .map_err(failed)?
+            .text()
+            .await
+            .map_err(failed)?;
+
+        let entry: LegacyEntry = serde_json::from_str(&body).map_err(|e| {
+            UrlShortenerError::repository_with_source(format!("Corrupt legacy entry at {}", url), e)
+        })?;
+        let original_url = OriginalUrl::new(entry.original_url)?;
+        let created_at = entry.created_at
+            .map_or_else(SystemTime::now, |secs| UNIX_EPOCH + Duration::from_secs(secs));
+
+        Ok(ShortenedUrl::with_created_at(
+            UrlId::new(format!("legacy-{}", code)),
+            code.clone(),
+            original_url,
+            created_at,
+        )
+        .with_access_count(entry.access_count))
+    }
+}
+
+#[async_trait]
+impl<R: AsyncUrlRepository> AsyncUrlRepository for HttpFallbackUrlRepository<R> {
+    async fn save(&self, url: ShortenedUrl) -> Result<()> {
+        self.primary.save(url).await
+    }
+
+    async fn find_by_short_code(&self, code: &ShortCode) -> Result<ShortenedUrl> {
+        match self.primary.find_by_short_code(code).await {
+            Err(UrlShortenerError::ShortCodeNotFound(_)) => self.fetch_legacy(code).await,
+            result => result,
+        }
+    }
+
+    async fn update(&self, url: ShortenedUrl) -> Result<()> {
+        self.primary.update(url).await
+    }
+
+    async fn increment_access_at(&self, code: &ShortCode, at: SystemTime) -> Result<u64> {
+        match self.primary.increment_access_at(code, at).await {
+            // Legacy codes resolve, but their accesses aren't recorded
+            Err(UrlShortenerError::ShortCodeNotFound(_)) => {
+                Ok(self.fetch_legacy(code).await?.access_count())
+            }
+            result => result,
+        }
+    }
+
+    async fn exists(&self, code: &ShortCode) -> Result<bool> {
+        self.primary.exists(code).await
+    }
+
+    async fn delete(&self, code: &ShortCode) -> Result<()> {
+        self.primary.delete(code).await
+    }
+
+    async fn list_all(&self) -> Result<Vec<ShortenedUrl>> {
+        self.primary.list_all().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+    use crate::service::AsyncUrlShortenerService;
+    use std::future::Future;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::sync::mpsc::{self, Receiver};
+    use std::thread;
+
+    /// Answer `responses.len()` requests on a local port, one per connection
+    ///
+    /// Returns the base URL and a receiver for the request lines received.
+    fn legacy_server(responses: Vec<(&'static str, &'static str)>) -> (String, Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (requests, received) = mpsc::channel();
+
+        thread::spawn(move || {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0; 4096];
+                let read = stream.read(&mut buf).unwrap();
+                let request = String::from_utf8_lossy(&buf[..read]);
+                let _ = requests.send(request.lines().next().unwrap_or_default().to_string());
+
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (format!("http://127.0.0.1:{port}/links/"), received)
+    }
+
+    fn run(test: impl Future<Output = ()>) {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(test);
+    }
+
+    fn code(code: &str) -> ShortCode {
+        ShortCode::new(code.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_legacy_hit() {
+        let body = r#"{"original_url": "https://legacy.example.com/page", "created_at": 1000,
+                       "access_count": 7}"#;
+        let (base_url, requests) = legacy_server(vec![("200 OK", body)]);
+        let repo = HttpFallbackUrlRepository::new(InMemoryUrlRepository::new(), base_url);
+
+        run(async {
+            let found = repo.find_by_short_code(&code("old123")).await.unwrap();
+            assert_eq!(found.original_url().as_str(), "https://legacy.example.com/page");
+            assert_eq!(found.id().as_str(), "legacy-old123");
+            assert_eq!(found.created_at(), UNIX_EPOCH + Duration::from_secs(1000));
+            assert_eq!(found.access_count(), 7);
+        });
+
+        assert_eq!(requests.recv().unwrap(), "GET /links/old123 HTTP/1.1");
+    }
+
+    #[test]
+    fn test_legacy_miss() {
+        let (base_url, _requests) = legacy_server(vec![("404 Not Found", "")]);
+        let repo = HttpFallbackUrlRepository::new(InMemoryUrlRepository::new(), base_url);
+
+        run(async {
+            let result = repo.find_by_short_code(&code("gone123")).await;
+            assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+        });
+    }
+
+    #[test]
+    fn test_legacy_errors_are_repository_errors() {
+        let (base_url, _requests) =
+            legacy_server(vec![("500 Internal Server Error", ""), ("200 OK", "not json")]);
+        let repo = HttpFallbackUrlRepository::new(InMemoryUrlRepository::new(), base_url);
+
+        run(async {
+            for _ in 0..2 {
+                let result = repo.find_by_short_code(&code("fail123")).await;
+                assert!(matches!(result, Err(UrlShortenerError::RepositoryError { .. })));
+            }
+        });
+    }
+
+    #[test]
+    fn test_primary_is_used_first_and_for_writes() {
+        // Nothing listens here, so any legacy request would fail
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let repo = HttpFallbackUrlRepository::new(InMemoryUrlRepository::new(), base_url);
+
+        run(async {
+            let url = OriginalUrl::new("https://new.example.com".to_string()).unwrap();
+            let entry = ShortenedUrl::new(UrlId::new("new".to_string()), code("new123"), url);
+            repo.save(entry).await.unwrap();
+
+            let found = repo.find_by_short_code(&code("new123")).await.unwrap();
+            assert_eq!(found.original_url().as_str(), "https://new.example.com/");
+            let count = repo.increment_access_at(&code("new123"), SystemTime::now()).await;
+            assert_eq!(count.unwrap(), 1);
+            assert!(AsyncUrlRepository::exists(repo.primary(), &code("new123")).await.unwrap());
+            assert_eq!(repo.list_all().await.unwrap().len(), 1);
+        });
+    }
+
+    #[test]
+    fn test_service_resolves_legacy_codes() {
+        let body = r#"{"original_url": "https://legacy.example.com/"}"#;
+        let (base_url, _requests) = legacy_server(vec![("200 OK", body), ("200 OK", body)]);
+        let repository =
+            Arc::new(HttpFallbackUrlRepository::new(InMemoryUrlRepository::new(), base_url));
+        let service = AsyncUrlShortenerService::new(repository, Arc::new(RandomIdGenerator::new()));
+
+        run(async {
+            let resolved = service.resolve_short_code(&code("old123")).await.unwrap();
+            assert_eq!(resolved.as_str(), "https://legacy.example.com/");
+        });
+    }
+}
@@ -2,16 +2,18 @@
 //!
 //! Uses a HashMap for storage with interior mutability pattern
 
-use crate::domain::{ShortCode, ShortenedUrl};
+use crate::domain::{OriginalUrl, ShortCode, ShortenedUrl};
 use crate::error::{Result, UrlShortenerError};
 use crate::ports::UrlRepository;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
 
 /// In-memory URL repository using a HashMap
 ///
 /// This adapter implements the UrlRepository port using an in-memory HashMap.
-/// Uses RwLock for thread-safe concurrent access.
+/// Uses RwLock for thread-safe concurrent access. A secondary index on the
+/// original URL makes `find_by_original_url` a constant-time lookup.
 ///
 /// # Examples
 ///
@@ -30,27 +32,92 @@ use std::sync::{Arc, RwLock};
 /// repo.save(shortened).unwrap();
 /// let found = repo.find_by_short_code(&code).unwrap();
 /// ```
+///
+/// # Cloning
+///
+/// The derived `Clone` is shallow: clones share the same underlying storage,
+/// so a write through one is visible through all of them. Use
+/// [`InMemoryUrlRepository::deep_clone`] for an independent copy.
 #[derive(Clone)]
 pub struct InMemoryUrlRepository {
-    storage: Arc<RwLock<HashMap<String, ShortenedUrl>>>,
+    storage: Arc<RwLock<Storage>>,
+}
+
+/// Entries keyed by short code, plus the original URL index and the
+/// reserved codes with the time their reservation ends
+#[derive(Clone, Default)]
+struct Storage {
+    entries: HashMap<String, ShortenedUrl>,
+    codes_by_original_url: HashMap<String, BTreeSet<String>>,
+    reservations: HashMap<String, SystemTime>,
+}
+
+impl Storage {
+    /// Insert or replace an entry, keeping the index in sync
+    fn insert(&mut self, url: ShortenedUrl) {
+        let key = url.short_code().as_str().to_string();
+        self.unindex(&key);
+
+        self.codes_by_original_url
+            .entry(url.original_url().as_str().to_string())
+            .or_default()
+            .insert(key.clone());
+        self.entries.insert(key, url);
+    }
+
+    /// Remove an entry, keeping the index in sync
+    fn remove(&mut self, key: &str) -> Option<ShortenedUrl> {
+        self.unindex(key);
+        self.entries.remove(key)
+    }
+
+    fn unindex(&mut self, key: &str) {
+        let Some(existing) = self.entries.get(key) else {
+            return;
+        };
+        let target = existing.original_url().as_str();
+
+        if let Some(codes) = self.codes_by_original_url.get_mut(target) {
+            codes.remove(key);
+            if codes.is_empty() {
+                self.codes_by_original_url.remove(target);
+            }
+        }
+    }
 }
 
 impl InMemoryUrlRepository {
     /// Create a new empty in-memory repository
     pub fn new() -> Self {
         Self {
-            storage: Arc::new(RwLock::new(HashMap::new())),
+            storage: Arc::new(RwLock::new(Storage::default())),
+        }
+    }
+
+    /// Create an independent copy of this repository
+    ///
+    /// Unlike `clone`, the copy gets its own storage populated with clones of
+    /// the current entries, so later writes to either repository don't affect
+    /// the other. Useful for snapshots and rollback in tests.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the storage lock is poisoned
+    pub fn deep_clone(&self) -> Self {
+        let storage = self.storage.read().unwrap().clone();
+        Self {
+            storage: Arc::new(RwLock::new(storage)),
         }
     }
 
     /// Get the number of stored URLs (useful for testing)
     pub fn len(&self) -> usize {
-        self.storage.read().unwrap().len()
+        self.storage.read().unwrap().entries.len()
     }
 
     /// Check if the repository is empty
     pub fn is_empty(&self) -> bool {
-        self.storage.read().unwrap().is_empty()
+        self.storage.read().unwrap().entries.is_empty()
     }
 }
 
@@ -63,65 +130,120 @@ impl Default for InMemoryUrlRepository {
 impl UrlRepository for InMemoryUrlRepository {
     fn save(&self, url: ShortenedUrl) -> Result<()> {
         let mut storage = self.storage.write()
-            .map_err(|e| UrlShortenerError::RepositoryError(
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
                 format!("Failed to acquire write lock: {}", e)
             ))?;
 
         let key = url.short_code().as_str().to_string();
 
-        if storage.contains_key(&key) {
+        if storage.entries.contains_key(&key) {
             return Err(UrlShortenerError::ShortCodeAlreadyExists(key));
         }
 
-        storage.insert(key, url);
+        storage.insert(url);
         Ok(())
     }
 
+    fn save_batch(&self, urls: Vec<ShortenedUrl>) -> Vec<Result<()>> {
+        let mut storage = match self.storage.write() {
+            Ok(storage) => storage,
+            Err(e) => {
+                let message = format!("Failed to acquire write lock: {}", e);
+                return urls.iter()
+                    .map(|_| Err(UrlShortenerError::StorageUnavailable(message.clone())))
+                    .collect();
+            }
+        };
+
+        urls.into_iter()
+            .map(|url| {
+                let key = url.short_code().as_str().to_string();
+
+                if storage.entries.contains_key(&key) {
+                    return Err(UrlShortenerError::ShortCodeAlreadyExists(key));
+                }
+
+                storage.insert(url);
+                Ok(())
+            })
+            .collect()
+    }
+
     fn find_by_short_code(&self, code: &ShortCode) -> Result<ShortenedUrl> {
         let storage = self.storage.read()
-            .map_err(|e| UrlShortenerError::RepositoryError(
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
                 format!("Failed to acquire read lock: {}", e)
             ))?;
 
-        storage.get(code.as_str())
+        storage.entries.get(code.as_str())
             .cloned()
             .ok_or_else(|| UrlShortenerError::ShortCodeNotFound(code.as_str().to_string()))
     }
 
     fn update(&self, url: ShortenedUrl) -> Result<()> {
         let mut storage = self.storage.write()
-            .map_err(|e| UrlShortenerError::RepositoryError(
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
                 format!("Failed to acquire write lock: {}", e)
             ))?;
 
         let key = url.short_code().as_str().to_string();
 
-        if !storage.contains_key(&key) {
+        if !storage.entries.contains_key(&key) {
             return Err(UrlShortenerError::ShortCodeNotFound(key));
         }
 
-        storage.insert(key, url);
+        storage.insert(url);
+        Ok(())
+    }
+
+    fn increment_access_at(&self, code: &ShortCode, at: SystemTime) -> Result<u64> {
+        let mut storage = self.storage.write()
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
+                format!("Failed to acquire write lock: {}", e)
+            ))?;
+
+        // Mutated in place; accesses don't touch the original URL index
+        let url = storage.entries.get_mut(code.as_str())
+            .ok_or_else(|| UrlShortenerError::ShortCodeNotFound(code.as_str().to_string()))?;
+        if url.is_access_limit_reached() {
+            return Err(UrlShortenerError::AccessLimitReached(code.as_str().to_string()));
+        }
+        url.record_access_at(at);
+
+        Ok(url.access_count())
+    }
+
+    fn record_referrer(&self, code: &ShortCode, referrer: Option<String>) -> Result<()> {
+        let mut storage = self.storage.write()
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
+                format!("Failed to acquire write lock: {}", e)
+            ))?;
+
+        storage.entries.get_mut(code.as_str())
+            .ok_or_else(|| UrlShortenerError::ShortCodeNotFound(code.as_str().to_string()))?
+            .record_referrer(referrer);
+
         Ok(())
     }
 
     fn exists(&self, code: &ShortCode) -> Result<bool> {
         let storage = self.storage.read()
-            .map_err(|e| UrlShortenerError::RepositoryError(
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
                 format!("Failed to acquire read lock: {}", e)
             ))?;
 
-        Ok(storage.contains_key(code.as_str()))
+        Ok(storage.entries.contains_key(code.as_str()))
     }
 
     fn delete(&self, code: &ShortCode) -> Result<()> {
         let mut storage = self.storage.write()
-            .map_err(|e| UrlShortenerError::RepositoryError(
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
                 format!("Failed to acquire write lock: {}", e)
             ))?;
 
         let key = code.as_str();
 
-        if !storage.contains_key(key) {
+        if !storage.entries.contains_key(key) {
             return Err(UrlShortenerError::ShortCodeNotFound(key.to_string()));
         }
 
@@ -129,13 +251,266 @@ impl UrlRepository for InMemoryUrlRepository {
         Ok(())
     }
 
+    fn delete_many(&self, codes: &[ShortCode]) -> Vec<Result<()>> {
+        let mut storage = match self.storage.write() {
+            Ok(storage) => storage,
+            Err(e) => {
+                let message = format!("Failed to acquire write lock: {}", e);
+                return codes.iter()
+                    .map(|_| Err(UrlShortenerError::StorageUnavailable(message.clone())))
+                    .collect();
+            }
+        };
+
+        codes.iter()
+            .map(|code| {
+                let key = code.as_str();
+
+                if !storage.entries.contains_key(key) {
+                    return Err(UrlShortenerError::ShortCodeNotFound(key.to_string()));
+                }
+
+                storage.remove(key);
+                Ok(())
+            })
+            .collect()
+    }
+
     fn list_all(&self) -> Result<Vec<ShortenedUrl>> {
         let storage = self.storage.read()
-            .map_err(|e| UrlShortenerError::RepositoryError(
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
+                format!("Failed to acquire read lock: {}", e)
+            ))?;
+
+        Ok(storage.entries.values().cloned().collect())
+    }
+
+    fn list_paginated(&self, offset: usize, limit: usize) -> Result<Vec<ShortenedUrl>> {
+        let storage = self.storage.read()
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
+                format!("Failed to acquire read lock: {}", e)
+            ))?;
+
+        // Sort references so only the requested page is cloned
+        let mut urls: Vec<&ShortenedUrl> = storage.entries.values().collect();
+        urls.sort_by(|a, b| {
+            (a.created_at(), a.short_code()).cmp(&(b.created_at(), b.short_code()))
+        });
+
+        Ok(urls.into_iter().skip(offset).take(limit).cloned().collect())
+    }
+
+    fn clear(&self) -> Result<()> {
+        let mut storage = self.storage.write()
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
+                format!("Failed to acquire write lock: {}", e)
+            ))?;
+
+        *storage = Storage::default();
+        Ok(())
+    }
+
+    fn delete_expired(&self, now: SystemTime) -> Result<usize> {
+        let mut storage = self.storage.write()
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
+                format!("Failed to acquire write lock: {}", e)
+            ))?;
+
+        let expired: Vec<String> = storage.entries.iter()
+            .filter(|(_, url)| url.is_expired_at(now))
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in &expired {
+            storage.remove(key);
+        }
+
+        Ok(expired.len())
+    }
+
+    fn count(&self) -> Result<usize> {
+        let storage = self.storage.read()
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
                 format!("Failed to acquire read lock: {}", e)
             ))?;
 
-        Ok(storage.values().cloned().collect())
+        Ok(storage.entries.len())
+    }
+
+    fn created_between(&self, start: SystemTime, end: SystemTime) -> Result<Vec<ShortenedUrl>> {
+        let storage = self.storage.read()
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
+                format!("Failed to acquire read lock: {}", e)
+            ))?;
+
+        let mut urls: Vec<&ShortenedUrl> = storage.entries
+            .values()
+            .filter(|entry| (start..end).contains(&entry.created_at()))
+            .collect();
+        urls.sort_by(|a, b| {
+            (a.created_at(), a.short_code()).cmp(&(b.created_at(), b.short_code()))
+        });
+
+        Ok(urls.into_iter().cloned().collect())
+    }
+
+    fn find_by_original_url(&self, url: &OriginalUrl) -> Result<Option<ShortenedUrl>> {
+        let storage = self.storage.read()
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
+                format!("Failed to acquire read lock: {}", e)
+            ))?;
+
+        let code = storage.codes_by_original_url
+            .get(url.as_str())
+            .and_then(|codes| codes.first());
+
+        Ok(code.and_then(|code| storage.entries.get(code)).cloned())
+    }
+
+    fn find_by_tag(&self, tag: &str) -> Result<Vec<ShortenedUrl>> {
+        let storage = self.storage.read()
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
+                format!("Failed to acquire read lock: {}", e)
+            ))?;
+
+        let mut urls: Vec<&ShortenedUrl> = storage.entries
+            .values()
+            .filter(|entry| entry.has_tag(tag))
+            .collect();
+        urls.sort_by(|a, b| a.short_code().cmp(b.short_code()));
+
+        Ok(urls.into_iter().cloned().collect())
+    }
+
+    fn find_by_domain(&self, domain: &str) -> Result<Vec<ShortenedUrl>> {
+        let storage = self.storage.read()
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
+                format!("Failed to acquire read lock: {}", e)
+            ))?;
+
+        let mut urls: Vec<&ShortenedUrl> = storage.entries
+            .values()
+            .filter(|entry| entry.original_url().has_domain(domain))
+            .collect();
+        urls.sort_by(|a, b| a.short_code().cmp(b.short_code()));
+
+        Ok(urls.into_iter().cloned().collect())
+    }
+
+    fn find_by_access_count_range(&self, min: u64, max: u64) -> Result<Vec<ShortenedUrl>> {
+        let storage = self.storage.read()
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
+                format!("Failed to acquire read lock: {}", e)
+            ))?;
+
+        let mut urls: Vec<&ShortenedUrl> = storage.entries
+            .values()
+            .filter(|entry| (min..=max).contains(&entry.access_count()))
+            .collect();
+        urls.sort_by(|a, b| a.short_code().cmp(b.short_code()));
+
+        Ok(urls.into_iter().cloned().collect())
+    }
+
+    fn swap_original_urls(&self, a: &ShortCode, b: &ShortCode) -> Result<()> {
+        let mut storage = self.storage.write()
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
+                format!("Failed to acquire write lock: {}", e)
+            ))?;
+
+        // Check both codes before touching either entry
+        for code in [a, b] {
+            if !storage.entries.contains_key(code.as_str()) {
+                return Err(UrlShortenerError::ShortCodeNotFound(code.as_str().to_string()));
+            }
+        }
+
+        let mut first = storage.entries[a.as_str()].clone();
+        let mut second = storage.entries[b.as_str()].clone();
+
+        let first_target = first.original_url().clone();
+        first.retarget(second.original_url().clone());
+        second.retarget(first_target);
+
+        storage.insert(first);
+        storage.insert(second);
+
+        Ok(())
+    }
+
+    fn reserve(&self, code: &ShortCode, now: SystemTime, until: SystemTime) -> Result<()> {
+        let mut storage = self.storage.write()
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
+                format!("Failed to acquire write lock: {}", e)
+            ))?;
+
+        // Forget lapsed reservations so abandoned ones don't pile up
+        storage.reservations.retain(|_, held_until| *held_until > now);
+
+        let key = code.as_str();
+        if storage.entries.contains_key(key) || storage.reservations.contains_key(key) {
+            return Err(UrlShortenerError::ShortCodeAlreadyExists(key.to_string()));
+        }
+
+        storage.reservations.insert(key.to_string(), until);
+        Ok(())
+    }
+
+    fn is_reserved(&self, code: &ShortCode, now: SystemTime) -> Result<bool> {
+        let storage = self.storage.read()
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
+                format!("Failed to acquire read lock: {}", e)
+            ))?;
+
+        Ok(storage.reservations
+            .get(code.as_str())
+            .is_some_and(|held_until| *held_until > now))
+    }
+
+    fn release_reservation(&self, code: &ShortCode) -> Result<()> {
+        let mut storage = self.storage.write()
+            .map_err(|e| UrlShortenerError::StorageUnavailable(
+                format!("Failed to acquire write lock: {}", e)
+            ))?;
+
+        storage.reservations.remove(code.as_str());
+        Ok(())
+    }
+}
+
+/// Async access for use from async services
+///
+/// Operations only take an in-process lock, so they complete without
+/// yielding.
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl crate::ports::AsyncUrlRepository for InMemoryUrlRepository {
+    async fn save(&self, url: ShortenedUrl) -> Result<()> {
+        UrlRepository::save(self, url)
+    }
+
+    async fn find_by_short_code(&self, code: &ShortCode) -> Result<ShortenedUrl> {
+        UrlRepository::find_by_short_code(self, code)
+    }
+
+    async fn update(&self, url: ShortenedUrl) -> Result<()> {
+        UrlRepository::update(self, url)
+    }
+
+    async fn increment_access_at(&self, code: &ShortCode, at: SystemTime) -> Result<u64> {
+        UrlRepository::increment_access_at(self, code, at)
+    }
+
+    async fn exists(&self, code: &ShortCode) -> Result<bool> {
+        UrlRepository::exists(self, code)
+    }
+
+    async fn delete(&self, code: &ShortCode) -> Result<()> {
+        UrlRepository::delete(self, code)
+    }
+
+    async fn list_all(&self) -> Result<Vec<ShortenedUrl>> {
+        UrlRepository::list_all(self)
     }
 }
 
@@ -143,6 +518,7 @@ impl UrlRepository for InMemoryUrlRepository {
 mod tests {
     use super::*;
     use crate::domain::{OriginalUrl, UrlId};
+    use std::time::{Duration, UNIX_EPOCH};
 
     fn create_test_url(code: &str) -> ShortenedUrl {
         let id = UrlId::new(format!("id-{}", code));
@@ -151,6 +527,13 @@ mod tests {
         ShortenedUrl::new(id, short_code, url)
     }
 
+    fn create_test_url_to(code: &str, target: &str) -> ShortenedUrl {
+        let id = UrlId::new(format!("id-{}", code));
+        let short_code = ShortCode::new(code.to_string()).unwrap();
+        let url = OriginalUrl::new(target.to_string()).unwrap();
+        ShortenedUrl::new(id, short_code, url)
+    }
+
     #[test]
     fn test_save_and_find() {
         let repo = InMemoryUrlRepository::new();
@@ -202,6 +585,27 @@ mod tests {
         assert_eq!(found.access_count(), 1);
     }
 
+    #[test]
+    fn test_record_referrer() {
+        let repo = InMemoryUrlRepository::new();
+        let url = create_test_url("refr1234");
+        let code = url.short_code().clone();
+        repo.save(url).unwrap();
+
+        repo.increment_access(&code).unwrap();
+        repo.record_referrer(&code, Some("newsletter".to_string())).unwrap();
+        repo.record_referrer(&code, None).unwrap();
+
+        let found = repo.find_by_short_code(&code).unwrap();
+        assert_eq!(found.access_count(), 1);
+        assert_eq!(found.referrer_counts().get("newsletter"), Some(&1));
+        assert_eq!(found.referrer_counts().get(ShortenedUrl::DIRECT_REFERRER), Some(&1));
+
+        let missing = ShortCode::new("missing1".to_string()).unwrap();
+        let result = repo.record_referrer(&missing, None);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+    }
+
     #[test]
     fn test_exists() {
         let repo = InMemoryUrlRepository::new();
@@ -241,4 +645,374 @@ mod tests {
         let urls = repo.list_all().unwrap();
         assert_eq!(urls.len(), 2);
     }
+
+    #[test]
+    fn test_count() {
+        let repo = InMemoryUrlRepository::new();
+        assert_eq!(repo.count().unwrap(), 0);
+
+        repo.save(create_test_url("count123")).unwrap();
+        repo.save(create_test_url("count456")).unwrap();
+        assert_eq!(repo.count().unwrap(), 2);
+
+        repo.delete(&ShortCode::new("count123".to_string()).unwrap()).unwrap();
+        assert_eq!(repo.count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_increment_access() {
+        let repo = InMemoryUrlRepository::new();
+        let url = create_test_url("incr1234");
+        let code = url.short_code().clone();
+        repo.save(url).unwrap();
+
+        let at = UNIX_EPOCH + Duration::from_secs(86_400 * 3);
+        assert_eq!(repo.increment_access_at(&code, at).unwrap(), 1);
+        assert_eq!(repo.increment_access(&code).unwrap(), 2);
+
+        let found = repo.find_by_short_code(&code).unwrap();
+        assert_eq!(found.access_count(), 2);
+        assert_eq!(found.access_counts_by_day().get(&3), Some(&1));
+
+        let missing = ShortCode::new("missing1".to_string()).unwrap();
+        let result = repo.increment_access(&missing);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+    }
+
+    #[test]
+    fn test_increment_access_from_many_threads() {
+        let repo = InMemoryUrlRepository::new();
+        let url = create_test_url("threads1");
+        let code = url.short_code().clone();
+        repo.save(url).unwrap();
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for _ in 0..250 {
+                        repo.increment_access(&code).unwrap();
+                    }
+                });
+            }
+        });
+
+        assert_eq!(repo.find_by_short_code(&code).unwrap().access_count(), 2000);
+    }
+
+    #[test]
+    fn test_created_between() {
+        let repo = InMemoryUrlRepository::new();
+        let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+        let entries = [("range030", 30), ("range010", 10), ("range020", 20), ("range021", 20)];
+        for (code, secs) in entries {
+            let url = create_test_url(code);
+            repo.save(ShortenedUrl::with_created_at(
+                url.id().clone(),
+                url.short_code().clone(),
+                url.original_url().clone(),
+                at(secs),
+            )).unwrap();
+        }
+
+        let codes = |start, end| -> Vec<String> {
+            repo.created_between(at(start), at(end)).unwrap()
+                .iter()
+                .map(|url| url.short_code().as_str().to_string())
+                .collect()
+        };
+
+        // Inclusive start, exclusive end
+        assert_eq!(codes(10, 30), ["range010", "range020", "range021"]);
+        assert_eq!(codes(20, 31), ["range020", "range021", "range030"]);
+        assert!(codes(11, 20).is_empty());
+        assert!(codes(30, 30).is_empty());
+    }
+
+    #[test]
+    fn test_find_by_tag() {
+        let repo = InMemoryUrlRepository::new();
+        let tagged = |code: &str, tags: &[&str]| {
+            create_test_url(code)
+                .with_tags(tags.iter().map(|tag| tag.to_string()).collect())
+                .unwrap()
+        };
+        repo.save(tagged("tagged02", &["newsletter", "twitter"])).unwrap();
+        repo.save(tagged("tagged01", &["newsletter"])).unwrap();
+        repo.save(tagged("tagged03", &["twitter"])).unwrap();
+        repo.save(create_test_url("untagged")).unwrap();
+
+        let codes = |tag: &str| -> Vec<String> {
+            repo.find_by_tag(tag).unwrap()
+                .iter()
+                .map(|url| url.short_code().as_str().to_string())
+                .collect()
+        };
+        assert_eq!(codes("newsletter"), ["tagged01", "tagged02"]);
+        assert_eq!(codes("twitter"), ["tagged02", "tagged03"]);
+        assert!(codes("print").is_empty());
+        assert!(codes("Twitter").is_empty());
+
+        let found = repo.find_by_tag("twitter").unwrap();
+        assert_eq!(found[0].tags(), ["newsletter", "twitter"]);
+    }
+
+    #[test]
+    fn test_find_by_domain() {
+        let repo = InMemoryUrlRepository::new();
+        repo.save(create_test_url_to("flagged2", "https://Bad.example/login")).unwrap();
+        repo.save(create_test_url_to("flagged1", "http://bad.example:8080/")).unwrap();
+        repo.save(create_test_url_to("subdomain", "https://www.bad.example/")).unwrap();
+        repo.save(create_test_url_to("lookalike", "https://notbad.example/")).unwrap();
+        repo.save(create_test_url_to("innocent", "https://good.example/bad.example")).unwrap();
+
+        let codes = |domain: &str| -> Vec<String> {
+            repo.find_by_domain(domain).unwrap()
+                .iter()
+                .map(|url| url.short_code().as_str().to_string())
+                .collect()
+        };
+        assert_eq!(codes("bad.example"), ["flagged1", "flagged2"]);
+        assert_eq!(codes("BAD.Example"), ["flagged1", "flagged2"]);
+        assert_eq!(codes("www.bad.example"), ["subdomain"]);
+        assert!(codes("example").is_empty());
+        assert!(codes("unknown.example").is_empty());
+    }
+
+    #[test]
+    fn test_find_by_access_count_range() {
+        let repo = InMemoryUrlRepository::new();
+        for (code, accesses) in [("count3", 3), ("count0b", 0), ("count0a", 0), ("count7", 7)] {
+            repo.save(create_test_url(code).with_access_count(accesses)).unwrap();
+        }
+
+        let codes = |min: u64, max: u64| -> Vec<String> {
+            repo.find_by_access_count_range(min, max).unwrap()
+                .iter()
+                .map(|url| url.short_code().as_str().to_string())
+                .collect()
+        };
+        assert_eq!(codes(0, 0), ["count0a", "count0b"]);
+        assert_eq!(codes(1, 7), ["count3", "count7"]);
+        assert_eq!(codes(3, 6), ["count3"]);
+        assert_eq!(codes(0, u64::MAX).len(), 4);
+        assert!(codes(8, 100).is_empty());
+        assert!(codes(7, 3).is_empty());
+    }
+
+    #[test]
+    fn test_clear() {
+        let repo = InMemoryUrlRepository::new();
+        repo.save(create_test_url("clear123")).unwrap();
+        repo.save(create_test_url("clear456")).unwrap();
+
+        repo.clear().unwrap();
+        assert_eq!(repo.count().unwrap(), 0);
+
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        assert!(repo.find_by_original_url(&url).unwrap().is_none());
+
+        // The repository stays usable
+        repo.save(create_test_url("clear123")).unwrap();
+        assert_eq!(repo.count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_reservations() {
+        let repo = InMemoryUrlRepository::new();
+        let at = |secs: u64| UNIX_EPOCH + Duration::from_secs(secs);
+        let code = ShortCode::new("held1234".to_string()).unwrap();
+
+        repo.reserve(&code, at(100), at(160)).unwrap();
+        assert!(repo.is_reserved(&code, at(159)).unwrap());
+        assert!(!repo.is_reserved(&code, at(160)).unwrap());
+        assert!(!repo.exists(&code).unwrap());
+
+        // Held codes and stored codes can't be reserved
+        let result = repo.reserve(&code, at(120), at(200));
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeAlreadyExists(_))));
+        repo.save(create_test_url("stored12")).unwrap();
+        let stored = ShortCode::new("stored12".to_string()).unwrap();
+        let result = repo.reserve(&stored, at(120), at(200));
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeAlreadyExists(_))));
+
+        // A lapsed reservation can be taken over
+        repo.reserve(&code, at(160), at(220)).unwrap();
+        assert!(repo.is_reserved(&code, at(200)).unwrap());
+
+        repo.release_reservation(&code).unwrap();
+        assert!(!repo.is_reserved(&code, at(200)).unwrap());
+    }
+
+    #[test]
+    fn test_swap_original_urls() {
+        let repo = InMemoryUrlRepository::new();
+        repo.save(create_test_url_to("swapaaaa", "https://a.example.com")).unwrap();
+        repo.save(create_test_url_to("swapbbbb", "https://b.example.com")).unwrap();
+        let a = ShortCode::new("swapaaaa".to_string()).unwrap();
+        let b = ShortCode::new("swapbbbb".to_string()).unwrap();
+
+        repo.swap_original_urls(&a, &b).unwrap();
+
+        let found_a = repo.find_by_short_code(&a).unwrap();
+        let found_b = repo.find_by_short_code(&b).unwrap();
+        assert_eq!(found_a.original_url().as_str(), "https://b.example.com/");
+        assert_eq!(found_b.original_url().as_str(), "https://a.example.com/");
+        assert_eq!(found_a.id().as_str(), "id-swapaaaa");
+        assert_eq!(found_b.id().as_str(), "id-swapbbbb");
+    }
+
+    #[test]
+    fn test_swap_original_urls_missing_code_leaves_entries_untouched() {
+        let repo = InMemoryUrlRepository::new();
+        repo.save(create_test_url_to("swapaaaa", "https://a.example.com")).unwrap();
+        let a = ShortCode::new("swapaaaa".to_string()).unwrap();
+        let missing = ShortCode::new("missing1".to_string()).unwrap();
+
+        let result = repo.swap_original_urls(&a, &missing);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+
+        let found_a = repo.find_by_short_code(&a).unwrap();
+        assert_eq!(found_a.original_url().as_str(), "https://a.example.com/");
+    }
+
+    #[test]
+    fn test_save_batch() {
+        let repo = InMemoryUrlRepository::new();
+        repo.save(create_test_url("taken123")).unwrap();
+
+        let results = repo.save_batch(vec![
+            create_test_url("batch123"),
+            create_test_url("taken123"),
+            create_test_url("batch456"),
+        ]);
+
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(UrlShortenerError::ShortCodeAlreadyExists(_))));
+        assert!(results[2].is_ok());
+        assert_eq!(repo.len(), 3);
+    }
+
+    #[test]
+    fn test_delete_many() {
+        let repo = InMemoryUrlRepository::new();
+        repo.save(create_test_url_to("dela1234", "https://a.example.com")).unwrap();
+        repo.save(create_test_url("delb1234")).unwrap();
+        repo.save(create_test_url("keep1234")).unwrap();
+
+        let codes: Vec<_> = ["dela1234", "missing1", "delb1234"]
+            .iter()
+            .map(|code| ShortCode::new(code.to_string()).unwrap())
+            .collect();
+        let results = repo.delete_many(&codes);
+
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(UrlShortenerError::ShortCodeNotFound(_))));
+        assert!(results[2].is_ok());
+        assert_eq!(repo.len(), 1);
+
+        let a = OriginalUrl::new("https://a.example.com".to_string()).unwrap();
+        assert!(repo.find_by_original_url(&a).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_find_by_original_url_tracks_changes() {
+        let repo = InMemoryUrlRepository::new();
+        let a = OriginalUrl::new("https://a.example.com".to_string()).unwrap();
+        let b = OriginalUrl::new("https://b.example.com".to_string()).unwrap();
+        assert!(repo.find_by_original_url(&a).unwrap().is_none());
+
+        repo.save(create_test_url_to("idxa1234", "https://a.example.com")).unwrap();
+        repo.save(create_test_url_to("idxb1234", "https://b.example.com")).unwrap();
+        let found = repo.find_by_original_url(&a).unwrap().unwrap();
+        assert_eq!(found.short_code().as_str(), "idxa1234");
+
+        // Retargeting moves the entry to its new URL
+        let mut moved = found;
+        moved.retarget(b.clone());
+        repo.update(moved).unwrap();
+        assert!(repo.find_by_original_url(&a).unwrap().is_none());
+
+        let code = ShortCode::new("idxb1234".to_string()).unwrap();
+        repo.delete(&code).unwrap();
+        let found = repo.find_by_original_url(&b).unwrap().unwrap();
+        assert_eq!(found.short_code().as_str(), "idxa1234");
+
+        repo.save(create_test_url_to("idxc1234", "https://a.example.com")).unwrap();
+        let c = ShortCode::new("idxc1234".to_string()).unwrap();
+        let a_code = ShortCode::new("idxa1234".to_string()).unwrap();
+        repo.swap_original_urls(&a_code, &c).unwrap();
+        let found = repo.find_by_original_url(&a).unwrap().unwrap();
+        assert_eq!(found.short_code().as_str(), "idxa1234");
+        let found = repo.find_by_original_url(&b).unwrap().unwrap();
+        assert_eq!(found.short_code().as_str(), "idxc1234");
+    }
+
+    #[test]
+    fn test_poisoned_lock_is_storage_unavailable() {
+        let repo = InMemoryUrlRepository::new();
+        let shared = repo.clone();
+
+        let handle = std::thread::spawn(move || {
+            let _guard = shared.storage.write().unwrap();
+            panic!("poison the storage lock");
+        });
+        assert!(handle.join().is_err());
+
+        let code = ShortCode::new("pois1234".to_string()).unwrap();
+        let result = repo.find_by_short_code(&code);
+        assert!(matches!(result, Err(UrlShortenerError::StorageUnavailable(_))));
+
+        let result = repo.save(create_test_url("pois1234"));
+        assert!(matches!(result, Err(UrlShortenerError::StorageUnavailable(_))));
+    }
+
+    #[test]
+    fn test_deep_clone_is_independent() {
+        let repo = InMemoryUrlRepository::new();
+        repo.save(create_test_url("orig1234")).unwrap();
+        let code = ShortCode::new("orig1234".to_string()).unwrap();
+
+        let copy = repo.deep_clone();
+        assert_eq!(copy.len(), 1);
+
+        let mut url = copy.find_by_short_code(&code).unwrap();
+        url.record_access();
+        copy.update(url).unwrap();
+        copy.save(create_test_url("copy1234")).unwrap();
+
+        assert_eq!(repo.len(), 1);
+        assert_eq!(repo.find_by_short_code(&code).unwrap().access_count(), 0);
+        assert_eq!(copy.len(), 2);
+        assert_eq!(copy.find_by_short_code(&code).unwrap().access_count(), 1);
+    }
+
+    #[test]
+    fn test_clone_shares_storage() {
+        let repo = InMemoryUrlRepository::new();
+        let shallow = repo.clone();
+
+        shallow.save(create_test_url("shar1234")).unwrap();
+        assert_eq!(repo.len(), 1);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_async_repository() {
+        use crate::ports::AsyncUrlRepository;
+        use futures::executor::block_on;
+
+        let repo = InMemoryUrlRepository::new();
+        let code = ShortCode::new("asyn1234".to_string()).unwrap();
+
+        block_on(AsyncUrlRepository::save(&repo, create_test_url("asyn1234"))).unwrap();
+        assert!(block_on(AsyncUrlRepository::exists(&repo, &code)).unwrap());
+        assert_eq!(block_on(AsyncUrlRepository::list_all(&repo)).unwrap().len(), 1);
+
+        // Both views share the same storage
+        assert!(UrlRepository::exists(&repo, &code).unwrap());
+
+        block_on(AsyncUrlRepository::delete(&repo, &code)).unwrap();
+        let result = block_on(AsyncUrlRepository::find_by_short_code(&repo, &code));
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+    }
 }
@@ -0,0 +1,11 @@
+//! Metrics implementations
+
+use crate::ports::Metrics;
+
+/// Metrics sink that ignores every event
+///
+/// The default for `UrlShortenerService`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopMetrics;
+
+impl Metrics for NoopMetrics {}
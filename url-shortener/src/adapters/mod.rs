@@ -4,7 +4,53 @@
 //! Following hexagonal architecture, these are our adapters.
 
 mod in_memory_repository;
+mod batched_counter_repository;
+mod fallback_id_generator;
+#[cfg(feature = "serde")]
+mod file_repository;
+#[cfg(feature = "hash")]
+mod hash_id_generator;
+#[cfg(feature = "http-fallback")]
+mod http_fallback_repository;
+mod nanoid_id_generator;
+mod pattern_id_generator;
 mod random_id_generator;
+mod retrying_repository;
+mod sequential_id_generator;
+mod timestamp_id_generator;
+mod clock;
+mod metrics;
+#[cfg(feature = "sqlite")]
+mod sqlite_repository;
+#[cfg(feature = "postgres")]
+mod postgres_repository;
+#[cfg(feature = "redis")]
+mod redis_repository;
+#[cfg(feature = "uuid")]
+mod uuid_id_generator;
 
 pub use in_memory_repository::InMemoryUrlRepository;
+pub use batched_counter_repository::BatchedCounterRepository;
+pub use fallback_id_generator::FallbackIdGenerator;
+#[cfg(feature = "serde")]
+pub use file_repository::FileUrlRepository;
+#[cfg(feature = "hash")]
+pub use hash_id_generator::HashIdGenerator;
+#[cfg(feature = "http-fallback")]
+pub use http_fallback_repository::HttpFallbackUrlRepository;
+pub use nanoid_id_generator::NanoidIdGenerator;
+pub use pattern_id_generator::PatternIdGenerator;
 pub use random_id_generator::RandomIdGenerator;
+pub use retrying_repository::RetryingRepository;
+pub use sequential_id_generator::SequentialIdGenerator;
+pub use timestamp_id_generator::TimestampIdGenerator;
+pub use clock::{ManualClock, SystemClock};
+pub use metrics::NoopMetrics;
+#[cfg(feature = "sqlite")]
+pub use sqlite_repository::SqliteUrlRepository;
+#[cfg(feature = "postgres")]
+pub use postgres_repository::PostgresUrlRepository;
+#[cfg(feature = "redis")]
+pub use redis_repository::RedisUrlRepository;
+#[cfg(feature = "uuid")]
+pub use uuid_id_generator::UuidIdGenerator;
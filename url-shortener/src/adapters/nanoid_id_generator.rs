@@ -0,0 +1,190 @@
+//! Nano ID style generator with a configurable alphabet
+
+use crate::domain::{ShortCode, UrlId};
+use crate::error::{Result, UrlShortenerError};
+use crate::ports::IdGenerator;
+use rand::Rng;
+
+/// Random ID generator drawing from a custom alphabet
+///
+/// Each character of a generated code is drawn uniformly from the alphabet.
+/// The default alphabet leaves out look-alike characters such as `0`/`O` and
+/// `1`/`l`/`I`, so codes can be read aloud or typed from print.
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::adapters::NanoidIdGenerator;
+/// use url_shortener::ports::IdGenerator;
+///
+/// let generator = NanoidIdGenerator::with_alphabet("abcdef", 8).unwrap();
+/// let code = generator.generate_short_code().unwrap();
+/// assert!(code.as_str().chars().all(|c| "abcdef".contains(c)));
+/// ```
+pub struct NanoidIdGenerator {
+    alphabet: Vec<char>,
+    short_code_length: usize,
+}
+
+impl NanoidIdGenerator {
+    /// Default alphabet, without visually ambiguous characters
+    pub const DEFAULT_ALPHABET: &'static str =
+        "346789ABCDEFGHJKLMNPQRTUVWXYabcdefghijkmnpqrtwxyz";
+
+    /// Default length for generated short codes
+    pub const DEFAULT_SHORT_CODE_LENGTH: usize = 8;
+
+    /// Length of generated IDs
+    const ID_LENGTH: usize = 16;
+
+    /// Create a generator with the default alphabet and length
+    pub fn new() -> Self {
+        Self::with_alphabet(Self::DEFAULT_ALPHABET, Self::DEFAULT_SHORT_CODE_LENGTH)
+            .expect("default alphabet is valid")
+    }
+
+    /// Create a generator with a custom alphabet and short code length
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::InvalidShortCode` if:
+    /// - The alphabet is empty or repeats a character
+    /// - The alphabet contains a non-alphanumeric character
+    /// - Codes of `length` characters would fall outside the valid range for
+    ///   ShortCode
+    pub fn with_alphabet(alphabet: &str, length: usize) -> Result<Self> {
+        let chars: Vec<char> = alphabet.chars().collect();
+
+        if chars.is_empty() {
+            return Err(UrlShortenerError::InvalidShortCode(
+                "Alphabet must not be empty".to_string()
+            ));
+        }
+
+        if let Some(c) = chars.iter().find(|c| !c.is_alphanumeric()) {
+            return Err(UrlShortenerError::InvalidShortCode(
+                format!("Alphabet character '{}' is not alphanumeric", c)
+            ));
+        }
+
+        for (i, c) in chars.iter().enumerate() {
+            if chars[..i].contains(c) {
+                return Err(UrlShortenerError::InvalidShortCode(
+                    format!("Alphabet repeats character '{}'", c)
+                ));
+            }
+        }
+
+        // ShortCode limits are in bytes, and alphabet characters may be multibyte
+        let shortest = chars.iter().map(|c| c.len_utf8()).min().unwrap_or(1);
+        let longest = chars.iter().map(|c| c.len_utf8()).max().unwrap_or(1);
+        if length * shortest < ShortCode::MIN_LENGTH || length * longest > ShortCode::MAX_LENGTH {
+            return Err(UrlShortenerError::InvalidShortCode(format!(
+                "Length must keep codes between {} and {} bytes",
+                ShortCode::MIN_LENGTH,
+                ShortCode::MAX_LENGTH
+            )));
+        }
+
+        Ok(Self {
+            alphabet: chars,
+            short_code_length: length,
+        })
+    }
+
+    /// Get the alphabet codes are drawn from
+    pub fn alphabet(&self) -> String {
+        self.alphabet.iter().collect()
+    }
+
+    /// Draw `length` characters uniformly from the alphabet
+    fn generate(&self, length: usize) -> String {
+        let mut rng = rand::thread_rng();
+
+        (0..length)
+            .map(|_| self.alphabet[rng.gen_range(0..self.alphabet.len())])
+            .collect()
+    }
+}
+
+impl Default for NanoidIdGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdGenerator for NanoidIdGenerator {
+    fn generate_id(&self) -> UrlId {
+        UrlId::new(self.generate(Self::ID_LENGTH))
+    }
+
+    fn generate_short_code(&self) -> Result<ShortCode> {
+        ShortCode::new(self.generate(self.short_code_length))
+    }
+
+    fn keyspace(&self) -> Option<u64> {
+        let alphabet_len = self.alphabet.len() as u64;
+        Some(alphabet_len.saturating_pow(self.short_code_length as u32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_alphabet_is_unambiguous() {
+        let generator = NanoidIdGenerator::new();
+
+        for _ in 0..100 {
+            let code = generator.generate_short_code().unwrap();
+            assert_eq!(code.as_str().len(), NanoidIdGenerator::DEFAULT_SHORT_CODE_LENGTH);
+            assert!(code.as_str().chars().all(|c| !"0O1lIo".contains(c)));
+        }
+    }
+
+    #[test]
+    fn test_custom_alphabet_and_length() {
+        let generator = NanoidIdGenerator::with_alphabet("xyz", 5).unwrap();
+
+        for _ in 0..100 {
+            let code = generator.generate_short_code().unwrap();
+            assert_eq!(code.as_str().len(), 5);
+            assert!(code.as_str().chars().all(|c| "xyz".contains(c)));
+        }
+        assert_eq!(generator.keyspace(), Some(3u64.pow(5)));
+    }
+
+    #[test]
+    fn test_uses_whole_alphabet() {
+        let generator = NanoidIdGenerator::with_alphabet("ab", 12).unwrap();
+        let mut seen = std::collections::HashSet::new();
+        for _ in 0..20 {
+            seen.extend(generator.generate_short_code().unwrap().as_str().chars());
+        }
+
+        assert_eq!(seen.len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_alphabets_rejected() {
+        for alphabet in ["", "abc-", "ab cd", "abca"] {
+            let result = NanoidIdGenerator::with_alphabet(alphabet, 6);
+            assert!(
+                matches!(result, Err(UrlShortenerError::InvalidShortCode(_))),
+                "alphabet {:?} should be rejected",
+                alphabet
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_length_rejected() {
+        assert!(NanoidIdGenerator::with_alphabet("abc", ShortCode::MIN_LENGTH - 1).is_err());
+        assert!(NanoidIdGenerator::with_alphabet("abc", ShortCode::MAX_LENGTH + 1).is_err());
+
+        // Two-byte characters halve the number of characters that fit
+        assert!(NanoidIdGenerator::with_alphabet("äö", 7).is_err());
+        assert!(NanoidIdGenerator::with_alphabet("äö", 6).is_ok());
+    }
+}
@@ -0,0 +1,187 @@
+//! Pattern-enforcing ID generator combinator
+
+use crate::domain::{OriginalUrl, ShortCode, UrlId};
+use crate::error::{Result, UrlShortenerError};
+use crate::ports::IdGenerator;
+
+/// Predicate a short code must satisfy
+type Predicate = Box<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// ID generator that only hands out short codes matching a predicate
+///
+/// Short codes are drawn from `inner` until one satisfies the predicate, up
+/// to `max_attempts` draws per code, after which generation fails with
+/// `IdGenerationFailed`. Errors from `inner` are returned right away. URL ids
+/// come from `inner` unchanged.
+///
+/// Rejected draws are wasted, so keep the accepted share of the inner
+/// generator's codes reasonably large. Deterministic generators produce the
+/// same code on every draw and fail if it doesn't match.
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::adapters::{PatternIdGenerator, RandomIdGenerator};
+/// use url_shortener::ports::IdGenerator;
+///
+/// // Three letters followed by three digits, like "abc123"
+/// let generator = PatternIdGenerator::with_pattern(RandomIdGenerator::with_length(6), "LLLDDD")
+///     .with_max_attempts(10_000);
+///
+/// let code = generator.generate_short_code().unwrap();
+/// assert!(code.as_str()[..3].chars().all(|c| c.is_ascii_alphabetic()));
+/// assert!(code.as_str()[3..].chars().all(|c| c.is_ascii_digit()));
+/// ```
+pub struct PatternIdGenerator<G> {
+    inner: G,
+    predicate: Predicate,
+    max_attempts: usize,
+}
+
+impl<G: IdGenerator> PatternIdGenerator<G> {
+    /// Default number of draws per short code
+    pub const DEFAULT_MAX_ATTEMPTS: usize = 1_000;
+
+    /// Only accept codes from `inner` for which `predicate` returns `true`
+    pub fn new(inner: G, predicate: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        Self {
+            inner,
+            predicate: Box::new(predicate),
+            max_attempts: Self::DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+
+    /// Only accept codes from `inner` matching `pattern` character by character
+    ///
+    /// In the pattern, `L` stands for an ASCII letter, `D` for a digit and
+    /// `?` for any character; every other character must appear as is. Codes
+    /// must be exactly as long as the pattern.
+    pub fn with_pattern(inner: G, pattern: &str) -> Self {
+        let pattern: Vec<char> = pattern.chars().collect();
+
+        Self::new(inner, move |code| {
+            code.chars().count() == pattern.len()
+                && code.chars().zip(&pattern).all(|(c, &p)| match p {
+                    'L' => c.is_ascii_alphabetic(),
+                    'D' => c.is_ascii_digit(),
+                    '?' => true,
+                    literal => c == literal,
+                })
+        })
+    }
+
+    /// Set the number of draws per short code
+    ///
+    /// Values below 1 are treated as 1.
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Get the wrapped generator
+    pub fn inner(&self) -> &G {
+        &self.inner
+    }
+
+    /// Number of draws per short code
+    pub fn max_attempts(&self) -> usize {
+        self.max_attempts
+    }
+
+    /// Draw codes with `generate` until one satisfies the predicate
+    fn draw(&self, generate: impl Fn() -> Result<ShortCode>) -> Result<ShortCode> {
+        for _ in 0..self.max_attempts {
+            let code = generate()?;
+            if (self.predicate)(code.as_str()) {
+                return Ok(code);
+            }
+        }
+
+        Err(UrlShortenerError::IdGenerationFailed(self.max_attempts))
+    }
+}
+
+impl<G: IdGenerator> IdGenerator for PatternIdGenerator<G> {
+    fn generate_id(&self) -> UrlId {
+        self.inner.generate_id()
+    }
+
+    fn generate_short_code(&self) -> Result<ShortCode> {
+        self.draw(|| self.inner.generate_short_code())
+    }
+
+    fn generate_short_code_for(&self, url: &OriginalUrl) -> Result<ShortCode> {
+        self.draw(|| self.inner.generate_short_code_for(url))
+    }
+
+    fn generate_short_code_with_length(&self, length: usize) -> Result<ShortCode> {
+        self.draw(|| self.inner.generate_short_code_with_length(length))
+    }
+
+    /// Unknown, since it depends on how many of the inner codes match
+    fn keyspace(&self) -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::{RandomIdGenerator, SequentialIdGenerator};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn seeded(length: usize) -> RandomIdGenerator {
+        RandomIdGenerator::with_length(length).with_rng(StdRng::seed_from_u64(825))
+    }
+
+    #[test]
+    fn test_codes_satisfy_predicate() {
+        let lowercase = |code: &str| code.chars().all(|c| c.is_ascii_lowercase());
+        let generator = PatternIdGenerator::new(seeded(4), lowercase);
+
+        for _ in 0..20 {
+            let code = generator.generate_short_code().unwrap();
+            assert!(lowercase(code.as_str()), "{} is not all lowercase", code);
+        }
+
+        let code = generator.generate_short_code_with_length(5).unwrap();
+        assert_eq!(code.as_str().len(), 5);
+        assert!(lowercase(code.as_str()));
+    }
+
+    #[test]
+    fn test_pattern_spec() {
+        let generator = PatternIdGenerator::with_pattern(seeded(6), "LLLDDD")
+            .with_max_attempts(100_000);
+
+        for _ in 0..5 {
+            let code = generator.generate_short_code().unwrap();
+            let (letters, digits) = code.as_str().split_at(3);
+            assert!(letters.chars().all(|c| c.is_ascii_alphabetic()), "{}", code);
+            assert!(digits.chars().all(|c| c.is_ascii_digit()), "{}", code);
+        }
+
+        // Literal characters and length must match exactly
+        let generator = PatternIdGenerator::with_pattern(SequentialIdGenerator::new(), "aa?b");
+        assert_eq!(generator.generate_short_code().unwrap().as_str(), "aaab");
+        let generator = PatternIdGenerator::with_pattern(SequentialIdGenerator::new(), "aa?")
+            .with_max_attempts(5);
+        assert!(generator.generate_short_code().is_err());
+    }
+
+    #[test]
+    fn test_gives_up_after_max_attempts() {
+        let generator = PatternIdGenerator::new(SequentialIdGenerator::new(), |_| false)
+            .with_max_attempts(3);
+
+        let result = generator.generate_short_code();
+        assert!(matches!(result, Err(UrlShortenerError::IdGenerationFailed(3))));
+        // The rejected codes were drawn from the inner generator
+        assert_eq!(generator.inner().generate_short_code().unwrap().as_str(), "aaad");
+
+        let generator = PatternIdGenerator::new(seeded(4), |_| true).with_max_attempts(0);
+        assert_eq!(generator.max_attempts(), 1);
+        assert_eq!(generator.keyspace(), None);
+    }
+}
@@ -0,0 +1,675 @@
+//! Postgres implementation of AsyncUrlRepository
+//!
+//! Persists shortened URLs with `sqlx`. Available with the `postgres` feature.
+
+use crate::domain::{OriginalUrl, ShortCode, ShortenedUrl, UrlId};
+use crate::error::{Result, UrlShortenerError};
+use crate::ports::AsyncUrlRepository;
+use async_trait::async_trait;
+use sqlx::postgres::{PgConnection, PgPool};
+use sqlx::FromRow;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// SQLSTATE Postgres reports for a unique constraint violation
+const UNIQUE_VIOLATION: &str = "23505";
+
+/// Statements run by `migrate`, in order
+const SCHEMA: [&str; 7] = [
+    "CREATE TABLE IF NOT EXISTS shortened_urls (
+         id TEXT NOT NULL,
+         short_code TEXT NOT NULL,
+         original_url TEXT NOT NULL,
+         created_at BIGINT NOT NULL,
+         access_count BIGINT NOT NULL DEFAULT 0,
+         active_from BIGINT,
+         expires_at BIGINT,
+         last_accessed_at BIGINT,
+         history_days BIGINT NOT NULL,
+         enabled BOOLEAN NOT NULL DEFAULT TRUE,
+         max_accesses BIGINT
+     )",
+    "CREATE UNIQUE INDEX IF NOT EXISTS idx_shortened_urls_short_code
+         ON shortened_urls (short_code)",
+    "CREATE TABLE IF NOT EXISTS url_notes (
+         short_code TEXT NOT NULL
+             REFERENCES shortened_urls (short_code) ON DELETE CASCADE,
+         position BIGINT NOT NULL,
+         author TEXT NOT NULL,
+         text TEXT NOT NULL,
+         at BIGINT NOT NULL,
+         PRIMARY KEY (short_code, position)
+     )",
+    "CREATE TABLE IF NOT EXISTS url_tags (
+         short_code TEXT NOT NULL
+             REFERENCES shortened_urls (short_code) ON DELETE CASCADE,
+         position BIGINT NOT NULL,
+         tag TEXT NOT NULL,
+         PRIMARY KEY (short_code, position)
+     )",
+    "CREATE INDEX IF NOT EXISTS idx_url_tags_tag ON url_tags (tag)",
+    "CREATE TABLE IF NOT EXISTS url_daily_counts (
+         short_code TEXT NOT NULL
+             REFERENCES shortened_urls (short_code) ON DELETE CASCADE,
+         day BIGINT NOT NULL,
+         count BIGINT NOT NULL,
+         PRIMARY KEY (short_code, day)
+     )",
+    "CREATE TABLE IF NOT EXISTS url_referrers (
+         short_code TEXT NOT NULL
+             REFERENCES shortened_urls (short_code) ON DELETE CASCADE,
+         referrer TEXT NOT NULL,
+         count BIGINT NOT NULL,
+         PRIMARY KEY (short_code, referrer)
+     )",
+];
+
+/// Columns selected for an entry, in `RawRow` order
+const COLUMNS: &str = "id, short_code, original_url, created_at, access_count, active_from,
+                       expires_at, last_accessed_at, history_days, enabled, max_accesses";
+
+/// Postgres-backed async URL repository
+///
+/// Uses the same layout as `SqliteUrlRepository`: entries live in a
+/// `shortened_urls` table with a unique index on `short_code`, and notes,
+/// tags, per-day and per-referrer access counts live in the `url_notes`,
+/// `url_tags`, `url_daily_counts` and `url_referrers` tables. Timestamps are
+/// stored as Unix seconds.
+///
+/// The schema isn't created on construction; call `migrate` once before use.
+///
+/// # Examples
+///
+/// ```no_run
+/// use url_shortener::adapters::PostgresUrlRepository;
+/// use url_shortener::ports::AsyncUrlRepository;
+///
+/// # async fn run() -> url_shortener::error::Result<()> {
+/// let repo = PostgresUrlRepository::connect("postgres://localhost/links").await?;
+/// repo.migrate().await?;
+/// println!("{} entries", repo.list_all().await?.len());
+/// # Ok(())
+/// # }
+/// ```
+pub struct PostgresUrlRepository {
+    pool: PgPool,
+}
+
+impl PostgresUrlRepository {
+    /// Create a repository on an existing connection pool
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Connect a new pool to the database at `url`
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::StorageUnavailable` if the database can't
+    /// be reached, or `UrlShortenerError::RepositoryError` if `url` is invalid
+    pub async fn connect(url: &str) -> Result<Self> {
+        let pool = PgPool::connect(url).await.map_err(query_error)?;
+        Ok(Self::new(pool))
+    }
+
+    /// Get the underlying connection pool
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    /// Create the tables and indexes if they don't exist yet
+    ///
+    /// Safe to run on every startup.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::RepositoryError` if the schema can't be
+    /// created
+    pub async fn migrate(&self) -> Result<()> {
+        let mut transaction = self.pool.begin().await.map_err(query_error)?;
+
+        for statement in SCHEMA {
+            sqlx::query(statement)
+                .execute(&mut *transaction)
+                .await
+                .map_err(|e| {
+                    UrlShortenerError::repository_with_source("Failed to create schema", e)
+                })?;
+        }
+
+        transaction.commit().await.map_err(query_error)
+    }
+
+    /// Attach notes, tags and per-day access counts to an entry
+    async fn load_related(
+        connection: &mut PgConnection,
+        mut url: ShortenedUrl,
+    ) -> Result<ShortenedUrl> {
+        let code = url.short_code().as_str().to_string();
+
+        let notes: Vec<(String, String, i64)> = sqlx::query_as(
+            "SELECT author, text, at FROM url_notes WHERE short_code = $1 ORDER BY position",
+        )
+        .bind(&code)
+        .fetch_all(&mut *connection)
+        .await
+        .map_err(query_error)?;
+
+        for (author, text, at) in notes {
+            url.add_note(author, text, from_secs(at))?;
+        }
+
+        let tags: Vec<String> =
+            sqlx::query_scalar("SELECT tag FROM url_tags WHERE short_code = $1 ORDER BY position")
+                .bind(&code)
+                .fetch_all(&mut *connection)
+                .await
+                .map_err(query_error)?;
+
+        if !tags.is_empty() {
+            url = url.with_tags(tags)?;
+        }
+
+        let days: Vec<(i64, i64)> =
+            sqlx::query_as("SELECT day, count FROM url_daily_counts WHERE short_code = $1")
+                .bind(&code)
+                .fetch_all(&mut *connection)
+                .await
+                .map_err(query_error)?;
+
+        let referrers: Vec<(String, i64)> =
+            sqlx::query_as("SELECT referrer, count FROM url_referrers WHERE short_code = $1")
+                .bind(&code)
+                .fetch_all(&mut *connection)
+                .await
+                .map_err(query_error)?;
+
+        Ok(url
+            .with_access_counts_by_day(
+                days.into_iter().map(|(day, count)| (day, count.max(0) as u64)).collect(),
+            )
+            .with_referrer_counts(
+                referrers
+                    .into_iter()
+                    .map(|(referrer, count)| (referrer, count.max(0) as u64))
+                    .collect(),
+            ))
+    }
+
+    /// Replace the stored notes, tags, per-day and per-referrer access counts
+    /// of an entry
+    async fn write_related(connection: &mut PgConnection, url: &ShortenedUrl) -> Result<()> {
+        let code = url.short_code().as_str();
+
+        for table in ["url_notes", "url_tags", "url_referrers"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE short_code = $1", table))
+                .bind(code)
+                .execute(&mut *connection)
+                .await
+                .map_err(query_error)?;
+        }
+
+        for (position, note) in url.notes().iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO url_notes (short_code, position, author, text, at)
+                 VALUES ($1, $2, $3, $4, $5)",
+            )
+            .bind(code)
+            .bind(position as i64)
+            .bind(note.author())
+            .bind(note.text())
+            .bind(to_secs(note.at()))
+            .execute(&mut *connection)
+            .await
+            .map_err(query_error)?;
+        }
+
+        for (position, tag) in url.tags().iter().enumerate() {
+            sqlx::query("INSERT INTO url_tags (short_code, position, tag) VALUES ($1, $2, $3)")
+                .bind(code)
+                .bind(position as i64)
+                .bind(tag)
+                .execute(&mut *connection)
+                .await
+                .map_err(query_error)?;
+        }
+
+        Self::write_daily_counts(&mut *connection, url).await?;
+
+        for (referrer, count) in url.referrer_counts() {
+            sqlx::query(
+                "INSERT INTO url_referrers (short_code, referrer, count) VALUES ($1, $2, $3)",
+            )
+            .bind(code)
+            .bind(referrer)
+            .bind(*count as i64)
+            .execute(&mut *connection)
+            .await
+            .map_err(query_error)?;
+        }
+
+        Ok(())
+    }
+
+    /// Replace the stored per-day access counts of an entry
+    async fn write_daily_counts(connection: &mut PgConnection, url: &ShortenedUrl) -> Result<()> {
+        let code = url.short_code().as_str();
+
+        sqlx::query("DELETE FROM url_daily_counts WHERE short_code = $1")
+            .bind(code)
+            .execute(&mut *connection)
+            .await
+            .map_err(query_error)?;
+
+        for (day, count) in url.access_counts_by_day() {
+            sqlx::query("INSERT INTO url_daily_counts (short_code, day, count) VALUES ($1, $2, $3)")
+                .bind(code)
+                .bind(*day)
+                .bind(*count as i64)
+                .execute(&mut *connection)
+                .await
+                .map_err(query_error)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AsyncUrlRepository for PostgresUrlRepository {
+    async fn save(&self, url: ShortenedUrl) -> Result<()> {
+        let mut transaction = self.pool.begin().await.map_err(query_error)?;
+
+        sqlx::query(
+            "INSERT INTO shortened_urls
+                 (id, short_code, original_url, created_at, access_count, active_from,
+                  expires_at, last_accessed_at, history_days, enabled, max_accesses)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+        )
+        .bind(url.id().as_str())
+        .bind(url.short_code().as_str())
+        .bind(url.original_url().as_str())
+        .bind(to_secs(url.created_at()))
+        .bind(url.access_count() as i64)
+        .bind(url.active_from().map(to_secs))
+        .bind(url.expires_at().map(to_secs))
+        .bind(url.last_accessed_at().map(to_secs))
+        .bind(url.history_days() as i64)
+        .bind(url.is_enabled())
+        .bind(url.max_accesses().map(|max| max.min(i64::MAX as u64) as i64))
+        .execute(&mut *transaction)
+        .await
+        .map_err(|e| match &e {
+            sqlx::Error::Database(db) if db.code().as_deref() == Some(UNIQUE_VIOLATION) => {
+                UrlShortenerError::ShortCodeAlreadyExists(url.short_code().as_str().to_string())
+            }
+            _ => query_error(e),
+        })?;
+
+        Self::write_related(&mut transaction, &url).await?;
+        transaction.commit().await.map_err(query_error)
+    }
+
+    async fn find_by_short_code(&self, code: &ShortCode) -> Result<ShortenedUrl> {
+        let mut connection = self.pool.acquire().await.map_err(query_error)?;
+
+        let raw: RawRow =
+            sqlx::query_as(&format!("SELECT {} FROM shortened_urls WHERE short_code = $1", COLUMNS))
+                .bind(code.as_str())
+                .fetch_optional(&mut *connection)
+                .await
+                .map_err(query_error)?
+                .ok_or_else(|| UrlShortenerError::ShortCodeNotFound(code.as_str().to_string()))?;
+
+        Self::load_related(&mut connection, raw.into_domain()?).await
+    }
+
+    async fn update(&self, url: ShortenedUrl) -> Result<()> {
+        let mut transaction = self.pool.begin().await.map_err(query_error)?;
+
+        let updated = sqlx::query(
+            "UPDATE shortened_urls
+             SET id = $1, original_url = $3, created_at = $4, access_count = $5,
+                 active_from = $6, expires_at = $7, last_accessed_at = $8,
+                 history_days = $9, enabled = $10, max_accesses = $11
+             WHERE short_code = $2",
+        )
+        .bind(url.id().as_str())
+        .bind(url.short_code().as_str())
+        .bind(url.original_url().as_str())
+        .bind(to_secs(url.created_at()))
+        .bind(url.access_count() as i64)
+        .bind(url.active_from().map(to_secs))
+        .bind(url.expires_at().map(to_secs))
+        .bind(url.last_accessed_at().map(to_secs))
+        .bind(url.history_days() as i64)
+        .bind(url.is_enabled())
+        .bind(url.max_accesses().map(|max| max.min(i64::MAX as u64) as i64))
+        .execute(&mut *transaction)
+        .await
+        .map_err(query_error)?
+        .rows_affected();
+
+        if updated == 0 {
+            return Err(UrlShortenerError::ShortCodeNotFound(
+                url.short_code().as_str().to_string()
+            ));
+        }
+
+        Self::write_related(&mut transaction, &url).await?;
+        transaction.commit().await.map_err(query_error)
+    }
+
+    async fn increment_access_at(&self, code: &ShortCode, at: SystemTime) -> Result<u64> {
+        let mut transaction = self.pool.begin().await.map_err(query_error)?;
+
+        // The row lock serializes concurrent accesses until the commit
+        let raw: RawRow = sqlx::query_as(&format!(
+            "SELECT {} FROM shortened_urls WHERE short_code = $1 FOR UPDATE",
+            COLUMNS
+        ))
+        .bind(code.as_str())
+        .fetch_optional(&mut *transaction)
+        .await
+        .map_err(query_error)?
+        .ok_or_else(|| UrlShortenerError::ShortCodeNotFound(code.as_str().to_string()))?;
+
+        let mut url = Self::load_related(&mut transaction, raw.into_domain()?).await?;
+        if url.is_access_limit_reached() {
+            return Err(UrlShortenerError::AccessLimitReached(code.as_str().to_string()));
+        }
+        url.record_access_at(at);
+
+        sqlx::query(
+            "UPDATE shortened_urls SET access_count = $1, last_accessed_at = $2
+             WHERE short_code = $3",
+        )
+        .bind(url.access_count() as i64)
+        .bind(url.last_accessed_at().map(to_secs))
+        .bind(code.as_str())
+        .execute(&mut *transaction)
+        .await
+        .map_err(query_error)?;
+
+        Self::write_daily_counts(&mut transaction, &url).await?;
+        transaction.commit().await.map_err(query_error)?;
+
+        Ok(url.access_count())
+    }
+
+    async fn exists(&self, code: &ShortCode) -> Result<bool> {
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM shortened_urls WHERE short_code = $1)")
+            .bind(code.as_str())
+            .fetch_one(&self.pool)
+            .await
+            .map_err(query_error)
+    }
+
+    async fn delete(&self, code: &ShortCode) -> Result<()> {
+        // Notes, tags and daily counts go with the entry via ON DELETE CASCADE
+        let deleted = sqlx::query("DELETE FROM shortened_urls WHERE short_code = $1")
+            .bind(code.as_str())
+            .execute(&self.pool)
+            .await
+            .map_err(query_error)?
+            .rows_affected();
+
+        if deleted == 0 {
+            return Err(UrlShortenerError::ShortCodeNotFound(code.as_str().to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn list_all(&self) -> Result<Vec<ShortenedUrl>> {
+        let mut connection = self.pool.acquire().await.map_err(query_error)?;
+
+        let rows: Vec<RawRow> =
+            sqlx::query_as(&format!("SELECT {} FROM shortened_urls ORDER BY short_code", COLUMNS))
+                .fetch_all(&mut *connection)
+                .await
+                .map_err(query_error)?;
+
+        let mut urls = Vec::with_capacity(rows.len());
+        for raw in rows {
+            urls.push(Self::load_related(&mut connection, raw.into_domain()?).await?);
+        }
+
+        Ok(urls)
+    }
+}
+
+/// Columns of a `shortened_urls` row before validation
+#[derive(FromRow)]
+struct RawRow {
+    id: String,
+    short_code: String,
+    original_url: String,
+    created_at: i64,
+    access_count: i64,
+    active_from: Option<i64>,
+    expires_at: Option<i64>,
+    last_accessed_at: Option<i64>,
+    history_days: i64,
+    enabled: bool,
+    max_accesses: Option<i64>,
+}
+
+impl RawRow {
+    /// Rebuild the domain entity, validating stored values
+    fn into_domain(self) -> Result<ShortenedUrl> {
+        let mut url = ShortenedUrl::with_created_at(
+            UrlId::new(self.id),
+            ShortCode::new(self.short_code)?,
+            OriginalUrl::from_stored(self.original_url)?,
+            from_secs(self.created_at),
+        )
+        .with_access_count(self.access_count.max(0) as u64)
+        .with_history_days(self.history_days.max(0) as usize)
+        .with_enabled(self.enabled);
+
+        if let Some(active_from) = self.active_from {
+            url = url.with_active_from(from_secs(active_from));
+        }
+
+        if let Some(expires_at) = self.expires_at {
+            url = url.with_expires_at(from_secs(expires_at));
+        }
+
+        if let Some(last_accessed_at) = self.last_accessed_at {
+            url = url.with_last_accessed_at(from_secs(last_accessed_at));
+        }
+
+        if let Some(max_accesses) = self.max_accesses {
+            url = url.with_max_accesses(max_accesses.max(0) as u64);
+        }
+
+        Ok(url)
+    }
+}
+
+/// Map a sqlx error, reporting connection trouble as `StorageUnavailable`
+fn query_error(e: sqlx::Error) -> UrlShortenerError {
+    match e {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed => {
+            UrlShortenerError::StorageUnavailable(format!("Postgres unavailable: {}", e))
+        }
+        _ => UrlShortenerError::repository_with_source("Postgres query failed", e),
+    }
+}
+
+fn to_secs(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn from_secs(secs: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs.max(0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::Future;
+
+    /// Run `test` against the database in `DATABASE_URL`, or skip it when no
+    /// database is configured
+    ///
+    /// Each test uses its own short codes and removes them first, so tests
+    /// can share a database.
+    fn with_repository<F, Fut>(name: &str, codes: &[&str], test: F)
+    where
+        F: FnOnce(PostgresUrlRepository) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let Ok(url) = std::env::var("DATABASE_URL") else {
+            eprintln!("DATABASE_URL not set, skipping {}", name);
+            return;
+        };
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let repo = PostgresUrlRepository::connect(&url).await.unwrap();
+            repo.migrate().await.unwrap();
+            for code in codes {
+                let _ = repo.delete(&ShortCode::new(code.to_string()).unwrap()).await;
+            }
+            test(repo).await;
+        });
+    }
+
+    fn create_test_url(code: &str) -> ShortenedUrl {
+        let id = UrlId::new(format!("id-{}", code));
+        let short_code = ShortCode::new(code.to_string()).unwrap();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        ShortenedUrl::new(id, short_code, url)
+    }
+
+    #[test]
+    fn test_save_find_update_delete() {
+        with_repository("crud", &["crud1234"], |repo| async move {
+            let code = ShortCode::new("crud1234".to_string()).unwrap();
+
+            repo.save(create_test_url("crud1234")).await.unwrap();
+            assert!(repo.exists(&code).await.unwrap());
+
+            let mut url = repo.find_by_short_code(&code).await.unwrap();
+            url.record_access_with_referrer(Some("newsletter".to_string()));
+            url.add_note("ops".to_string(), "campaign link".to_string(), SystemTime::now())
+                .unwrap();
+            let url = url.with_tags(vec!["promo".to_string()]).unwrap().with_max_accesses(5);
+            repo.update(url).await.unwrap();
+
+            let found = repo.find_by_short_code(&code).await.unwrap();
+            assert_eq!(found.access_count(), 1);
+            assert_eq!(found.notes()[0].text(), "campaign link");
+            assert_eq!(found.tags(), ["promo".to_string()]);
+            assert_eq!(found.access_counts_by_day().values().sum::<u64>(), 1);
+            assert_eq!(found.referrer_counts().get("newsletter"), Some(&1));
+            assert_eq!(found.max_accesses(), Some(5));
+            assert!(repo.list_all().await.unwrap().iter().any(|u| u.short_code() == &code));
+
+            repo.delete(&code).await.unwrap();
+            assert!(!repo.exists(&code).await.unwrap());
+        });
+    }
+
+    #[test]
+    fn test_urls_outside_default_limits_read_back() {
+        let codes = ["wide0000", "wide0001", "wide0002"];
+        with_repository("wide", &codes, |repo| async move {
+            let targets = [
+                OriginalUrl::new_with_schemes("mailto:team@example.com".to_string(), &["mailto"]),
+                OriginalUrl::new_with_schemes("ftp://example.com/file".to_string(), &["ftp"]),
+                OriginalUrl::new_with_max_len(
+                    format!("https://example.com/{}", "a".repeat(3000)),
+                    4096,
+                ),
+            ];
+
+            for (code, target) in codes.into_iter().zip(targets) {
+                let code = ShortCode::new(code.to_string()).unwrap();
+                let url =
+                    ShortenedUrl::new(UrlId::new(code.to_string()), code.clone(), target.unwrap());
+                repo.save(url.clone()).await.unwrap();
+
+                let found = repo.find_by_short_code(&code).await.unwrap();
+                assert_eq!(found.original_url(), url.original_url());
+            }
+            assert!(repo.list_all().await.is_ok());
+        });
+    }
+
+    #[test]
+    fn test_duplicate_short_code() {
+        with_repository("duplicate", &["dupe1234"], |repo| async move {
+            repo.save(create_test_url("dupe1234")).await.unwrap();
+            let result = repo.save(create_test_url("dupe1234")).await;
+            assert!(matches!(result, Err(UrlShortenerError::ShortCodeAlreadyExists(_))));
+        });
+    }
+
+    #[test]
+    fn test_missing_code_not_found() {
+        with_repository("missing", &["missing1"], |repo| async move {
+            let code = ShortCode::new("missing1".to_string()).unwrap();
+
+            assert!(matches!(
+                repo.find_by_short_code(&code).await,
+                Err(UrlShortenerError::ShortCodeNotFound(_))
+            ));
+            assert!(matches!(
+                repo.update(create_test_url("missing1")).await,
+                Err(UrlShortenerError::ShortCodeNotFound(_))
+            ));
+            assert!(matches!(
+                repo.delete(&code).await,
+                Err(UrlShortenerError::ShortCodeNotFound(_))
+            ));
+        });
+    }
+
+    #[test]
+    fn test_migrate_is_idempotent() {
+        with_repository("migrate", &["keep1234"], |repo| async move {
+            let code = ShortCode::new("keep1234".to_string()).unwrap();
+            repo.save(create_test_url("keep1234")).await.unwrap();
+
+            repo.migrate().await.unwrap();
+            assert!(repo.exists(&code).await.unwrap());
+        });
+    }
+
+    #[test]
+    fn test_concurrent_increments_stay_within_limit() {
+        with_repository("increment", &["incr1234", "missing2"], |repo| async move {
+            let code = ShortCode::new("incr1234".to_string()).unwrap();
+            repo.save(create_test_url("incr1234").with_max_accesses(15)).await.unwrap();
+
+            let at = UNIX_EPOCH + Duration::from_secs(3 * 86_400 + 5);
+            let results = futures::future::join_all(
+                (0..20).map(|_| repo.increment_access_at(&code, at)),
+            )
+            .await;
+            assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 15);
+            assert!(results.iter().any(|result| {
+                matches!(result, Err(UrlShortenerError::AccessLimitReached(_)))
+            }));
+
+            let found = repo.find_by_short_code(&code).await.unwrap();
+            assert_eq!(found.access_count(), 15);
+            assert_eq!(found.access_counts_by_day().get(&3), Some(&15));
+            assert_eq!(found.last_accessed_at(), Some(at));
+
+            let missing = ShortCode::new("missing2".to_string()).unwrap();
+            assert!(matches!(
+                repo.increment_access_at(&missing, at).await,
+                Err(UrlShortenerError::ShortCodeNotFound(_))
+            ));
+        });
+    }
+}
@@ -3,13 +3,18 @@
 use crate::domain::{ShortCode, UrlId};
 use crate::error::{Result, UrlShortenerError};
 use crate::ports::IdGenerator;
-use rand::Rng;
+use rand::{Rng, RngCore};
+use std::sync::{Mutex, PoisonError};
 
 /// Random ID generator using alphanumeric characters
 ///
 /// Generates random IDs and short codes using secure random number generation.
+/// By default each call draws from the thread-local RNG; `with_rng` injects a
+/// specific RNG instead, such as a seeded `StdRng` for reproducible tests.
 pub struct RandomIdGenerator {
     short_code_length: usize,
+    charset: &'static [u8],
+    rng: Option<Mutex<Box<dyn RngCore + Send>>>,
 }
 
 impl RandomIdGenerator {
@@ -23,6 +28,36 @@ impl RandomIdGenerator {
     pub fn new() -> Self {
         Self {
             short_code_length: Self::DEFAULT_SHORT_CODE_LENGTH,
+            charset: Self::CHARSET,
+            rng: None,
+        }
+    }
+
+    /// Create a random ID generator that avoids easily misread characters
+    ///
+    /// Codes leave out `0`, `O`, `1`, `l` and `I`, drawing from the remaining
+    /// 57 letters and digits, so they can be read aloud or copied from print
+    /// without guessing. The smaller charset shrinks the keyspace: 6
+    /// characters give about 34 billion codes instead of 57 billion, so
+    /// collisions come sooner and `keyspace` reports the reduced size. Ask
+    /// for longer codes (for example with the service's preferred code
+    /// length) to make up for it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_shortener::adapters::RandomIdGenerator;
+    /// use url_shortener::ports::IdGenerator;
+    ///
+    /// let generator = RandomIdGenerator::unambiguous();
+    /// let code = generator.generate_short_code().unwrap();
+    /// assert!(!code.as_str().contains(['0', 'O', '1', 'l', 'I']));
+    /// assert_eq!(generator.keyspace(), Some(57u64.pow(6)));
+    /// ```
+    pub fn unambiguous() -> Self {
+        Self {
+            charset: Self::UNAMBIGUOUS_CHARSET,
+            ..Self::new()
         }
     }
 
@@ -33,7 +68,7 @@ impl RandomIdGenerator {
     /// Panics if the length is outside the valid range for ShortCode
     pub fn with_length(length: usize) -> Self {
         assert!(
-            length >= ShortCode::MIN_LENGTH && length <= ShortCode::MAX_LENGTH,
+            (ShortCode::MIN_LENGTH..=ShortCode::MAX_LENGTH).contains(&length),
             "Short code length must be between {} and {}",
             ShortCode::MIN_LENGTH,
             ShortCode::MAX_LENGTH
@@ -41,18 +76,74 @@ impl RandomIdGenerator {
 
         Self {
             short_code_length: length,
+            charset: Self::CHARSET,
+            rng: None,
         }
     }
 
+    /// Draw from `rng` instead of the thread-local RNG
+    ///
+    /// With a seeded RNG the sequence of generated IDs and codes is
+    /// reproducible. Calls share the RNG behind a mutex.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_shortener::adapters::RandomIdGenerator;
+    /// use url_shortener::ports::IdGenerator;
+    /// use rand::SeedableRng;
+    /// use rand::rngs::StdRng;
+    ///
+    /// let a = RandomIdGenerator::new().with_rng(StdRng::seed_from_u64(7));
+    /// let b = RandomIdGenerator::new().with_rng(StdRng::seed_from_u64(7));
+    /// assert_eq!(a.generate_short_code().unwrap(), b.generate_short_code().unwrap());
+    /// ```
+    pub fn with_rng(mut self, rng: impl RngCore + Send + 'static) -> Self {
+        self.rng = Some(Mutex::new(Box::new(rng)));
+        self
+    }
+
+    /// Characters used for generated IDs and short codes
+    const CHARSET: &'static [u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+    /// `CHARSET` without the easily confused `0`, `O`, `1`, `l` and `I`
+    const UNAMBIGUOUS_CHARSET: &'static [u8] =
+        b"abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
     /// Generate a random alphanumeric string
     fn generate_alphanumeric(&self, length: usize) -> String {
-        const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
-        let mut rng = rand::thread_rng();
+        match &self.rng {
+            Some(rng) => {
+                // The RNG state stays usable even if a holder panicked
+                let mut rng = rng.lock().unwrap_or_else(PoisonError::into_inner);
+                Self::draw(&mut **rng, self.charset, length)
+            }
+            None => Self::draw(&mut rand::thread_rng(), self.charset, length),
+        }
+    }
+
+    /// Generate a short code of `length` characters
+    fn generate_code(&self, length: usize) -> Result<ShortCode> {
+        // Try multiple times to generate a valid code
+        for attempt in 0..Self::MAX_ATTEMPTS {
+            let code = self.generate_alphanumeric(length);
+
+            match ShortCode::new(code) {
+                Ok(short_code) => return Ok(short_code),
+                Err(_) if attempt < Self::MAX_ATTEMPTS - 1 => continue,
+                Err(e) => return Err(e),
+            }
+        }
 
+        Err(UrlShortenerError::IdGenerationFailed(Self::MAX_ATTEMPTS))
+    }
+
+    /// Draw `length` characters from `charset`
+    fn draw<R: Rng + ?Sized>(rng: &mut R, charset: &[u8], length: usize) -> String {
         (0..length)
             .map(|_| {
-                let idx = rng.gen_range(0..CHARSET.len());
-                CHARSET[idx] as char
+                let idx = rng.gen_range(0..charset.len());
+                charset[idx] as char
             })
             .collect()
     }
@@ -72,24 +163,32 @@ impl IdGenerator for RandomIdGenerator {
     }
 
     fn generate_short_code(&self) -> Result<ShortCode> {
-        // Try multiple times to generate a valid code
-        for attempt in 0..Self::MAX_ATTEMPTS {
-            let code = self.generate_alphanumeric(self.short_code_length);
+        self.generate_code(self.short_code_length)
+    }
 
-            match ShortCode::new(code) {
-                Ok(short_code) => return Ok(short_code),
-                Err(_) if attempt < Self::MAX_ATTEMPTS - 1 => continue,
-                Err(e) => return Err(e),
-            }
+    fn generate_short_code_with_length(&self, length: usize) -> Result<ShortCode> {
+        if !(ShortCode::MIN_LENGTH..=ShortCode::MAX_LENGTH).contains(&length) {
+            return Err(UrlShortenerError::InvalidShortCode(format!(
+                "Length must be between {} and {}",
+                ShortCode::MIN_LENGTH,
+                ShortCode::MAX_LENGTH
+            )));
         }
 
-        Err(UrlShortenerError::IdGenerationFailed(Self::MAX_ATTEMPTS))
+        self.generate_code(length)
+    }
+
+    fn keyspace(&self) -> Option<u64> {
+        let charset_len = self.charset.len() as u64;
+        Some(charset_len.saturating_pow(self.short_code_length as u32))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
 
     #[test]
     fn test_generate_id() {
@@ -135,6 +234,55 @@ mod tests {
         assert_eq!(code.as_str().len(), 8);
     }
 
+    #[test]
+    fn test_generate_short_code_with_length() {
+        let generator = RandomIdGenerator::new();
+
+        for length in ShortCode::MIN_LENGTH..=ShortCode::MAX_LENGTH {
+            let code = generator.generate_short_code_with_length(length).unwrap();
+            assert_eq!(code.as_str().len(), length);
+        }
+
+        let result = generator.generate_short_code_with_length(ShortCode::MAX_LENGTH + 1);
+        assert!(matches!(result, Err(UrlShortenerError::InvalidShortCode(_))));
+    }
+
+    #[test]
+    fn test_keyspace() {
+        assert_eq!(RandomIdGenerator::with_length(4).keyspace(), Some(62u64.pow(4)));
+        assert_eq!(RandomIdGenerator::new().keyspace(), Some(62u64.pow(6)));
+
+        // 62^12 doesn't fit in a u64
+        assert_eq!(RandomIdGenerator::with_length(12).keyspace(), Some(u64::MAX));
+    }
+
+    #[test]
+    fn test_unambiguous_codes_avoid_confusable_characters() {
+        assert_eq!(RandomIdGenerator::UNAMBIGUOUS_CHARSET.len(), 57);
+
+        let generator = RandomIdGenerator::unambiguous();
+        for _ in 0..1000 {
+            let code = generator.generate_short_code().unwrap();
+            assert!(!code.as_str().contains(['0', 'O', '1', 'l', 'I']), "{}", code);
+            assert!(ShortCode::is_valid(code.as_str()));
+        }
+
+        let code = generator.generate_short_code_with_length(ShortCode::MAX_LENGTH).unwrap();
+        assert!(!code.as_str().contains(['0', 'O', '1', 'l', 'I']));
+        assert_eq!(generator.keyspace(), Some(57u64.pow(6)));
+    }
+
+    #[test]
+    fn test_seeded_rng_sequence() {
+        let generator = RandomIdGenerator::new().with_rng(StdRng::seed_from_u64(42));
+
+        let codes: Vec<String> = (0..3)
+            .map(|_| generator.generate_short_code().unwrap().into_inner())
+            .collect();
+        // StdRng output is only stable within a rand release
+        assert_eq!(codes, ["GHNzcz", "T0ia5F", "yiGnaG"]);
+    }
+
     #[test]
     #[should_panic]
     fn test_invalid_length_too_short() {
@@ -0,0 +1,644 @@
+//! Redis implementation of UrlRepository
+//!
+//! Stores shortened URLs in Redis with the `redis` crate. Available with the
+//! `redis` feature.
+
+use crate::domain::{ShortCode, ShortenedUrl, day_index};
+use crate::error::{Result, UrlShortenerError};
+use crate::ports::UrlRepository;
+use redis::{Client, Connection, RedisError, Script};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Stores an entry and its counters unless its key already exists
+///
+/// ARGV: entry JSON, access count, last access time in Unix seconds (empty
+/// if never accessed), then pairs of `day:` or `ref:` field and count.
+const SAVE_SCRIPT: &str = r"
+if redis.call('EXISTS', KEYS[1]) == 1 then
+    return 0
+end
+redis.call('HSET', KEYS[1], 'entry', ARGV[1], 'access_count', ARGV[2])
+if ARGV[3] ~= '' then
+    redis.call('HSET', KEYS[1], 'last_accessed_at', ARGV[3])
+end
+for i = 4, #ARGV, 2 do
+    redis.call('HSET', KEYS[1], ARGV[i], ARGV[i + 1])
+end
+return 1
+";
+
+/// Replaces an entry only if its key already exists, keeping its counters
+const UPDATE_SCRIPT: &str = r"
+if redis.call('EXISTS', KEYS[1]) == 0 then
+    return 0
+end
+redis.call('HSET', KEYS[1], 'entry', ARGV[1])
+return 1
+";
+
+/// Records an access to an existing entry within its access limit
+///
+/// ARGV: access time in Unix seconds, its day index. Returns the new access
+/// count, -1 if the limit is reached, or nil if the key doesn't exist. Only
+/// the newest `history_days` per-day counts are kept.
+const INCREMENT_SCRIPT: &str = r"
+local entry = redis.call('HGET', KEYS[1], 'entry')
+if not entry then
+    return false
+end
+entry = cjson.decode(entry)
+
+local count = tonumber(redis.call('HGET', KEYS[1], 'access_count')) or 0
+local max = entry['max_accesses']
+if type(max) == 'number' and count >= max then
+    return -1
+end
+
+count = redis.call('HINCRBY', KEYS[1], 'access_count', 1)
+redis.call('HSET', KEYS[1], 'last_accessed_at', ARGV[1])
+redis.call('HINCRBY', KEYS[1], 'day:' .. ARGV[2], 1)
+
+local days = {}
+for _, field in ipairs(redis.call('HKEYS', KEYS[1])) do
+    local day = string.match(field, '^day:(-?%d+)$')
+    if day then
+        table.insert(days, tonumber(day))
+    end
+end
+table.sort(days)
+for i = 1, #days - (tonumber(entry['history_days']) or #days) do
+    redis.call('HDEL', KEYS[1], 'day:' .. days[i])
+end
+return count
+";
+
+/// Counts an access towards a referrer of an existing entry
+///
+/// ARGV: referrer, maximum number of referrers. Returns 0 if the key doesn't
+/// exist. Once the maximum is reached, a new referrer replaces the one with
+/// the lowest count, the first in byte order on ties.
+const REFERRER_SCRIPT: &str = r"
+if redis.call('EXISTS', KEYS[1]) == 0 then
+    return 0
+end
+
+local field = 'ref:' .. ARGV[1]
+if redis.call('HEXISTS', KEYS[1], field) == 0 then
+    local fields = redis.call('HGETALL', KEYS[1])
+    local referrers, least, least_count = 0, nil, nil
+    for i = 1, #fields, 2 do
+        local referrer = string.match(fields[i], '^ref:(.*)$')
+        if referrer then
+            local count = tonumber(fields[i + 1])
+            referrers = referrers + 1
+            if not least or count < least_count
+                or (count == least_count and referrer < least) then
+                least, least_count = referrer, count
+            end
+        end
+    end
+    if least and referrers >= tonumber(ARGV[2]) then
+        redis.call('HDEL', KEYS[1], 'ref:' .. least)
+    end
+end
+
+redis.call('HINCRBY', KEYS[1], field, 1)
+return 1
+";
+
+/// Redis-backed URL repository
+///
+/// Each entry is a Redis hash at `{prefix}{short_code}`. The `entry` field
+/// holds the entry serialized as JSON. The access counters are kept in
+/// separate fields so `increment_access_at` and `record_referrer` can update
+/// them atomically: `access_count`, `last_accessed_at` in Unix seconds, one
+/// `day:{index}` field per day with accesses and one `ref:{referrer}` field
+/// per referrer. These fields are authoritative when an entry is read back;
+/// `update` only replaces `entry` and leaves them alone.
+///
+/// `save` only writes if the key is absent and `update` only if it is
+/// present, each checked and written in a single Lua script. The connection
+/// is guarded by a mutex, so the repository can be shared between threads.
+///
+/// # Examples
+///
+/// ```no_run
+/// use url_shortener::adapters::RedisUrlRepository;
+/// use url_shortener::ports::UrlRepository;
+///
+/// let repo = RedisUrlRepository::new("redis://127.0.0.1/")
+///     .unwrap()
+///     .with_key_prefix("links:");
+/// println!("{} entries", repo.list_all().unwrap().len());
+/// ```
+pub struct RedisUrlRepository {
+    connection: Mutex<Connection>,
+    key_prefix: String,
+}
+
+impl RedisUrlRepository {
+    /// Default prefix for the keys holding entries
+    pub const DEFAULT_KEY_PREFIX: &'static str = "url_shortener:";
+
+    /// Number of keys requested per `SCAN` call in `list_all`
+    const SCAN_COUNT: usize = 100;
+
+    /// Connect to the Redis server at `url`
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::StorageUnavailable` if the server can't be
+    /// reached, or `UrlShortenerError::RepositoryError` if the URL is invalid
+    pub fn new(url: &str) -> Result<Self> {
+        let connection = Client::open(url)
+            .and_then(|client| client.get_connection())
+            .map_err(|e| redis_error("Failed to connect to Redis", e))?;
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+            key_prefix: Self::DEFAULT_KEY_PREFIX.to_string(),
+        })
+    }
+
+    /// Use a custom prefix for the keys holding entries
+    ///
+    /// Defaults to `DEFAULT_KEY_PREFIX`. Repositories with different
+    /// prefixes can share a Redis database without seeing each other's entries.
+    pub fn with_key_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.key_prefix = prefix.into();
+        self
+    }
+
+    /// Get the prefix for the keys holding entries
+    pub fn key_prefix(&self) -> &str {
+        &self.key_prefix
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Connection>> {
+        self.connection.lock().map_err(|e| UrlShortenerError::StorageUnavailable(
+            format!("Failed to acquire connection lock: {}", e)
+        ))
+    }
+
+    fn key(&self, code: &str) -> String {
+        format!("{}{}", self.key_prefix, code)
+    }
+
+    /// `SCAN` pattern matching every key under the prefix
+    fn pattern(&self) -> String {
+        let mut pattern = String::with_capacity(self.key_prefix.len() + 1);
+        for c in self.key_prefix.chars() {
+            if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+                pattern.push('\\');
+            }
+            pattern.push(c);
+        }
+        pattern.push('*');
+        pattern
+    }
+
+    /// Write an entry with the given script, returning whether it was written
+    ///
+    /// With `counters`, the access counters are passed to the script as well.
+    fn write(&self, script: &str, url: &ShortenedUrl, counters: bool) -> Result<bool> {
+        let entry = serde_json::to_string(url).map_err(|e| {
+            UrlShortenerError::repository_with_source("Failed to serialize entry", e)
+        })?;
+
+        let script = Script::new(script);
+        let mut invocation = script.key(self.key(url.short_code().as_str()));
+        invocation.arg(entry);
+        if counters {
+            let last_accessed_at = url.last_accessed_at().map(to_secs);
+            invocation
+                .arg(url.access_count())
+                .arg(last_accessed_at.map_or(String::new(), |secs| secs.to_string()));
+            for (day, count) in url.access_counts_by_day() {
+                invocation.arg(format!("day:{}", day)).arg(*count);
+            }
+            for (referrer, count) in url.referrer_counts() {
+                invocation.arg(format!("ref:{}", referrer)).arg(*count);
+            }
+        }
+
+        let mut connection = self.lock()?;
+
+        let written: i64 = invocation
+            .invoke(&mut *connection)
+            .map_err(|e| redis_error("Failed to write entry", e))?;
+
+        Ok(written == 1)
+    }
+
+    /// Read the entry at `key`, or `None` if it doesn't exist
+    fn find_key(connection: &mut Connection, key: &str) -> Result<Option<ShortenedUrl>> {
+        let mut fields: HashMap<String, String> = redis::cmd("HGETALL")
+            .arg(key)
+            .query(connection)
+            .map_err(|e| redis_error("Failed to read entry", e))?;
+
+        let Some(entry) = fields.remove("entry") else {
+            return Ok(None);
+        };
+
+        let url: ShortenedUrl = serde_json::from_str(&entry).map_err(|e| {
+            UrlShortenerError::repository_with_source(format!("Corrupt entry at {}", key), e)
+        })?;
+
+        let counter = |value: &str| {
+            value.parse::<u64>().map_err(|e| {
+                UrlShortenerError::repository_with_source(format!("Corrupt counter at {}", key), e)
+            })
+        };
+        let access_count = match fields.get("access_count") {
+            Some(count) => counter(count)?,
+            None => 0,
+        };
+        let mut days = BTreeMap::new();
+        let mut referrers = HashMap::new();
+        for (field, count) in &fields {
+            if let Some(day) = field.strip_prefix("day:").and_then(|day| day.parse().ok()) {
+                days.insert(day, counter(count)?);
+            } else if let Some(referrer) = field.strip_prefix("ref:") {
+                referrers.insert(referrer.to_string(), counter(count)?);
+            }
+        }
+
+        let url = url
+            .with_access_count(access_count)
+            .with_access_counts_by_day(days)
+            .with_referrer_counts(referrers);
+        Ok(Some(match fields.get("last_accessed_at") {
+            Some(secs) => {
+                url.with_last_accessed_at(UNIX_EPOCH + Duration::from_secs(counter(secs)?))
+            }
+            None => url,
+        }))
+    }
+}
+
+impl UrlRepository for RedisUrlRepository {
+    fn save(&self, url: ShortenedUrl) -> Result<()> {
+        if self.write(SAVE_SCRIPT, &url, true)? {
+            Ok(())
+        } else {
+            Err(UrlShortenerError::ShortCodeAlreadyExists(url.short_code().as_str().to_string()))
+        }
+    }
+
+    fn find_by_short_code(&self, code: &ShortCode) -> Result<ShortenedUrl> {
+        let mut connection = self.lock()?;
+
+        Self::find_key(&mut connection, &self.key(code.as_str()))?
+            .ok_or_else(|| UrlShortenerError::ShortCodeNotFound(code.as_str().to_string()))
+    }
+
+    fn update(&self, url: ShortenedUrl) -> Result<()> {
+        if self.write(UPDATE_SCRIPT, &url, false)? {
+            Ok(())
+        } else {
+            Err(UrlShortenerError::ShortCodeNotFound(url.short_code().as_str().to_string()))
+        }
+    }
+
+    fn increment_access_at(&self, code: &ShortCode, at: SystemTime) -> Result<u64> {
+        let mut connection = self.lock()?;
+
+        let count: Option<i64> = Script::new(INCREMENT_SCRIPT)
+            .key(self.key(code.as_str()))
+            .arg(to_secs(at))
+            .arg(day_index(at))
+            .invoke(&mut *connection)
+            .map_err(|e| redis_error("Failed to record access", e))?;
+
+        match count {
+            Some(-1) => Err(UrlShortenerError::AccessLimitReached(code.as_str().to_string())),
+            Some(count) => Ok(count.max(0) as u64),
+            None => Err(UrlShortenerError::ShortCodeNotFound(code.as_str().to_string())),
+        }
+    }
+
+    fn record_referrer(&self, code: &ShortCode, referrer: Option<String>) -> Result<()> {
+        let mut connection = self.lock()?;
+
+        let recorded: i64 = Script::new(REFERRER_SCRIPT)
+            .key(self.key(code.as_str()))
+            .arg(ShortenedUrl::referrer_key(referrer))
+            .arg(ShortenedUrl::MAX_REFERRERS)
+            .invoke(&mut *connection)
+            .map_err(|e| redis_error("Failed to record referrer", e))?;
+
+        if recorded == 0 {
+            return Err(UrlShortenerError::ShortCodeNotFound(code.as_str().to_string()));
+        }
+
+        Ok(())
+    }
+
+    fn exists(&self, code: &ShortCode) -> Result<bool> {
+        let mut connection = self.lock()?;
+
+        redis::cmd("EXISTS")
+            .arg(self.key(code.as_str()))
+            .query(&mut *connection)
+            .map_err(|e| redis_error("Failed to check entry", e))
+    }
+
+    fn delete(&self, code: &ShortCode) -> Result<()> {
+        let mut connection = self.lock()?;
+
+        let deleted: i64 = redis::cmd("DEL")
+            .arg(self.key(code.as_str()))
+            .query(&mut *connection)
+            .map_err(|e| redis_error("Failed to delete entry", e))?;
+
+        if deleted == 0 {
+            return Err(UrlShortenerError::ShortCodeNotFound(code.as_str().to_string()));
+        }
+
+        Ok(())
+    }
+
+    fn list_all(&self) -> Result<Vec<ShortenedUrl>> {
+        let mut connection = self.lock()?;
+
+        let pattern = self.pattern();
+        let mut keys = Vec::new();
+        let mut cursor = 0u64;
+        loop {
+            let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(Self::SCAN_COUNT)
+                .query(&mut *connection)
+                .map_err(|e| redis_error("Failed to list entries", e))?;
+
+            keys.extend(batch);
+            cursor = next;
+            if cursor == 0 {
+                break;
+            }
+        }
+
+        // SCAN may return a key more than once
+        keys.sort();
+        keys.dedup();
+
+        let mut urls = Vec::with_capacity(keys.len());
+        for key in keys {
+            // Entries deleted since the scan are skipped
+            if let Some(url) = Self::find_key(&mut connection, &key)? {
+                urls.push(url);
+            }
+        }
+
+        Ok(urls)
+    }
+}
+
+/// Unix seconds of `time`, clamped to zero before the epoch
+fn to_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Map a Redis error, treating connection failures as `StorageUnavailable`
+fn redis_error(message: &str, error: RedisError) -> UrlShortenerError {
+    if error.is_io_error()
+        || error.is_connection_refusal()
+        || error.is_connection_dropped()
+        || error.is_timeout()
+    {
+        UrlShortenerError::StorageUnavailable(format!("{}: {}", message, error))
+    } else {
+        UrlShortenerError::repository_with_source(message, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{OriginalUrl, UrlId};
+    use std::thread;
+
+    /// Connect to the server in `REDIS_URL` with a key prefix private to the
+    /// test, or return `None` to skip the test when no server is configured
+    fn test_repository(name: &str) -> Option<RedisUrlRepository> {
+        let Ok(url) = std::env::var("REDIS_URL") else {
+            eprintln!("REDIS_URL not set, skipping {}", name);
+            return None;
+        };
+
+        let prefix = format!("url_shortener_test:{}:{}:", std::process::id(), name);
+        let repo = RedisUrlRepository::new(&url).unwrap().with_key_prefix(prefix);
+        clear(&repo);
+        Some(repo)
+    }
+
+    fn clear(repo: &RedisUrlRepository) {
+        for url in repo.list_all().unwrap() {
+            repo.delete(url.short_code()).unwrap();
+        }
+    }
+
+    fn create_test_url(code: &str) -> ShortenedUrl {
+        let id = UrlId::new(format!("id-{}", code));
+        let short_code = ShortCode::new(code.to_string()).unwrap();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        ShortenedUrl::new(id, short_code, url)
+    }
+
+    #[test]
+    fn test_save_find_update_delete() {
+        let Some(repo) = test_repository("crud") else { return };
+        let code = ShortCode::new("crud1234".to_string()).unwrap();
+
+        repo.save(create_test_url("crud1234")).unwrap();
+        assert!(repo.exists(&code).unwrap());
+
+        let mut url = repo.find_by_short_code(&code).unwrap();
+        assert_eq!(url.id().as_str(), "id-crud1234");
+        url.record_access();
+        url.add_note("ops".to_string(), "checked".to_string(), SystemTime::now()).unwrap();
+        repo.update(url).unwrap();
+
+        let found = repo.find_by_short_code(&code).unwrap();
+        assert_eq!(found.access_count(), 1);
+        assert_eq!(found.notes().len(), 1);
+        assert!(found.last_accessed_at().is_some());
+
+        repo.delete(&code).unwrap();
+        assert!(!repo.exists(&code).unwrap());
+    }
+
+    #[test]
+    fn test_urls_outside_default_limits_read_back() {
+        let Some(repo) = test_repository("wide") else { return };
+        let targets = [
+            OriginalUrl::new_with_schemes("mailto:team@example.com".to_string(), &["mailto"]),
+            OriginalUrl::new_with_schemes("ftp://example.com/file".to_string(), &["ftp"]),
+            OriginalUrl::new_with_max_len(
+                format!("https://example.com/{}", "a".repeat(3000)),
+                4096,
+            ),
+        ];
+
+        for (i, target) in targets.into_iter().enumerate() {
+            let code = ShortCode::new(format!("wide{:04}", i)).unwrap();
+            let id = UrlId::new(code.to_string());
+            let url = ShortenedUrl::new(id, code.clone(), target.unwrap());
+            repo.save(url.clone()).unwrap();
+
+            assert_eq!(repo.find_by_short_code(&code).unwrap().original_url(), url.original_url());
+        }
+        assert_eq!(repo.list_all().unwrap().len(), 3);
+
+        clear(&repo);
+    }
+
+    #[test]
+    fn test_errors_match_in_memory_repository() {
+        let Some(repo) = test_repository("errors") else { return };
+        let missing = ShortCode::new("missing1".to_string()).unwrap();
+
+        repo.save(create_test_url("dupe1234")).unwrap();
+        let result = repo.save(create_test_url("dupe1234"));
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeAlreadyExists(_))));
+
+        let result = repo.update(create_test_url("missing1"));
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+        assert!(!repo.exists(&missing).unwrap());
+
+        let result = repo.delete(&missing);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+
+        let result = repo.increment_access(&missing);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+
+        repo.save(create_test_url("limit123").with_max_accesses(1)).unwrap();
+        let limited = ShortCode::new("limit123".to_string()).unwrap();
+        assert_eq!(repo.increment_access(&limited).unwrap(), 1);
+        let result = repo.increment_access(&limited);
+        assert!(matches!(result, Err(UrlShortenerError::AccessLimitReached(_))));
+
+        clear(&repo);
+    }
+
+    #[test]
+    fn test_list_all_scans_prefix() {
+        let Some(repo) = test_repository("list") else { return };
+        let Some(other) = test_repository("list_other") else { return };
+
+        for i in 0..250 {
+            repo.save(create_test_url(&format!("list{:04}", i))).unwrap();
+        }
+        other.save(create_test_url("other123")).unwrap();
+
+        assert_eq!(repo.list_all().unwrap().len(), 250);
+        assert_eq!(other.list_all().unwrap().len(), 1);
+
+        clear(&repo);
+        clear(&other);
+    }
+
+    #[test]
+    fn test_concurrent_increments_are_not_lost() {
+        let Some(repo) = test_repository("increment") else { return };
+        let url = std::env::var("REDIS_URL").unwrap();
+        let code = ShortCode::new("incr1234".to_string()).unwrap();
+        repo.save(create_test_url("incr1234")).unwrap();
+
+        let prefix = repo.key_prefix().to_string();
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let repo = RedisUrlRepository::new(&url).unwrap().with_key_prefix(prefix.clone());
+                let code = code.clone();
+                thread::spawn(move || {
+                    for _ in 0..50 {
+                        repo.increment_access(&code).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let found = repo.find_by_short_code(&code).unwrap();
+        assert_eq!(found.access_count(), 200);
+        assert_eq!(found.access_counts_by_day().values().sum::<u64>(), 200);
+        assert!(found.last_accessed_at().is_some());
+
+        clear(&repo);
+    }
+
+    #[test]
+    fn test_update_keeps_concurrent_increments() {
+        let Some(repo) = test_repository("update_counters") else { return };
+        let code = ShortCode::new("keep1234".to_string()).unwrap();
+        repo.save(create_test_url("keep1234")).unwrap();
+
+        // A stale copy written back after an increment must not undo it
+        let mut stale = repo.find_by_short_code(&code).unwrap();
+        let at = UNIX_EPOCH + Duration::from_secs(3 * 86_400 + 5);
+        repo.increment_access_at(&code, at).unwrap();
+        stale.add_note("ops".to_string(), "checked".to_string(), SystemTime::now()).unwrap();
+        repo.update(stale).unwrap();
+
+        let found = repo.find_by_short_code(&code).unwrap();
+        assert_eq!(found.access_count(), 1);
+        assert_eq!(found.notes().len(), 1);
+        assert_eq!(found.last_accessed_at(), Some(at));
+        assert_eq!(found.access_counts_by_day().get(&3), Some(&1));
+
+        clear(&repo);
+    }
+
+    #[test]
+    fn test_referrers_keep_concurrent_increments() {
+        let Some(repo) = test_repository("referrers") else { return };
+        let code = ShortCode::new("refr1234".to_string()).unwrap();
+        let mut url = create_test_url("refr1234");
+        url.record_referrer(Some("saved".to_string()));
+        repo.save(url).unwrap();
+
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..25 {
+                        repo.increment_access(&code).unwrap();
+                        repo.record_referrer(&code, Some("newsletter".to_string())).unwrap();
+                    }
+                });
+            }
+        });
+
+        let found = repo.find_by_short_code(&code).unwrap();
+        assert_eq!(found.access_count(), 100);
+        assert_eq!(found.referrer_counts().get("newsletter"), Some(&100));
+        assert_eq!(found.referrer_counts().get("saved"), Some(&1));
+
+        // A full set of referrers evicts the least counted one, like the domain
+        let mut expected = found.clone();
+        for i in 0..ShortenedUrl::MAX_REFERRERS {
+            let referrer = Some(format!("site{:03}", i));
+            repo.record_referrer(&code, referrer.clone()).unwrap();
+            expected.record_referrer(referrer);
+        }
+        repo.record_referrer(&code, None).unwrap();
+        expected.record_referrer(None);
+        let found = repo.find_by_short_code(&code).unwrap();
+        assert_eq!(found.referrer_counts(), expected.referrer_counts());
+
+        let missing = ShortCode::new("missing1".to_string()).unwrap();
+        let result = repo.record_referrer(&missing, None);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+
+        clear(&repo);
+    }
+}
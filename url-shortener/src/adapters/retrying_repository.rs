@@ -0,0 +1,355 @@
+//! Repository decorator that retries transient failures
+
+use crate::domain::{OriginalUrl, ShortCode, ShortenedUrl};
+use crate::error::{Result, UrlShortenerError};
+use crate::ports::UrlRepository;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Repository decorator that retries operations failing with storage errors
+///
+/// Operations that fail with `StorageUnavailable` or `RepositoryError` are
+/// retried up to `max_attempts` times in total, sleeping `backoff` before the
+/// first retry and doubling it before each further one. Logical errors such
+/// as `ShortCodeNotFound` or `ShortCodeAlreadyExists` are returned right away.
+///
+/// A write that failed after the backend applied it is applied again by the
+/// retry. `save` then reports `ShortCodeAlreadyExists`, and
+/// `increment_access` may count an access twice.
+///
+/// `save_batch` and `delete_many` retry entry by entry through `save` and
+/// `delete`, instead of calling the inner repository's batch methods.
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::adapters::{InMemoryUrlRepository, RetryingRepository};
+/// use std::time::Duration;
+///
+/// let repo = RetryingRepository::new(InMemoryUrlRepository::new())
+///     .with_max_attempts(5)
+///     .with_backoff(Duration::from_millis(10));
+/// assert_eq!(repo.max_attempts(), 5);
+/// ```
+pub struct RetryingRepository<R: UrlRepository> {
+    inner: R,
+    max_attempts: usize,
+    backoff: Duration,
+}
+
+impl<R: UrlRepository> RetryingRepository<R> {
+    /// Default number of attempts per operation, including the first
+    pub const DEFAULT_MAX_ATTEMPTS: usize = 3;
+
+    /// Default wait before the first retry
+    pub const DEFAULT_BACKOFF: Duration = Duration::from_millis(50);
+
+    /// Wrap a repository with the default retry policy
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            max_attempts: Self::DEFAULT_MAX_ATTEMPTS,
+            backoff: Self::DEFAULT_BACKOFF,
+        }
+    }
+
+    /// Set the number of attempts per operation, including the first
+    ///
+    /// Values below 1 are treated as 1, which disables retries.
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the wait before the first retry; later waits double
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Get the wrapped repository
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Number of attempts per operation, including the first
+    pub fn max_attempts(&self) -> usize {
+        self.max_attempts
+    }
+
+    /// Run `operation`, retrying it while it fails with a storage error
+    fn retry<T>(&self, mut operation: impl FnMut() -> Result<T>) -> Result<T> {
+        let mut backoff = self.backoff;
+        let mut attempt = 1;
+
+        loop {
+            match operation() {
+                Err(e) if is_transient(&e) && attempt < self.max_attempts => {
+                    thread::sleep(backoff);
+                    backoff = backoff.saturating_mul(2);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Whether an error may go away when the operation is retried
+fn is_transient(error: &UrlShortenerError) -> bool {
+    matches!(
+        error,
+        UrlShortenerError::StorageUnavailable(_) | UrlShortenerError::RepositoryError { .. }
+    )
+}
+
+impl<R: UrlRepository> UrlRepository for RetryingRepository<R> {
+    fn save(&self, url: ShortenedUrl) -> Result<()> {
+        self.retry(|| self.inner.save(url.clone()))
+    }
+
+    fn find_by_short_code(&self, code: &ShortCode) -> Result<ShortenedUrl> {
+        self.retry(|| self.inner.find_by_short_code(code))
+    }
+
+    fn update(&self, url: ShortenedUrl) -> Result<()> {
+        self.retry(|| self.inner.update(url.clone()))
+    }
+
+    fn increment_access(&self, code: &ShortCode) -> Result<u64> {
+        self.retry(|| self.inner.increment_access(code))
+    }
+
+    fn increment_access_at(&self, code: &ShortCode, at: SystemTime) -> Result<u64> {
+        self.retry(|| self.inner.increment_access_at(code, at))
+    }
+
+    fn record_referrer(&self, code: &ShortCode, referrer: Option<String>) -> Result<()> {
+        self.retry(|| self.inner.record_referrer(code, referrer.clone()))
+    }
+
+    fn exists(&self, code: &ShortCode) -> Result<bool> {
+        self.retry(|| self.inner.exists(code))
+    }
+
+    fn delete(&self, code: &ShortCode) -> Result<()> {
+        self.retry(|| self.inner.delete(code))
+    }
+
+    fn list_all(&self) -> Result<Vec<ShortenedUrl>> {
+        self.retry(|| self.inner.list_all())
+    }
+
+    fn clear(&self) -> Result<()> {
+        self.retry(|| self.inner.clear())
+    }
+
+    fn delete_expired(&self, now: SystemTime) -> Result<usize> {
+        self.retry(|| self.inner.delete_expired(now))
+    }
+
+    fn list_paginated(&self, offset: usize, limit: usize) -> Result<Vec<ShortenedUrl>> {
+        self.retry(|| self.inner.list_paginated(offset, limit))
+    }
+
+    fn count(&self) -> Result<usize> {
+        self.retry(|| self.inner.count())
+    }
+
+    fn created_between(&self, start: SystemTime, end: SystemTime) -> Result<Vec<ShortenedUrl>> {
+        self.retry(|| self.inner.created_between(start, end))
+    }
+
+    fn find_by_original_url(&self, url: &OriginalUrl) -> Result<Option<ShortenedUrl>> {
+        self.retry(|| self.inner.find_by_original_url(url))
+    }
+
+    fn find_by_tag(&self, tag: &str) -> Result<Vec<ShortenedUrl>> {
+        self.retry(|| self.inner.find_by_tag(tag))
+    }
+
+    fn find_by_domain(&self, domain: &str) -> Result<Vec<ShortenedUrl>> {
+        self.retry(|| self.inner.find_by_domain(domain))
+    }
+
+    fn find_by_access_count_range(&self, min: u64, max: u64) -> Result<Vec<ShortenedUrl>> {
+        self.retry(|| self.inner.find_by_access_count_range(min, max))
+    }
+
+    fn swap_original_urls(&self, a: &ShortCode, b: &ShortCode) -> Result<()> {
+        self.retry(|| self.inner.swap_original_urls(a, b))
+    }
+
+    fn reserve(&self, code: &ShortCode, now: SystemTime, until: SystemTime) -> Result<()> {
+        self.retry(|| self.inner.reserve(code, now, until))
+    }
+
+    fn is_reserved(&self, code: &ShortCode, now: SystemTime) -> Result<bool> {
+        self.retry(|| self.inner.is_reserved(code, now))
+    }
+
+    fn release_reservation(&self, code: &ShortCode) -> Result<()> {
+        self.retry(|| self.inner.release_reservation(code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::InMemoryUrlRepository;
+    use crate::domain::UrlId;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Repository whose first `failures` calls fail with a storage error
+    struct FlakyRepository {
+        inner: InMemoryUrlRepository,
+        failures: AtomicUsize,
+        calls: AtomicUsize,
+    }
+
+    impl FlakyRepository {
+        fn new(failures: usize) -> Self {
+            Self {
+                inner: InMemoryUrlRepository::new(),
+                failures: AtomicUsize::new(failures),
+                calls: AtomicUsize::new(0),
+            }
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+
+        /// Count the call and fail it while failures remain
+        fn call(&self) -> Result<()> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let remaining = self.failures.load(Ordering::SeqCst);
+
+            if remaining > 0 {
+                self.failures.store(remaining - 1, Ordering::SeqCst);
+                return Err(UrlShortenerError::StorageUnavailable("connection reset".to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    impl UrlRepository for FlakyRepository {
+        fn save(&self, url: ShortenedUrl) -> Result<()> {
+            self.call()?;
+            self.inner.save(url)
+        }
+
+        fn find_by_short_code(&self, code: &ShortCode) -> Result<ShortenedUrl> {
+            self.call()?;
+            self.inner.find_by_short_code(code)
+        }
+
+        fn update(&self, url: ShortenedUrl) -> Result<()> {
+            self.call()?;
+            self.inner.update(url)
+        }
+
+        fn exists(&self, code: &ShortCode) -> Result<bool> {
+            self.call()?;
+            self.inner.exists(code)
+        }
+
+        fn delete(&self, code: &ShortCode) -> Result<()> {
+            self.call()?;
+            self.inner.delete(code)
+        }
+
+        fn list_all(&self) -> Result<Vec<ShortenedUrl>> {
+            self.call()?;
+            self.inner.list_all()
+        }
+    }
+
+    fn create_test_url(code: &str) -> ShortenedUrl {
+        let id = UrlId::new(format!("id-{}", code));
+        let short_code = ShortCode::new(code.to_string()).unwrap();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        ShortenedUrl::new(id, short_code, url)
+    }
+
+    fn retrying(failures: usize) -> RetryingRepository<FlakyRepository> {
+        RetryingRepository::new(FlakyRepository::new(failures)).with_backoff(Duration::ZERO)
+    }
+
+    #[test]
+    fn test_retries_until_success() {
+        let repo = retrying(2);
+
+        repo.save(create_test_url("retry123")).unwrap();
+        assert_eq!(repo.inner().calls(), 3);
+        assert_eq!(repo.inner().inner.len(), 1);
+    }
+
+    #[test]
+    fn test_gives_up_after_max_attempts() {
+        let repo = retrying(5).with_max_attempts(3);
+
+        let result = repo.list_all();
+        assert!(matches!(result, Err(UrlShortenerError::StorageUnavailable(_))));
+        assert_eq!(repo.inner().calls(), 3);
+
+        // Retries are disabled with a single attempt
+        let repo = retrying(5).with_max_attempts(0);
+        assert!(repo.list_all().is_err());
+        assert_eq!(repo.inner().calls(), 1);
+    }
+
+    #[test]
+    fn test_logical_errors_are_not_retried() {
+        let repo = retrying(0);
+        let code = ShortCode::new("missing1".to_string()).unwrap();
+
+        let result = repo.find_by_short_code(&code);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+        assert_eq!(repo.inner().calls(), 1);
+
+        repo.save(create_test_url("dupe1234")).unwrap();
+        let result = repo.save(create_test_url("dupe1234"));
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeAlreadyExists(_))));
+        assert_eq!(repo.inner().calls(), 3);
+    }
+
+    #[test]
+    fn test_repository_errors_are_retried() {
+        struct Broken(AtomicUsize);
+
+        impl Broken {
+            fn fail<T>(&self) -> Result<T> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Err(UrlShortenerError::repository("disk I/O error"))
+            }
+        }
+
+        impl UrlRepository for Broken {
+            fn save(&self, _: ShortenedUrl) -> Result<()> {
+                self.fail()
+            }
+            fn find_by_short_code(&self, _: &ShortCode) -> Result<ShortenedUrl> {
+                self.fail()
+            }
+            fn update(&self, _: ShortenedUrl) -> Result<()> {
+                self.fail()
+            }
+            fn exists(&self, _: &ShortCode) -> Result<bool> {
+                self.fail()
+            }
+            fn delete(&self, _: &ShortCode) -> Result<()> {
+                self.fail()
+            }
+            fn list_all(&self) -> Result<Vec<ShortenedUrl>> {
+                self.fail()
+            }
+        }
+
+        let repo = RetryingRepository::new(Broken(AtomicUsize::new(0)))
+            .with_max_attempts(4)
+            .with_backoff(Duration::ZERO);
+        assert!(matches!(repo.count(), Err(UrlShortenerError::RepositoryError { .. })));
+        assert_eq!(repo.inner().0.load(Ordering::SeqCst), 4);
+    }
+}
@@ -0,0 +1,141 @@
+//! Sequential ID generator implementation
+
+use super::RandomIdGenerator;
+use crate::domain::{ShortCode, UrlId};
+use crate::encoding::{BASE62_CHARSET, base62_encode};
+use crate::error::{Result, UrlShortenerError};
+use crate::ports::IdGenerator;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Sequential ID generator producing dense base62 short codes
+///
+/// Each short code encodes the next value of an atomic counter in base62,
+/// left-padded to `ShortCode::MIN_LENGTH`, so codes never collide with each
+/// other. Codes use the same alphanumeric charset as `RandomIdGenerator`, with
+/// `a` as the zero digit: the first codes are `aaaa`, `aaab`, `aaac`, ...
+///
+/// URL ids are still random, since they don't need to be short.
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::adapters::SequentialIdGenerator;
+/// use url_shortener::ports::IdGenerator;
+///
+/// let generator = SequentialIdGenerator::new();
+/// assert_eq!(generator.generate_short_code().unwrap().as_str(), "aaaa");
+/// assert_eq!(generator.generate_short_code().unwrap().as_str(), "aaab");
+/// ```
+pub struct SequentialIdGenerator {
+    next: AtomicU64,
+    ids: RandomIdGenerator,
+}
+
+impl SequentialIdGenerator {
+    /// Create a generator whose counter starts at zero
+    pub fn new() -> Self {
+        Self::with_start(0)
+    }
+
+    /// Create a generator whose counter starts at `value`
+    ///
+    /// Use this to resume after a restart, passing the counter value
+    /// following the last code handed out (see `next_value`).
+    pub fn with_start(value: u64) -> Self {
+        Self {
+            next: AtomicU64::new(value),
+            ids: RandomIdGenerator::new(),
+        }
+    }
+
+    /// Counter value the next short code will encode
+    pub fn next_value(&self) -> u64 {
+        self.next.load(Ordering::SeqCst)
+    }
+
+    /// Encode a counter value as base62, padded to `ShortCode::MIN_LENGTH`
+    fn encode(value: u64) -> String {
+        let digits = base62_encode(value);
+        let padding = ShortCode::MIN_LENGTH.saturating_sub(digits.len());
+
+        let zero = BASE62_CHARSET[0] as char;
+        std::iter::repeat_n(zero, padding).chain(digits.chars()).collect()
+    }
+}
+
+impl Default for SequentialIdGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdGenerator for SequentialIdGenerator {
+    fn generate_id(&self) -> UrlId {
+        self.ids.generate_id()
+    }
+
+    fn generate_short_code(&self) -> Result<ShortCode> {
+        // u64::MAX encodes to 11 characters, well within ShortCode::MAX_LENGTH
+        let value = self.next
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_add(1))
+            .map_err(|_| UrlShortenerError::IdGenerationFailed(1))?;
+
+        ShortCode::new(Self::encode(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_codes_are_deterministic() {
+        let generator = SequentialIdGenerator::new();
+        let codes: Vec<String> = (0..4)
+            .map(|_| generator.generate_short_code().unwrap().as_str().to_string())
+            .collect();
+
+        assert_eq!(codes, vec!["aaaa", "aaab", "aaac", "aaad"]);
+    }
+
+    #[test]
+    fn test_codes_strictly_increase() {
+        let generator = SequentialIdGenerator::with_start(60);
+        let codes: Vec<String> = (0..4)
+            .map(|_| generator.generate_short_code().unwrap().as_str().to_string())
+            .collect();
+
+        // Digits roll over after '9', the last character of the charset
+        assert_eq!(codes, vec!["aaa8", "aaa9", "aaba", "aabb"]);
+
+        let digit = |c: u8| BASE62_CHARSET.iter().position(|&d| d == c);
+        for pair in codes.windows(2) {
+            let a: Vec<_> = pair[0].bytes().map(digit).collect();
+            let b: Vec<_> = pair[1].bytes().map(digit).collect();
+            assert!(a < b, "{} should sort before {}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn test_with_start_resumes() {
+        let generator = SequentialIdGenerator::with_start(62 * 62 * 62 * 62);
+        assert_eq!(generator.generate_short_code().unwrap().as_str(), "baaaa");
+        assert_eq!(generator.next_value(), 62 * 62 * 62 * 62 + 1);
+    }
+
+    #[test]
+    fn test_largest_value_is_valid() {
+        let generator = SequentialIdGenerator::with_start(u64::MAX - 1);
+        let code = generator.generate_short_code().unwrap();
+        assert!(code.as_str().len() <= ShortCode::MAX_LENGTH);
+
+        let result = generator.generate_short_code();
+        assert!(matches!(result, Err(UrlShortenerError::IdGenerationFailed(_))));
+    }
+
+    #[test]
+    fn test_generate_id_unique() {
+        let generator = SequentialIdGenerator::new();
+        assert_ne!(generator.generate_id(), generator.generate_id());
+    }
+}
@@ -0,0 +1,1111 @@
+//! SQLite implementation of UrlRepository
+//!
+//! Persists shortened URLs with `rusqlite`. Available with the `sqlite` feature.
+
+use crate::domain::{OriginalUrl, ShortCode, ShortenedUrl, UrlId};
+use crate::error::{Result, UrlShortenerError};
+use crate::ports::UrlRepository;
+use rusqlite::{
+    Connection, ErrorCode, OptionalExtension, Params, Row, Transaction, params,
+};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// SQLite-backed URL repository
+///
+/// Entries live in a `shortened_urls` table with a unique index on
+/// `short_code`; notes, tags, per-day and per-referrer access counts live in
+/// the `url_notes`, `url_tags`, `url_daily_counts` and `url_referrers`
+/// tables. Timestamps are stored as Unix seconds. The connection is guarded
+/// by a mutex, so the repository can be shared between threads.
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::adapters::SqliteUrlRepository;
+/// use url_shortener::ports::UrlRepository;
+/// use url_shortener::domain::{UrlId, ShortCode, OriginalUrl, ShortenedUrl};
+///
+/// let repo = SqliteUrlRepository::in_memory().unwrap();
+///
+/// let code = ShortCode::new("abc123".to_string()).unwrap();
+/// let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+/// repo.save(ShortenedUrl::new(UrlId::new("1".to_string()), code.clone(), url)).unwrap();
+///
+/// assert!(repo.exists(&code).unwrap());
+/// ```
+pub struct SqliteUrlRepository {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteUrlRepository {
+    /// Open (or create) a database file and ensure the schema exists
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::RepositoryError` if the database cannot be
+    /// opened or the schema cannot be created
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        let connection = Connection::open(path)
+            .map_err(|e| UrlShortenerError::repository_with_source("Failed to open database", e))?;
+        Self::with_connection(connection)
+    }
+
+    /// Create a repository backed by a private in-memory database
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::RepositoryError` if the schema cannot be created
+    pub fn in_memory() -> Result<Self> {
+        let connection = Connection::open_in_memory()
+            .map_err(|e| UrlShortenerError::repository_with_source("Failed to open database", e))?;
+        Self::with_connection(connection)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self> {
+        connection
+            .execute_batch(
+                "PRAGMA foreign_keys = ON;
+                 CREATE TABLE IF NOT EXISTS shortened_urls (
+                     id TEXT NOT NULL,
+                     short_code TEXT NOT NULL,
+                     original_url TEXT NOT NULL,
+                     created_at INTEGER NOT NULL,
+                     access_count INTEGER NOT NULL DEFAULT 0,
+                     active_from INTEGER,
+                     expires_at INTEGER,
+                     last_accessed_at INTEGER,
+                     history_days INTEGER NOT NULL,
+                     enabled INTEGER NOT NULL DEFAULT 1,
+                     max_accesses INTEGER
+                 );
+                 CREATE UNIQUE INDEX IF NOT EXISTS idx_shortened_urls_short_code
+                     ON shortened_urls (short_code);
+                 CREATE INDEX IF NOT EXISTS idx_shortened_urls_original_url
+                     ON shortened_urls (original_url);
+                 CREATE TABLE IF NOT EXISTS url_notes (
+                     short_code TEXT NOT NULL
+                         REFERENCES shortened_urls (short_code) ON DELETE CASCADE,
+                     position INTEGER NOT NULL,
+                     author TEXT NOT NULL,
+                     text TEXT NOT NULL,
+                     at INTEGER NOT NULL,
+                     PRIMARY KEY (short_code, position)
+                 );
+                 CREATE TABLE IF NOT EXISTS url_tags (
+                     short_code TEXT NOT NULL
+                         REFERENCES shortened_urls (short_code) ON DELETE CASCADE,
+                     position INTEGER NOT NULL,
+                     tag TEXT NOT NULL,
+                     PRIMARY KEY (short_code, position)
+                 );
+                 CREATE INDEX IF NOT EXISTS idx_url_tags_tag ON url_tags (tag);
+                 CREATE TABLE IF NOT EXISTS url_daily_counts (
+                     short_code TEXT NOT NULL
+                         REFERENCES shortened_urls (short_code) ON DELETE CASCADE,
+                     day INTEGER NOT NULL,
+                     count INTEGER NOT NULL,
+                     PRIMARY KEY (short_code, day)
+                 );
+                 CREATE TABLE IF NOT EXISTS url_referrers (
+                     short_code TEXT NOT NULL
+                         REFERENCES shortened_urls (short_code) ON DELETE CASCADE,
+                     referrer TEXT NOT NULL,
+                     count INTEGER NOT NULL,
+                     PRIMARY KEY (short_code, referrer)
+                 );",
+            )
+            .map_err(|e| UrlShortenerError::repository_with_source("Failed to create schema", e))?;
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Connection>> {
+        self.connection.lock().map_err(|e| UrlShortenerError::StorageUnavailable(
+            format!("Failed to acquire connection lock: {}", e)
+        ))
+    }
+
+    /// Load the entries selected by `clause`, appended after `FROM`
+    fn list_rows(&self, clause: &str, params: impl Params) -> Result<Vec<ShortenedUrl>> {
+        let connection = self.lock()?;
+        let sql = format!(
+            "SELECT id, short_code, original_url, created_at, access_count, active_from,
+                    expires_at, last_accessed_at, history_days, enabled, max_accesses
+             FROM shortened_urls {}",
+            clause
+        );
+        let mut statement = connection.prepare(&sql).map_err(query_error)?;
+
+        let rows = statement
+            .query_map(params, RawRow::from_row)
+            .map_err(query_error)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(query_error)?;
+
+        rows.into_iter()
+            .map(|raw| Self::load_related(&connection, raw.into_domain()?))
+            .collect()
+    }
+
+    fn find_in(connection: &Connection, code: &ShortCode) -> Result<Option<ShortenedUrl>> {
+        Self::find_one(connection, "short_code", code.as_str())
+    }
+
+    /// Find the first row whose `column` equals `value`
+    fn find_one(
+        connection: &Connection,
+        column: &str,
+        value: &str,
+    ) -> Result<Option<ShortenedUrl>> {
+        let sql = format!(
+            "SELECT id, short_code, original_url, created_at, access_count, active_from,
+                    expires_at, last_accessed_at, history_days, enabled, max_accesses
+             FROM shortened_urls WHERE {} = ?1 ORDER BY short_code LIMIT 1",
+            column
+        );
+        let url = connection
+            .query_row(&sql, params![value], RawRow::from_row)
+            .optional()
+            .map_err(query_error)?;
+
+        match url {
+            Some(raw) => Ok(Some(Self::load_related(connection, raw.into_domain()?)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Attach notes, tags, per-day and per-referrer access counts to an entry
+    fn load_related(connection: &Connection, url: ShortenedUrl) -> Result<ShortenedUrl> {
+        let url = Self::load_notes(connection, url)?;
+        let url = Self::load_tags(connection, url)?;
+        let url = Self::load_referrers(connection, url)?;
+
+        let mut statement = connection
+            .prepare_cached("SELECT day, count FROM url_daily_counts WHERE short_code = ?1")
+            .map_err(query_error)?;
+
+        let days = statement
+            .query_map(params![url.short_code().as_str()], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?.max(0) as u64))
+            })
+            .map_err(query_error)?
+            .collect::<rusqlite::Result<_>>()
+            .map_err(query_error)?;
+
+        Ok(url.with_access_counts_by_day(days))
+    }
+
+    /// Replace the stored notes, tags, per-day and per-referrer access counts
+    /// of an entry
+    fn write_related(transaction: &Transaction<'_>, url: &ShortenedUrl) -> Result<()> {
+        Self::write_notes(transaction, url)?;
+        Self::write_tags(transaction, url)?;
+        Self::write_daily_counts(transaction, url)?;
+        Self::write_referrers(transaction, url)
+    }
+
+    fn write_daily_counts(transaction: &Transaction<'_>, url: &ShortenedUrl) -> Result<()> {
+        let code = url.short_code().as_str();
+        transaction
+            .execute("DELETE FROM url_daily_counts WHERE short_code = ?1", params![code])
+            .map_err(query_error)?;
+
+        for (day, count) in url.access_counts_by_day() {
+            transaction
+                .execute(
+                    "INSERT INTO url_daily_counts (short_code, day, count) VALUES (?1, ?2, ?3)",
+                    params![code, day, *count as i64],
+                )
+                .map_err(query_error)?;
+        }
+
+        Ok(())
+    }
+
+    fn load_referrers(connection: &Connection, url: ShortenedUrl) -> Result<ShortenedUrl> {
+        let mut statement = connection
+            .prepare_cached("SELECT referrer, count FROM url_referrers WHERE short_code = ?1")
+            .map_err(query_error)?;
+
+        let referrers = statement
+            .query_map(params![url.short_code().as_str()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?.max(0) as u64))
+            })
+            .map_err(query_error)?
+            .collect::<rusqlite::Result<_>>()
+            .map_err(query_error)?;
+
+        Ok(url.with_referrer_counts(referrers))
+    }
+
+    fn write_referrers(transaction: &Transaction<'_>, url: &ShortenedUrl) -> Result<()> {
+        let code = url.short_code().as_str();
+        transaction
+            .execute("DELETE FROM url_referrers WHERE short_code = ?1", params![code])
+            .map_err(query_error)?;
+
+        for (referrer, count) in url.referrer_counts() {
+            transaction
+                .execute(
+                    "INSERT INTO url_referrers (short_code, referrer, count) VALUES (?1, ?2, ?3)",
+                    params![code, referrer, *count as i64],
+                )
+                .map_err(query_error)?;
+        }
+
+        Ok(())
+    }
+
+    fn load_notes(connection: &Connection, mut url: ShortenedUrl) -> Result<ShortenedUrl> {
+        let mut statement = connection
+            .prepare_cached(
+                "SELECT author, text, at FROM url_notes WHERE short_code = ?1 ORDER BY position",
+            )
+            .map_err(query_error)?;
+
+        let notes = statement
+            .query_map(params![url.short_code().as_str()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?))
+            })
+            .map_err(query_error)?;
+
+        for note in notes {
+            let (author, text, at) = note.map_err(query_error)?;
+            url.add_note(author, text, from_secs(at))?;
+        }
+
+        Ok(url)
+    }
+
+    fn write_notes(transaction: &Transaction<'_>, url: &ShortenedUrl) -> Result<()> {
+        let code = url.short_code().as_str();
+        transaction
+            .execute("DELETE FROM url_notes WHERE short_code = ?1", params![code])
+            .map_err(query_error)?;
+
+        for (position, note) in url.notes().iter().enumerate() {
+            transaction
+                .execute(
+                    "INSERT INTO url_notes (short_code, position, author, text, at)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![code, position as i64, note.author(), note.text(), to_secs(note.at())],
+                )
+                .map_err(query_error)?;
+        }
+
+        Ok(())
+    }
+
+    fn load_tags(connection: &Connection, url: ShortenedUrl) -> Result<ShortenedUrl> {
+        let mut statement = connection
+            .prepare_cached("SELECT tag FROM url_tags WHERE short_code = ?1 ORDER BY position")
+            .map_err(query_error)?;
+
+        let tags: Vec<String> = statement
+            .query_map(params![url.short_code().as_str()], |row| row.get(0))
+            .map_err(query_error)?
+            .collect::<rusqlite::Result<_>>()
+            .map_err(query_error)?;
+
+        if tags.is_empty() {
+            return Ok(url);
+        }
+        url.with_tags(tags)
+    }
+
+    fn write_tags(transaction: &Transaction<'_>, url: &ShortenedUrl) -> Result<()> {
+        let code = url.short_code().as_str();
+        transaction
+            .execute("DELETE FROM url_tags WHERE short_code = ?1", params![code])
+            .map_err(query_error)?;
+
+        for (position, tag) in url.tags().iter().enumerate() {
+            transaction
+                .execute(
+                    "INSERT INTO url_tags (short_code, position, tag) VALUES (?1, ?2, ?3)",
+                    params![code, position as i64, tag],
+                )
+                .map_err(query_error)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl UrlRepository for SqliteUrlRepository {
+    fn save(&self, url: ShortenedUrl) -> Result<()> {
+        let mut connection = self.lock()?;
+        let transaction = connection.transaction().map_err(query_error)?;
+
+        transaction
+            .execute(
+                "INSERT INTO shortened_urls
+                     (id, short_code, original_url, created_at, access_count, active_from,
+                      expires_at, last_accessed_at, history_days, enabled, max_accesses)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    url.id().as_str(),
+                    url.short_code().as_str(),
+                    url.original_url().as_str(),
+                    to_secs(url.created_at()),
+                    url.access_count() as i64,
+                    url.active_from().map(to_secs),
+                    url.expires_at().map(to_secs),
+                    url.last_accessed_at().map(to_secs),
+                    url.history_days() as i64,
+                    url.is_enabled(),
+                    url.max_accesses().map(|max| max.min(i64::MAX as u64) as i64),
+                ],
+            )
+            .map_err(|e| match e.sqlite_error_code() {
+                Some(ErrorCode::ConstraintViolation) => {
+                    UrlShortenerError::ShortCodeAlreadyExists(url.short_code().as_str().to_string())
+                }
+                _ => query_error(e),
+            })?;
+
+        Self::write_related(&transaction, &url)?;
+        transaction.commit().map_err(query_error)
+    }
+
+    fn find_by_short_code(&self, code: &ShortCode) -> Result<ShortenedUrl> {
+        let connection = self.lock()?;
+        Self::find_in(&connection, code)?
+            .ok_or_else(|| UrlShortenerError::ShortCodeNotFound(code.as_str().to_string()))
+    }
+
+    fn update(&self, url: ShortenedUrl) -> Result<()> {
+        let mut connection = self.lock()?;
+        let transaction = connection.transaction().map_err(query_error)?;
+
+        let updated = transaction
+            .execute(
+                "UPDATE shortened_urls
+                 SET id = ?1, original_url = ?3, created_at = ?4, access_count = ?5,
+                     active_from = ?6, expires_at = ?7, last_accessed_at = ?8,
+                     history_days = ?9, enabled = ?10, max_accesses = ?11
+                 WHERE short_code = ?2",
+                params![
+                    url.id().as_str(),
+                    url.short_code().as_str(),
+                    url.original_url().as_str(),
+                    to_secs(url.created_at()),
+                    url.access_count() as i64,
+                    url.active_from().map(to_secs),
+                    url.expires_at().map(to_secs),
+                    url.last_accessed_at().map(to_secs),
+                    url.history_days() as i64,
+                    url.is_enabled(),
+                    url.max_accesses().map(|max| max.min(i64::MAX as u64) as i64),
+                ],
+            )
+            .map_err(query_error)?;
+
+        if updated == 0 {
+            return Err(UrlShortenerError::ShortCodeNotFound(
+                url.short_code().as_str().to_string()
+            ));
+        }
+
+        Self::write_related(&transaction, &url)?;
+        transaction.commit().map_err(query_error)
+    }
+
+    fn increment_access_at(&self, code: &ShortCode, at: SystemTime) -> Result<u64> {
+        // The connection lock and transaction keep the read-modify-write atomic
+        let mut connection = self.lock()?;
+        let transaction = connection.transaction().map_err(query_error)?;
+
+        let mut url = Self::find_in(&transaction, code)?
+            .ok_or_else(|| UrlShortenerError::ShortCodeNotFound(code.as_str().to_string()))?;
+        if url.is_access_limit_reached() {
+            return Err(UrlShortenerError::AccessLimitReached(code.as_str().to_string()));
+        }
+        url.record_access_at(at);
+
+        transaction
+            .execute(
+                "UPDATE shortened_urls SET access_count = ?1, last_accessed_at = ?2
+                 WHERE short_code = ?3",
+                params![
+                    url.access_count() as i64,
+                    url.last_accessed_at().map(to_secs),
+                    code.as_str(),
+                ],
+            )
+            .map_err(query_error)?;
+        Self::write_daily_counts(&transaction, &url)?;
+        transaction.commit().map_err(query_error)?;
+
+        Ok(url.access_count())
+    }
+
+    fn record_referrer(&self, code: &ShortCode, referrer: Option<String>) -> Result<()> {
+        let mut connection = self.lock()?;
+        let transaction = connection.transaction().map_err(query_error)?;
+
+        let mut url = Self::find_in(&transaction, code)?
+            .ok_or_else(|| UrlShortenerError::ShortCodeNotFound(code.as_str().to_string()))?;
+        url.record_referrer(referrer);
+
+        Self::write_referrers(&transaction, &url)?;
+        transaction.commit().map_err(query_error)
+    }
+
+    fn exists(&self, code: &ShortCode) -> Result<bool> {
+        let connection = self.lock()?;
+        connection
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM shortened_urls WHERE short_code = ?1)",
+                params![code.as_str()],
+                |row| row.get(0),
+            )
+            .map_err(query_error)
+    }
+
+    fn delete(&self, code: &ShortCode) -> Result<()> {
+        let connection = self.lock()?;
+        let deleted = connection
+            .execute("DELETE FROM shortened_urls WHERE short_code = ?1", params![code.as_str()])
+            .map_err(query_error)?;
+
+        if deleted == 0 {
+            return Err(UrlShortenerError::ShortCodeNotFound(code.as_str().to_string()));
+        }
+
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<()> {
+        // Related rows go with their entries via ON DELETE CASCADE
+        let connection = self.lock()?;
+        connection.execute("DELETE FROM shortened_urls", []).map_err(query_error)?;
+        Ok(())
+    }
+
+    fn delete_expired(&self, now: SystemTime) -> Result<usize> {
+        let connection = self.lock()?;
+        connection
+            .execute(
+                "DELETE FROM shortened_urls WHERE expires_at IS NOT NULL AND expires_at <= ?1",
+                params![to_secs(now)],
+            )
+            .map_err(query_error)
+    }
+
+    fn count(&self) -> Result<usize> {
+        let connection = self.lock()?;
+        connection
+            .query_row("SELECT COUNT(*) FROM shortened_urls", [], |row| row.get(0))
+            .map_err(query_error)
+    }
+
+    fn list_all(&self) -> Result<Vec<ShortenedUrl>> {
+        self.list_rows("", [])
+    }
+
+    fn list_paginated(&self, offset: usize, limit: usize) -> Result<Vec<ShortenedUrl>> {
+        // SQLite limits are signed 64-bit; clamp larger values
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let offset = i64::try_from(offset).unwrap_or(i64::MAX);
+        self.list_rows(
+            "ORDER BY created_at, short_code LIMIT ?1 OFFSET ?2",
+            params![limit, offset],
+        )
+    }
+
+    fn created_between(&self, start: SystemTime, end: SystemTime) -> Result<Vec<ShortenedUrl>> {
+        self.list_rows(
+            "WHERE created_at >= ?1 AND created_at < ?2 ORDER BY created_at, short_code",
+            params![to_secs(start), to_secs(end)],
+        )
+    }
+
+    fn find_by_original_url(&self, url: &OriginalUrl) -> Result<Option<ShortenedUrl>> {
+        let connection = self.lock()?;
+        Self::find_one(&connection, "original_url", url.as_str())
+    }
+
+    fn find_by_tag(&self, tag: &str) -> Result<Vec<ShortenedUrl>> {
+        self.list_rows(
+            "WHERE short_code IN (SELECT short_code FROM url_tags WHERE tag = ?1)
+             ORDER BY short_code",
+            params![tag],
+        )
+    }
+
+    fn find_by_access_count_range(&self, min: u64, max: u64) -> Result<Vec<ShortenedUrl>> {
+        // Counts are stored as signed 64-bit; clamp larger bounds
+        let min = i64::try_from(min).unwrap_or(i64::MAX);
+        let max = i64::try_from(max).unwrap_or(i64::MAX);
+        self.list_rows(
+            "WHERE access_count BETWEEN ?1 AND ?2 ORDER BY short_code",
+            params![min, max],
+        )
+    }
+
+    fn swap_original_urls(&self, a: &ShortCode, b: &ShortCode) -> Result<()> {
+        let mut connection = self.lock()?;
+        let transaction = connection.transaction().map_err(query_error)?;
+
+        let target_of = |code: &ShortCode| -> Result<String> {
+            transaction
+                .query_row(
+                    "SELECT original_url FROM shortened_urls WHERE short_code = ?1",
+                    params![code.as_str()],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(query_error)?
+                .ok_or_else(|| UrlShortenerError::ShortCodeNotFound(code.as_str().to_string()))
+        };
+        let first_target = target_of(a)?;
+        let second_target = target_of(b)?;
+
+        for (code, target) in [(a, &second_target), (b, &first_target)] {
+            transaction
+                .execute(
+                    "UPDATE shortened_urls SET original_url = ?1 WHERE short_code = ?2",
+                    params![target, code.as_str()],
+                )
+                .map_err(query_error)?;
+        }
+
+        transaction.commit().map_err(query_error)
+    }
+}
+
+/// Columns of a `shortened_urls` row before validation
+struct RawRow {
+    id: String,
+    short_code: String,
+    original_url: String,
+    created_at: i64,
+    access_count: i64,
+    active_from: Option<i64>,
+    expires_at: Option<i64>,
+    last_accessed_at: Option<i64>,
+    history_days: i64,
+    enabled: bool,
+    max_accesses: Option<i64>,
+}
+
+impl RawRow {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            short_code: row.get(1)?,
+            original_url: row.get(2)?,
+            created_at: row.get(3)?,
+            access_count: row.get(4)?,
+            active_from: row.get(5)?,
+            expires_at: row.get(6)?,
+            last_accessed_at: row.get(7)?,
+            history_days: row.get(8)?,
+            enabled: row.get(9)?,
+            max_accesses: row.get(10)?,
+        })
+    }
+
+    /// Rebuild the domain entity, validating stored values
+    fn into_domain(self) -> Result<ShortenedUrl> {
+        let mut url = ShortenedUrl::with_created_at(
+            UrlId::new(self.id),
+            ShortCode::new(self.short_code)?,
+            OriginalUrl::from_stored(self.original_url)?,
+            from_secs(self.created_at),
+        )
+        .with_access_count(self.access_count.max(0) as u64)
+        .with_history_days(self.history_days.max(0) as usize)
+        .with_enabled(self.enabled);
+
+        if let Some(active_from) = self.active_from {
+            url = url.with_active_from(from_secs(active_from));
+        }
+
+        if let Some(expires_at) = self.expires_at {
+            url = url.with_expires_at(from_secs(expires_at));
+        }
+
+        if let Some(last_accessed_at) = self.last_accessed_at {
+            url = url.with_last_accessed_at(from_secs(last_accessed_at));
+        }
+
+        if let Some(max_accesses) = self.max_accesses {
+            url = url.with_max_accesses(max_accesses.max(0) as u64);
+        }
+
+        Ok(url)
+    }
+}
+
+fn query_error(e: rusqlite::Error) -> UrlShortenerError {
+    UrlShortenerError::repository_with_source("SQLite query failed", e)
+}
+
+fn to_secs(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn from_secs(secs: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs.max(0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_url(code: &str) -> ShortenedUrl {
+        let id = UrlId::new(format!("id-{}", code));
+        let short_code = ShortCode::new(code.to_string()).unwrap();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        ShortenedUrl::new(id, short_code, url)
+    }
+
+    fn create_test_url_to(code: &str, target: &str) -> ShortenedUrl {
+        let id = UrlId::new(format!("id-{}", code));
+        let short_code = ShortCode::new(code.to_string()).unwrap();
+        let url = OriginalUrl::new(target.to_string()).unwrap();
+        ShortenedUrl::new(id, short_code, url)
+    }
+
+    #[test]
+    fn test_save_and_find() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        let url = create_test_url("test1234");
+        let code = ShortCode::new("test1234".to_string()).unwrap();
+
+        repo.save(url.clone()).unwrap();
+
+        let found = repo.find_by_short_code(&code).unwrap();
+        assert_eq!(found.short_code(), url.short_code());
+        assert_eq!(found.id(), url.id());
+        assert_eq!(found.original_url(), url.original_url());
+        assert_eq!(to_secs(found.created_at()), to_secs(url.created_at()));
+    }
+
+    #[test]
+    fn test_urls_outside_default_limits_read_back() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        let targets = [
+            OriginalUrl::new_with_schemes("mailto:team@example.com".to_string(), &["mailto"]),
+            OriginalUrl::new_with_schemes("ftp://example.com/file".to_string(), &["ftp"]),
+            OriginalUrl::new_with_max_len(
+                format!("https://example.com/{}", "a".repeat(3000)),
+                4096,
+            ),
+        ];
+
+        for (i, target) in targets.into_iter().enumerate() {
+            let code = ShortCode::new(format!("wide{:04}", i)).unwrap();
+            let id = UrlId::new(code.to_string());
+            let url = ShortenedUrl::new(id, code.clone(), target.unwrap());
+            repo.save(url.clone()).unwrap();
+
+            assert_eq!(repo.find_by_short_code(&code).unwrap().original_url(), url.original_url());
+        }
+        assert_eq!(repo.list_all().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_duplicate_short_code() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        let url1 = create_test_url("dupe1234");
+        let url2 = create_test_url("dupe1234");
+
+        repo.save(url1).unwrap();
+        let result = repo.save(url2);
+
+        assert!(result.is_err());
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeAlreadyExists(_))));
+    }
+
+    #[test]
+    fn test_find_not_found() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        let code = ShortCode::new("notfound".to_string()).unwrap();
+
+        let result = repo.find_by_short_code(&code);
+        assert!(result.is_err());
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+    }
+
+    #[test]
+    fn test_update() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        let mut url = create_test_url("updt1234");
+        let code = ShortCode::new("updt1234".to_string()).unwrap();
+
+        repo.save(url.clone()).unwrap();
+
+        // Update access count
+        let accessed_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        url.record_access_at(accessed_at);
+        repo.update(url.clone()).unwrap();
+
+        let found = repo.find_by_short_code(&code).unwrap();
+        assert_eq!(found.access_count(), 1);
+        assert_eq!(found.last_accessed_at(), Some(accessed_at));
+        assert_eq!(found.access_counts_by_day(), url.access_counts_by_day());
+    }
+
+    #[test]
+    fn test_update_not_found() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        let result = repo.update(create_test_url("missing1"));
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+    }
+
+    #[test]
+    fn test_exists() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        let url = create_test_url("exst1234");
+        let code = ShortCode::new("exst1234".to_string()).unwrap();
+
+        assert!(!repo.exists(&code).unwrap());
+
+        repo.save(url).unwrap();
+
+        assert!(repo.exists(&code).unwrap());
+    }
+
+    #[test]
+    fn test_delete() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        let url = create_test_url("dele1234");
+        let code = ShortCode::new("dele1234".to_string()).unwrap();
+
+        repo.save(url).unwrap();
+        assert!(repo.exists(&code).unwrap());
+
+        repo.delete(&code).unwrap();
+        assert!(!repo.exists(&code).unwrap());
+
+        let result = repo.delete(&code);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+    }
+
+    #[test]
+    fn test_list_all() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+
+        let urls = repo.list_all().unwrap();
+        assert_eq!(urls.len(), 0);
+
+        repo.save(create_test_url("list1234")).unwrap();
+        repo.save(create_test_url("list5678")).unwrap();
+
+        let urls = repo.list_all().unwrap();
+        assert_eq!(urls.len(), 2);
+        assert_eq!(repo.count().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_clear_removes_related_rows() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        let mut url = create_test_url("clear123").with_tags(vec!["ops".to_string()]).unwrap();
+        url.add_note("ops".to_string(), "note".to_string(), SystemTime::now()).unwrap();
+        url.record_access();
+        repo.save(url).unwrap();
+
+        repo.clear().unwrap();
+        assert_eq!(repo.count().unwrap(), 0);
+
+        let connection = repo.lock().unwrap();
+        let related: i64 = connection
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM url_notes) + (SELECT COUNT(*) FROM url_tags)
+                      + (SELECT COUNT(*) FROM url_daily_counts)
+                      + (SELECT COUNT(*) FROM url_referrers)",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(related, 0);
+    }
+
+    #[test]
+    fn test_delete_expired() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        let deadline = UNIX_EPOCH + Duration::from_secs(1_000);
+        repo.save(create_test_url("expired1").with_expires_at(deadline)).unwrap();
+        repo.save(create_test_url("later123").with_expires_at(deadline + Duration::from_secs(1)))
+            .unwrap();
+        repo.save(create_test_url("forever1")).unwrap();
+
+        assert_eq!(repo.delete_expired(deadline - Duration::from_secs(1)).unwrap(), 0);
+        assert_eq!(repo.delete_expired(deadline).unwrap(), 1);
+
+        let mut remaining: Vec<_> = repo.list_all().unwrap()
+            .iter()
+            .map(|url| url.short_code().as_str().to_string())
+            .collect();
+        remaining.sort();
+        assert_eq!(remaining, ["forever1", "later123"]);
+    }
+
+    #[test]
+    fn test_list_paginated_orders_by_creation_time() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        for (code, secs) in [("page0002", 20), ("page0001", 10), ("page0000", 20)] {
+            let created_at = UNIX_EPOCH + Duration::from_secs(secs);
+            let url = create_test_url(code);
+            repo.save(ShortenedUrl::with_created_at(
+                url.id().clone(),
+                url.short_code().clone(),
+                url.original_url().clone(),
+                created_at,
+            )).unwrap();
+        }
+
+        let page: Vec<String> = repo.list_paginated(1, 5).unwrap()
+            .iter()
+            .map(|url| url.short_code().as_str().to_string())
+            .collect();
+        assert_eq!(page, ["page0000", "page0002"]);
+        assert!(repo.list_paginated(3, 5).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_increment_access() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        let url = create_test_url("incr1234");
+        let code = url.short_code().clone();
+        repo.save(url).unwrap();
+
+        let at = UNIX_EPOCH + Duration::from_secs(86_400 * 3);
+        assert_eq!(repo.increment_access_at(&code, at).unwrap(), 1);
+        assert_eq!(repo.increment_access_at(&code, at).unwrap(), 2);
+
+        let found = repo.find_by_short_code(&code).unwrap();
+        assert_eq!(found.access_count(), 2);
+        assert_eq!(found.last_accessed_at(), Some(at));
+        assert_eq!(found.access_counts_by_day().get(&3), Some(&2));
+
+        let missing = ShortCode::new("missing1".to_string()).unwrap();
+        let result = repo.increment_access(&missing);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+    }
+
+    #[test]
+    fn test_record_referrer() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        let url = create_test_url("refr1234");
+        let code = url.short_code().clone();
+        repo.save(url).unwrap();
+
+        repo.increment_access(&code).unwrap();
+        repo.record_referrer(&code, Some("newsletter".to_string())).unwrap();
+        repo.record_referrer(&code, Some("newsletter".to_string())).unwrap();
+        repo.record_referrer(&code, None).unwrap();
+
+        let found = repo.find_by_short_code(&code).unwrap();
+        assert_eq!(found.access_count(), 1);
+        assert_eq!(found.referrer_counts().get("newsletter"), Some(&2));
+        assert_eq!(found.referrer_counts().get(ShortenedUrl::DIRECT_REFERRER), Some(&1));
+
+        // Referrer counts are replaced along with the rest of the entry
+        repo.update(found.with_referrer_counts(Default::default())).unwrap();
+        assert!(repo.find_by_short_code(&code).unwrap().referrer_counts().is_empty());
+
+        let missing = ShortCode::new("missing1".to_string()).unwrap();
+        let result = repo.record_referrer(&missing, None);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+    }
+
+    #[test]
+    fn test_increment_access_respects_limit() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        let url = create_test_url("once1234").with_max_accesses(1);
+        let code = url.short_code().clone();
+        repo.save(url).unwrap();
+        assert_eq!(repo.find_by_short_code(&code).unwrap().max_accesses(), Some(1));
+
+        assert_eq!(repo.increment_access(&code).unwrap(), 1);
+        let result = repo.increment_access(&code);
+        assert!(matches!(result, Err(UrlShortenerError::AccessLimitReached(_))));
+        assert_eq!(repo.find_by_short_code(&code).unwrap().access_count(), 1);
+    }
+
+    #[test]
+    fn test_created_between() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        for (code, secs) in [("range020", 20), ("range010", 10), ("range030", 30)] {
+            let url = create_test_url(code);
+            repo.save(ShortenedUrl::with_created_at(
+                url.id().clone(),
+                url.short_code().clone(),
+                url.original_url().clone(),
+                UNIX_EPOCH + Duration::from_secs(secs),
+            )).unwrap();
+        }
+
+        let start = UNIX_EPOCH + Duration::from_secs(10);
+        let end = UNIX_EPOCH + Duration::from_secs(30);
+        let found: Vec<String> = repo.created_between(start, end).unwrap()
+            .iter()
+            .map(|url| url.short_code().as_str().to_string())
+            .collect();
+        assert_eq!(found, ["range010", "range020"]);
+    }
+
+    #[test]
+    fn test_notes_and_activation_persist() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        let launch = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut url = create_test_url("note1234")
+            .with_history_days(7)
+            .with_active_from(launch)
+            .with_expires_at(launch + Duration::from_secs(3600));
+        url.add_note("alice".to_string(), "first".to_string(), launch).unwrap();
+        repo.save(url.clone()).unwrap();
+
+        url.add_note("bob".to_string(), "second".to_string(), launch).unwrap();
+        repo.update(url.clone()).unwrap();
+
+        let found = repo.find_by_short_code(url.short_code()).unwrap();
+        assert_eq!(found.active_from(), Some(launch));
+        assert_eq!(found.expires_at(), url.expires_at());
+        assert_eq!(found.history_days(), 7);
+        assert_eq!(found.notes(), url.notes());
+        assert!(found.is_enabled());
+
+        url.set_enabled(false);
+        repo.update(url.clone()).unwrap();
+        assert!(!repo.find_by_short_code(url.short_code()).unwrap().is_enabled());
+    }
+
+    #[test]
+    fn test_tags_persist_and_are_queryable() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        let tags = vec!["twitter".to_string(), "newsletter".to_string()];
+        let url = create_test_url("tag12345").with_tags(tags.clone()).unwrap();
+        repo.save(url.clone()).unwrap();
+        repo.save(create_test_url("tag67890")).unwrap();
+
+        assert_eq!(repo.find_by_short_code(url.short_code()).unwrap().tags(), tags);
+
+        let found = repo.find_by_tag("newsletter").unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].short_code(), url.short_code());
+        assert!(repo.find_by_tag("print").unwrap().is_empty());
+
+        repo.update(url.with_tags(vec!["print".to_string()]).unwrap()).unwrap();
+        assert!(repo.find_by_tag("newsletter").unwrap().is_empty());
+        assert_eq!(repo.find_by_tag("print").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_swap_original_urls() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        repo.save(create_test_url_to("swapaaaa", "https://a.example.com")).unwrap();
+        repo.save(create_test_url_to("swapbbbb", "https://b.example.com")).unwrap();
+        let a = ShortCode::new("swapaaaa".to_string()).unwrap();
+        let b = ShortCode::new("swapbbbb".to_string()).unwrap();
+        let missing = ShortCode::new("missing1".to_string()).unwrap();
+
+        let result = repo.swap_original_urls(&a, &missing);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+
+        repo.swap_original_urls(&a, &b).unwrap();
+
+        let found_a = repo.find_by_short_code(&a).unwrap();
+        let found_b = repo.find_by_short_code(&b).unwrap();
+        assert_eq!(found_a.original_url().as_str(), "https://b.example.com/");
+        assert_eq!(found_b.original_url().as_str(), "https://a.example.com/");
+    }
+
+    #[test]
+    fn test_find_by_original_url() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        repo.save(create_test_url_to("orig1234", "https://a.example.com")).unwrap();
+        let target = OriginalUrl::new("https://a.example.com".to_string()).unwrap();
+        let other = OriginalUrl::new("https://b.example.com".to_string()).unwrap();
+
+        let found = repo.find_by_original_url(&target).unwrap().unwrap();
+        assert_eq!(found.short_code().as_str(), "orig1234");
+        assert!(repo.find_by_original_url(&other).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_find_by_domain() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        repo.save(create_test_url_to("domain02", "https://a.example.com/x")).unwrap();
+        repo.save(create_test_url_to("domain01", "https://A.Example.com/y")).unwrap();
+        repo.save(create_test_url_to("domain03", "https://b.example.com/")).unwrap();
+
+        let found = repo.find_by_domain("a.EXAMPLE.com").unwrap();
+        let codes: Vec<&str> = found.iter().map(|url| url.short_code().as_str()).collect();
+        assert_eq!(codes, ["domain01", "domain02"]);
+        assert!(repo.find_by_domain("example.com").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_find_by_access_count_range() {
+        let repo = SqliteUrlRepository::in_memory().unwrap();
+        for (code, accesses) in [("count3", 3), ("count0b", 0), ("count0a", 0)] {
+            repo.save(create_test_url(code).with_access_count(accesses)).unwrap();
+        }
+
+        let codes = |min: u64, max: u64| -> Vec<String> {
+            repo.find_by_access_count_range(min, max).unwrap()
+                .iter()
+                .map(|url| url.short_code().as_str().to_string())
+                .collect()
+        };
+        assert_eq!(codes(0, 0), ["count0a", "count0b"]);
+        assert_eq!(codes(1, u64::MAX), ["count3"]);
+        assert!(codes(4, 10).is_empty());
+    }
+
+    #[test]
+    fn test_persists_across_connections() {
+        let dir = std::env::temp_dir().join(format!("url_shortener_sqlite_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("urls.db");
+        let _ = std::fs::remove_file(&path);
+
+        {
+            let repo = SqliteUrlRepository::new(&path).unwrap();
+            repo.save(create_test_url("disk1234")).unwrap();
+        }
+
+        let repo = SqliteUrlRepository::new(&path).unwrap();
+        let code = ShortCode::new("disk1234".to_string()).unwrap();
+        assert!(repo.exists(&code).unwrap());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_query_errors_keep_their_source() {
+        use std::error::Error;
+
+        let dir = std::env::temp_dir()
+            .join(format!("url_shortener_sqlite_source_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("urls.db");
+        let _ = std::fs::remove_file(&path);
+
+        let repo = SqliteUrlRepository::new(&path).unwrap();
+        Connection::open(&path).unwrap()
+            .execute_batch("DROP TABLE url_daily_counts; DROP TABLE shortened_urls;")
+            .unwrap();
+
+        let code = ShortCode::new("gone1234".to_string()).unwrap();
+        let err = repo.exists(&code).unwrap_err();
+        assert!(matches!(err, UrlShortenerError::RepositoryError { .. }));
+
+        let source = err.source().expect("the SQLite error should be attached");
+        assert!(source.downcast_ref::<rusqlite::Error>().is_some());
+        assert!(source.to_string().contains("no such table"), "{}", source);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,184 @@
+//! Timestamp ID generator implementation
+
+use super::{RandomIdGenerator, SystemClock};
+use crate::domain::{ShortCode, UrlId};
+use crate::error::Result;
+use crate::ports::{Clock, IdGenerator};
+use rand::Rng;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::UNIX_EPOCH;
+
+/// ID generator producing short codes that sort by creation time
+///
+/// Each short code is the current Unix time in milliseconds, base62-encoded
+/// into `TIMESTAMP_LENGTH` characters, followed by `SUFFIX_LENGTH` random
+/// characters that keep codes generated within the same millisecond apart.
+/// The digits run `0-9`, `A-Z`, `a-z`, which is also their byte order, so a
+/// code generated in a later millisecond sorts after an earlier one. Codes
+/// from the same millisecond are ordered by their random suffix.
+///
+/// The timestamp never goes backwards: if the clock is set back, codes keep
+/// the latest millisecond seen so far. Seven base62 digits last until the
+/// year 2081, after which the timestamp wraps around and ordering restarts.
+///
+/// URL ids are random, since they don't need to be short.
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::adapters::{ManualClock, TimestampIdGenerator};
+/// use url_shortener::ports::IdGenerator;
+/// use std::sync::Arc;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let clock = Arc::new(ManualClock::new(UNIX_EPOCH + Duration::from_secs(1_700_000_000)));
+/// let generator = TimestampIdGenerator::new().with_clock(clock.clone());
+///
+/// let first = generator.generate_short_code().unwrap();
+/// clock.advance(Duration::from_millis(1));
+/// let second = generator.generate_short_code().unwrap();
+/// assert!(first < second);
+/// ```
+pub struct TimestampIdGenerator {
+    clock: Arc<dyn Clock>,
+    last_millis: AtomicU64,
+    ids: RandomIdGenerator,
+}
+
+impl TimestampIdGenerator {
+    /// Number of characters encoding the timestamp
+    pub const TIMESTAMP_LENGTH: usize = 7;
+
+    /// Number of random characters following the timestamp
+    pub const SUFFIX_LENGTH: usize = 3;
+
+    /// Characters used as base62 digits, in digit and byte order
+    const CHARSET: &'static [u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+    /// Create a generator reading the system clock
+    pub fn new() -> Self {
+        Self {
+            clock: Arc::new(SystemClock),
+            last_millis: AtomicU64::new(0),
+            ids: RandomIdGenerator::new(),
+        }
+    }
+
+    /// Use a custom clock for the embedded timestamps
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Current Unix time in milliseconds, never less than a previous call's
+    fn next_millis(&self) -> u64 {
+        let now = self.clock
+            .now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0);
+
+        let previous = self.last_millis.fetch_max(now, Ordering::SeqCst);
+        previous.max(now)
+    }
+
+    /// Encode `value` as `TIMESTAMP_LENGTH` base62 digits, dropping higher digits
+    fn encode(mut value: u64) -> String {
+        let base = Self::CHARSET.len() as u64;
+        let mut digits = vec![Self::CHARSET[0]; Self::TIMESTAMP_LENGTH];
+
+        for digit in digits.iter_mut().rev() {
+            *digit = Self::CHARSET[(value % base) as usize];
+            value /= base;
+        }
+
+        digits.into_iter().map(char::from).collect()
+    }
+}
+
+impl Default for TimestampIdGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdGenerator for TimestampIdGenerator {
+    fn generate_id(&self) -> UrlId {
+        self.ids.generate_id()
+    }
+
+    fn generate_short_code(&self) -> Result<ShortCode> {
+        let mut code = Self::encode(self.next_millis());
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..Self::SUFFIX_LENGTH {
+            code.push(Self::CHARSET[rng.gen_range(0..Self::CHARSET.len())] as char);
+        }
+
+        ShortCode::new(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::ManualClock;
+    use std::thread;
+    use std::time::Duration;
+
+    fn at(millis: u64) -> Arc<ManualClock> {
+        Arc::new(ManualClock::new(UNIX_EPOCH + Duration::from_millis(millis)))
+    }
+
+    #[test]
+    fn test_later_codes_sort_after_earlier_ones() {
+        let generator = TimestampIdGenerator::new();
+
+        let first = generator.generate_short_code().unwrap();
+        thread::sleep(Duration::from_millis(5));
+        let second = generator.generate_short_code().unwrap();
+
+        assert!(first < second, "{} should sort before {}", first, second);
+    }
+
+    #[test]
+    fn test_code_layout() {
+        let generator = TimestampIdGenerator::new().with_clock(at(62));
+        let code = generator.generate_short_code().unwrap();
+
+        let length = TimestampIdGenerator::TIMESTAMP_LENGTH + TimestampIdGenerator::SUFFIX_LENGTH;
+        assert_eq!(code.as_str().len(), length);
+        assert!(code.as_str().starts_with("0000010"));
+    }
+
+    #[test]
+    fn test_ordering_across_digit_boundaries() {
+        let clock = at(1_700_000_000_000);
+        let generator = TimestampIdGenerator::new().with_clock(clock.clone());
+        let mut previous = generator.generate_short_code().unwrap();
+
+        // Step through millisecond values where digits roll over
+        for step in [1, 9, 10, 26, 26, 61, 3_844, 1_000_000] {
+            clock.advance(Duration::from_millis(step));
+            let code = generator.generate_short_code().unwrap();
+            assert!(previous < code, "{} should sort before {}", previous, code);
+            previous = code;
+        }
+    }
+
+    #[test]
+    fn test_clock_going_backwards_keeps_latest_timestamp() {
+        let clock = at(1_700_000_000_000);
+        let generator = TimestampIdGenerator::new().with_clock(clock.clone());
+        let first = generator.generate_short_code().unwrap();
+
+        clock.set(UNIX_EPOCH + Duration::from_millis(1_600_000_000_000));
+        let second = generator.generate_short_code().unwrap();
+
+        let timestamp = |code: &ShortCode| {
+            code.as_str()[..TimestampIdGenerator::TIMESTAMP_LENGTH].to_string()
+        };
+        assert_eq!(timestamp(&first), timestamp(&second));
+    }
+}
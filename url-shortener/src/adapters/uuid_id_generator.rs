@@ -0,0 +1,91 @@
+//! UUID-based ID generator implementation
+
+use super::RandomIdGenerator;
+use crate::domain::{ShortCode, UrlId};
+use crate::error::Result;
+use crate::ports::IdGenerator;
+use uuid::Uuid;
+
+/// ID generator producing UUIDv4 ids
+///
+/// `generate_id` returns a random (version 4) UUID in its hyphenated form,
+/// suitable as a database key. Short codes are still short alphanumeric
+/// codes, produced as by `RandomIdGenerator`.
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::adapters::UuidIdGenerator;
+/// use url_shortener::ports::IdGenerator;
+///
+/// let generator = UuidIdGenerator::new();
+/// let id = generator.generate_id();
+/// assert_eq!(id.as_str().len(), 36);
+/// ```
+pub struct UuidIdGenerator {
+    short_codes: RandomIdGenerator,
+}
+
+impl UuidIdGenerator {
+    /// Create a UUID ID generator with the default short code length
+    pub fn new() -> Self {
+        Self {
+            short_codes: RandomIdGenerator::new(),
+        }
+    }
+
+    /// Create a UUID ID generator with a specific short code length
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length is outside the valid range for ShortCode
+    pub fn with_length(length: usize) -> Self {
+        Self {
+            short_codes: RandomIdGenerator::with_length(length),
+        }
+    }
+}
+
+impl Default for UuidIdGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdGenerator for UuidIdGenerator {
+    fn generate_id(&self) -> UrlId {
+        UrlId::new(Uuid::new_v4().hyphenated().to_string())
+    }
+
+    fn generate_short_code(&self) -> Result<ShortCode> {
+        self.short_codes.generate_short_code()
+    }
+
+    fn keyspace(&self) -> Option<u64> {
+        self.short_codes.keyspace()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_id_is_uuid_v4() {
+        let generator = UuidIdGenerator::new();
+        let id = generator.generate_id();
+
+        let parsed = Uuid::parse_str(id.as_str()).unwrap();
+        assert_eq!(parsed.get_version_num(), 4);
+        assert_eq!(parsed.hyphenated().to_string(), id.as_str());
+        assert_ne!(generator.generate_id(), id);
+    }
+
+    #[test]
+    fn test_short_code_stays_short() {
+        let code = UuidIdGenerator::with_length(7).generate_short_code().unwrap();
+
+        assert_eq!(code.as_str().len(), 7);
+        assert!(code.as_str().chars().all(|c| c.is_ascii_alphanumeric()));
+    }
+}
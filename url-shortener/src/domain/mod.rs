@@ -6,11 +6,25 @@
 //! - Rich domain models with behavior
 
 mod short_code;
+mod short_url;
 mod url_id;
 mod original_url;
 mod shortened_url;
+mod shortened_url_builder;
+mod note;
+mod redirect_kind;
+mod redirect_response;
+#[cfg(feature = "serde")]
+mod serde_time;
 
 pub use short_code::ShortCode;
+pub use short_url::ShortUrl;
 pub use url_id::UrlId;
 pub use original_url::OriginalUrl;
 pub use shortened_url::ShortenedUrl;
+#[cfg(feature = "redis")]
+pub(crate) use shortened_url::day_index;
+pub use shortened_url_builder::ShortenedUrlBuilder;
+pub use note::Note;
+pub use redirect_kind::RedirectKind;
+pub use redirect_response::RedirectResponse;
@@ -0,0 +1,44 @@
+//! Annotation attached to a shortened URL
+
+use std::time::SystemTime;
+
+/// A note left on a shortened URL
+///
+/// Notes form an append-only edit history: each one records who wrote it,
+/// what they wrote and when.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Note {
+    author: String,
+    text: String,
+    #[cfg_attr(feature = "serde", serde(with = "super::serde_time::unix_seconds"))]
+    at: SystemTime,
+}
+
+impl Note {
+    /// Maximum length of a note's text, in characters
+    pub const MAX_TEXT_LENGTH: usize = 1000;
+
+    /// Maximum length of a note's author, in characters
+    pub const MAX_AUTHOR_LENGTH: usize = 100;
+
+    /// Create a new note
+    pub fn new(author: String, text: String, at: SystemTime) -> Self {
+        Self { author, text, at }
+    }
+
+    /// Get the author of the note
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+
+    /// Get the note text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Get the time the note was written
+    pub fn at(&self) -> SystemTime {
+        self.at
+    }
+}
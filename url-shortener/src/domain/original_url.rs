@@ -8,15 +8,50 @@ use url::Url;
 ///
 /// OriginalUrl ensures that the URL is valid and well-formed.
 /// Once created, it's guaranteed to be a valid URL.
+///
+/// With the `serde` feature, URLs serialize as plain strings. On
+/// deserialization they must be well-formed, but the scheme and length
+/// limits of `new` aren't applied again, so URLs built with
+/// `new_with_schemes` or `new_with_max_len` read back unchanged.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(into = "String"))]
 pub struct OriginalUrl(Url);
 
 impl OriginalUrl {
+    /// Schemes accepted by `new`
+    pub const DEFAULT_SCHEMES: &'static [&'static str] = &["http", "https"];
+
+    /// Maximum length in bytes of a URL accepted by `new`
+    pub const MAX_URL_LENGTH: usize = 2048;
+
+    /// Default ports removed by `canonical`, for schemes the URL parser
+    /// doesn't already normalize
+    pub const DEFAULT_PORTS: &'static [(&'static str, u16)] = &[
+        ("git", 9418),
+        ("ldap", 389),
+        ("ldaps", 636),
+        ("rtsp", 554),
+        ("sftp", 22),
+        ("ssh", 22),
+    ];
+
+    /// Query parameters removed by `without_tracking_params`
+    pub const TRACKING_PARAMS: &'static [&'static str] = &[
+        "utm_source",
+        "utm_medium",
+        "utm_campaign",
+        "utm_term",
+        "utm_content",
+        "fbclid",
+        "gclid",
+    ];
+
     /// Create a new validated OriginalUrl
     ///
     /// # Errors
     ///
-    /// Returns `UrlShortenerError::InvalidUrl` if the URL is malformed
+    /// Returns `UrlShortenerError::InvalidUrl` if the URL is malformed or
+    /// longer than `MAX_URL_LENGTH`
     ///
     /// # Examples
     ///
@@ -30,33 +65,363 @@ impl OriginalUrl {
     /// assert!(OriginalUrl::new("not a url".to_string()).is_err());
     /// ```
     pub fn new(url: String) -> Result<Self> {
+        Self::parse(url, Self::DEFAULT_SCHEMES, Self::MAX_URL_LENGTH)
+    }
+
+    /// Create a validated OriginalUrl with a custom length limit
+    ///
+    /// Behaves like `new`, but rejects URLs longer than `max` bytes instead
+    /// of `MAX_URL_LENGTH`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::InvalidUrl` if the URL is malformed or
+    /// longer than `max`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_shortener::domain::OriginalUrl;
+    ///
+    /// let url = "https://example.com/a/long/path".to_string();
+    /// assert!(OriginalUrl::new_with_max_len(url.clone(), 64).is_ok());
+    /// assert!(OriginalUrl::new_with_max_len(url, 16).is_err());
+    /// ```
+    pub fn new_with_max_len(url: String, max: usize) -> Result<Self> {
+        Self::parse(url, Self::DEFAULT_SCHEMES, max)
+    }
+
+    /// Create a validated OriginalUrl, allowing the given schemes
+    ///
+    /// Schemes are compared case-insensitively. Use this for links such as
+    /// `ftp://` or `mailto:` that `new` rejects.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::InvalidUrl` if the URL is malformed, longer
+    /// than `MAX_URL_LENGTH`, or its scheme isn't in `allowed`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_shortener::domain::OriginalUrl;
+    ///
+    /// let url = OriginalUrl::new_with_schemes(
+    ///     "mailto:team@example.com".to_string(),
+    ///     &["https", "mailto"],
+    /// ).unwrap();
+    /// assert_eq!(url.scheme(), "mailto");
+    ///
+    /// let ftp = "ftp://example.com".to_string();
+    /// assert!(OriginalUrl::new_with_schemes(ftp, &["https"]).is_err());
+    /// ```
+    pub fn new_with_schemes(url: String, allowed: &[&str]) -> Result<Self> {
+        Self::parse(url, allowed, Self::MAX_URL_LENGTH)
+    }
+
+    /// Rebuild a URL read back from storage
+    ///
+    /// Only checks that the URL is well-formed. The scheme and length limits
+    /// were applied when the URL was created and may have been wider than
+    /// those of `new`, so they aren't applied again.
+    #[cfg(any(feature = "serde", feature = "sqlite", feature = "postgres"))]
+    pub(crate) fn from_stored(url: String) -> Result<Self> {
+        Url::parse(&url)
+            .map(Self)
+            .map_err(|e| UrlShortenerError::InvalidUrl(e.to_string()))
+    }
+
+    /// Validate the length and scheme of a URL and parse it
+    fn parse(url: String, allowed: &[&str], max_len: usize) -> Result<Self> {
+        // Checked before parsing so oversized input costs no parsing work
+        check_length(&url, max_len)?;
+
         let parsed = Url::parse(&url)
             .map_err(|e| UrlShortenerError::InvalidUrl(e.to_string()))?;
 
-        // Ensure we have a valid scheme (http or https)
-        if parsed.scheme() != "http" && parsed.scheme() != "https" {
-            return Err(UrlShortenerError::InvalidUrl(
-                format!("Unsupported scheme: {}. Only http and https are allowed", parsed.scheme())
-            ));
+        Self::from_parsed(parsed, allowed)
+    }
+
+    /// Validate the scheme of an already parsed URL
+    fn from_parsed(parsed: Url, allowed: &[&str]) -> Result<Self> {
+        if !allowed.iter().any(|scheme| scheme.eq_ignore_ascii_case(parsed.scheme())) {
+            return Err(UrlShortenerError::InvalidUrl(format!(
+                "Unsupported scheme: {}. Allowed schemes: {}",
+                parsed.scheme(),
+                allowed.join(", ")
+            )));
         }
 
         Ok(Self(parsed))
     }
 
+    /// Create an OriginalUrl in normalized form
+    ///
+    /// Equivalent spellings of the same URL produce the same value: the host
+    /// is lowercased, a default port (80 for http, 443 for https) is removed
+    /// and query parameters are sorted. `new` keeps the URL as given.
+    ///
+    /// http(s) URLs always carry a path, so an empty path is still shown as
+    /// `/` here; `normalized_key` drops it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::InvalidUrl` if the URL is malformed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_shortener::domain::OriginalUrl;
+    ///
+    /// let raw = "https://EXAMPLE.com:443/?b=2&a=1".to_string();
+    /// let url = OriginalUrl::new_normalized(raw).unwrap();
+    /// assert_eq!(url.as_str(), "https://example.com/?a=1&b=2");
+    /// ```
+    pub fn new_normalized(url: String) -> Result<Self> {
+        Ok(Self::new(url)?.normalized())
+    }
+
+    /// Get a key identifying this URL up to equivalent spellings
+    ///
+    /// Two URLs that differ only in host casing, an explicit default port, a
+    /// trailing slash on an empty path or the order of query parameters have
+    /// the same key. Repositories can use it to deduplicate entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_shortener::domain::OriginalUrl;
+    ///
+    /// let a = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// let b = OriginalUrl::new("https://EXAMPLE.com:443/".to_string()).unwrap();
+    /// assert_eq!(a.normalized_key(), b.normalized_key());
+    /// assert_eq!(a.normalized_key(), "https://example.com");
+    /// ```
+    pub fn normalized_key(&self) -> String {
+        let url = self.normalized().0;
+
+        if url.path() != "/" {
+            return url.into();
+        }
+
+        let mut key = url[..url::Position::BeforePath].to_string();
+        key.push_str(&url[url::Position::AfterPath..]);
+        key
+    }
+
+    /// Canonical copy with query parameters sorted
+    fn normalized(&self) -> OriginalUrl {
+        let mut url = self.canonical().0;
+
+        let sorted = url.query().map(|query| {
+            let mut params: Vec<&str> = query.split('&').filter(|p| !p.is_empty()).collect();
+            params.sort_unstable();
+            params.join("&")
+        });
+        url.set_query(sorted.as_deref().filter(|query| !query.is_empty()));
+
+        Self(url)
+    }
+
+    /// Get a copy of this URL without the `TRACKING_PARAMS` query parameters
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_shortener::domain::OriginalUrl;
+    ///
+    /// let raw = "https://example.com/post?id=7&utm_source=news&fbclid=abc".to_string();
+    /// let url = OriginalUrl::new(raw).unwrap();
+    /// assert_eq!(url.without_tracking_params().as_str(), "https://example.com/post?id=7");
+    /// ```
+    pub fn without_tracking_params(&self) -> OriginalUrl {
+        self.without_params(Self::TRACKING_PARAMS)
+    }
+
+    /// Get a copy of this URL without the query parameters named in `names`
+    ///
+    /// Names are compared after percent-decoding. The remaining parameters
+    /// keep their order and encoding, and the `?` is dropped if none remain.
+    pub fn without_params(&self, names: &[&str]) -> OriginalUrl {
+        let mut url = self.0.clone();
+
+        let kept = url.query().map(|query| {
+            query
+                .split('&')
+                .filter(|param| {
+                    let name = url::form_urlencoded::parse(param.as_bytes()).next();
+                    name.is_some_and(|(name, _)| !names.contains(&name.as_ref()))
+                })
+                .collect::<Vec<_>>()
+                .join("&")
+        });
+        url.set_query(kept.as_deref().filter(|query| !query.is_empty()));
+
+        Self(url)
+    }
+
     /// Get the URL as a string slice
     pub fn as_str(&self) -> &str {
         self.0.as_str()
     }
 
+    /// Get the underlying parsed URL
+    pub fn as_url(&self) -> &Url {
+        &self.0
+    }
+
     /// Get the domain of the URL
     pub fn domain(&self) -> Option<&str> {
         self.0.domain()
     }
 
+    /// Check whether the URL's domain is exactly `domain`
+    ///
+    /// Matching ignores case and a trailing dot. Subdomains don't match, and
+    /// neither do URLs whose host is an IP address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_shortener::domain::OriginalUrl;
+    ///
+    /// let url = OriginalUrl::new("https://www.example.com/page".to_string()).unwrap();
+    /// assert!(url.has_domain("WWW.Example.com"));
+    /// assert!(!url.has_domain("example.com"));
+    /// ```
+    pub fn has_domain(&self, domain: &str) -> bool {
+        self.domain().is_some_and(|own| {
+            own.trim_end_matches('.').eq_ignore_ascii_case(domain.trim_end_matches('.'))
+        })
+    }
+
+    /// Check whether the URL's host is `host`, treating `www.` as equivalent
+    ///
+    /// Matching ignores case, a trailing dot and a leading `www.` on either
+    /// side, so `https://www.example.com` is on the same site as
+    /// `example.com`. Other subdomains are different sites. Useful for
+    /// telling links to your own site apart from external ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_shortener::domain::OriginalUrl;
+    ///
+    /// let url = OriginalUrl::new("https://www.example.com/page".to_string()).unwrap();
+    /// assert!(url.is_same_site("Example.com"));
+    /// assert!(!url.is_same_site("blog.example.com"));
+    /// ```
+    pub fn is_same_site(&self, host: &str) -> bool {
+        fn site(host: &str) -> &str {
+            let host = host.trim_end_matches('.');
+            match host.get(..4) {
+                Some(www) if www.eq_ignore_ascii_case("www.") => &host[4..],
+                _ => host,
+            }
+        }
+
+        self.host_str()
+            .is_some_and(|own| site(own).eq_ignore_ascii_case(site(host)))
+    }
+
+    /// Get the host of the URL
+    ///
+    /// Unlike `domain`, this also returns IP address hosts.
+    pub fn host_str(&self) -> Option<&str> {
+        self.0.host_str()
+    }
+
     /// Get the scheme (http or https)
     pub fn scheme(&self) -> &str {
         self.0.scheme()
     }
+
+    /// Get the path of the URL
+    ///
+    /// http(s) URLs always have a path; for a bare domain it is `/`.
+    pub fn path(&self) -> &str {
+        self.0.path()
+    }
+
+    /// Get the query string, without the leading `?`
+    pub fn query(&self) -> Option<&str> {
+        self.0.query()
+    }
+
+    /// Get the fragment, without the leading `#`
+    pub fn fragment(&self) -> Option<&str> {
+        self.0.fragment()
+    }
+
+    /// Get the explicit port of the URL
+    ///
+    /// Returns `None` when no port is given or it is the scheme's default.
+    pub fn port(&self) -> Option<u16> {
+        self.0.port()
+    }
+
+    /// Get a canonical copy of this URL
+    ///
+    /// Removes an explicit port when it's the default for the scheme in
+    /// `DEFAULT_PORTS` and lowercases the host. The original value is left
+    /// untouched.
+    ///
+    /// http, https, ftp, ws and wss URLs are already canonical: parsing drops
+    /// their default ports and lowercases their hosts. This only changes URLs
+    /// with other schemes, as accepted by `new_with_schemes`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_shortener::domain::OriginalUrl;
+    ///
+    /// let raw = "ssh://Git.Example.com:22/repo".to_string();
+    /// let url = OriginalUrl::new_with_schemes(raw, &["ssh"]).unwrap();
+    /// assert_eq!(url.canonical().as_str(), "ssh://git.example.com/repo");
+    /// assert_eq!(url.as_str(), "ssh://Git.Example.com:22/repo");
+    /// ```
+    pub fn canonical(&self) -> OriginalUrl {
+        self.canonical_with_default_ports(Self::DEFAULT_PORTS)
+    }
+
+    /// Get a canonical copy of this URL using the given default ports
+    ///
+    /// Like `canonical`, but takes the default port of each scheme from
+    /// `default_ports` instead of `DEFAULT_PORTS`. Schemes are compared
+    /// case-insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_shortener::domain::OriginalUrl;
+    ///
+    /// let raw = "ssh://example.com:2222/repo".to_string();
+    /// let url = OriginalUrl::new_with_schemes(raw, &["ssh"]).unwrap();
+    /// let canonical = url.canonical_with_default_ports(&[("ssh", 2222)]);
+    /// assert_eq!(canonical.as_str(), "ssh://example.com/repo");
+    /// ```
+    pub fn canonical_with_default_ports(&self, default_ports: &[(&str, u16)]) -> OriginalUrl {
+        let mut url = self.0.clone();
+
+        let default_port = default_ports
+            .iter()
+            .find(|(scheme, _)| scheme.eq_ignore_ascii_case(url.scheme()))
+            .map(|&(_, port)| port);
+        if url.port().is_some() && url.port() == default_port {
+            // Only fails for URLs that cannot have a port, which these have
+            let _ = url.set_port(None);
+        }
+
+        // Hosts of schemes the parser doesn't know keep their casing
+        if let Some(host) = url.host_str() {
+            let lowercase = host.to_ascii_lowercase();
+            if lowercase != host {
+                let _ = url.set_host(Some(&lowercase));
+            }
+        }
+
+        Self(url)
+    }
 }
 
 impl fmt::Display for OriginalUrl {
@@ -65,6 +430,64 @@ impl fmt::Display for OriginalUrl {
     }
 }
 
+impl TryFrom<String> for OriginalUrl {
+    type Error = UrlShortenerError;
+
+    fn try_from(url: String) -> Result<Self> {
+        Self::new(url)
+    }
+}
+
+/// Convert an already parsed URL without parsing it again
+///
+/// Applies the same scheme and length limits as `OriginalUrl::new`.
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::domain::OriginalUrl;
+///
+/// let parsed = url::Url::parse("https://example.com/path").unwrap();
+/// let url = OriginalUrl::try_from(parsed).unwrap();
+/// assert_eq!(url.as_url().path(), "/path");
+/// ```
+impl TryFrom<Url> for OriginalUrl {
+    type Error = UrlShortenerError;
+
+    fn try_from(url: Url) -> Result<Self> {
+        check_length(url.as_str(), Self::MAX_URL_LENGTH)?;
+        Self::from_parsed(url, Self::DEFAULT_SCHEMES)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for OriginalUrl {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let url = String::deserialize(deserializer)?;
+        Self::from_stored(url).map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<OriginalUrl> for String {
+    fn from(url: OriginalUrl) -> Self {
+        url.0.into()
+    }
+}
+
+fn check_length(url: &str, max_len: usize) -> Result<()> {
+    if url.len() > max_len {
+        return Err(UrlShortenerError::InvalidUrl(format!(
+            "URL is {} bytes long; the maximum is {} bytes",
+            url.len(),
+            max_len
+        )));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -96,6 +519,74 @@ mod tests {
         assert!(matches!(result, Err(UrlShortenerError::InvalidUrl(_))));
     }
 
+    #[test]
+    fn test_new_with_schemes_accepts_custom_scheme() {
+        let allowed = ["http", "https", "ftp", "mailto"];
+
+        let raw = "ftp://files.example.com/a.txt".to_string();
+        let url = OriginalUrl::new_with_schemes(raw, &allowed).unwrap();
+        assert_eq!(url.scheme(), "ftp");
+        assert_eq!(url.host_str(), Some("files.example.com"));
+
+        let url = OriginalUrl::new_with_schemes("mailto:team@example.com".to_string(), &allowed)
+            .unwrap();
+        assert_eq!(url.scheme(), "mailto");
+        assert_eq!(url.host_str(), None);
+
+        let url = OriginalUrl::new_with_schemes("FTP://example.com".to_string(), &["ftp"]);
+        assert!(url.is_ok());
+    }
+
+    #[test]
+    fn test_new_with_schemes_rejects_disallowed_scheme() {
+        let result = OriginalUrl::new_with_schemes("https://example.com".to_string(), &["ftp"]);
+        match result {
+            Err(UrlShortenerError::InvalidUrl(message)) => {
+                assert_eq!(message, "Unsupported scheme: https. Allowed schemes: ftp");
+            }
+            other => panic!("expected InvalidUrl, got {:?}", other),
+        }
+
+        let result = OriginalUrl::new("ftp://example.com".to_string());
+        match result {
+            Err(UrlShortenerError::InvalidUrl(message)) => {
+                assert!(message.contains("Allowed schemes: http, https"), "{}", message);
+            }
+            other => panic!("expected InvalidUrl, got {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_original_url_serde_round_trip() {
+        let url = OriginalUrl::new("https://example.com/path?q=1".to_string()).unwrap();
+        let json = serde_json::to_string(&url).unwrap();
+        assert_eq!(json, "\"https://example.com/path?q=1\"");
+        assert_eq!(serde_json::from_str::<OriginalUrl>(&json).unwrap(), url);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_original_url_deserialize_rejects_invalid() {
+        assert!(serde_json::from_str::<OriginalUrl>("\"not a url\"").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_original_url_serde_round_trip_keeps_wider_limits() {
+        let mailto = "mailto:team@example.com".to_string();
+        let mailto = OriginalUrl::new_with_schemes(mailto, &["mailto"]).unwrap();
+        let ftp = "ftp://example.com/file".to_string();
+        let ftp = OriginalUrl::new_with_schemes(ftp, &["ftp"]).unwrap();
+        let long = format!("https://example.com/{}", "a".repeat(3000));
+        let long = OriginalUrl::new_with_max_len(long, 4096).unwrap();
+
+        for url in [mailto, ftp, long] {
+            let json = serde_json::to_string(&url).unwrap();
+            assert_eq!(serde_json::from_str::<OriginalUrl>(&json).unwrap(), url);
+        }
+    }
+
     #[test]
     fn test_url_with_query_params() {
         let url = OriginalUrl::new("https://example.com/search?q=test".to_string()).unwrap();
@@ -107,4 +598,263 @@ mod tests {
         let url = OriginalUrl::new("https://example.com/page#section".to_string()).unwrap();
         assert!(url.as_str().contains("#section"));
     }
+
+    #[test]
+    fn test_normalized_key_host_casing() {
+        let a = OriginalUrl::new("https://example.com/Path".to_string()).unwrap();
+        let b = OriginalUrl::new("https://EXAMPLE.com/Path".to_string()).unwrap();
+        assert_eq!(a.normalized_key(), b.normalized_key());
+
+        // Paths stay case-sensitive
+        let c = OriginalUrl::new("https://example.com/path".to_string()).unwrap();
+        assert_ne!(a.normalized_key(), c.normalized_key());
+    }
+
+    #[test]
+    fn test_normalized_key_default_ports_and_trailing_slash() {
+        let keys: Vec<String> = [
+            "https://example.com",
+            "https://example.com/",
+            "https://example.com:443",
+            "HTTPS://Example.COM:443/",
+        ]
+        .iter()
+        .map(|u| OriginalUrl::new(u.to_string()).unwrap().normalized_key())
+        .collect();
+
+        assert!(keys.iter().all(|k| k == "https://example.com"));
+
+        let other_port = OriginalUrl::new("https://example.com:8443".to_string()).unwrap();
+        assert_eq!(other_port.normalized_key(), "https://example.com:8443");
+    }
+
+    #[test]
+    fn test_normalized_key_query_order() {
+        let a = OriginalUrl::new("https://example.com/s?q=rust&page=2".to_string()).unwrap();
+        let b = OriginalUrl::new("https://example.com/s?page=2&q=rust".to_string()).unwrap();
+        assert_eq!(a.normalized_key(), b.normalized_key());
+        assert_eq!(a.normalized_key(), "https://example.com/s?page=2&q=rust");
+
+        let root = OriginalUrl::new("https://example.com/?b=1&a=2#top".to_string()).unwrap();
+        assert_eq!(root.normalized_key(), "https://example.com?a=2&b=1#top");
+
+        let empty = OriginalUrl::new("https://example.com/s?&".to_string()).unwrap();
+        assert_eq!(empty.normalized_key(), "https://example.com/s");
+    }
+
+    #[test]
+    fn test_new_normalized() {
+        let url = OriginalUrl::new_normalized(
+            "http://Example.com:80/a?z=1&y=2".to_string()
+        ).unwrap();
+        assert_eq!(url.as_str(), "http://example.com/a?y=2&z=1");
+
+        // new keeps the URL as given
+        let url = OriginalUrl::new("http://example.com/a?z=1&y=2".to_string()).unwrap();
+        assert_eq!(url.as_str(), "http://example.com/a?z=1&y=2");
+
+        assert!(OriginalUrl::new_normalized("ftp://example.com".to_string()).is_err());
+    }
+
+    fn with_scheme(url: &str) -> OriginalUrl {
+        let scheme = url.split(':').next().unwrap();
+        OriginalUrl::new_with_schemes(url.to_string(), &[scheme]).unwrap()
+    }
+
+    #[test]
+    fn test_canonical_strips_default_port() {
+        let url = with_scheme("ssh://ex.com:22/repo");
+        assert_eq!(url.as_str(), "ssh://ex.com:22/repo");
+        assert_eq!(url.canonical().as_str(), "ssh://ex.com/repo");
+
+        let url = with_scheme("ldap://ex.com:389/dc=ex");
+        assert_eq!(url.canonical().as_str(), "ldap://ex.com/dc=ex");
+
+        let url = with_scheme("ssh://ex.com:2222/repo");
+        let canonical = url.canonical_with_default_ports(&[("SSH", 2222)]);
+        assert_eq!(canonical.as_str(), "ssh://ex.com/repo");
+    }
+
+    #[test]
+    fn test_canonical_preserves_non_default_port() {
+        // The hosts are still lowercased, so the URLs are canonicalized
+        let url = with_scheme("ssh://EX.com:2222/repo");
+        assert_eq!(url.canonical().as_str(), "ssh://ex.com:2222/repo");
+
+        // 22 is not the default for ldap
+        let url = with_scheme("ldap://EX.com:22/");
+        assert_eq!(url.canonical().as_str(), "ldap://ex.com:22/");
+
+        // Schemes without a known default keep every port
+        let url = with_scheme("custom://EX.com:22/");
+        assert_eq!(url.canonical().as_str(), "custom://ex.com:22/");
+    }
+
+    #[test]
+    fn test_canonical_lowercases_host() {
+        let url = with_scheme("ssh://Git.EXAMPLE.com/Repo");
+        assert_eq!(url.as_str(), "ssh://Git.EXAMPLE.com/Repo");
+        assert_eq!(url.canonical().as_str(), "ssh://git.example.com/Repo");
+    }
+
+    #[test]
+    fn test_has_domain() {
+        let url = OriginalUrl::new("https://Docs.Example.com./guide".to_string()).unwrap();
+        assert!(url.has_domain("docs.example.com"));
+        assert!(url.has_domain("DOCS.EXAMPLE.COM."));
+        assert!(!url.has_domain("example.com"));
+        assert!(!url.has_domain("cs.example.com"));
+
+        let ip = OriginalUrl::new("http://127.0.0.1/".to_string()).unwrap();
+        assert!(!ip.has_domain("127.0.0.1"));
+    }
+
+    #[test]
+    fn test_is_same_site() {
+        let url = OriginalUrl::new("https://example.com/a".to_string()).unwrap();
+        assert!(url.is_same_site("example.com"));
+        assert!(url.is_same_site("EXAMPLE.com"));
+        assert!(url.is_same_site("www.example.com"));
+        assert!(url.is_same_site("WWW.Example.com."));
+
+        let www = OriginalUrl::new("https://WWW.example.com:8443/".to_string()).unwrap();
+        assert!(www.is_same_site("example.com"));
+        assert!(www.is_same_site("www.example.com"));
+
+        assert!(!url.is_same_site("example.org"));
+        assert!(!url.is_same_site("blog.example.com"));
+        assert!(!url.is_same_site("wwwexample.com"));
+        assert!(!url.is_same_site(""));
+
+        let ip = OriginalUrl::new("http://127.0.0.1/".to_string()).unwrap();
+        assert!(ip.is_same_site("127.0.0.1"));
+    }
+
+    #[test]
+    fn test_host_str() {
+        let url = OriginalUrl::new("https://example.com/path".to_string()).unwrap();
+        assert_eq!(url.host_str(), Some("example.com"));
+
+        let url = OriginalUrl::new("http://127.0.0.1:8080/".to_string()).unwrap();
+        assert_eq!(url.host_str(), Some("127.0.0.1"));
+        assert_eq!(url.domain(), None);
+    }
+
+    #[test]
+    fn test_max_url_length_boundary() {
+        let prefix = "https://example.com/";
+        let padding = "a".repeat(OriginalUrl::MAX_URL_LENGTH - prefix.len());
+        let at_limit = format!("{}{}", prefix, padding);
+        assert_eq!(at_limit.len(), OriginalUrl::MAX_URL_LENGTH);
+        assert!(OriginalUrl::new(at_limit.clone()).is_ok());
+
+        let over_limit = format!("{}a", at_limit);
+        let result = OriginalUrl::new(over_limit);
+        match result {
+            Err(UrlShortenerError::InvalidUrl(msg)) => assert!(msg.contains("2048")),
+            other => panic!("expected InvalidUrl, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_new_with_max_len() {
+        let url = "https://example.com/abc".to_string();
+        assert!(OriginalUrl::new_with_max_len(url.clone(), url.len()).is_ok());
+
+        let result = OriginalUrl::new_with_max_len(url.clone(), url.len() - 1);
+        assert!(matches!(result, Err(UrlShortenerError::InvalidUrl(_))));
+
+        // A larger cap admits URLs that `new` rejects
+        let long = format!("https://example.com/{}", "a".repeat(OriginalUrl::MAX_URL_LENGTH));
+        assert!(OriginalUrl::new(long.clone()).is_err());
+        assert!(OriginalUrl::new_with_max_len(long, 4096).is_ok());
+    }
+
+    #[test]
+    fn test_components_present() {
+        let raw = "https://example.com:8443/docs/page?lang=en&v=2#intro";
+        let url = OriginalUrl::new(raw.to_string()).unwrap();
+
+        assert_eq!(url.path(), "/docs/page");
+        assert_eq!(url.query(), Some("lang=en&v=2"));
+        assert_eq!(url.fragment(), Some("intro"));
+        assert_eq!(url.port(), Some(8443));
+    }
+
+    #[test]
+    fn test_components_absent() {
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+
+        assert_eq!(url.path(), "/");
+        assert_eq!(url.query(), None);
+        assert_eq!(url.fragment(), None);
+        assert_eq!(url.port(), None);
+
+        // A default port is not reported
+        let url = OriginalUrl::new("https://example.com:443".to_string()).unwrap();
+        assert_eq!(url.port(), None);
+    }
+
+    #[test]
+    fn test_without_tracking_params() {
+        let url = OriginalUrl::new(
+            "https://example.com/a?utm_source=x&page=2&gclid=1&q=a%20b&utm_campaign=y#top"
+                .to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            url.without_tracking_params().as_str(),
+            "https://example.com/a?page=2&q=a%20b#top"
+        );
+
+        // Only tracking parameters: the `?` goes too
+        let url = OriginalUrl::new("https://example.com/?utm_medium=email".to_string()).unwrap();
+        assert_eq!(url.without_tracking_params().as_str(), "https://example.com/");
+
+        // Encoded names still match; similar names don't
+        let url = OriginalUrl::new(
+            "https://example.com/?utm%5Fterm=x&utm_sources=y&fbclid".to_string()
+        )
+        .unwrap();
+        assert_eq!(url.without_tracking_params().as_str(), "https://example.com/?utm_sources=y");
+
+        let url = OriginalUrl::new("https://example.com/path".to_string()).unwrap();
+        assert_eq!(url.without_tracking_params(), url);
+    }
+
+    #[test]
+    fn test_without_params() {
+        let url = OriginalUrl::new("https://example.com/?ref=tw&id=1&session=s".to_string())
+            .unwrap();
+        assert_eq!(
+            url.without_params(&["ref", "session"]).as_str(),
+            "https://example.com/?id=1"
+        );
+        assert_eq!(url.without_params(&[]).as_str(), "https://example.com/?ref=tw&id=1&session=s");
+    }
+
+    #[test]
+    fn test_try_from_parsed_url() {
+        let parsed = Url::parse("https://example.com/docs?lang=en").unwrap();
+
+        let url = OriginalUrl::try_from(parsed.clone()).unwrap();
+        assert_eq!(url.as_url(), &parsed);
+        assert_eq!(url, OriginalUrl::new(parsed.to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_try_from_parsed_url_rejects_scheme() {
+        let parsed = Url::parse("ftp://example.com/file.txt").unwrap();
+
+        let result = OriginalUrl::try_from(parsed);
+        assert!(matches!(result, Err(UrlShortenerError::InvalidUrl(_))));
+    }
+
+    #[test]
+    fn test_try_from_parsed_url_rejects_oversized() {
+        let path = "a".repeat(OriginalUrl::MAX_URL_LENGTH);
+        let parsed = Url::parse(&format!("https://example.com/{}", path)).unwrap();
+
+        assert!(OriginalUrl::try_from(parsed).is_err());
+    }
 }
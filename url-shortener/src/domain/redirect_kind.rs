@@ -0,0 +1,35 @@
+//! HTTP redirect kinds for resolved short codes
+
+/// Kind of HTTP redirect a web server should answer a short code with
+///
+/// Browsers and proxies cache permanent redirects, so later visits may skip
+/// the shortener and go unrecorded. Temporary redirects reach the shortener
+/// every time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RedirectKind {
+    /// `301 Moved Permanently`
+    Permanent,
+    /// `302 Found`
+    Temporary,
+}
+
+impl RedirectKind {
+    /// Get the HTTP status code for this kind of redirect
+    pub fn status_code(self) -> u16 {
+        match self {
+            RedirectKind::Permanent => 301,
+            RedirectKind::Temporary => 302,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_codes() {
+        assert_eq!(RedirectKind::Permanent.status_code(), 301);
+        assert_eq!(RedirectKind::Temporary.status_code(), 302);
+    }
+}
@@ -0,0 +1,74 @@
+//! Everything an HTTP layer needs to answer a short code
+
+use std::time::SystemTime;
+
+/// HTTP redirect for a resolved short code, with cache hints
+///
+/// Built by `UrlShortenerService::build_redirect_full`. Maps directly onto
+/// the response: `status` and the `Location` header, `Cache-Control:
+/// max-age={cache_control_seconds}` and, for links that expire, an `Expires`
+/// header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedirectResponse {
+    status: u16,
+    location: String,
+    cache_control_seconds: u64,
+    expires_at: Option<SystemTime>,
+}
+
+impl RedirectResponse {
+    /// Create a redirect response
+    pub fn new(
+        status: u16,
+        location: String,
+        cache_control_seconds: u64,
+        expires_at: Option<SystemTime>,
+    ) -> Self {
+        Self {
+            status,
+            location,
+            cache_control_seconds,
+            expires_at,
+        }
+    }
+
+    /// Get the HTTP status code
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    /// Get the `Location` header value
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+
+    /// Get the `Cache-Control` max-age, in seconds
+    pub fn cache_control_seconds(&self) -> u64 {
+        self.cache_control_seconds
+    }
+
+    /// Get the time the link expires, for the `Expires` header
+    ///
+    /// `None` for links that never expire.
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.expires_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_accessors() {
+        let expires_at = UNIX_EPOCH + Duration::from_secs(1_000);
+        let response =
+            RedirectResponse::new(302, "https://example.com/".to_string(), 60, Some(expires_at));
+
+        assert_eq!(response.status(), 302);
+        assert_eq!(response.location(), "https://example.com/");
+        assert_eq!(response.cache_control_seconds(), 60);
+        assert_eq!(response.expires_at(), Some(expires_at));
+    }
+}
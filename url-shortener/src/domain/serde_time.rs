@@ -0,0 +1,60 @@
+//! Serde helpers encoding `SystemTime` as Unix timestamps
+//!
+//! Timestamps are whole seconds since the Unix epoch; sub-second precision
+//! is dropped on serialization. Timestamps too large for `SystemTime` are
+//! rejected on deserialization.
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serializer};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn to_secs(time: &SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn from_secs<E: Error>(secs: u64) -> Result<SystemTime, E> {
+    UNIX_EPOCH
+        .checked_add(Duration::from_secs(secs))
+        .ok_or_else(|| E::custom(format!("timestamp {} is out of range", secs)))
+}
+
+/// `SystemTime` as Unix seconds
+pub(crate) mod unix_seconds {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        time: &SystemTime,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(to_secs(time))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<SystemTime, D::Error> {
+        from_secs(u64::deserialize(deserializer)?)
+    }
+}
+
+/// `Option<SystemTime>` as optional Unix seconds
+pub(crate) mod option_unix_seconds {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        time: &Option<SystemTime>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match time {
+            Some(time) => serializer.serialize_some(&to_secs(time)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<SystemTime>, D::Error> {
+        Option::<u64>::deserialize(deserializer)?.map(from_secs).transpose()
+    }
+}
@@ -10,10 +10,22 @@ use std::fmt;
 ///
 /// Short codes must be:
 /// - Between 4 and 12 characters long
-/// - Contain only alphanumeric characters (a-z, A-Z, 0-9)
+/// - Contain only alphanumeric characters (a-z, A-Z, 0-9), except for at
+///   most one `SEPARATOR` between two alphanumeric parts, as in the
+///   namespaced code `acme-x7k2`
 ///
 /// Once created, a ShortCode is guaranteed to be valid.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// Short codes are ordered lexicographically by their string value.
+///
+/// With the `serde` feature, short codes serialize as plain strings and are
+/// validated on deserialization.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
 pub struct ShortCode(String);
 
 impl ShortCode {
@@ -23,13 +35,21 @@ impl ShortCode {
     /// Maximum length for a short code
     pub const MAX_LENGTH: usize = 12;
 
+    /// Separator between a namespace prefix and the rest of a code
+    pub const SEPARATOR: char = '-';
+
+    /// Characters a check character is computed over, in digit order
+    const CHECKSUM_CHARSET: &'static [u8] =
+        b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
     /// Create a new validated ShortCode
     ///
     /// # Errors
     ///
     /// Returns `UrlShortenerError::InvalidShortCode` if:
     /// - Length is outside the valid range
-    /// - Contains non-alphanumeric characters
+    /// - Contains non-alphanumeric characters other than a single separator
+    ///   between two alphanumeric parts
     ///
     /// # Examples
     ///
@@ -43,34 +63,184 @@ impl ShortCode {
     /// assert!(ShortCode::new("abc".to_string()).is_err());
     ///
     /// // Invalid characters
-    /// assert!(ShortCode::new("abc-123".to_string()).is_err());
+    /// assert!(ShortCode::new("abc_123".to_string()).is_err());
+    ///
+    /// // A namespace prefix
+    /// assert!(ShortCode::new("acme-x7k2".to_string()).is_ok());
     /// ```
     pub fn new(code: String) -> Result<Self> {
         Self::validate(&code)?;
         Ok(Self(code))
     }
 
+    /// Create a ShortCode, lowercasing it first
+    ///
+    /// For case-insensitive matching, where `AbC123` and `abc123` name the
+    /// same code.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::InvalidShortCode` under the same
+    /// conditions as `new`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_shortener::domain::ShortCode;
+    ///
+    /// let code = ShortCode::new_case_insensitive("AbC123".to_string()).unwrap();
+    /// assert_eq!(code.as_str(), "abc123");
+    /// ```
+    pub fn new_case_insensitive(code: String) -> Result<Self> {
+        Self::new(code.to_lowercase())
+    }
+
+    /// Create a ShortCode, dropping every non-alphanumeric character first
+    ///
+    /// For forgiving input paths such as imports, where codes may carry stray
+    /// spaces or punctuation. Separators are dropped too, so the result never
+    /// has a namespace prefix.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::InvalidShortCode` if the cleaned code is
+    /// outside the valid length range
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_shortener::domain::ShortCode;
+    ///
+    /// let code = ShortCode::new_lenient(" ab-c 1 ".to_string()).unwrap();
+    /// assert_eq!(code.as_str(), "abc1");
+    /// ```
+    pub fn new_lenient(code: String) -> Result<Self> {
+        Self::new(code.chars().filter(|c| c.is_alphanumeric()).collect())
+    }
+
+    /// Check whether `code` would be accepted by `new`
+    ///
+    /// Doesn't allocate, so it suits validating input on a hot path before
+    /// constructing a ShortCode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_shortener::domain::ShortCode;
+    ///
+    /// assert!(ShortCode::is_valid("abc123"));
+    /// assert!(!ShortCode::is_valid("abc"));
+    /// assert!(!ShortCode::is_valid("abc_123"));
+    /// ```
+    pub fn is_valid(code: &str) -> bool {
+        Self::check(code).is_none()
+    }
+
     /// Validate a short code string
     fn validate(code: &str) -> Result<()> {
-        if code.len() < Self::MIN_LENGTH {
-            return Err(UrlShortenerError::InvalidShortCode(
+        let message = match Self::check(code) {
+            None => return Ok(()),
+            Some(Violation::TooShort) => {
                 format!("Too short: must be at least {} characters", Self::MIN_LENGTH)
-            ));
+            }
+            Some(Violation::TooLong) => {
+                format!("Too long: must be at most {} characters", Self::MAX_LENGTH)
+            }
+            Some(Violation::InvalidCharacter) => format!(
+                "Must contain only alphanumeric characters, with at most one '{}' between them",
+                Self::SEPARATOR
+            ),
+        };
+
+        Err(UrlShortenerError::InvalidShortCode(message))
+    }
+
+    /// Find the first rule `code` breaks, if any
+    fn check(code: &str) -> Option<Violation> {
+        if code.len() < Self::MIN_LENGTH {
+            return Some(Violation::TooShort);
         }
 
         if code.len() > Self::MAX_LENGTH {
-            return Err(UrlShortenerError::InvalidShortCode(
-                format!("Too long: must be at most {} characters", Self::MAX_LENGTH)
-            ));
+            return Some(Violation::TooLong);
         }
 
-        if !code.chars().all(|c| c.is_alphanumeric()) {
-            return Err(UrlShortenerError::InvalidShortCode(
-                "Must contain only alphanumeric characters".to_string()
-            ));
+        let is_part = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_alphanumeric());
+        let valid = match code.split_once(Self::SEPARATOR) {
+            Some((prefix, rest)) => is_part(prefix) && is_part(rest),
+            None => is_part(code),
+        };
+        if !valid {
+            return Some(Violation::InvalidCharacter);
         }
 
-        Ok(())
+        None
+    }
+
+    /// Append a check character to this code
+    ///
+    /// The check character is computed with the Luhn mod 62 algorithm over
+    /// the ASCII letters and digits of the code; a separator is skipped.
+    /// `verify_checksum` then catches any single mistyped character and most
+    /// swaps of adjacent characters.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::InvalidShortCode` if the code is already
+    /// `MAX_LENGTH` characters long or contains non-ASCII characters
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url_shortener::domain::ShortCode;
+    ///
+    /// let code = ShortCode::new("abc123".to_string()).unwrap().with_checksum().unwrap();
+    /// assert_eq!(code.as_str().len(), 7);
+    /// assert!(code.verify_checksum());
+    ///
+    /// let typo = ShortCode::new(code.as_str().replacen('b', "h", 1)).unwrap();
+    /// assert!(!typo.verify_checksum());
+    /// ```
+    pub fn with_checksum(&self) -> Result<ShortCode> {
+        let check = Self::check_character(&self.0).ok_or_else(|| {
+            UrlShortenerError::InvalidShortCode(
+                "Check characters need ASCII letters and digits".to_string()
+            )
+        })?;
+
+        Self::new(format!("{}{}", self.0, check))
+    }
+
+    /// Check whether the last character is the check character of the rest
+    ///
+    /// Returns `false` for codes that `with_checksum` couldn't have produced.
+    pub fn verify_checksum(&self) -> bool {
+        let Some(last) = self.0.chars().last() else {
+            return false;
+        };
+        let body = &self.0[..self.0.len() - last.len_utf8()];
+
+        Self::check_character(body) == Some(last)
+    }
+
+    /// Compute the Luhn mod 62 check character for `body`
+    ///
+    /// Returns `None` if `body` contains a character other than an ASCII
+    /// letter, digit or separator.
+    pub(crate) fn check_character(body: &str) -> Option<char> {
+        let base = Self::CHECKSUM_CHARSET.len();
+        let mut sum = 0;
+
+        let digits = body.bytes().rev().filter(|&b| b != Self::SEPARATOR as u8);
+        for (position, byte) in digits.enumerate() {
+            let value = Self::CHECKSUM_CHARSET.iter().position(|&c| c == byte)?;
+            // Double every other digit, starting with the rightmost one
+            let value = if position % 2 == 0 { value * 2 } else { value };
+            sum += value / base + value % base;
+        }
+
+        let check = (base - sum % base) % base;
+        Some(Self::CHECKSUM_CHARSET[check] as char)
     }
 
     /// Get the short code as a string slice
@@ -84,12 +254,33 @@ impl ShortCode {
     }
 }
 
+/// A short code validation rule
+enum Violation {
+    TooShort,
+    TooLong,
+    InvalidCharacter,
+}
+
 impl fmt::Display for ShortCode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
     }
 }
 
+impl TryFrom<String> for ShortCode {
+    type Error = UrlShortenerError;
+
+    fn try_from(code: String) -> Result<Self> {
+        Self::new(code)
+    }
+}
+
+impl From<ShortCode> for String {
+    fn from(code: ShortCode) -> Self {
+        code.into_inner()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,6 +291,45 @@ mod tests {
         assert_eq!(code.as_str(), "abc123");
     }
 
+    #[test]
+    fn test_namespaced_short_code() {
+        let code = ShortCode::new("acme-x7k2".to_string()).unwrap();
+        assert_eq!(code.as_str().split_once(ShortCode::SEPARATOR), Some(("acme", "x7k2")));
+
+        // The separator counts towards the length
+        assert!(ShortCode::new("a-bc".to_string()).is_ok());
+        assert!(ShortCode::new("a-b".to_string()).is_err());
+        assert!(ShortCode::new("acmecorp-x7k2".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_new_case_insensitive_lowercases() {
+        let code = ShortCode::new_case_insensitive("AbC123".to_string()).unwrap();
+        assert_eq!(code, ShortCode::new("abc123".to_string()).unwrap());
+        assert!(ShortCode::new_case_insensitive("AbC".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_new_lenient_strips_invalid_characters() {
+        assert_eq!(ShortCode::new_lenient("ab-c1".to_string()).unwrap().as_str(), "abc1");
+        assert_eq!(ShortCode::new_lenient("x7 k2_q!".to_string()).unwrap().as_str(), "x7k2q");
+
+        // Already valid codes are kept as they are, apart from separators
+        assert_eq!(ShortCode::new_lenient("AbC123".to_string()).unwrap().as_str(), "AbC123");
+        assert_eq!(ShortCode::new_lenient("acme-x7k2".to_string()).unwrap().as_str(), "acmex7k2");
+    }
+
+    #[test]
+    fn test_new_lenient_rejects_out_of_bounds_result() {
+        let result = ShortCode::new_lenient("!!".to_string());
+        assert!(matches!(result, Err(UrlShortenerError::InvalidShortCode(_))));
+        assert!(ShortCode::new_lenient("a-b-c".to_string()).is_err());
+        assert!(ShortCode::new_lenient(format!("{}--", "a".repeat(13))).is_err());
+
+        // The strict constructor is unaffected
+        assert!(ShortCode::new("ab c1".to_string()).is_err());
+    }
+
     #[test]
     fn test_short_code_too_short() {
         let result = ShortCode::new("abc".to_string());
@@ -116,7 +346,10 @@ mod tests {
     #[test]
     fn test_short_code_invalid_characters() {
         let invalid_codes = vec![
-            "abc-123",
+            "abc--123",
+            "a-b-c123",
+            "-abc123",
+            "abc123-",
             "abc_123",
             "abc 123",
             "abc@123",
@@ -129,6 +362,110 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_is_valid_matches_new() {
+        let long = "a".repeat(ShortCode::MAX_LENGTH + 1);
+        let max = "a".repeat(ShortCode::MAX_LENGTH);
+        let cases = [
+            ("abc123", true),
+            ("abcd", true),
+            (max.as_str(), true),
+            ("abc", false),
+            ("", false),
+            (long.as_str(), false),
+            ("abc-123", true),
+            ("abc-12-3", false),
+            ("-abc123", false),
+            ("abc_123", false),
+            ("abc 123", false),
+            ("abc@123", false),
+            ("abc#123", false),
+        ];
+
+        for (code, valid) in cases {
+            assert_eq!(ShortCode::is_valid(code), valid, "is_valid({:?})", code);
+            assert_eq!(ShortCode::new(code.to_string()).is_ok(), valid, "new({:?})", code);
+        }
+    }
+
+    #[test]
+    fn test_checksum_round_trip() {
+        let code = ShortCode::new("x7Kq2".to_string()).unwrap();
+        let checked = code.with_checksum().unwrap();
+
+        assert_eq!(checked.as_str().len(), code.as_str().len() + 1);
+        assert!(checked.as_str().starts_with(code.as_str()));
+        assert!(checked.verify_checksum());
+
+        let namespaced = ShortCode::new("acme-x7k2".to_string()).unwrap();
+        assert!(namespaced.with_checksum().unwrap().verify_checksum());
+    }
+
+    #[test]
+    fn test_checksum_catches_single_character_errors() {
+        let checked = ShortCode::new("abc123".to_string()).unwrap().with_checksum().unwrap();
+        let charset = std::str::from_utf8(ShortCode::CHECKSUM_CHARSET).unwrap();
+
+        for (index, original) in checked.as_str().char_indices() {
+            for replacement in charset.chars().filter(|&c| c != original) {
+                let mut typo = checked.as_str().to_string();
+                typo.replace_range(index..index + 1, &replacement.to_string());
+
+                let typo = ShortCode::new(typo).unwrap();
+                assert!(!typo.verify_checksum(), "{} should fail", typo);
+            }
+        }
+    }
+
+    #[test]
+    fn test_checksum_rejects_codes_it_cannot_extend() {
+        let full = ShortCode::new("a".repeat(ShortCode::MAX_LENGTH)).unwrap();
+        assert!(matches!(full.with_checksum(), Err(UrlShortenerError::InvalidShortCode(_))));
+
+        let accented = ShortCode::new("caf\u{e9}123".to_string()).unwrap();
+        assert!(accented.with_checksum().is_err());
+        assert!(!accented.verify_checksum());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_short_code_serde_round_trip() {
+        let code = ShortCode::new("abc123".to_string()).unwrap();
+        let json = serde_json::to_string(&code).unwrap();
+        assert_eq!(json, "\"abc123\"");
+        assert_eq!(serde_json::from_str::<ShortCode>(&json).unwrap(), code);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_short_code_deserialize_rejects_invalid() {
+        assert!(serde_json::from_str::<ShortCode>("\"abc_123\"").is_err());
+        assert!(serde_json::from_str::<ShortCode>("\"abc\"").is_err());
+    }
+
+    #[test]
+    fn test_ordering_is_lexicographic() {
+        let code = |s: &str| ShortCode::new(s.to_string()).unwrap();
+        let mut codes: Vec<ShortCode> = ["beta", "Zeta", "alpha", "acme-x7k2", "1234"]
+            .into_iter()
+            .map(code)
+            .collect();
+        codes.sort();
+
+        let sorted: Vec<&str> = codes.iter().map(ShortCode::as_str).collect();
+        assert_eq!(sorted, ["1234", "Zeta", "acme-x7k2", "alpha", "beta"]);
+
+        // Consistent with Eq
+        assert_eq!(code("alpha").cmp(&code("alpha")), std::cmp::Ordering::Equal);
+
+        let mut counts = std::collections::BTreeMap::new();
+        *counts.entry(code("beta")).or_insert(0) += 1;
+        *counts.entry(code("alpha")).or_insert(0) += 1;
+        *counts.entry(code("beta")).or_insert(0) += 1;
+        assert_eq!(counts.get(&code("beta")), Some(&2));
+        assert_eq!(counts.keys().next(), Some(&code("alpha")));
+    }
+
     #[test]
     fn test_short_code_valid_edge_cases() {
         // Min length
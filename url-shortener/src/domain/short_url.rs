@@ -0,0 +1,158 @@
+//! Full short URL type
+//!
+//! ShortUrl pairs the base URL of a deployment with a short code, so the
+//! public link is built and parsed in one place.
+
+use super::ShortCode;
+use crate::error::{Result, UrlShortenerError};
+use std::fmt;
+use std::str::FromStr;
+
+/// A short link: a base URL followed by a short code
+///
+/// Displays as `{base}/{code}`. Trailing slashes on the base are dropped, so
+/// `https://sho.rt` and `https://sho.rt/` give the same link. The base is
+/// kept as given otherwise; it may leave out the scheme.
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::domain::{ShortCode, ShortUrl};
+///
+/// let code = ShortCode::new("abc123".to_string()).unwrap();
+/// let link = ShortUrl::new("https://sho.rt/".to_string(), code);
+/// assert_eq!(link.to_string(), "https://sho.rt/abc123");
+///
+/// let parsed: ShortUrl = "sho.rt/abc123".parse().unwrap();
+/// assert_eq!(parsed.base(), "sho.rt");
+/// assert_eq!(parsed.code().as_str(), "abc123");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShortUrl {
+    base: String,
+    code: ShortCode,
+}
+
+impl ShortUrl {
+    /// Create a short link from a base URL and a short code
+    pub fn new(base: String, code: ShortCode) -> Self {
+        Self {
+            base: base.trim_end_matches('/').to_string(),
+            code,
+        }
+    }
+
+    /// Get the base URL, without a trailing slash
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// Get the short code
+    pub fn code(&self) -> &ShortCode {
+        &self.code
+    }
+
+    /// Split into the base URL and the short code
+    pub fn into_parts(self) -> (String, ShortCode) {
+        (self.base, self.code)
+    }
+}
+
+impl fmt::Display for ShortUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.base, self.code)
+    }
+}
+
+/// Parse a full short link
+///
+/// The last path segment is the short code and everything before it is the
+/// base. A trailing slash after the code is ignored.
+///
+/// # Errors
+///
+/// Returns `UrlShortenerError::InvalidUrl` if there is no base or no code
+/// segment, or `UrlShortenerError::InvalidShortCode` if the code segment
+/// isn't a valid short code
+impl FromStr for ShortUrl {
+    type Err = UrlShortenerError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let trimmed = s.trim().trim_end_matches('/');
+
+        // Don't mistake the slashes of `scheme://` for the code separator
+        let host_start = trimmed.find("://").map_or(0, |i| i + 3);
+        let Some((base, code)) = trimmed[host_start..]
+            .rsplit_once('/')
+            .map(|(host, code)| (&trimmed[..host_start + host.len()], code))
+        else {
+            return Err(UrlShortenerError::InvalidUrl(format!(
+                "'{}' has no short code segment",
+                s
+            )));
+        };
+
+        if base.len() == host_start {
+            return Err(UrlShortenerError::InvalidUrl(format!("'{}' has no host", s)));
+        }
+
+        Ok(Self::new(base.to_string(), ShortCode::new(code.to_string())?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(code: &str) -> ShortCode {
+        ShortCode::new(code.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_display_trims_trailing_slashes() {
+        for base in ["https://sho.rt", "https://sho.rt/", "https://sho.rt//"] {
+            let link = ShortUrl::new(base.to_string(), code("abc123"));
+            assert_eq!(link.to_string(), "https://sho.rt/abc123");
+            assert_eq!(link.base(), "https://sho.rt");
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for raw in [
+            "https://sho.rt/abc123",
+            "http://localhost:8080/acme-x7k2",
+            "https://example.com/go/abc123",
+            "sho.rt/abc123",
+        ] {
+            let link: ShortUrl = raw.parse().unwrap();
+            assert_eq!(link.to_string(), raw);
+            assert_eq!(link.to_string().parse::<ShortUrl>().unwrap(), link);
+        }
+
+        let link: ShortUrl = "https://example.com/go/abc123/".parse().unwrap();
+        assert_eq!(link.into_parts(), ("https://example.com/go".to_string(), code("abc123")));
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_code() {
+        for raw in ["https://sho.rt/abc_123", "https://sho.rt/ab", "https://sho.rt/abc123?x=1"] {
+            let result = raw.parse::<ShortUrl>();
+            assert!(
+                matches!(result, Err(UrlShortenerError::InvalidShortCode(_))),
+                "{raw} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_missing_parts() {
+        for raw in ["https://abc123", "abc123", "/abc123", "https:///abc123", ""] {
+            let result = raw.parse::<ShortUrl>();
+            assert!(
+                matches!(result, Err(UrlShortenerError::InvalidUrl(_))),
+                "{raw:?} should be rejected"
+            );
+        }
+    }
+}
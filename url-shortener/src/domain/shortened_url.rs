@@ -2,23 +2,86 @@
 //!
 //! This is the main aggregate root in our domain model.
 
-use super::{OriginalUrl, ShortCode, UrlId};
-use std::time::SystemTime;
+use super::{Note, OriginalUrl, RedirectKind, ShortCode, ShortenedUrlBuilder, UrlId};
+use crate::error::{Result, UrlShortenerError};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// A shortened URL aggregate
 ///
 /// Combines all the information about a shortened URL into a single domain entity.
 /// This is an aggregate root in DDD terms.
+///
+/// With the `serde` feature, timestamps serialize as Unix seconds.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ShortenedUrl {
     id: UrlId,
     short_code: ShortCode,
     original_url: OriginalUrl,
+    #[cfg_attr(feature = "serde", serde(with = "super::serde_time::unix_seconds"))]
     created_at: SystemTime,
     access_count: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    max_accesses: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    notes: Vec<Note>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    tags: Vec<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "super::serde_time::option_unix_seconds")
+    )]
+    active_from: Option<SystemTime>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "super::serde_time::option_unix_seconds")
+    )]
+    expires_at: Option<SystemTime>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "super::serde_time::option_unix_seconds")
+    )]
+    last_accessed_at: Option<SystemTime>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    daily_counts: BTreeMap<i64, u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    referrer_counts: HashMap<String, u64>,
+    #[cfg_attr(feature = "serde", serde(default = "default_history_days"))]
+    history_days: usize,
+    #[cfg_attr(feature = "serde", serde(default = "default_enabled"))]
+    enabled: bool,
+}
+
+#[cfg(feature = "serde")]
+fn default_history_days() -> usize {
+    ShortenedUrl::DEFAULT_HISTORY_DAYS
+}
+
+#[cfg(feature = "serde")]
+fn default_enabled() -> bool {
+    true
 }
 
 impl ShortenedUrl {
+    /// Maximum number of notes kept on a single shortened URL
+    pub const MAX_NOTES: usize = 100;
+
+    /// Maximum number of tags on a single shortened URL
+    pub const MAX_TAGS: usize = 20;
+
+    /// Maximum length of a tag, in characters
+    pub const MAX_TAG_LENGTH: usize = 32;
+
+    /// Default number of days kept in the per-day access histogram
+    pub const DEFAULT_HISTORY_DAYS: usize = 90;
+
+    /// Maximum number of distinct referrers counted on a single shortened URL
+    pub const MAX_REFERRERS: usize = 100;
+
+    /// Referrer that accesses without one are counted under
+    pub const DIRECT_REFERRER: &'static str = "direct";
+
     /// Create a new ShortenedUrl
     ///
     /// # Examples
@@ -34,13 +97,18 @@ impl ShortenedUrl {
     /// assert_eq!(shortened.access_count(), 0);
     /// ```
     pub fn new(id: UrlId, short_code: ShortCode, original_url: OriginalUrl) -> Self {
-        Self {
-            id,
-            short_code,
-            original_url,
-            created_at: SystemTime::now(),
-            access_count: 0,
-        }
+        Self::builder(id, short_code, original_url).build()
+    }
+
+    /// Start building a ShortenedUrl with optional fields
+    ///
+    /// See `ShortenedUrlBuilder`.
+    pub fn builder(
+        id: UrlId,
+        short_code: ShortCode,
+        original_url: OriginalUrl,
+    ) -> ShortenedUrlBuilder {
+        ShortenedUrlBuilder::new(id, short_code, original_url)
     }
 
     /// Create a ShortenedUrl with a specific creation time (for testing)
@@ -56,9 +124,155 @@ impl ShortenedUrl {
             original_url,
             created_at,
             access_count: 0,
+            max_accesses: None,
+            notes: Vec::new(),
+            tags: Vec::new(),
+            active_from: None,
+            expires_at: None,
+            last_accessed_at: None,
+            daily_counts: BTreeMap::new(),
+            referrer_counts: HashMap::new(),
+            history_days: ShortenedUrl::DEFAULT_HISTORY_DAYS,
+            enabled: true,
         }
     }
 
+    /// Set the access count
+    ///
+    /// Intended for adapters that rebuild entries from storage.
+    pub fn with_access_count(mut self, access_count: u64) -> Self {
+        self.access_count = access_count;
+        self
+    }
+
+    /// Label this shortened URL with tags, replacing any existing ones
+    ///
+    /// Tags are kept in the given order and compared exactly, so
+    /// `"Newsletter"` and `"newsletter"` are different tags.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::InvalidTag` if:
+    /// - A tag is empty or only whitespace
+    /// - A tag is longer than `MAX_TAG_LENGTH` characters
+    /// - A tag appears more than once
+    /// - There are more than `MAX_TAGS` tags
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::domain::{ShortenedUrl, UrlId, ShortCode, OriginalUrl};
+    /// # let id = UrlId::new("123".to_string());
+    /// # let code = ShortCode::new("abc123".to_string()).unwrap();
+    /// # let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// let shortened = ShortenedUrl::new(id, code, url)
+    ///     .with_tags(vec!["newsletter".to_string(), "twitter".to_string()])
+    ///     .unwrap();
+    ///
+    /// assert!(shortened.has_tag("twitter"));
+    /// ```
+    pub fn with_tags(mut self, tags: Vec<String>) -> Result<Self> {
+        check_tags(&tags)?;
+        self.tags = tags;
+        Ok(self)
+    }
+
+    /// Schedule this shortened URL to only resolve from the given time on
+    pub fn with_active_from(mut self, active_from: SystemTime) -> Self {
+        self.active_from = Some(active_from);
+        self
+    }
+
+    /// Expire this shortened URL `ttl` after its creation time
+    ///
+    /// A `ttl` reaching past the latest representable time (such as
+    /// `Duration::MAX`) means the URL never expires.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::domain::{ShortenedUrl, UrlId, ShortCode, OriginalUrl};
+    /// # use std::time::Duration;
+    /// # let id = UrlId::new("123".to_string());
+    /// # let code = ShortCode::new("abc123".to_string()).unwrap();
+    /// # let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// let shortened = ShortenedUrl::new(id, code, url)
+    ///     .with_expiry(Duration::from_secs(3600));
+    ///
+    /// assert!(!shortened.is_expired());
+    /// ```
+    pub fn with_expiry(self, ttl: Duration) -> Self {
+        match self.created_at.checked_add(ttl) {
+            Some(expires_at) => self.with_expires_at(expires_at),
+            None => self,
+        }
+    }
+
+    /// Expire this shortened URL at the given time
+    ///
+    /// Intended for adapters that rebuild entries from storage.
+    pub fn with_expires_at(mut self, expires_at: SystemTime) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Limit how many times this shortened URL may be resolved
+    ///
+    /// Once `access_count` reaches `max_accesses`, resolving fails with
+    /// `UrlShortenerError::AccessLimitReached`. Use 1 for one-time links.
+    pub fn with_max_accesses(mut self, max_accesses: u64) -> Self {
+        self.max_accesses = Some(max_accesses);
+        self
+    }
+
+    /// Set whether this shortened URL is enabled
+    ///
+    /// Intended for adapters that rebuild entries from storage.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Set the last access time
+    ///
+    /// Intended for adapters that rebuild entries from storage.
+    pub fn with_last_accessed_at(mut self, last_accessed_at: SystemTime) -> Self {
+        self.last_accessed_at = Some(last_accessed_at);
+        self
+    }
+
+    /// Keep at most `days` days in the per-day access histogram
+    ///
+    /// Once more days have been recorded, the oldest are evicted. Defaults to
+    /// `DEFAULT_HISTORY_DAYS`. The total `access_count` is unaffected.
+    pub fn with_history_days(mut self, days: usize) -> Self {
+        self.history_days = days;
+        self.evict_old_days();
+        self
+    }
+
+    /// Set the per-day access histogram
+    ///
+    /// Intended for adapters that rebuild entries from storage. The history
+    /// cap is applied, so call this after `with_history_days`.
+    pub fn with_access_counts_by_day(mut self, counts: BTreeMap<i64, u64>) -> Self {
+        self.daily_counts = counts;
+        self.evict_old_days();
+        self
+    }
+
+    /// Set the per-referrer access counts
+    ///
+    /// Intended for adapters that rebuild entries from storage. If there are
+    /// more than `MAX_REFERRERS` referrers, the least counted are dropped.
+    pub fn with_referrer_counts(mut self, counts: HashMap<String, u64>) -> Self {
+        self.referrer_counts = counts;
+        while self.referrer_counts.len() > Self::MAX_REFERRERS {
+            self.evict_least_referred();
+        }
+        self
+    }
+
     /// Get the unique identifier
     pub fn id(&self) -> &UrlId {
         &self.id
@@ -84,9 +298,41 @@ impl ShortenedUrl {
         self.access_count
     }
 
+    /// Get the time of the most recent access, if any
+    pub fn last_accessed_at(&self) -> Option<SystemTime> {
+        self.last_accessed_at
+    }
+
+    /// Get how long ago this shortened URL was created
+    ///
+    /// Returns zero if `created_at` lies in the future, for example after a
+    /// clock adjustment.
+    pub fn age(&self) -> Duration {
+        self.age_at(SystemTime::now())
+    }
+
+    /// Get the age of this shortened URL at the given time
+    pub fn age_at(&self, now: SystemTime) -> Duration {
+        now.duration_since(self.created_at).unwrap_or_default()
+    }
+
+    /// Get how long ago this shortened URL was last accessed, if ever
+    ///
+    /// Saturates to zero like `age`.
+    pub fn time_since_last_access(&self) -> Option<Duration> {
+        self.time_since_last_access_at(SystemTime::now())
+    }
+
+    /// Get the time since the last access, as seen at the given time
+    pub fn time_since_last_access_at(&self, now: SystemTime) -> Option<Duration> {
+        self.last_accessed_at
+            .map(|accessed_at| now.duration_since(accessed_at).unwrap_or_default())
+    }
+
     /// Record an access to this shortened URL
     ///
-    /// This increments the access counter.
+    /// This increments the access counter and sets the last access time to
+    /// now.
     ///
     /// # Examples
     ///
@@ -104,10 +350,390 @@ impl ShortenedUrl {
     /// assert_eq!(shortened.access_count(), 2);
     /// ```
     pub fn record_access(&mut self) {
+        self.record_access_at(SystemTime::now());
+    }
+
+    /// Record an access that happened at `when`
+    ///
+    /// Like `record_access`, with an explicit time.
+    pub fn record_access_at(&mut self, when: SystemTime) {
         self.access_count = self.access_count.saturating_add(1);
+        self.last_accessed_at = Some(when);
+
+        let day = self.daily_counts.entry(day_index(when)).or_insert(0);
+        *day = day.saturating_add(1);
+        self.evict_old_days();
+    }
+
+    /// Record an access now, counting it towards `referrer`
+    ///
+    /// Like `record_access`, and also counts the access as described in
+    /// `record_referrer`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::domain::{ShortenedUrl, UrlId, ShortCode, OriginalUrl};
+    /// # let id = UrlId::new("123".to_string());
+    /// # let code = ShortCode::new("abc123".to_string()).unwrap();
+    /// # let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// let mut shortened = ShortenedUrl::new(id, code, url);
+    ///
+    /// shortened.record_access_with_referrer(Some("newsletter".to_string()));
+    /// shortened.record_access_with_referrer(None);
+    ///
+    /// assert_eq!(shortened.access_count(), 2);
+    /// assert_eq!(shortened.referrer_counts().get("newsletter"), Some(&1));
+    /// assert_eq!(shortened.referrer_counts().get("direct"), Some(&1));
+    /// ```
+    pub fn record_access_with_referrer(&mut self, referrer: Option<String>) {
+        self.record_access();
+        self.record_referrer(referrer);
+    }
+
+    /// Count an access towards `referrer`, without recording the access
+    /// itself
+    ///
+    /// Accesses without a referrer, or with a blank one, are counted under
+    /// `DIRECT_REFERRER`. Once `MAX_REFERRERS` referrers are counted, a new
+    /// referrer replaces the one with the lowest count, the first in
+    /// alphabetical order on ties. Referrers are compared exactly.
+    pub fn record_referrer(&mut self, referrer: Option<String>) {
+        let referrer = Self::referrer_key(referrer);
+
+        if !self.referrer_counts.contains_key(&referrer)
+            && self.referrer_counts.len() >= Self::MAX_REFERRERS
+        {
+            self.evict_least_referred();
+        }
+
+        let count = self.referrer_counts.entry(referrer).or_insert(0);
+        *count = count.saturating_add(1);
+    }
+
+    /// Get access counts per referrer
+    ///
+    /// Only accesses recorded with a referrer, or explicitly as direct, are
+    /// counted, so the counts may add up to less than `access_count`.
+    pub fn referrer_counts(&self) -> &HashMap<String, u64> {
+        &self.referrer_counts
+    }
+
+    /// Referrer that `record_referrer` counts an access under
+    pub(crate) fn referrer_key(referrer: Option<String>) -> String {
+        referrer
+            .filter(|referrer| !referrer.trim().is_empty())
+            .unwrap_or_else(|| Self::DIRECT_REFERRER.to_string())
+    }
+
+    fn evict_least_referred(&mut self) {
+        let least = self.referrer_counts
+            .iter()
+            .min_by(|a, b| (a.1, a.0).cmp(&(b.1, b.0)))
+            .map(|(referrer, _)| referrer.clone());
+
+        if let Some(referrer) = least {
+            self.referrer_counts.remove(&referrer);
+        }
+    }
+
+    /// Get access counts per day, keyed by days since the Unix epoch (UTC)
+    ///
+    /// Only the most recent `history_days` days with accesses are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::domain::{ShortenedUrl, UrlId, ShortCode, OriginalUrl};
+    /// # use std::time::{Duration, UNIX_EPOCH};
+    /// # let id = UrlId::new("123".to_string());
+    /// # let code = ShortCode::new("abc123".to_string()).unwrap();
+    /// # let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// let mut shortened = ShortenedUrl::new(id, code, url);
+    ///
+    /// // 1970-01-03, day 2 since the epoch
+    /// shortened.record_access_at(UNIX_EPOCH + Duration::from_secs(2 * 86_400 + 60));
+    /// assert_eq!(shortened.access_counts_by_day().get(&2), Some(&1));
+    /// ```
+    pub fn access_counts_by_day(&self) -> &BTreeMap<i64, u64> {
+        &self.daily_counts
+    }
+
+    /// Weigh the per-day access counts by how recent they are
+    ///
+    /// Each day's count is halved for every `half_life` between the start of
+    /// that day and the start of the day containing `now` (both UTC), so
+    /// accesses from today count fully and old ones fade towards zero. Only
+    /// days still in the per-day histogram contribute. With a zero
+    /// `half_life`, only today's accesses count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::domain::{ShortenedUrl, UrlId, ShortCode, OriginalUrl};
+    /// # use std::time::{Duration, UNIX_EPOCH};
+    /// # let id = UrlId::new("123".to_string());
+    /// # let code = ShortCode::new("abc123".to_string()).unwrap();
+    /// # let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// let day = |d: u64| UNIX_EPOCH + Duration::from_secs(d * 86_400);
+    /// let mut shortened = ShortenedUrl::new(id, code, url);
+    /// shortened.record_access_at(day(8));
+    /// shortened.record_access_at(day(10));
+    ///
+    /// let half_life = Duration::from_secs(2 * 86_400);
+    /// assert_eq!(shortened.trending_score_at(day(10), half_life), 1.5);
+    /// ```
+    pub fn trending_score_at(&self, now: SystemTime, half_life: Duration) -> f64 {
+        let today = day_index(now);
+        let half_life = half_life.as_secs_f64();
+
+        self.daily_counts
+            .iter()
+            .map(|(&day, &count)| {
+                let age = (today - day).max(0) as f64 * SECONDS_PER_DAY as f64;
+                let half_lives = if age == 0.0 { 0.0 } else { age / half_life };
+                count as f64 * 0.5f64.powf(half_lives)
+            })
+            .sum()
+    }
+
+    /// Get the maximum number of days kept in the per-day histogram
+    pub fn history_days(&self) -> usize {
+        self.history_days
+    }
+
+    fn evict_old_days(&mut self) {
+        while self.daily_counts.len() > self.history_days {
+            self.daily_counts.pop_first();
+        }
+    }
+
+    /// Get the scheduled activation time, if any
+    pub fn active_from(&self) -> Option<SystemTime> {
+        self.active_from
+    }
+
+    /// Check whether this shortened URL may be resolved at the given time
+    ///
+    /// URLs without a scheduled activation time are always active.
+    pub fn is_active_at(&self, now: SystemTime) -> bool {
+        self.active_from.is_none_or(|active_from| now >= active_from)
+    }
+
+    /// Check whether this shortened URL is enabled
+    ///
+    /// Disabled URLs don't resolve, but keep their statistics. URLs are
+    /// enabled when created.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enable or disable this shortened URL
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Get the most accesses allowed, if limited
+    pub fn max_accesses(&self) -> Option<u64> {
+        self.max_accesses
+    }
+
+    /// Check whether this shortened URL has used up its accesses
+    ///
+    /// URLs without a limit never reach it.
+    pub fn is_access_limit_reached(&self) -> bool {
+        self.max_accesses.is_some_and(|max| self.access_count >= max)
+    }
+
+    /// Get the expiry time, if any
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.expires_at
+    }
+
+    /// Check whether this shortened URL has expired
+    ///
+    /// URLs without an expiry time never expire.
+    pub fn is_expired(&self) -> bool {
+        self.is_expired_at(SystemTime::now())
+    }
+
+    /// Check whether this shortened URL has expired at the given time
+    ///
+    /// The expiry time itself already counts as expired.
+    pub fn is_expired_at(&self, now: SystemTime) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    /// Get the tags of this shortened URL
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Check whether this shortened URL carries the given tag
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Get the notes attached to this shortened URL, oldest first
+    pub fn notes(&self) -> &[Note] {
+        &self.notes
+    }
+
+    /// Attach a note to this shortened URL
+    ///
+    /// Notes are kept in the order they were added.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::InvalidNote` if:
+    /// - The author or text is empty
+    /// - The author is longer than `Note::MAX_AUTHOR_LENGTH` characters
+    /// - The text is longer than `Note::MAX_TEXT_LENGTH` characters
+    /// - The URL already has `MAX_NOTES` notes
+    pub fn add_note(&mut self, author: String, text: String, at: SystemTime) -> Result<()> {
+        if author.trim().is_empty() {
+            return Err(UrlShortenerError::InvalidNote(
+                "Author must not be empty".to_string()
+            ));
+        }
+
+        if author.chars().count() > Note::MAX_AUTHOR_LENGTH {
+            return Err(UrlShortenerError::InvalidNote(
+                format!("Author must be at most {} characters", Note::MAX_AUTHOR_LENGTH)
+            ));
+        }
+
+        if text.trim().is_empty() {
+            return Err(UrlShortenerError::InvalidNote(
+                "Text must not be empty".to_string()
+            ));
+        }
+
+        if text.chars().count() > Note::MAX_TEXT_LENGTH {
+            return Err(UrlShortenerError::InvalidNote(
+                format!("Text must be at most {} characters", Note::MAX_TEXT_LENGTH)
+            ));
+        }
+
+        if self.notes.len() >= Self::MAX_NOTES {
+            return Err(UrlShortenerError::InvalidNote(
+                format!("At most {} notes are allowed", Self::MAX_NOTES)
+            ));
+        }
+
+        self.notes.push(Note::new(author, text, at));
+        Ok(())
+    }
+
+    /// Copy this shortened URL under a new id and short code
+    ///
+    /// Everything else, including the creation time, access count and notes,
+    /// is carried over. Used when re-issuing a link under a new code.
+    pub fn reissue(&self, id: UrlId, short_code: ShortCode) -> ShortenedUrl {
+        Self {
+            id,
+            short_code,
+            ..self.clone()
+        }
+    }
+
+    /// Point this shortened URL at a new original URL
+    ///
+    /// The id, short code, creation time and access count are preserved.
+    pub fn retarget(&mut self, original_url: OriginalUrl) {
+        self.original_url = original_url;
+    }
+
+    /// Check whether `other` maps the same short code to the same original URL
+    ///
+    /// Unlike `==`, this ignores the id and everything that changes as the
+    /// link is used or managed, such as the creation time and access count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::domain::{ShortenedUrl, UrlId, ShortCode, OriginalUrl};
+    /// # let id = UrlId::new("123".to_string());
+    /// # let code = ShortCode::new("abc123".to_string()).unwrap();
+    /// # let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// let shortened = ShortenedUrl::new(id, code, url);
+    /// let visited = shortened.clone().with_access_count(42);
+    ///
+    /// assert_ne!(shortened, visited);
+    /// assert!(shortened.same_mapping(&visited));
+    /// ```
+    pub fn same_mapping(&self, other: &ShortenedUrl) -> bool {
+        self.short_code == other.short_code && self.original_url == other.original_url
+    }
+
+    /// Describe the HTTP redirect to the original URL
+    ///
+    /// Returns the status code and the `Location` header value, leaving the
+    /// response itself to whichever web framework is in use.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::domain::{ShortenedUrl, UrlId, ShortCode, OriginalUrl, RedirectKind};
+    /// # let id = UrlId::new("123".to_string());
+    /// # let code = ShortCode::new("abc123".to_string()).unwrap();
+    /// # let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// let shortened = ShortenedUrl::new(id, code, url);
+    ///
+    /// let (status, location) = shortened.redirect(RedirectKind::Temporary);
+    /// assert_eq!(status, 302);
+    /// assert_eq!(location, "https://example.com/");
+    /// ```
+    pub fn redirect(&self, kind: RedirectKind) -> (u16, String) {
+        (kind.status_code(), self.original_url.as_str().to_string())
     }
 }
 
+/// Check tags against the rules of `ShortenedUrl::with_tags`
+pub(super) fn check_tags(tags: &[String]) -> Result<()> {
+    if tags.len() > ShortenedUrl::MAX_TAGS {
+        return Err(UrlShortenerError::InvalidTag(
+            format!("At most {} tags are allowed", ShortenedUrl::MAX_TAGS)
+        ));
+    }
+
+    for (i, tag) in tags.iter().enumerate() {
+        if tag.trim().is_empty() {
+            return Err(UrlShortenerError::InvalidTag(
+                "Tags must not be empty".to_string()
+            ));
+        }
+
+        if tag.chars().count() > ShortenedUrl::MAX_TAG_LENGTH {
+            return Err(UrlShortenerError::InvalidTag(format!(
+                "Tag '{}' is longer than {} characters",
+                tag,
+                ShortenedUrl::MAX_TAG_LENGTH
+            )));
+        }
+
+        if tags[..i].contains(tag) {
+            return Err(UrlShortenerError::InvalidTag(
+                format!("Tag '{}' is repeated", tag)
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Length of a day in the per-day histogram
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Days since the Unix epoch (UTC), negative before it
+pub(crate) fn day_index(time: SystemTime) -> i64 {
+    let seconds = match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => after.as_secs() as i64,
+        Err(before) => -(before.duration().as_secs_f64().ceil() as i64),
+    };
+    seconds.div_euclid(SECONDS_PER_DAY)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -140,6 +766,179 @@ mod tests {
         assert_eq!(url.access_count(), 2);
     }
 
+    #[test]
+    fn test_same_mapping_ignores_volatile_fields() {
+        let url = create_test_url();
+        let created_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let visited = ShortenedUrl::with_created_at(
+            UrlId::new("other-id".to_string()),
+            url.short_code().clone(),
+            url.original_url().clone(),
+            created_at,
+        )
+        .with_access_count(7);
+
+        assert_ne!(url, visited);
+        assert!(url.same_mapping(&visited));
+        assert!(visited.same_mapping(&url));
+
+        let mut retargeted = url.clone();
+        retargeted.retarget(OriginalUrl::new("https://example.org".to_string()).unwrap());
+        assert!(!url.same_mapping(&retargeted));
+
+        let reissued = url.reissue(
+            UrlId::new("test-id".to_string()),
+            ShortCode::new("othercode".to_string()).unwrap(),
+        );
+        assert!(!url.same_mapping(&reissued));
+    }
+
+    #[test]
+    fn test_record_access_at() {
+        use std::time::UNIX_EPOCH;
+
+        let mut url = create_test_url();
+        assert_eq!(url.last_accessed_at(), None);
+
+        let first = UNIX_EPOCH + Duration::from_secs(100);
+        let second = UNIX_EPOCH + Duration::from_secs(200);
+        url.record_access_at(first);
+        assert_eq!(url.last_accessed_at(), Some(first));
+        url.record_access_at(second);
+        assert_eq!(url.last_accessed_at(), Some(second));
+        assert_eq!(url.access_count(), 2);
+
+        url.record_access();
+        assert!(url.last_accessed_at().unwrap() > second);
+    }
+
+    #[test]
+    fn test_access_counts_by_day() {
+        let day = |d: u64, hour: u64| UNIX_EPOCH + Duration::from_secs(d * 86_400 + hour * 3600);
+        let mut url = create_test_url();
+        assert!(url.access_counts_by_day().is_empty());
+
+        url.record_access_at(day(19_000, 0));
+        url.record_access_at(day(19_000, 23));
+        url.record_access_at(day(19_001, 12));
+        url.record_access_at(day(19_003, 1));
+
+        let expected = BTreeMap::from([(19_000, 2), (19_001, 1), (19_003, 1)]);
+        assert_eq!(url.access_counts_by_day(), &expected);
+        assert_eq!(url.access_count(), 4);
+    }
+
+    #[test]
+    fn test_referrer_counts() {
+        let mut url = create_test_url();
+
+        url.record_access_with_referrer(Some("twitter".to_string()));
+        url.record_access_with_referrer(Some("newsletter".to_string()));
+        url.record_access_with_referrer(Some("twitter".to_string()));
+        url.record_access_with_referrer(None);
+        url.record_access_with_referrer(Some("  ".to_string()));
+        url.record_access();
+
+        assert_eq!(url.access_count(), 6);
+        let counts = url.referrer_counts();
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts.get("twitter"), Some(&2));
+        assert_eq!(counts.get("newsletter"), Some(&1));
+        assert_eq!(counts.get(ShortenedUrl::DIRECT_REFERRER), Some(&2));
+    }
+
+    #[test]
+    fn test_referrer_counts_evict_least_counted() {
+        let mut url = create_test_url();
+        for i in 0..ShortenedUrl::MAX_REFERRERS {
+            url.record_referrer(Some(format!("site{:03}", i)));
+        }
+        url.record_referrer(Some("site000".to_string()));
+        url.record_referrer(Some("site001".to_string()));
+
+        // site002 is the first of the referrers counted once
+        url.record_referrer(Some("latecomer".to_string()));
+
+        let counts = url.referrer_counts();
+        assert_eq!(counts.len(), ShortenedUrl::MAX_REFERRERS);
+        assert_eq!(counts.get("site002"), None);
+        assert_eq!(counts.get("site000"), Some(&2));
+        assert_eq!(counts.get("latecomer"), Some(&1));
+        assert_eq!(url.access_count(), 0);
+
+        let counts = (0..150).map(|i| (format!("site{:03}", i), i as u64)).collect();
+        let url = create_test_url().with_referrer_counts(counts);
+        assert_eq!(url.referrer_counts().len(), ShortenedUrl::MAX_REFERRERS);
+        assert_eq!(url.referrer_counts().get("site049"), None);
+        assert_eq!(url.referrer_counts().get("site050"), Some(&50));
+    }
+
+    #[test]
+    fn test_history_days_evicts_oldest() {
+        let day = |d: u64| UNIX_EPOCH + Duration::from_secs(d * 86_400);
+        let mut url = create_test_url().with_history_days(2);
+        assert_eq!(url.history_days(), 2);
+
+        url.record_access_at(day(10));
+        url.record_access_at(day(11));
+        url.record_access_at(day(11));
+        url.record_access_at(day(12));
+
+        let expected = BTreeMap::from([(11, 2), (12, 1)]);
+        assert_eq!(url.access_counts_by_day(), &expected);
+        assert_eq!(url.access_count(), 4);
+
+        // Shrinking the cap evicts immediately
+        let url = url.with_history_days(1);
+        assert_eq!(url.access_counts_by_day(), &BTreeMap::from([(12, 1)]));
+    }
+
+    #[test]
+    fn test_trending_score_decays_with_age() {
+        let day = |d: u64| UNIX_EPOCH + Duration::from_secs(d * 86_400);
+        let half_life = Duration::from_secs(86_400);
+        let mut url = create_test_url();
+        assert_eq!(url.trending_score_at(day(10), half_life), 0.0);
+
+        for _ in 0..8 {
+            url.record_access_at(day(7));
+        }
+        url.record_access_at(day(10) + Duration::from_secs(3600));
+
+        // 8 accesses three half-lives ago plus one today
+        assert_eq!(url.trending_score_at(day(10), half_life), 2.0);
+        assert_eq!(url.trending_score_at(day(11), half_life), 1.0);
+        assert_eq!(url.trending_score_at(day(10), Duration::ZERO), 1.0);
+
+        // Accesses after `now` count as today's
+        assert_eq!(url.trending_score_at(day(7), half_life), 9.0);
+    }
+
+    #[test]
+    fn test_day_index() {
+        assert_eq!(day_index(UNIX_EPOCH), 0);
+        assert_eq!(day_index(UNIX_EPOCH + Duration::from_secs(86_399)), 0);
+        assert_eq!(day_index(UNIX_EPOCH + Duration::from_secs(86_400)), 1);
+        assert_eq!(day_index(UNIX_EPOCH - Duration::from_secs(1)), -1);
+        assert_eq!(day_index(UNIX_EPOCH - Duration::from_secs(86_400)), -1);
+        assert_eq!(day_index(UNIX_EPOCH - Duration::from_secs(86_401)), -2);
+    }
+
+    #[test]
+    fn test_retarget_preserves_metadata() {
+        let mut url = create_test_url();
+        url.record_access();
+        let created_at = url.created_at();
+
+        let new_target = OriginalUrl::new("https://other.com".to_string()).unwrap();
+        url.retarget(new_target.clone());
+
+        assert_eq!(url.original_url(), &new_target);
+        assert_eq!(url.id().as_str(), "test-id");
+        assert_eq!(url.access_count(), 1);
+        assert_eq!(url.created_at(), created_at);
+    }
+
     #[test]
     fn test_access_count_saturation() {
         let id = UrlId::new("test".to_string());
@@ -152,6 +951,16 @@ mod tests {
             original_url: url,
             created_at: SystemTime::now(),
             access_count: u64::MAX - 1,
+            max_accesses: None,
+            notes: Vec::new(),
+            tags: Vec::new(),
+            active_from: None,
+            expires_at: None,
+            last_accessed_at: None,
+            daily_counts: BTreeMap::new(),
+            referrer_counts: HashMap::new(),
+            history_days: ShortenedUrl::DEFAULT_HISTORY_DAYS,
+            enabled: true,
         };
 
         shortened.record_access();
@@ -161,4 +970,327 @@ mod tests {
         shortened.record_access();
         assert_eq!(shortened.access_count(), u64::MAX);
     }
+
+    #[test]
+    fn test_with_tags() {
+        let id = UrlId::new("test".to_string());
+        let code = ShortCode::new("test1234".to_string()).unwrap();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+
+        let untagged = ShortenedUrl::new(id, code, url);
+        assert!(untagged.tags().is_empty());
+
+        let tagged = untagged
+            .with_tags(vec!["newsletter".to_string(), "twitter".to_string()])
+            .unwrap();
+        assert_eq!(tagged.tags(), ["newsletter", "twitter"]);
+        assert!(tagged.has_tag("twitter"));
+        assert!(!tagged.has_tag("Twitter"));
+    }
+
+    #[test]
+    fn test_with_tags_rejects_invalid() {
+        let id = UrlId::new("test".to_string());
+        let code = ShortCode::new("test1234".to_string()).unwrap();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let shortened = ShortenedUrl::new(id, code, url);
+
+        let too_long = "x".repeat(ShortenedUrl::MAX_TAG_LENGTH + 1);
+        let too_many = (0..=ShortenedUrl::MAX_TAGS).map(|i| format!("tag{}", i)).collect();
+        let invalid = [
+            vec!["".to_string()],
+            vec!["  ".to_string()],
+            vec![too_long],
+            vec!["promo".to_string(), "promo".to_string()],
+            too_many,
+        ];
+
+        for tags in invalid {
+            let result = shortened.clone().with_tags(tags.clone());
+            assert!(
+                matches!(result, Err(UrlShortenerError::InvalidTag(_))),
+                "tags {:?} should be rejected",
+                tags
+            );
+        }
+
+        let longest = "x".repeat(ShortenedUrl::MAX_TAG_LENGTH);
+        assert!(shortened.with_tags(vec![longest]).is_ok());
+    }
+
+    #[test]
+    fn test_add_note() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let mut url = create_test_url();
+        let first = UNIX_EPOCH + Duration::from_secs(100);
+        let second = UNIX_EPOCH + Duration::from_secs(200);
+
+        url.add_note("alice".to_string(), "Created for launch".to_string(), first).unwrap();
+        url.add_note("bob".to_string(), "Retargeted".to_string(), second).unwrap();
+
+        let notes = url.notes();
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].author(), "alice");
+        assert_eq!(notes[0].text(), "Created for launch");
+        assert_eq!(notes[0].at(), first);
+        assert_eq!(notes[1].author(), "bob");
+        assert_eq!(notes[1].at(), second);
+    }
+
+    #[test]
+    fn test_add_note_rejects_invalid() {
+        let mut url = create_test_url();
+        let now = SystemTime::now();
+
+        let result = url.add_note("".to_string(), "text".to_string(), now);
+        assert!(matches!(result, Err(UrlShortenerError::InvalidNote(_))));
+
+        let result = url.add_note("alice".to_string(), " ".to_string(), now);
+        assert!(matches!(result, Err(UrlShortenerError::InvalidNote(_))));
+
+        let too_long = "a".repeat(Note::MAX_TEXT_LENGTH + 1);
+        let result = url.add_note("alice".to_string(), too_long, now);
+        assert!(matches!(result, Err(UrlShortenerError::InvalidNote(_))));
+
+        let long_author = "é".repeat(Note::MAX_AUTHOR_LENGTH + 1);
+        let result = url.add_note(long_author, "text".to_string(), now);
+        assert!(matches!(result, Err(UrlShortenerError::InvalidNote(_))));
+
+        assert!(url.notes().is_empty());
+
+        // The limit counts characters, not bytes
+        let author = "é".repeat(Note::MAX_AUTHOR_LENGTH);
+        url.add_note(author, "text".to_string(), now).unwrap();
+    }
+
+    #[test]
+    fn test_add_note_bounded() {
+        let mut url = create_test_url();
+        let now = SystemTime::now();
+
+        for i in 0..ShortenedUrl::MAX_NOTES {
+            url.add_note("alice".to_string(), format!("note {}", i), now).unwrap();
+        }
+
+        let result = url.add_note("alice".to_string(), "one too many".to_string(), now);
+        assert!(matches!(result, Err(UrlShortenerError::InvalidNote(_))));
+        assert_eq!(url.notes().len(), ShortenedUrl::MAX_NOTES);
+    }
+
+    #[test]
+    fn test_enabled() {
+        let id = UrlId::new("test".to_string());
+        let code = ShortCode::new("test1234".to_string()).unwrap();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+
+        let mut shortened = ShortenedUrl::new(id, code, url);
+        assert!(shortened.is_enabled());
+
+        shortened.set_enabled(false);
+        assert!(!shortened.is_enabled());
+        assert!(shortened.clone().with_enabled(true).is_enabled());
+    }
+
+    #[test]
+    fn test_active_from() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let url = create_test_url();
+        assert_eq!(url.active_from(), None);
+        assert!(url.is_active_at(UNIX_EPOCH));
+
+        let launch = UNIX_EPOCH + Duration::from_secs(1_000);
+        let url = url.with_active_from(launch);
+        assert_eq!(url.active_from(), Some(launch));
+        assert!(!url.is_active_at(launch - Duration::from_secs(1)));
+        assert!(url.is_active_at(launch));
+        assert!(url.is_active_at(launch + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_expiry() {
+        use std::time::UNIX_EPOCH;
+
+        let url = create_test_url();
+        assert_eq!(url.expires_at(), None);
+        assert!(!url.is_expired());
+        assert!(!url.is_expired_at(SystemTime::now() + Duration::from_secs(1_000_000)));
+
+        let created_at = UNIX_EPOCH + Duration::from_secs(1_000);
+        let url = ShortenedUrl::with_created_at(
+            UrlId::new("test-id".to_string()),
+            ShortCode::new("testcode".to_string()).unwrap(),
+            OriginalUrl::new("https://example.com".to_string()).unwrap(),
+            created_at,
+        )
+        .with_expiry(Duration::from_secs(60));
+
+        let deadline = created_at + Duration::from_secs(60);
+        assert_eq!(url.expires_at(), Some(deadline));
+        assert!(!url.is_expired_at(deadline - Duration::from_secs(1)));
+        assert!(url.is_expired_at(deadline));
+        assert!(url.is_expired());
+    }
+
+    #[test]
+    fn test_expiry_overflow_never_expires() {
+        let url = create_test_url().with_expiry(Duration::MAX);
+
+        assert_eq!(url.expires_at(), None);
+        assert!(!url.is_expired());
+    }
+
+    #[test]
+    fn test_age() {
+        use std::time::UNIX_EPOCH;
+
+        let created_at = SystemTime::now() - Duration::from_secs(3_600);
+        let mut url = ShortenedUrl::with_created_at(
+            UrlId::new("test-id".to_string()),
+            ShortCode::new("testcode".to_string()).unwrap(),
+            OriginalUrl::new("https://example.com".to_string()).unwrap(),
+            created_at,
+        );
+
+        assert!(url.age() >= Duration::from_secs(3_600));
+        assert_eq!(url.age_at(created_at + Duration::from_secs(90)), Duration::from_secs(90));
+        // A creation time in the future saturates instead of panicking
+        assert_eq!(url.age_at(UNIX_EPOCH), Duration::ZERO);
+
+        assert_eq!(url.time_since_last_access(), None);
+        let accessed_at = created_at + Duration::from_secs(600);
+        url.record_access_at(accessed_at);
+        assert!(url.time_since_last_access().unwrap() >= Duration::from_secs(3_000));
+        assert_eq!(
+            url.time_since_last_access_at(accessed_at + Duration::from_secs(5)),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(url.time_since_last_access_at(created_at), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_redirect() {
+        let url = create_test_url();
+
+        assert_eq!(
+            url.redirect(RedirectKind::Permanent),
+            (301, "https://example.com/".to_string())
+        );
+        assert_eq!(
+            url.redirect(RedirectKind::Temporary),
+            (302, "https://example.com/".to_string())
+        );
+    }
+
+    #[test]
+    fn test_access_limit() {
+        let mut url = create_test_url();
+        assert_eq!(url.max_accesses(), None);
+        url.record_access();
+        assert!(!url.is_access_limit_reached());
+
+        let mut url = create_test_url().with_max_accesses(2);
+        assert_eq!(url.max_accesses(), Some(2));
+        url.record_access();
+        assert!(!url.is_access_limit_reached());
+        url.record_access();
+        assert!(url.is_access_limit_reached());
+
+        assert!(create_test_url().with_max_accesses(0).is_access_limit_reached());
+    }
+
+    #[test]
+    fn test_reissue() {
+        let mut url = create_test_url();
+        url.record_access();
+
+        let new_code = ShortCode::new("newcode1".to_string()).unwrap();
+        let copy = url.reissue(UrlId::new("new-id".to_string()), new_code.clone());
+
+        assert_eq!(copy.id().as_str(), "new-id");
+        assert_eq!(copy.short_code(), &new_code);
+        assert_eq!(copy.original_url(), url.original_url());
+        assert_eq!(copy.created_at(), url.created_at());
+        assert_eq!(copy.access_count(), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let created_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut url = ShortenedUrl::with_created_at(
+            UrlId::new("id-1".to_string()),
+            ShortCode::new("abc123".to_string()).unwrap(),
+            OriginalUrl::new("https://example.com/".to_string()).unwrap(),
+            created_at,
+        )
+        .with_access_count(7);
+        url.add_note("alice".to_string(), "hello".to_string(), created_at).unwrap();
+        url.record_access_at(created_at);
+        url.record_referrer(Some("newsletter".to_string()));
+
+        let json = serde_json::to_string(&url).unwrap();
+        assert!(json.contains("\"created_at\":1700000000"));
+
+        let decoded: ShortenedUrl = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, url);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize_rejects_invalid_short_code() {
+        let json = r#"{
+            "id": "id-1",
+            "short_code": "bad code!",
+            "original_url": "https://example.com/",
+            "created_at": 1700000000,
+            "access_count": 0
+        }"#;
+
+        assert!(serde_json::from_str::<ShortenedUrl>(json).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize_rejects_out_of_range_timestamps() {
+        let json = r#"{
+            "id": "id-1",
+            "short_code": "abc123",
+            "original_url": "https://example.com/",
+            "created_at": 18446744073709551615,
+            "access_count": 0
+        }"#;
+        let error = serde_json::from_str::<ShortenedUrl>(json).unwrap_err();
+        assert!(error.to_string().contains("out of range"), "{}", error);
+
+        let json = r#"{
+            "id": "id-1",
+            "short_code": "abc123",
+            "original_url": "https://example.com/",
+            "created_at": 1700000000,
+            "access_count": 0,
+            "expires_at": 18446744073709551615
+        }"#;
+        assert!(serde_json::from_str::<ShortenedUrl>(json).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize_minimal_entry() {
+        let json = r#"{
+            "id": "id-1",
+            "short_code": "abc123",
+            "original_url": "https://example.com/",
+            "created_at": 1700000000,
+            "access_count": 3
+        }"#;
+
+        let url: ShortenedUrl = serde_json::from_str(json).unwrap();
+        assert_eq!(url.access_count(), 3);
+        assert!(url.notes().is_empty());
+        assert_eq!(url.active_from(), None);
+    }
 }
@@ -0,0 +1,268 @@
+//! Builder for ShortenedUrl
+//!
+//! Collects the optional fields of a shortened URL in one place.
+
+use super::shortened_url::check_tags;
+use super::{OriginalUrl, ShortCode, ShortenedUrl, UrlId};
+use crate::error::Result;
+use std::time::{Duration, SystemTime};
+
+/// Builder for `ShortenedUrl`
+///
+/// The id, short code and original URL are required up front. Every other
+/// field defaults to what `ShortenedUrl::new` gives: created now, never
+/// accessed, enabled, and without tags, limits, schedule or expiry.
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::domain::{ShortenedUrl, UrlId, ShortCode, OriginalUrl};
+/// use std::time::Duration;
+///
+/// let shortened = ShortenedUrl::builder(
+///     UrlId::new("123".to_string()),
+///     ShortCode::new("abc123".to_string()).unwrap(),
+///     OriginalUrl::new("https://example.com".to_string()).unwrap(),
+/// )
+/// .expiry(Duration::from_secs(3600))
+/// .max_accesses(10)
+/// .tags(vec!["newsletter".to_string()])
+/// .unwrap()
+/// .build();
+///
+/// assert_eq!(shortened.max_accesses(), Some(10));
+/// assert!(shortened.has_tag("newsletter"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ShortenedUrlBuilder {
+    id: UrlId,
+    short_code: ShortCode,
+    original_url: OriginalUrl,
+    created_at: Option<SystemTime>,
+    access_count: u64,
+    max_accesses: Option<u64>,
+    tags: Vec<String>,
+    active_from: Option<SystemTime>,
+    expiry: Option<Expiry>,
+    last_accessed_at: Option<SystemTime>,
+    history_days: usize,
+    enabled: bool,
+}
+
+/// When a built entry expires
+#[derive(Debug, Clone, Copy)]
+enum Expiry {
+    At(SystemTime),
+    After(Duration),
+}
+
+impl ShortenedUrlBuilder {
+    /// Start building a shortened URL from its required fields
+    pub fn new(id: UrlId, short_code: ShortCode, original_url: OriginalUrl) -> Self {
+        Self {
+            id,
+            short_code,
+            original_url,
+            created_at: None,
+            access_count: 0,
+            max_accesses: None,
+            tags: Vec::new(),
+            active_from: None,
+            expiry: None,
+            last_accessed_at: None,
+            history_days: ShortenedUrl::DEFAULT_HISTORY_DAYS,
+            enabled: true,
+        }
+    }
+
+    /// Set the creation time; defaults to the time `build` is called
+    pub fn created_at(mut self, created_at: SystemTime) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    /// Set the access count; defaults to 0
+    pub fn access_count(mut self, access_count: u64) -> Self {
+        self.access_count = access_count;
+        self
+    }
+
+    /// Limit how many times the entry may be resolved
+    ///
+    /// See `ShortenedUrl::with_max_accesses`.
+    pub fn max_accesses(mut self, max_accesses: u64) -> Self {
+        self.max_accesses = Some(max_accesses);
+        self
+    }
+
+    /// Label the entry with tags
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::InvalidTag` under the same conditions as
+    /// `ShortenedUrl::with_tags`
+    pub fn tags(mut self, tags: Vec<String>) -> Result<Self> {
+        check_tags(&tags)?;
+        self.tags = tags;
+        Ok(self)
+    }
+
+    /// Only resolve the entry from the given time on
+    pub fn active_from(mut self, active_from: SystemTime) -> Self {
+        self.active_from = Some(active_from);
+        self
+    }
+
+    /// Expire the entry at the given time, replacing any `expiry`
+    pub fn expires_at(mut self, expires_at: SystemTime) -> Self {
+        self.expiry = Some(Expiry::At(expires_at));
+        self
+    }
+
+    /// Expire the entry `ttl` after its creation time, replacing any
+    /// `expires_at`
+    pub fn expiry(mut self, ttl: Duration) -> Self {
+        self.expiry = Some(Expiry::After(ttl));
+        self
+    }
+
+    /// Set the last access time
+    pub fn last_accessed_at(mut self, last_accessed_at: SystemTime) -> Self {
+        self.last_accessed_at = Some(last_accessed_at);
+        self
+    }
+
+    /// Keep at most `days` days in the per-day access histogram
+    ///
+    /// Defaults to `ShortenedUrl::DEFAULT_HISTORY_DAYS`.
+    pub fn history_days(mut self, days: usize) -> Self {
+        self.history_days = days;
+        self
+    }
+
+    /// Set whether the entry is enabled; defaults to `true`
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Build the shortened URL
+    pub fn build(self) -> ShortenedUrl {
+        let created_at = self.created_at.unwrap_or_else(SystemTime::now);
+        let mut url = ShortenedUrl::with_created_at(
+            self.id,
+            self.short_code,
+            self.original_url,
+            created_at,
+        )
+        .with_access_count(self.access_count)
+        .with_tags(self.tags)
+        .expect("tags are validated when set")
+        .with_history_days(self.history_days)
+        .with_enabled(self.enabled);
+
+        if let Some(max_accesses) = self.max_accesses {
+            url = url.with_max_accesses(max_accesses);
+        }
+
+        if let Some(active_from) = self.active_from {
+            url = url.with_active_from(active_from);
+        }
+
+        match self.expiry {
+            Some(Expiry::At(expires_at)) => url = url.with_expires_at(expires_at),
+            Some(Expiry::After(ttl)) => url = url.with_expiry(ttl),
+            None => {}
+        }
+
+        if let Some(last_accessed_at) = self.last_accessed_at {
+            url = url.with_last_accessed_at(last_accessed_at);
+        }
+
+        url
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    fn builder() -> ShortenedUrlBuilder {
+        ShortenedUrl::builder(
+            UrlId::new("test-id".to_string()),
+            ShortCode::new("testcode".to_string()).unwrap(),
+            OriginalUrl::new("https://example.com".to_string()).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_minimal_entry_uses_defaults() {
+        let before = SystemTime::now();
+        let url = builder().build();
+
+        assert_eq!(url.id().as_str(), "test-id");
+        assert_eq!(url.short_code().as_str(), "testcode");
+        assert_eq!(url.original_url().as_str(), "https://example.com/");
+        assert!(url.created_at() >= before && url.created_at() <= SystemTime::now());
+        assert_eq!(url.access_count(), 0);
+        assert!(url.is_enabled());
+        assert!(url.tags().is_empty());
+        assert_eq!(url.max_accesses(), None);
+        assert_eq!(url.active_from(), None);
+        assert_eq!(url.expires_at(), None);
+        assert_eq!(url.last_accessed_at(), None);
+        assert_eq!(url.history_days(), ShortenedUrl::DEFAULT_HISTORY_DAYS);
+    }
+
+    #[test]
+    fn test_fully_specified_entry() {
+        let at = |secs: u64| UNIX_EPOCH + Duration::from_secs(secs);
+        let url = builder()
+            .created_at(at(1_000))
+            .access_count(7)
+            .max_accesses(10)
+            .tags(vec!["newsletter".to_string(), "twitter".to_string()])
+            .unwrap()
+            .active_from(at(2_000))
+            .expiry(Duration::from_secs(5_000))
+            .last_accessed_at(at(3_000))
+            .history_days(30)
+            .enabled(false)
+            .build();
+
+        assert_eq!(url.created_at(), at(1_000));
+        assert_eq!(url.access_count(), 7);
+        assert_eq!(url.max_accesses(), Some(10));
+        assert_eq!(url.tags(), ["newsletter", "twitter"]);
+        assert_eq!(url.active_from(), Some(at(2_000)));
+        assert_eq!(url.expires_at(), Some(at(6_000)));
+        assert_eq!(url.last_accessed_at(), Some(at(3_000)));
+        assert_eq!(url.history_days(), 30);
+        assert!(!url.is_enabled());
+    }
+
+    #[test]
+    fn test_last_expiry_setting_wins() {
+        let at = |secs: u64| UNIX_EPOCH + Duration::from_secs(secs);
+        let url = builder()
+            .created_at(at(1_000))
+            .expiry(Duration::from_secs(60))
+            .expires_at(at(9_000))
+            .build();
+        assert_eq!(url.expires_at(), Some(at(9_000)));
+
+        let url = builder()
+            .created_at(at(1_000))
+            .expires_at(at(9_000))
+            .expiry(Duration::from_secs(60))
+            .build();
+        assert_eq!(url.expires_at(), Some(at(1_060)));
+    }
+
+    #[test]
+    fn test_invalid_tags_are_rejected() {
+        let result = builder().tags(vec!["twitter".to_string(), "twitter".to_string()]);
+        assert!(matches!(result, Err(crate::error::UrlShortenerError::InvalidTag(_))));
+    }
+}
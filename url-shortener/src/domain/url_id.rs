@@ -9,6 +9,7 @@ use std::fmt;
 /// Uses the newtype pattern to ensure type safety - you cannot accidentally
 /// pass a regular String where a UrlId is expected.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct UrlId(String);
 
 impl UrlId {
@@ -68,6 +69,15 @@ mod tests {
         assert_ne!(id1, id3);
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_url_id_serde_round_trip() {
+        let id = UrlId::new("abc123".to_string());
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, "\"abc123\"");
+        assert_eq!(serde_json::from_str::<UrlId>(&json).unwrap(), id);
+    }
+
     #[test]
     fn test_url_id_display() {
         let id = UrlId::new("display_test".to_string());
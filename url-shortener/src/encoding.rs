@@ -0,0 +1,107 @@
+//! Base62 encoding of integers
+//!
+//! Shared by the ID generators and available for custom `IdGenerator`
+//! implementations.
+
+/// Characters used as base62 digits, in digit order
+///
+/// The same alphanumeric charset `RandomIdGenerator` draws short codes from,
+/// so `a` is the zero digit and `9` the highest.
+pub const BASE62_CHARSET: &[u8; 62] =
+    b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Encode `n` in base62, most significant digit first, without padding
+///
+/// Zero encodes as a single zero digit, `a`. The longest encoding, of
+/// `u64::MAX`, is 11 characters.
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::encoding::base62_encode;
+///
+/// assert_eq!(base62_encode(0), "a");
+/// assert_eq!(base62_encode(61), "9");
+/// assert_eq!(base62_encode(62), "ba");
+/// ```
+pub fn base62_encode(mut n: u64) -> String {
+    let base = BASE62_CHARSET.len() as u64;
+    let mut digits = Vec::new();
+
+    loop {
+        digits.push(BASE62_CHARSET[(n % base) as usize]);
+        n /= base;
+        if n == 0 {
+            break;
+        }
+    }
+
+    digits.iter().rev().map(|&b| b as char).collect()
+}
+
+/// Decode a base62 string produced by `base62_encode`
+///
+/// Leading zero digits are accepted, so padded encodings decode too.
+/// Returns `None` if `s` is empty, contains a character outside
+/// `BASE62_CHARSET`, or encodes a value that doesn't fit in a `u64`.
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::encoding::base62_decode;
+///
+/// assert_eq!(base62_decode("ba"), Some(62));
+/// assert_eq!(base62_decode("aaba"), Some(62));
+/// assert_eq!(base62_decode("b-a"), None);
+/// ```
+pub fn base62_decode(s: &str) -> Option<u64> {
+    if s.is_empty() {
+        return None;
+    }
+
+    let base = BASE62_CHARSET.len() as u64;
+    s.bytes().try_fold(0u64, |value, byte| {
+        let digit = BASE62_CHARSET.iter().position(|&c| c == byte)? as u64;
+        value.checked_mul(base)?.checked_add(digit)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_round_trip_over_many_values() {
+        let mut rng = StdRng::seed_from_u64(62);
+        let edges = [0, 1, 61, 62, 63, 3_843, 3_844, u64::MAX - 1, u64::MAX];
+        // Random values of every magnitude, not just ones near u64::MAX
+        let random = (0..10_000).map(|_| rng.r#gen::<u64>() >> rng.gen_range(0..64));
+
+        for n in edges.into_iter().chain(random) {
+            let encoded = base62_encode(n);
+            assert_eq!(base62_decode(&encoded), Some(n), "{} encoded as {}", n, encoded);
+        }
+    }
+
+    #[test]
+    fn test_encoding_is_unpadded_and_ordered_by_digit() {
+        assert_eq!(base62_encode(0), "a");
+        assert_eq!(base62_encode(25), "z");
+        assert_eq!(base62_encode(26), "A");
+        assert_eq!(base62_encode(62 * 62), "baa");
+        assert_eq!(base62_encode(u64::MAX).len(), 11);
+    }
+
+    #[test]
+    fn test_decode_rejects_invalid_input() {
+        assert_eq!(base62_decode(""), None);
+        assert_eq!(base62_decode("ab_c"), None);
+        assert_eq!(base62_decode("é"), None);
+
+        // 62^11 and 62 * u64::MAX don't fit in a u64
+        assert_eq!(base62_decode(&format!("b{}", "a".repeat(11))), None);
+        assert_eq!(base62_decode(&format!("{}a", base62_encode(u64::MAX))), None);
+    }
+}
@@ -4,8 +4,15 @@
 
 use thiserror::Error;
 
+/// Boxed underlying error attached to a repository failure
+pub type BoxedSource = Box<dyn std::error::Error + Send + Sync>;
+
 /// Domain errors that can occur in the URL shortener service
-#[derive(Error, Debug, PartialEq)]
+///
+/// New variants may be added in minor releases, so matches should include a
+/// wildcard arm.
+#[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum UrlShortenerError {
     /// The provided URL is invalid or malformed
     #[error("Invalid URL format: {0}")]
@@ -19,18 +26,152 @@ pub enum UrlShortenerError {
     #[error("Short code '{0}' is already in use")]
     ShortCodeAlreadyExists(String),
 
+    /// The short code's check character doesn't match the rest of the code,
+    /// most likely because it was mistyped
+    #[error("Short code '{0}' fails its checksum")]
+    ChecksumMismatch(String),
+
+    /// The short code is reserved and can't be claimed
+    #[error("Short code '{0}' is reserved")]
+    ReservedShortCode(String),
+
     /// The requested short code was not found
     #[error("Short code '{0}' not found")]
     ShortCodeNotFound(String),
 
+    /// The short code exists but its scheduled activation time hasn't arrived
+    #[error("Short code '{0}' is not active yet")]
+    NotYetActive(String),
+
+    /// The short code exists but has passed its expiry time
+    #[error("Short code '{0}' has expired")]
+    ShortCodeExpired(String),
+
+    /// The URL didn't respond, or answered with an error status, when
+    /// checked before shortening
+    #[error("URL is unreachable: {0}")]
+    UrlUnreachable(String),
+
+    /// The URL's domain is blocked, or missing from the allowlist
+    #[error("Domain is not allowed: {0}")]
+    DomainNotAllowed(String),
+
+    /// The short code exists but has been resolved as often as it allows
+    #[error("Short code '{0}' has reached its access limit")]
+    AccessLimitReached(String),
+
+    /// The short code exists but has been disabled
+    #[error("Short code '{0}' is disabled")]
+    ShortCodeDisabled(String),
+
     /// Repository operation failed
-    #[error("Repository error: {0}")]
-    RepositoryError(String),
+    ///
+    /// Adapters can attach the underlying error as `source`, which callers
+    /// can reach through `std::error::Error::source`.
+    #[error("Repository error: {message}")]
+    RepositoryError {
+        /// Description of the failure
+        message: String,
+        /// Underlying cause, if any
+        #[source]
+        source: Option<BoxedSource>,
+    },
+
+    /// The storage backend could not be reached, for example because a lock
+    /// was poisoned by a panicking thread
+    ///
+    /// Unlike `RepositoryError`, this says nothing about the stored data, so
+    /// callers may retry.
+    #[error("Storage unavailable: {0}")]
+    StorageUnavailable(String),
 
     /// ID generation failed
     #[error("Failed to generate unique ID after {0} attempts")]
     IdGenerationFailed(usize),
+
+    /// The port implementation doesn't support the requested operation
+    #[error("Unsupported operation: {0}")]
+    Unsupported(String),
+
+    /// A note could not be added (too long, empty, or too many notes)
+    #[error("Invalid note: {0}")]
+    InvalidNote(String),
+
+    /// A tag is empty, too long or repeated, or there are too many tags
+    #[error("Invalid tag: {0}")]
+    InvalidTag(String),
+
+    /// A signed link token failed verification
+    #[error("Invalid link signature")]
+    InvalidSignature,
+
+    /// A mutating operation was attempted on a read-only service
+    #[error("Service is in read-only mode")]
+    ReadOnly,
+
+    /// A service setting is out of range
+    #[error("Invalid configuration: {0}")]
+    InvalidConfiguration(String),
+}
+
+impl UrlShortenerError {
+    /// Create a `RepositoryError` without an underlying cause
+    pub fn repository(message: impl Into<String>) -> Self {
+        Self::RepositoryError {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Create a `RepositoryError` that wraps an underlying cause
+    pub fn repository_with_source(
+        message: impl Into<String>,
+        source: impl Into<BoxedSource>,
+    ) -> Self {
+        Self::RepositoryError {
+            message: message.into(),
+            source: Some(source.into()),
+        }
+    }
+}
+
+/// Errors compare equal when they are the same variant with the same message.
+///
+/// Attached sources are not compared beyond their effect on the message,
+/// since `dyn Error` has no notion of equality.
+impl PartialEq for UrlShortenerError {
+    fn eq(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+            && self.to_string() == other.to_string()
+    }
 }
 
 /// Result type alias for URL shortener operations
 pub type Result<T> = std::result::Result<T, UrlShortenerError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn test_repository_error_source() {
+        let cause = std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "connection refused");
+        let err = UrlShortenerError::repository_with_source("Failed to save", cause);
+
+        assert_eq!(err.to_string(), "Repository error: Failed to save");
+
+        let source = err.source().expect("source should be attached");
+        assert_eq!(source.to_string(), "connection refused");
+        let io = source.downcast_ref::<std::io::Error>().unwrap();
+        assert_eq!(io.kind(), std::io::ErrorKind::ConnectionRefused);
+    }
+
+    #[test]
+    fn test_repository_error_without_source() {
+        let err = UrlShortenerError::repository("Failed to save");
+        assert!(err.source().is_none());
+        assert_eq!(err, UrlShortenerError::repository("Failed to save"));
+        assert_ne!(err, UrlShortenerError::repository("Something else"));
+    }
+}
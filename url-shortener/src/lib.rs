@@ -26,6 +26,7 @@
 //! │  │       Ports (Traits)            │   │
 //! │  │  - UrlRepository                │   │
 //! │  │  - IdGenerator                  │   │
+//! │  │  - Clock                        │   │
 //! │  └─────────────────────────────────┘   │
 //! └─────────────────────────────────────────┘
 //!         ↑                    ↑
@@ -113,6 +114,7 @@
 
 pub mod adapters;
 pub mod domain;
+pub mod encoding;
 pub mod error;
 pub mod ports;
 pub mod service;
@@ -0,0 +1,40 @@
+//! Async ID generator port
+//!
+//! Async counterpart of `IdGenerator`. Available with the `async` feature.
+
+use super::IdGenerator;
+use crate::domain::{ShortCode, UrlId};
+use crate::error::Result;
+use async_trait::async_trait;
+
+/// Async port for generating unique identifiers
+///
+/// Every synchronous `IdGenerator` is also an `AsyncIdGenerator`, since
+/// generating an id doesn't block. Implement this directly only for
+/// generators that need I/O, such as ones backed by a database sequence.
+#[async_trait]
+pub trait AsyncIdGenerator: Send + Sync {
+    /// Generate a unique URL identifier
+    async fn generate_id(&self) -> UrlId;
+
+    /// Generate a unique short code
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a unique code cannot be generated
+    async fn generate_short_code(&self) -> Result<ShortCode>;
+}
+
+#[async_trait]
+impl<T> AsyncIdGenerator for T
+where
+    T: IdGenerator + ?Sized,
+{
+    async fn generate_id(&self) -> UrlId {
+        IdGenerator::generate_id(self)
+    }
+
+    async fn generate_short_code(&self) -> Result<ShortCode> {
+        IdGenerator::generate_short_code(self)
+    }
+}
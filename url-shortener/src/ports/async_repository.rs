@@ -0,0 +1,77 @@
+//! Async repository port for URL persistence
+//!
+//! Async counterpart of `UrlRepository` for storage backends with async
+//! drivers. Available with the `async` feature.
+
+use crate::domain::{ShortCode, ShortenedUrl};
+use crate::error::{Result, UrlShortenerError};
+use async_trait::async_trait;
+use std::time::SystemTime;
+
+/// Async port for URL persistence
+///
+/// Mirrors `UrlRepository` method for method, with the same error contract.
+/// Implement this for backends whose drivers are async (for example sqlx),
+/// so they can be used without blocking the executor.
+#[async_trait]
+pub trait AsyncUrlRepository: Send + Sync {
+    /// Save a shortened URL
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The short code already exists
+    /// - The storage operation fails
+    async fn save(&self, url: ShortenedUrl) -> Result<()>;
+
+    /// Find a shortened URL by its short code
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::ShortCodeNotFound` if the code doesn't exist
+    async fn find_by_short_code(&self, code: &ShortCode) -> Result<ShortenedUrl>;
+
+    /// Update an existing shortened URL
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URL doesn't exist or the update fails
+    async fn update(&self, url: ShortenedUrl) -> Result<()>;
+
+    /// Record an access to a short code at `at`, returning the new access count
+    ///
+    /// Same contract as `UrlRepository::increment_access_at`: entries that
+    /// have reached their `max_accesses` aren't incremented. The default
+    /// implementation reads the entry and writes it back with `update`, so
+    /// concurrent calls can lose increments and overshoot the limit.
+    /// Implementations should override it to apply the increment atomically.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::ShortCodeNotFound` if the code doesn't
+    /// exist, or `UrlShortenerError::AccessLimitReached` if the entry has
+    /// reached its access limit
+    async fn increment_access_at(&self, code: &ShortCode, at: SystemTime) -> Result<u64> {
+        let mut url = self.find_by_short_code(code).await?;
+        if url.is_access_limit_reached() {
+            return Err(UrlShortenerError::AccessLimitReached(code.as_str().to_string()));
+        }
+        url.record_access_at(at);
+        let count = url.access_count();
+        self.update(url).await?;
+        Ok(count)
+    }
+
+    /// Check if a short code exists
+    async fn exists(&self, code: &ShortCode) -> Result<bool>;
+
+    /// Delete a shortened URL by its short code
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the code doesn't exist or the delete fails
+    async fn delete(&self, code: &ShortCode) -> Result<()>;
+
+    /// Get all shortened URLs (useful for admin/testing)
+    async fn list_all(&self) -> Result<Vec<ShortenedUrl>>;
+}
@@ -0,0 +1,14 @@
+//! Clock port
+//!
+//! Abstracts the current time so time-dependent behavior can be tested
+
+use std::time::SystemTime;
+
+/// Port for reading the current time
+///
+/// The service asks the clock instead of calling `SystemTime::now()`
+/// directly, so tests can inject a fixed or manually advanced time.
+pub trait Clock: Send + Sync {
+    /// Get the current time
+    fn now(&self) -> SystemTime;
+}
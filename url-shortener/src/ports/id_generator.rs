@@ -2,8 +2,8 @@
 //!
 //! Abstracts the generation of unique identifiers and short codes
 
-use crate::domain::{ShortCode, UrlId};
-use crate::error::Result;
+use crate::domain::{OriginalUrl, ShortCode, UrlId};
+use crate::error::{Result, UrlShortenerError};
 
 /// Port for generating unique identifiers
 ///
@@ -22,4 +22,42 @@ pub trait IdGenerator: Send + Sync {
     ///
     /// Returns an error if a unique code cannot be generated
     fn generate_short_code(&self) -> Result<ShortCode>;
+
+    /// Generate a short code for a specific original URL
+    ///
+    /// Generators that derive codes from the URL (such as hash-based ones)
+    /// override this. The default ignores `url` and calls
+    /// `generate_short_code`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a code cannot be generated
+    fn generate_short_code_for(&self, url: &OriginalUrl) -> Result<ShortCode> {
+        let _ = url;
+        self.generate_short_code()
+    }
+
+    /// Generate a short code of a specific length
+    ///
+    /// The service calls this to widen codes when generated codes keep
+    /// colliding. The default returns `UrlShortenerError::Unsupported`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the generator can't produce codes of `length`
+    /// characters
+    fn generate_short_code_with_length(&self, length: usize) -> Result<ShortCode> {
+        Err(UrlShortenerError::Unsupported(format!(
+            "generating short codes of length {}",
+            length
+        )))
+    }
+
+    /// Number of distinct short codes this generator can produce
+    ///
+    /// Returns `None` if the generator can't report its keyspace. Values that
+    /// don't fit in a `u64` saturate to `u64::MAX`.
+    fn keyspace(&self) -> Option<u64> {
+        None
+    }
 }
@@ -0,0 +1,20 @@
+//! Metrics port
+//!
+//! Lets the service report events to a metrics backend without knowing it
+
+/// Port for service metrics
+///
+/// The service calls these hooks as operations complete. Every method does
+/// nothing by default, so implementations only override the events they
+/// track. Hooks run on the request path and should be cheap, for example
+/// incrementing an atomic counter.
+pub trait Metrics: Send + Sync {
+    /// A shortened URL was created
+    fn on_shorten(&self) {}
+
+    /// A short code was resolved to its destination
+    fn on_resolve_hit(&self) {}
+
+    /// A short code could not be resolved because it doesn't exist
+    fn on_resolve_miss(&self) {}
+}
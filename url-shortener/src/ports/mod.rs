@@ -5,6 +5,18 @@
 
 mod repository;
 mod id_generator;
+mod clock;
+mod metrics;
+#[cfg(feature = "async")]
+mod async_repository;
+#[cfg(feature = "async")]
+mod async_id_generator;
 
 pub use repository::UrlRepository;
 pub use id_generator::IdGenerator;
+pub use clock::Clock;
+pub use metrics::Metrics;
+#[cfg(feature = "async")]
+pub use async_repository::AsyncUrlRepository;
+#[cfg(feature = "async")]
+pub use async_id_generator::AsyncIdGenerator;
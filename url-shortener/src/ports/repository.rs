@@ -3,8 +3,9 @@
 //! This trait defines the interface for storing and retrieving shortened URLs.
 //! Different implementations can provide different storage backends (in-memory, database, etc.)
 
-use crate::domain::{ShortCode, ShortenedUrl};
-use crate::error::Result;
+use crate::domain::{OriginalUrl, ShortCode, ShortenedUrl};
+use crate::error::{Result, UrlShortenerError};
+use std::time::SystemTime;
 
 /// Port for URL persistence
 ///
@@ -25,6 +26,15 @@ pub trait UrlRepository: Send + Sync {
     /// - The storage operation fails
     fn save(&self, url: ShortenedUrl) -> Result<()>;
 
+    /// Save several shortened URLs, reporting a result per entry
+    ///
+    /// A failure only affects its own entry; the rest are still saved. The
+    /// default implementation calls `save` for each entry. Implementations
+    /// can override it to take their lock or transaction once per batch.
+    fn save_batch(&self, urls: Vec<ShortenedUrl>) -> Vec<Result<()>> {
+        urls.into_iter().map(|url| self.save(url)).collect()
+    }
+
     /// Find a shortened URL by its short code
     ///
     /// # Errors
@@ -41,6 +51,62 @@ pub trait UrlRepository: Send + Sync {
     /// Returns an error if the URL doesn't exist or the update fails
     fn update(&self, url: ShortenedUrl) -> Result<()>;
 
+    /// Record an access to a short code now, returning the new access count
+    ///
+    /// Equivalent to `increment_access_at` with the current system time.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::ShortCodeNotFound` if the code doesn't exist
+    fn increment_access(&self, code: &ShortCode) -> Result<u64> {
+        self.increment_access_at(code, SystemTime::now())
+    }
+
+    /// Record an access to a short code at `at`, returning the new access count
+    ///
+    /// Updates the access count, last access time and per-day counts as
+    /// `ShortenedUrl::record_access_at` does. The default implementation
+    /// reads the entry, records the access and writes it back with `update`,
+    /// so concurrent calls can lose increments. Implementations should
+    /// override it to apply the increment atomically.
+    ///
+    /// Entries that have reached their `max_accesses` aren't incremented, so
+    /// an atomic implementation also keeps concurrent resolves within the
+    /// limit.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::ShortCodeNotFound` if the code doesn't
+    /// exist, or `UrlShortenerError::AccessLimitReached` if the entry has
+    /// reached its access limit
+    fn increment_access_at(&self, code: &ShortCode, at: SystemTime) -> Result<u64> {
+        let mut url = self.find_by_short_code(code)?;
+        if url.is_access_limit_reached() {
+            return Err(UrlShortenerError::AccessLimitReached(code.as_str().to_string()));
+        }
+        url.record_access_at(at);
+        let count = url.access_count();
+        self.update(url)?;
+        Ok(count)
+    }
+
+    /// Count an access to a short code towards `referrer`
+    ///
+    /// Updates the per-referrer counts as `ShortenedUrl::record_referrer`
+    /// does; the access itself is recorded by `increment_access_at`. The
+    /// default implementation reads the entry and writes it back with
+    /// `update`, so it can undo concurrent increments. Implementations should
+    /// override it to only write the referrer counts.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::ShortCodeNotFound` if the code doesn't exist
+    fn record_referrer(&self, code: &ShortCode, referrer: Option<String>) -> Result<()> {
+        let mut url = self.find_by_short_code(code)?;
+        url.record_referrer(referrer);
+        self.update(url)
+    }
+
     /// Check if a short code exists
     fn exists(&self, code: &ShortCode) -> Result<bool>;
 
@@ -51,6 +117,211 @@ pub trait UrlRepository: Send + Sync {
     /// Returns an error if the code doesn't exist or the delete fails
     fn delete(&self, code: &ShortCode) -> Result<()>;
 
+    /// Delete several shortened URLs, reporting a result per code
+    ///
+    /// A failure only affects its own code; the rest are still deleted, and
+    /// codes that don't exist report `UrlShortenerError::ShortCodeNotFound`.
+    /// The default implementation calls `delete` for each code.
+    /// Implementations can override it to take their lock or transaction
+    /// once per batch.
+    fn delete_many(&self, codes: &[ShortCode]) -> Vec<Result<()>> {
+        codes.iter().map(|code| self.delete(code)).collect()
+    }
+
     /// Get all shortened URLs (useful for admin/testing)
     fn list_all(&self) -> Result<Vec<ShortenedUrl>>;
+
+    /// Delete every shortened URL
+    ///
+    /// The default implementation deletes the entries returned by `list_all`
+    /// one at a time, ignoring entries deleted concurrently. Implementations
+    /// should override it with a single bulk delete.
+    fn clear(&self) -> Result<()> {
+        for url in self.list_all()? {
+            match self.delete(url.short_code()) {
+                Ok(()) | Err(UrlShortenerError::ShortCodeNotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete every shortened URL that has expired at `now`, returning how
+    /// many were deleted
+    ///
+    /// Uses the same rule as `ShortenedUrl::is_expired_at`. The default
+    /// implementation scans `list_all` and deletes matching entries one at a
+    /// time, ignoring entries deleted concurrently. Implementations should
+    /// override it with a single bulk delete.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage operation fails
+    fn delete_expired(&self, now: SystemTime) -> Result<usize> {
+        let mut deleted = 0;
+        for url in self.list_all()? {
+            if !url.is_expired_at(now) {
+                continue;
+            }
+            match self.delete(url.short_code()) {
+                Ok(()) => deleted += 1,
+                Err(UrlShortenerError::ShortCodeNotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// Get a page of shortened URLs
+    ///
+    /// Entries are ordered by creation time, then short code, so consecutive
+    /// pages neither overlap nor skip entries while the repository is
+    /// unchanged. Returns up to `limit` entries after skipping `offset`; an
+    /// offset past the end yields an empty page. The default implementation
+    /// sorts the result of `list_all`.
+    fn list_paginated(&self, offset: usize, limit: usize) -> Result<Vec<ShortenedUrl>> {
+        let mut urls = self.list_all()?;
+        urls.sort_by(|a, b| {
+            (a.created_at(), a.short_code()).cmp(&(b.created_at(), b.short_code()))
+        });
+        Ok(urls.into_iter().skip(offset).take(limit).collect())
+    }
+
+    /// Count the stored shortened URLs
+    ///
+    /// The default implementation calls `list_all`, cloning every entry;
+    /// implementations should override it with a cheaper count.
+    fn count(&self) -> Result<usize> {
+        Ok(self.list_all()?.len())
+    }
+
+    /// Find the shortened URLs created in `[start, end)`
+    ///
+    /// The range includes `start` and excludes `end`. Entries are ordered by
+    /// creation time, then short code. The default implementation filters
+    /// `list_all`.
+    fn created_between(&self, start: SystemTime, end: SystemTime) -> Result<Vec<ShortenedUrl>> {
+        let mut urls: Vec<ShortenedUrl> = self.list_all()?
+            .into_iter()
+            .filter(|entry| (start..end).contains(&entry.created_at()))
+            .collect();
+        urls.sort_by(|a, b| {
+            (a.created_at(), a.short_code()).cmp(&(b.created_at(), b.short_code()))
+        });
+        Ok(urls)
+    }
+
+    /// Find a shortened URL pointing at exactly the given original URL
+    ///
+    /// Returns `None` if no entry maps to `url`. If several do, any one of
+    /// them may be returned. The default implementation scans `list_all`;
+    /// implementations should override it with an indexed lookup.
+    fn find_by_original_url(&self, url: &OriginalUrl) -> Result<Option<ShortenedUrl>> {
+        Ok(self.list_all()?
+            .into_iter()
+            .find(|entry| entry.original_url() == url))
+    }
+
+    /// Find the shortened URLs carrying the given tag, ordered by short code
+    ///
+    /// Tags are matched exactly. Returns an empty list if no entry has the
+    /// tag. The default implementation scans `list_all`.
+    fn find_by_tag(&self, tag: &str) -> Result<Vec<ShortenedUrl>> {
+        let mut urls: Vec<ShortenedUrl> = self.list_all()?
+            .into_iter()
+            .filter(|entry| entry.has_tag(tag))
+            .collect();
+        urls.sort_by(|a, b| a.short_code().cmp(b.short_code()));
+        Ok(urls)
+    }
+
+    /// Find the shortened URLs whose original URL is on `domain`, ordered by
+    /// short code
+    ///
+    /// The host must equal `domain`, ignoring case; subdomains don't match.
+    /// See `OriginalUrl::has_domain`. The default implementation scans
+    /// `list_all`.
+    fn find_by_domain(&self, domain: &str) -> Result<Vec<ShortenedUrl>> {
+        let mut urls: Vec<ShortenedUrl> = self.list_all()?
+            .into_iter()
+            .filter(|entry| entry.original_url().has_domain(domain))
+            .collect();
+        urls.sort_by(|a, b| a.short_code().cmp(b.short_code()));
+        Ok(urls)
+    }
+
+    /// Find the shortened URLs accessed between `min` and `max` times,
+    /// inclusive, ordered by short code
+    ///
+    /// Returns an empty list if `min` is greater than `max`. The default
+    /// implementation scans `list_all`.
+    fn find_by_access_count_range(&self, min: u64, max: u64) -> Result<Vec<ShortenedUrl>> {
+        let mut urls: Vec<ShortenedUrl> = self.list_all()?
+            .into_iter()
+            .filter(|entry| (min..=max).contains(&entry.access_count()))
+            .collect();
+        urls.sort_by(|a, b| a.short_code().cmp(b.short_code()));
+        Ok(urls)
+    }
+
+    /// Swap the original URLs of two shortened URLs
+    ///
+    /// Each entry keeps its own id, creation time and access count; only the
+    /// destinations are exchanged. Either both entries are updated or neither is.
+    ///
+    /// The default implementation is built from `find_by_short_code` and
+    /// `update`: if writing the second entry fails, the first is written back
+    /// as it was. It is not atomic with respect to concurrent writers, and a
+    /// failed rollback leaves only the first entry changed. Implementations
+    /// with transactional storage should override it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::ShortCodeNotFound` if either code doesn't exist
+    fn swap_original_urls(&self, a: &ShortCode, b: &ShortCode) -> Result<()> {
+        let original = self.find_by_short_code(a)?;
+        let mut first = original.clone();
+        let mut second = self.find_by_short_code(b)?;
+
+        first.retarget(second.original_url().clone());
+        second.retarget(original.original_url().clone());
+
+        self.update(first)?;
+        self.update(second).inspect_err(|_| {
+            let _ = self.update(original);
+        })
+    }
+
+    /// Hold `code` until `until`, so it can't be claimed in the meantime
+    ///
+    /// A reservation doesn't create an entry; it only makes `is_reserved`
+    /// report the code as reserved until `until`. Reservations that have
+    /// lapsed at `now` don't count. The default returns
+    /// `UrlShortenerError::Unsupported`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::ShortCodeAlreadyExists` if the code is
+    /// stored or already reserved at `now`
+    fn reserve(&self, code: &ShortCode, now: SystemTime, until: SystemTime) -> Result<()> {
+        let _ = (now, until);
+        Err(UrlShortenerError::Unsupported(format!("reserving short code '{}'", code)))
+    }
+
+    /// Check whether `code` has a reservation that is still held at `now`
+    ///
+    /// The default returns `false`, matching the default `reserve`, which
+    /// never reserves anything.
+    fn is_reserved(&self, code: &ShortCode, now: SystemTime) -> Result<bool> {
+        let _ = (code, now);
+        Ok(false)
+    }
+
+    /// Drop the reservation of `code`, if there is one
+    ///
+    /// The default does nothing.
+    fn release_reservation(&self, code: &ShortCode) -> Result<()> {
+        let _ = code;
+        Ok(())
+    }
 }
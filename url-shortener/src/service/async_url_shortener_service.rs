@@ -0,0 +1,360 @@
+//! Async URL shortener application service
+//!
+//! Async counterpart of `UrlShortenerService`, built on the async ports.
+//! Available with the `async` feature.
+
+use crate::adapters::SystemClock;
+use crate::domain::{OriginalUrl, ShortCode, ShortenedUrl};
+use crate::error::{Result, UrlShortenerError};
+use crate::ports::{AsyncIdGenerator, AsyncUrlRepository, Clock};
+use super::url_shortener_service::ensure_resolvable;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// Async application service for URL shortening operations
+///
+/// Provides the core operations of `UrlShortenerService` (shortening,
+/// resolving, statistics, deletion and listing) with the same semantics and
+/// errors, awaiting the repository instead of blocking on it. Any synchronous
+/// `IdGenerator` can be used as the id generator.
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::service::AsyncUrlShortenerService;
+/// use url_shortener::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+/// use url_shortener::domain::OriginalUrl;
+/// use std::sync::Arc;
+///
+/// # futures::executor::block_on(async {
+/// let repository = Arc::new(InMemoryUrlRepository::new());
+/// let id_generator = Arc::new(RandomIdGenerator::new());
+/// let service = AsyncUrlShortenerService::new(repository, id_generator);
+///
+/// let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+/// let shortened = service.shorten_url(url).await.unwrap();
+/// let original = service.resolve_short_code(shortened.short_code()).await.unwrap();
+/// assert_eq!(original.as_str(), "https://example.com/");
+/// # });
+/// ```
+pub struct AsyncUrlShortenerService<R, G>
+where
+    R: AsyncUrlRepository,
+    G: AsyncIdGenerator,
+{
+    repository: Arc<R>,
+    id_generator: Arc<G>,
+    clock: Arc<dyn Clock>,
+    read_only: bool,
+}
+
+impl<R, G> AsyncUrlShortenerService<R, G>
+where
+    R: AsyncUrlRepository,
+    G: AsyncIdGenerator,
+{
+    /// Maximum attempts to generate a unique short code
+    const MAX_GENERATION_ATTEMPTS: usize = 10;
+
+    /// Create a new async URL shortener service
+    ///
+    /// # Arguments
+    ///
+    /// * `repository` - Implementation of the AsyncUrlRepository port
+    /// * `id_generator` - Implementation of the AsyncIdGenerator port
+    pub fn new(repository: Arc<R>, id_generator: Arc<G>) -> Self {
+        Self {
+            repository,
+            id_generator,
+            clock: Arc::new(SystemClock),
+            read_only: false,
+        }
+    }
+
+    /// Put the service in read-only mode
+    ///
+    /// See `UrlShortenerService::read_only`.
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Check whether the service is in read-only mode
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Use a custom clock for time-dependent operations
+    ///
+    /// Defaults to `SystemClock`.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Shorten a URL with an auto-generated short code
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A unique short code cannot be generated
+    /// - The repository operation fails
+    pub async fn shorten_url(&self, original_url: OriginalUrl) -> Result<ShortenedUrl> {
+        self.ensure_writable()?;
+
+        let short_code = self.generate_unique_short_code().await?;
+
+        let id = self.id_generator.generate_id().await;
+        let shortened_url = ShortenedUrl::new(id, short_code, original_url);
+
+        self.repository.save(shortened_url.clone()).await?;
+
+        Ok(shortened_url)
+    }
+
+    /// Shorten a URL that only starts resolving at `active_from`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A unique short code cannot be generated
+    /// - The repository operation fails
+    pub async fn shorten_url_scheduled(
+        &self,
+        original_url: OriginalUrl,
+        active_from: SystemTime,
+    ) -> Result<ShortenedUrl> {
+        self.ensure_writable()?;
+
+        let short_code = self.generate_unique_short_code().await?;
+
+        let id = self.id_generator.generate_id().await;
+        let shortened_url = ShortenedUrl::new(id, short_code, original_url)
+            .with_active_from(active_from);
+
+        self.repository.save(shortened_url.clone()).await?;
+
+        Ok(shortened_url)
+    }
+
+    /// Shorten a URL that stops resolving `ttl` after it is created
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A unique short code cannot be generated
+    /// - The repository operation fails
+    pub async fn shorten_url_with_expiry(
+        &self,
+        original_url: OriginalUrl,
+        ttl: Duration,
+    ) -> Result<ShortenedUrl> {
+        self.ensure_writable()?;
+
+        let short_code = self.generate_unique_short_code().await?;
+
+        let id = self.id_generator.generate_id().await;
+        let now = self.clock.now();
+        let shortened_url = ShortenedUrl::with_created_at(id, short_code, original_url, now)
+            .with_expiry(ttl);
+
+        self.repository.save(shortened_url.clone()).await?;
+
+        Ok(shortened_url)
+    }
+
+    /// Shorten a URL with a custom short code
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The short code is already in use
+    /// - The repository operation fails
+    pub async fn shorten_url_with_code(
+        &self,
+        original_url: OriginalUrl,
+        short_code: ShortCode,
+    ) -> Result<ShortenedUrl> {
+        self.ensure_writable()?;
+
+        if self.repository.exists(&short_code).await? {
+            return Err(UrlShortenerError::ShortCodeAlreadyExists(
+                short_code.as_str().to_string()
+            ));
+        }
+
+        let id = self.id_generator.generate_id().await;
+        let shortened_url = ShortenedUrl::new(id, short_code, original_url);
+
+        self.repository.save(shortened_url.clone()).await?;
+
+        Ok(shortened_url)
+    }
+
+    /// Resolve a short code to its original URL, recording the access
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The short code doesn't exist
+    /// - The short code has been disabled (`ShortCodeDisabled`)
+    /// - The short code is scheduled to activate later (`NotYetActive`)
+    /// - The short code is past its expiry time (`ShortCodeExpired`)
+    /// - The short code has been resolved `max_accesses` times (`AccessLimitReached`)
+    /// - The repository operation fails
+    pub async fn resolve_short_code(&self, short_code: &ShortCode) -> Result<OriginalUrl> {
+        self.ensure_writable()?;
+
+        let shortened_url = self.repository.find_by_short_code(short_code).await?;
+        let now = self.clock.now();
+        ensure_resolvable(&shortened_url, now)?;
+
+        // Record the access in a single repository operation, so concurrent
+        // resolves don't lose increments or overshoot the access limit
+        self.repository.increment_access_at(short_code, now).await?;
+
+        Ok(shortened_url.original_url().clone())
+    }
+
+    /// Get statistics for a short code
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the short code doesn't exist
+    pub async fn get_statistics(&self, short_code: &ShortCode) -> Result<ShortenedUrl> {
+        self.repository.find_by_short_code(short_code).await
+    }
+
+    /// Delete a shortened URL
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The short code doesn't exist
+    /// - The repository operation fails
+    pub async fn delete_short_code(&self, short_code: &ShortCode) -> Result<()> {
+        self.ensure_writable()?;
+        self.repository.delete(short_code).await
+    }
+
+    /// List all shortened URLs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    pub async fn list_all(&self) -> Result<Vec<ShortenedUrl>> {
+        self.repository.list_all().await
+    }
+
+    /// Fail with `ReadOnly` if the service is in read-only mode
+    fn ensure_writable(&self) -> Result<()> {
+        if self.read_only {
+            return Err(UrlShortenerError::ReadOnly);
+        }
+        Ok(())
+    }
+
+    /// Generate a unique short code
+    async fn generate_unique_short_code(&self) -> Result<ShortCode> {
+        for _ in 0..Self::MAX_GENERATION_ATTEMPTS {
+            let code = self.id_generator.generate_short_code().await?;
+
+            if !self.repository.exists(&code).await? {
+                return Ok(code);
+            }
+        }
+
+        Err(UrlShortenerError::IdGenerationFailed(Self::MAX_GENERATION_ATTEMPTS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::{InMemoryUrlRepository, ManualClock, RandomIdGenerator};
+    use futures::executor::block_on;
+    use std::time::UNIX_EPOCH;
+
+    fn create_service() -> AsyncUrlShortenerService<InMemoryUrlRepository, RandomIdGenerator> {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(RandomIdGenerator::new());
+        AsyncUrlShortenerService::new(repository, id_generator)
+    }
+
+    #[test]
+    fn test_shorten_and_resolve() {
+        block_on(async {
+            let service = create_service();
+            let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+
+            let shortened = service.shorten_url(url.clone()).await.unwrap();
+            let resolved = service.resolve_short_code(shortened.short_code()).await.unwrap();
+            assert_eq!(resolved, url);
+
+            let stats = service.get_statistics(shortened.short_code()).await.unwrap();
+            assert_eq!(stats.access_count(), 1);
+        });
+    }
+
+    #[test]
+    fn test_custom_code_conflict() {
+        block_on(async {
+            let service = create_service();
+            let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+            let code = ShortCode::new("custom".to_string()).unwrap();
+
+            service.shorten_url_with_code(url.clone(), code.clone()).await.unwrap();
+            let result = service.shorten_url_with_code(url, code).await;
+            assert!(matches!(result, Err(UrlShortenerError::ShortCodeAlreadyExists(_))));
+        });
+    }
+
+    #[test]
+    fn test_expiry_and_activation() {
+        block_on(async {
+            let start = UNIX_EPOCH + Duration::from_secs(10_000);
+            let clock = Arc::new(ManualClock::new(start));
+            let service = create_service().with_clock(clock.clone());
+
+            let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+            let expiring = service
+                .shorten_url_with_expiry(url.clone(), Duration::from_secs(60))
+                .await
+                .unwrap();
+            let scheduled = service
+                .shorten_url_scheduled(url, start + Duration::from_secs(60))
+                .await
+                .unwrap();
+
+            let result = service.resolve_short_code(scheduled.short_code()).await;
+            assert!(matches!(result, Err(UrlShortenerError::NotYetActive(_))));
+            service.resolve_short_code(expiring.short_code()).await.unwrap();
+
+            clock.advance(Duration::from_secs(60));
+            let result = service.resolve_short_code(expiring.short_code()).await;
+            assert!(matches!(result, Err(UrlShortenerError::ShortCodeExpired(_))));
+            service.resolve_short_code(scheduled.short_code()).await.unwrap();
+        });
+    }
+
+    #[test]
+    fn test_delete_and_read_only() {
+        block_on(async {
+            let repository = Arc::new(InMemoryUrlRepository::new());
+            let id_generator = Arc::new(RandomIdGenerator::new());
+            let service = AsyncUrlShortenerService::new(repository.clone(), id_generator.clone());
+
+            let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+            let shortened = service.shorten_url(url.clone()).await.unwrap();
+            assert_eq!(service.list_all().await.unwrap().len(), 1);
+
+            let replica = AsyncUrlShortenerService::new(repository, id_generator).read_only();
+            let result = replica.delete_short_code(shortened.short_code()).await;
+            assert!(matches!(result, Err(UrlShortenerError::ReadOnly)));
+            assert_eq!(replica.list_all().await.unwrap().len(), 1);
+
+            service.delete_short_code(shortened.short_code()).await.unwrap();
+            assert!(service.list_all().await.unwrap().is_empty());
+        });
+    }
+}
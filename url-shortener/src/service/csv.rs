@@ -0,0 +1,160 @@
+//! Minimal CSV encoding and decoding
+//!
+//! Follows RFC 4180: fields containing a comma, quote or line break are
+//! quoted, with embedded quotes doubled. Records end with `\n`; `\r\n` is
+//! accepted when reading.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Append one record to `out`
+pub(crate) fn write_record(out: &mut String, fields: &[&str]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_field(out, field);
+    }
+    out.push('\n');
+}
+
+/// Append a single field, quoting it if needed
+fn write_field(out: &mut String, field: &str) {
+    if !field.contains([',', '"', '\n', '\r']) {
+        out.push_str(field);
+        return;
+    }
+
+    out.push('"');
+    out.push_str(&field.replace('"', "\"\""));
+    out.push('"');
+}
+
+/// A parsed record and the line it starts on
+///
+/// `fields` is `None` if the record is malformed, for example because of a
+/// stray or unterminated quote.
+pub(crate) struct Record {
+    pub(crate) line: usize,
+    pub(crate) fields: Option<Vec<String>>,
+}
+
+/// Split `input` into records
+///
+/// A malformed record doesn't affect the records after it, except that an
+/// unterminated quote swallows the rest of the input.
+pub(crate) fn parse(input: &str) -> Vec<Record> {
+    let mut chars = input.chars().peekable();
+    let mut line = 1;
+    let mut records = Vec::new();
+
+    while chars.peek().is_some() {
+        let start = line;
+        let fields = parse_record(&mut chars, &mut line);
+        records.push(Record { line: start, fields });
+    }
+
+    records
+}
+
+/// Parse one record, consuming its line terminator
+fn parse_record(chars: &mut Peekable<Chars<'_>>, line: &mut usize) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut valid = true;
+
+    loop {
+        let mut field = String::new();
+        let quoted = chars.next_if_eq(&'"').is_some();
+
+        if quoted {
+            loop {
+                match chars.next() {
+                    None => return None,
+                    Some('"') if chars.next_if_eq(&'"').is_some() => field.push('"'),
+                    Some('"') => break,
+                    Some(c) => {
+                        if c == '\n' {
+                            *line += 1;
+                        }
+                        field.push(c);
+                    }
+                }
+            }
+        }
+
+        loop {
+            match chars.next() {
+                Some(',') => break,
+                None => {
+                    fields.push(field);
+                    return valid.then_some(fields);
+                }
+                Some('\n') => {
+                    *line += 1;
+                    fields.push(field);
+                    return valid.then_some(fields);
+                }
+                Some('\r') if chars.peek() == Some(&'\n') => {}
+                Some(c) => {
+                    // Quotes are only allowed around a whole field
+                    if quoted || c == '"' {
+                        valid = false;
+                    }
+                    field.push(c);
+                }
+            }
+        }
+
+        fields.push(field);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(fields: &[&str]) -> String {
+        let mut out = String::new();
+        write_record(&mut out, fields);
+        out
+    }
+
+    fn owned(fields: &[&str]) -> Option<Vec<String>> {
+        Some(fields.iter().map(|field| field.to_string()).collect())
+    }
+
+    #[test]
+    fn test_plain_fields_unquoted() {
+        assert_eq!(record(&["abc", "https://example.com/", "1"]), "abc,https://example.com/,1\n");
+    }
+
+    #[test]
+    fn test_special_fields_quoted() {
+        assert_eq!(record(&["a,b", "say \"hi\"", "x\ny"]), "\"a,b\",\"say \"\"hi\"\"\",\"x\ny\"\n");
+    }
+
+    #[test]
+    fn test_parse_round_trips_written_records() {
+        let mut out = String::new();
+        write_record(&mut out, &["a,b", "say \"hi\"", ""]);
+        write_record(&mut out, &["line\nbreak", "plain"]);
+
+        let records = parse(&out);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].line, 1);
+        assert_eq!(records[0].fields, owned(&["a,b", "say \"hi\"", ""]));
+        assert_eq!(records[1].line, 2);
+        assert_eq!(records[1].fields, owned(&["line\nbreak", "plain"]));
+    }
+
+    #[test]
+    fn test_parse_reports_malformed_records() {
+        let records = parse("ok,1\r\nbad\"quote,2\n\"closed\"junk,3\nlast,4\n\"open,5\n");
+        let lines: Vec<(usize, bool)> = records
+            .iter()
+            .map(|record| (record.line, record.fields.is_some()))
+            .collect();
+
+        assert_eq!(lines, [(1, true), (2, false), (3, false), (4, true), (5, false)]);
+    }
+}
@@ -0,0 +1,50 @@
+//! Outcome of a CSV import
+
+/// Summary of `UrlShortenerService::import_csv`
+///
+/// Line numbers are 1-based and refer to the line a row starts on.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    imported: usize,
+    duplicate_lines: Vec<usize>,
+    invalid_lines: Vec<usize>,
+}
+
+impl ImportReport {
+    /// Number of rows stored
+    pub fn imported(&self) -> usize {
+        self.imported
+    }
+
+    /// Number of rows skipped because their short code was already taken
+    pub fn skipped_duplicates(&self) -> usize {
+        self.duplicate_lines.len()
+    }
+
+    /// Number of rows rejected as malformed or invalid
+    pub fn invalid(&self) -> usize {
+        self.invalid_lines.len()
+    }
+
+    /// Lines of the rows skipped as duplicates, in input order
+    pub fn duplicate_lines(&self) -> &[usize] {
+        &self.duplicate_lines
+    }
+
+    /// Lines of the rows rejected as invalid, in input order
+    pub fn invalid_lines(&self) -> &[usize] {
+        &self.invalid_lines
+    }
+
+    pub(crate) fn record_imported(&mut self) {
+        self.imported += 1;
+    }
+
+    pub(crate) fn record_duplicate(&mut self, line: usize) {
+        self.duplicate_lines.push(line);
+    }
+
+    pub(crate) fn record_invalid(&mut self, line: usize) {
+        self.invalid_lines.push(line);
+    }
+}
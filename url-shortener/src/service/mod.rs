@@ -3,6 +3,29 @@
 //! The service layer orchestrates domain logic and uses ports to interact
 //! with external dependencies.
 
+#[cfg(feature = "async")]
+mod async_url_shortener_service;
+#[cfg(feature = "signing")]
+mod signing;
+mod csv;
+mod import_report;
+#[cfg(feature = "qr")]
+mod qr;
+#[cfg(feature = "validation")]
+mod reachability;
+mod recent_requests;
+mod rolling_counter;
+mod select_filter;
+mod template;
+#[cfg(feature = "tracing")]
+mod trace;
 mod url_shortener_service;
+mod url_shortener_service_builder;
 
+#[cfg(feature = "async")]
+pub use async_url_shortener_service::AsyncUrlShortenerService;
+pub use import_report::ImportReport;
+pub use select_filter::SelectFilter;
+pub use template::UnknownPlaceholderPolicy;
 pub use url_shortener_service::UrlShortenerService;
+pub use url_shortener_service_builder::UrlShortenerServiceBuilder;
@@ -0,0 +1,61 @@
+//! QR codes for short links
+
+use crate::error::{Result, UrlShortenerError};
+use qrcode::QrCode;
+use qrcode::render::svg;
+
+/// Smallest width and height of a rendered QR code, in pixels
+const MIN_DIMENSION: u32 = 200;
+
+/// Render `link` as an SVG QR code
+///
+/// # Errors
+///
+/// Returns `UrlShortenerError::InvalidUrl` if the link doesn't fit in a QR
+/// code
+pub(crate) fn render_svg(link: &str) -> Result<String> {
+    let code = QrCode::new(link.as_bytes()).map_err(|e| {
+        UrlShortenerError::InvalidUrl(format!("Can't encode '{}' as a QR code: {}", link, e))
+    })?;
+
+    Ok(code
+        .render::<svg::Color>()
+        .min_dimensions(MIN_DIMENSION, MIN_DIMENSION)
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+    use crate::domain::{OriginalUrl, ShortCode};
+    use crate::service::UrlShortenerService;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_qr_code_svg() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let service = UrlShortenerService::new(repository, Arc::new(RandomIdGenerator::new()));
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let shortened = service.shorten_url(url).unwrap();
+
+        let svg = service.qr_code_svg(shortened.short_code(), "https://sho.rt/").unwrap();
+        assert!(svg.contains("<svg"));
+        assert_eq!(
+            svg,
+            render_svg(&format!("https://sho.rt/{}", shortened.short_code())).unwrap()
+        );
+
+        let unknown = ShortCode::new("missing".to_string()).unwrap();
+        assert!(matches!(
+            service.qr_code_svg(&unknown, "https://sho.rt"),
+            Err(UrlShortenerError::ShortCodeNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_oversized_link_rejected() {
+        let link = format!("https://sho.rt/{}", "a".repeat(8_000));
+        assert!(matches!(render_svg(&link), Err(UrlShortenerError::InvalidUrl(_))));
+    }
+}
@@ -0,0 +1,110 @@
+//! Reachability checks for URLs about to be shortened
+
+use crate::domain::OriginalUrl;
+use crate::error::{Result, UrlShortenerError};
+use reqwest::blocking::Client;
+use reqwest::redirect::Policy;
+use std::time::Duration;
+
+/// Check that `url` answers a `HEAD` request with a 2xx or 3xx status
+///
+/// Redirects are not followed; a redirect already shows the URL exists.
+///
+/// # Errors
+///
+/// Returns `UrlShortenerError::UrlUnreachable` if the request fails or times
+/// out, or the server answers with a 4xx or 5xx status
+pub(crate) fn check_reachable(url: &OriginalUrl, timeout: Duration) -> Result<()> {
+    let unreachable =
+        |reason: String| UrlShortenerError::UrlUnreachable(format!("{url}: {reason}"));
+
+    let client = Client::builder()
+        .timeout(timeout)
+        .redirect(Policy::none())
+        .build()
+        .map_err(|e| unreachable(e.to_string()))?;
+    let response = client
+        .head(url.as_url().clone())
+        .send()
+        .map_err(|e| unreachable(e.to_string()))?;
+
+    let status = response.status();
+    if status.is_success() || status.is_redirection() {
+        Ok(())
+    } else {
+        Err(unreachable(format!("server answered {status}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+    use crate::service::UrlShortenerService;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::thread;
+
+    /// Serve a single request with `status` on a local port
+    fn serve_once(status: &'static str) -> OriginalUrl {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0; 1024];
+            let _ = stream.read(&mut buf).unwrap();
+            let response =
+                format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+
+        OriginalUrl::new(format!("http://127.0.0.1:{port}/page")).unwrap()
+    }
+
+    fn create_service() -> UrlShortenerService<InMemoryUrlRepository, RandomIdGenerator> {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(RandomIdGenerator::new());
+        UrlShortenerService::new(repository, id_generator)
+    }
+
+    #[test]
+    fn test_success_and_redirect_are_reachable() {
+        for status in ["200 OK", "301 Moved Permanently"] {
+            let url = serve_once(status);
+            assert!(check_reachable(&url, Duration::from_secs(5)).is_ok(), "{status}");
+        }
+    }
+
+    #[test]
+    fn test_error_status_is_unreachable() {
+        for status in ["404 Not Found", "503 Service Unavailable"] {
+            let url = serve_once(status);
+            let result = check_reachable(&url, Duration::from_secs(5));
+            assert!(matches!(result, Err(UrlShortenerError::UrlUnreachable(_))), "{status}");
+        }
+    }
+
+    #[test]
+    fn test_connection_failure_is_unreachable() {
+        // Bind and drop to get a port nothing listens on
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let url = OriginalUrl::new(format!("http://127.0.0.1:{port}/")).unwrap();
+
+        let result = check_reachable(&url, Duration::from_secs(5));
+        assert!(matches!(result, Err(UrlShortenerError::UrlUnreachable(_))));
+    }
+
+    #[test]
+    fn test_shorten_url_checked() {
+        let service = create_service();
+
+        let shortened = service.shorten_url_checked(serve_once("200 OK")).unwrap();
+        assert!(service.resolve_short_code(shortened.short_code()).is_ok());
+
+        let result = service.shorten_url_checked(serve_once("404 Not Found"));
+        assert!(matches!(result, Err(UrlShortenerError::UrlUnreachable(_))));
+        assert_eq!(service.list_all().unwrap().len(), 1);
+    }
+}
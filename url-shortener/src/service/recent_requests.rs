@@ -0,0 +1,114 @@
+//! Bounded cache of recently seen request ids
+//!
+//! Used to make resolves idempotent per request id.
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// Set of recently seen keys, bounded by age and size
+///
+/// Keys older than `ttl` are evicted on each access. When more than
+/// `capacity` keys are held, the oldest are evicted first.
+#[derive(Debug)]
+pub(crate) struct RecentRequests {
+    ttl: Duration,
+    capacity: usize,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    seen: HashSet<String>,
+    order: VecDeque<(String, SystemTime)>,
+}
+
+impl RecentRequests {
+    pub(crate) fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Record `key` at `now`, returning `true` if it wasn't seen recently
+    pub(crate) fn insert(&self, key: &str, now: SystemTime) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        self.evict_expired(&mut state, now);
+
+        if state.seen.contains(key) {
+            return false;
+        }
+
+        state.seen.insert(key.to_string());
+        state.order.push_back((key.to_string(), now));
+
+        while state.order.len() > self.capacity {
+            if let Some((oldest, _)) = state.order.pop_front() {
+                state.seen.remove(&oldest);
+            }
+        }
+
+        true
+    }
+
+    /// Forget `key` so that the next `insert` of it counts as new
+    pub(crate) fn remove(&self, key: &str) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.seen.remove(key) {
+            state.order.retain(|(k, _)| k != key);
+        }
+    }
+
+    fn evict_expired(&self, state: &mut State, now: SystemTime) {
+        while let Some((key, at)) = state.order.front() {
+            let age = now.duration_since(*at).unwrap_or_default();
+            if age < self.ttl {
+                break;
+            }
+            state.seen.remove(key);
+            state.order.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn test_insert_dedups_within_ttl() {
+        let recent = RecentRequests::new(Duration::from_secs(60), 10);
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+
+        assert!(recent.insert("a", now));
+        assert!(!recent.insert("a", now + Duration::from_secs(59)));
+        assert!(recent.insert("a", now + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_capacity_evicts_oldest() {
+        let recent = RecentRequests::new(Duration::from_secs(60), 2);
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+
+        assert!(recent.insert("a", now));
+        assert!(recent.insert("b", now));
+        assert!(recent.insert("c", now));
+
+        // "a" was evicted to make room for "c"
+        assert!(recent.insert("a", now));
+        assert!(!recent.insert("c", now));
+    }
+
+    #[test]
+    fn test_remove() {
+        let recent = RecentRequests::new(Duration::from_secs(60), 10);
+        let now = UNIX_EPOCH;
+
+        assert!(recent.insert("a", now));
+        recent.remove("a");
+        assert!(recent.insert("a", now));
+    }
+}
@@ -0,0 +1,102 @@
+//! Process-local rolling event counter
+//!
+//! Counts events in one-minute buckets kept in a fixed-size ring buffer.
+
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Counter of recent events, bucketed by minute
+///
+/// Only the last `RollingCounter::BUCKETS` minutes are retained; older
+/// buckets are overwritten as the ring wraps around.
+#[derive(Debug)]
+pub(crate) struct RollingCounter {
+    buckets: Mutex<[Bucket; Self::BUCKETS]>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Bucket {
+    minute: u64,
+    count: u64,
+}
+
+impl RollingCounter {
+    /// Number of one-minute buckets retained
+    pub(crate) const BUCKETS: usize = 60;
+
+    pub(crate) fn new() -> Self {
+        Self {
+            buckets: Mutex::new([Bucket::default(); Self::BUCKETS]),
+        }
+    }
+
+    /// Record one event at `now`
+    pub(crate) fn record(&self, now: SystemTime) {
+        let minute = minute_of(now);
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let bucket = &mut buckets[(minute % Self::BUCKETS as u64) as usize];
+
+        if bucket.minute != minute {
+            *bucket = Bucket { minute, count: 0 };
+        }
+        bucket.count = bucket.count.saturating_add(1);
+    }
+
+    /// Count events in the `window` ending at `now`
+    ///
+    /// The window is rounded up to whole minutes and clamped to the retained
+    /// history.
+    pub(crate) fn count_last(&self, window: Duration, now: SystemTime) -> u64 {
+        let minutes = window.as_secs().div_ceil(60).min(Self::BUCKETS as u64);
+        let current = minute_of(now);
+        let buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        buckets
+            .iter()
+            .filter(|b| b.count > 0 && b.minute <= current && current - b.minute < minutes)
+            .map(|b| b.count)
+            .sum()
+    }
+}
+
+fn minute_of(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 60)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn test_counts_within_window() {
+        let counter = RollingCounter::new();
+        let start = UNIX_EPOCH + Duration::from_secs(1_000 * 60);
+
+        counter.record(start);
+        counter.record(start);
+        counter.record(start + 5 * MINUTE);
+
+        let now = start + 5 * MINUTE;
+        assert_eq!(counter.count_last(MINUTE, now), 1);
+        assert_eq!(counter.count_last(6 * MINUTE, now), 3);
+        assert_eq!(counter.count_last(Duration::from_secs(3600), now), 3);
+    }
+
+    #[test]
+    fn test_buckets_age_out() {
+        let counter = RollingCounter::new();
+        let start = UNIX_EPOCH + Duration::from_secs(1_000 * 60);
+        counter.record(start);
+
+        let later = start + 61 * MINUTE;
+        assert_eq!(counter.count_last(Duration::from_secs(3600), later), 0);
+
+        // A wrapped-around bucket is reset rather than accumulated
+        counter.record(start + 60 * MINUTE);
+        assert_eq!(counter.count_last(MINUTE, start + 60 * MINUTE), 1);
+    }
+}
@@ -0,0 +1,73 @@
+//! Composable filter for `UrlShortenerService::select`
+
+use crate::domain::ShortenedUrl;
+use std::time::SystemTime;
+
+/// Predicates for selecting shortened URLs
+///
+/// All configured predicates must match (AND semantics); an empty filter
+/// matches every entry. Supported predicates:
+/// - `min_access_count` - access count is at least the given value
+/// - `scheme` - the destination scheme equals the given one (case-insensitive)
+/// - `host` - the destination host equals the given one (case-insensitive)
+/// - `not_expired` - the entry has not expired at the given time
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::service::SelectFilter;
+///
+/// let filter = SelectFilter::new()
+///     .min_access_count(10)
+///     .scheme("https");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelectFilter {
+    min_access_count: Option<u64>,
+    scheme: Option<String>,
+    host: Option<String>,
+    not_expired_at: Option<SystemTime>,
+}
+
+impl SelectFilter {
+    /// Create a filter that matches everything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only match entries accessed at least `count` times
+    pub fn min_access_count(mut self, count: u64) -> Self {
+        self.min_access_count = Some(count);
+        self
+    }
+
+    /// Only match destinations with the given scheme
+    pub fn scheme(mut self, scheme: impl Into<String>) -> Self {
+        self.scheme = Some(scheme.into());
+        self
+    }
+
+    /// Only match destinations on the given host
+    pub fn host(mut self, host: impl Into<String>) -> Self {
+        self.host = Some(host.into());
+        self
+    }
+
+    /// Only match entries that have not expired at `now`
+    pub fn not_expired(mut self, now: SystemTime) -> Self {
+        self.not_expired_at = Some(now);
+        self
+    }
+
+    /// Check whether an entry satisfies every configured predicate
+    pub fn matches(&self, url: &ShortenedUrl) -> bool {
+        let original = url.original_url();
+
+        self.min_access_count.is_none_or(|min| url.access_count() >= min)
+            && self.scheme.as_deref().is_none_or(|s| original.scheme().eq_ignore_ascii_case(s))
+            && self.host.as_deref().is_none_or(|h| {
+                original.host_str().is_some_and(|host| host.eq_ignore_ascii_case(h))
+            })
+            && self.not_expired_at.is_none_or(|now| !url.is_expired_at(now))
+    }
+}
@@ -0,0 +1,75 @@
+//! Tamper-evident short link tokens
+//!
+//! A token has the form `{code}.{signature}`, where the signature is the
+//! unpadded base64url encoding of HMAC-SHA256 over the short code.
+
+use crate::domain::ShortCode;
+use crate::error::{Result, UrlShortenerError};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Sign a short code, producing a `{code}.{signature}` token
+pub(crate) fn sign(code: &ShortCode, secret: &[u8]) -> String {
+    let signature = mac(secret, code.as_str()).finalize().into_bytes();
+    format!("{}.{}", code, URL_SAFE_NO_PAD.encode(signature))
+}
+
+/// Verify a token and return the short code it carries
+///
+/// The signature is checked in constant time.
+pub(crate) fn verify(token: &str, secret: &[u8]) -> Result<ShortCode> {
+    let (code, signature) = token
+        .split_once('.')
+        .ok_or(UrlShortenerError::InvalidSignature)?;
+
+    let signature = URL_SAFE_NO_PAD
+        .decode(signature)
+        .map_err(|_| UrlShortenerError::InvalidSignature)?;
+
+    mac(secret, code)
+        .verify_slice(&signature)
+        .map_err(|_| UrlShortenerError::InvalidSignature)?;
+
+    ShortCode::new(code.to_string())
+}
+
+fn mac(secret: &[u8], code: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(code.as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"top-secret";
+
+    #[test]
+    fn test_sign_and_verify() {
+        let code = ShortCode::new("abc123".to_string()).unwrap();
+        let token = sign(&code, SECRET);
+
+        assert!(token.starts_with("abc123."));
+        assert_eq!(verify(&token, SECRET).unwrap(), code);
+    }
+
+    #[test]
+    fn test_verify_rejects_wrong_secret() {
+        let code = ShortCode::new("abc123".to_string()).unwrap();
+        let token = sign(&code, SECRET);
+
+        let result = verify(&token, b"other-secret");
+        assert!(matches!(result, Err(UrlShortenerError::InvalidSignature)));
+    }
+
+    #[test]
+    fn test_verify_rejects_malformed_token() {
+        assert!(matches!(verify("abc123", SECRET), Err(UrlShortenerError::InvalidSignature)));
+        assert!(matches!(verify("abc123.!!!", SECRET), Err(UrlShortenerError::InvalidSignature)));
+    }
+}
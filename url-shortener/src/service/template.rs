@@ -0,0 +1,146 @@
+//! Resolve-time placeholder expansion for destination URLs
+//!
+//! Destinations may contain placeholders such as `{code}` or `{ts}` that are
+//! filled in when the short code is resolved.
+
+use crate::domain::{OriginalUrl, ShortCode};
+use crate::error::{Result, UrlShortenerError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How unknown placeholders in a destination URL are handled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnknownPlaceholderPolicy {
+    /// Leave unknown placeholders in the URL untouched
+    #[default]
+    Keep,
+    /// Reject the URL with `UrlShortenerError::InvalidUrl`
+    Reject,
+}
+
+/// Placeholder openers as they may appear in a parsed URL
+///
+/// The `url` crate percent-encodes braces in the path but not in the query,
+/// so both forms have to be recognised.
+const OPENERS: &[&str] = &["{", "%7B", "%7b"];
+const CLOSERS: &[&str] = &["}", "%7D", "%7d"];
+
+/// Expand the known placeholders in `url`
+///
+/// Supported placeholders:
+/// - `{code}` - the short code being resolved
+/// - `{ts}` - the resolve time as Unix seconds
+///
+/// The expanded string is validated again through `OriginalUrl::new`.
+pub(crate) fn expand(
+    url: &OriginalUrl,
+    code: &ShortCode,
+    now: SystemTime,
+    policy: UnknownPlaceholderPolicy,
+) -> Result<OriginalUrl> {
+    let input = url.as_str();
+    let timestamp = now
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+        .to_string();
+
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        let Some((open_len, name, close_len)) = match_placeholder(rest) else {
+            let ch = rest.chars().next().unwrap_or_default();
+            output.push(ch);
+            rest = &rest[ch.len_utf8()..];
+            continue;
+        };
+
+        let whole = &rest[..open_len + name.len() + close_len];
+        match name {
+            "code" => output.push_str(code.as_str()),
+            "ts" => output.push_str(&timestamp),
+            _ => match policy {
+                UnknownPlaceholderPolicy::Keep => output.push_str(whole),
+                UnknownPlaceholderPolicy::Reject => {
+                    return Err(UrlShortenerError::InvalidUrl(
+                        format!("Unknown placeholder: {{{}}}", name)
+                    ));
+                }
+            },
+        }
+        rest = &rest[whole.len()..];
+    }
+
+    if output == input {
+        return Ok(url.clone());
+    }
+
+    // The service enforces its own cap on expanded URLs
+    OriginalUrl::new_with_max_len(output, usize::MAX)
+}
+
+/// Match a placeholder at the start of `s`
+///
+/// Returns the opener length, the placeholder name and the closer length.
+fn match_placeholder(s: &str) -> Option<(usize, &str, usize)> {
+    let open_len = OPENERS.iter().find(|o| s.starts_with(*o))?.len();
+    let after_open = &s[open_len..];
+
+    let name_len = after_open
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(after_open.len());
+    if name_len == 0 {
+        return None;
+    }
+
+    let (name, after_name) = after_open.split_at(name_len);
+    let close_len = CLOSERS.iter().find(|c| after_name.starts_with(*c))?.len();
+
+    Some((open_len, name, close_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn code() -> ShortCode {
+        ShortCode::new("abc123".to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_expand_code_in_path_and_query() {
+        let url = OriginalUrl::new("https://example.com/{code}?src={code}".to_string()).unwrap();
+        let expanded = expand(&url, &code(), SystemTime::now(), UnknownPlaceholderPolicy::Keep).unwrap();
+        assert_eq!(expanded.as_str(), "https://example.com/abc123?src=abc123");
+    }
+
+    #[test]
+    fn test_expand_timestamp() {
+        let url = OriginalUrl::new("https://example.com/beacon?ts={ts}".to_string()).unwrap();
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let expanded = expand(&url, &code(), now, UnknownPlaceholderPolicy::Keep).unwrap();
+        assert_eq!(expanded.as_str(), "https://example.com/beacon?ts=1700000000");
+    }
+
+    #[test]
+    fn test_no_placeholders_passes_through() {
+        let url = OriginalUrl::new("https://example.com/plain?q=1".to_string()).unwrap();
+        let expanded = expand(&url, &code(), SystemTime::now(), UnknownPlaceholderPolicy::Reject).unwrap();
+        assert_eq!(expanded, url);
+    }
+
+    #[test]
+    fn test_unknown_placeholder_kept() {
+        let url = OriginalUrl::new("https://example.com/?a={other}&c={code}".to_string()).unwrap();
+        let expanded = expand(&url, &code(), SystemTime::now(), UnknownPlaceholderPolicy::Keep).unwrap();
+        assert_eq!(expanded.as_str(), "https://example.com/?a={other}&c=abc123");
+    }
+
+    #[test]
+    fn test_unknown_placeholder_rejected() {
+        let url = OriginalUrl::new("https://example.com/{other}".to_string()).unwrap();
+        let result = expand(&url, &code(), SystemTime::now(), UnknownPlaceholderPolicy::Reject);
+        assert!(matches!(result, Err(UrlShortenerError::InvalidUrl(_))));
+    }
+}
@@ -0,0 +1,95 @@
+//! Tracing instrumentation for service operations
+//!
+//! The service opens a span per operation; these helpers fill in the
+//! outcome once the operation returns.
+
+use crate::domain::ShortenedUrl;
+use crate::error::{Result, UrlShortenerError};
+use tracing::Span;
+
+/// Record the outcome of the operation on the current span
+///
+/// Emits a `warn` event for unknown short codes and a `debug` event
+/// otherwise, so the span fields show up alongside the event.
+pub(crate) fn record_outcome<T>(result: &Result<T>) {
+    let span = Span::current();
+
+    match result {
+        Ok(_) => {
+            span.record("outcome", "ok");
+            tracing::debug!("completed");
+        }
+        Err(UrlShortenerError::ShortCodeNotFound(code)) => {
+            span.record("outcome", "not_found");
+            tracing::warn!(short_code = %code, "short code not found");
+        }
+        Err(err) => {
+            span.record("outcome", "error");
+            tracing::debug!(error = %err, "failed");
+        }
+    }
+}
+
+/// Record the generated short code and outcome of a shorten
+pub(crate) fn record_shortened(result: &Result<ShortenedUrl>) {
+    if let Ok(shortened_url) = result {
+        let short_code = shortened_url.short_code();
+        Span::current().record("short_code", tracing::field::display(short_code));
+    }
+
+    record_outcome(result);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+    use crate::domain::{OriginalUrl, ShortCode};
+    use crate::service::UrlShortenerService;
+    use std::sync::Arc;
+    use tracing_test::traced_test;
+
+    fn service() -> UrlShortenerService<InMemoryUrlRepository, RandomIdGenerator> {
+        UrlShortenerService::new(
+            Arc::new(InMemoryUrlRepository::new()),
+            Arc::new(RandomIdGenerator::new()),
+        )
+    }
+
+    fn example_url() -> OriginalUrl {
+        OriginalUrl::new("https://example.com".to_string()).unwrap()
+    }
+
+    #[traced_test]
+    #[test]
+    fn test_resolve_span_records_short_code() {
+        let service = service();
+        let code = ShortCode::new("traced1".to_string()).unwrap();
+        service.shorten_url_with_code(example_url(), code.clone()).unwrap();
+
+        service.resolve_short_code(&code).unwrap();
+
+        assert!(logs_contain("resolve_short_code{short_code=traced1 outcome=\"ok\"}"));
+        assert!(!logs_contain("WARN"));
+    }
+
+    #[traced_test]
+    #[test]
+    fn test_shorten_span_records_generated_code() {
+        let shortened = service().shorten_url(example_url()).unwrap();
+
+        let field = format!("shorten_url{{short_code={} ", shortened.short_code());
+        assert!(logs_contain(&field));
+    }
+
+    #[traced_test]
+    #[test]
+    fn test_unknown_code_warns() {
+        let code = ShortCode::new("missing".to_string()).unwrap();
+
+        assert!(service().resolve_short_code(&code).is_err());
+
+        assert!(logs_contain("outcome=\"not_found\""));
+        assert!(logs_contain("WARN"));
+        assert!(logs_contain("short code not found"));
+    }
+}
@@ -3,10 +3,24 @@
 //! This service orchestrates the URL shortening logic using the domain model
 //! and ports for external dependencies.
 
-use crate::domain::{OriginalUrl, ShortCode, ShortenedUrl};
+use crate::adapters::{NoopMetrics, SystemClock};
+use crate::domain::{
+    Note, OriginalUrl, RedirectKind, RedirectResponse, ShortCode, ShortenedUrl, UrlId,
+};
 use crate::error::{Result, UrlShortenerError};
-use crate::ports::{IdGenerator, UrlRepository};
+use crate::ports::{Clock, IdGenerator, Metrics, UrlRepository};
+use super::csv;
+use super::import_report::ImportReport;
+use super::recent_requests::RecentRequests;
+use super::rolling_counter::RollingCounter;
+use super::select_filter::SelectFilter;
+use super::url_shortener_service_builder::UrlShortenerServiceBuilder;
+use super::template::{self, UnknownPlaceholderPolicy};
+#[cfg(feature = "tracing")]
+use super::trace;
+use std::collections::{BTreeSet, HashSet};
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Application service for URL shortening operations
 ///
@@ -36,6 +50,27 @@ where
 {
     repository: Arc<R>,
     id_generator: Arc<G>,
+    clock: Arc<dyn Clock>,
+    metrics: Arc<dyn Metrics>,
+    unknown_placeholders: UnknownPlaceholderPolicy,
+    read_only: bool,
+    case_insensitive: bool,
+    checksums: bool,
+    reserved_codes: HashSet<String>,
+    domain_blocklist: Vec<String>,
+    domain_allowlist: Vec<String>,
+    prefix: Option<String>,
+    preferred_code_length: Option<usize>,
+    max_generation_attempts: usize,
+    default_expiry: Option<Duration>,
+    default_destination: Option<OriginalUrl>,
+    canonical_output: bool,
+    redirect_kind: RedirectKind,
+    redirect_max_age: Duration,
+    max_expanded_url_length: usize,
+    created_recently: RollingCounter,
+    resolved_recently: RollingCounter,
+    recent_requests: RecentRequests,
 }
 
 impl<R, G> UrlShortenerService<R, G>
@@ -43,8 +78,36 @@ where
     R: UrlRepository,
     G: IdGenerator,
 {
-    /// Maximum attempts to generate a unique short code
-    const MAX_GENERATION_ATTEMPTS: usize = 10;
+    /// Default number of attempts to generate a unique short code at each
+    /// length
+    pub const DEFAULT_MAX_GENERATION_ATTEMPTS: usize = 10;
+
+    /// Default time a request id is remembered by `resolve_idempotent`
+    pub const DEFAULT_IDEMPOTENCY_TTL: Duration = Duration::from_secs(5 * 60);
+
+    /// Default number of request ids remembered by `resolve_idempotent`
+    pub const DEFAULT_IDEMPOTENCY_CAPACITY: usize = 10_000;
+
+    /// Default `Cache-Control` max-age of the redirects from
+    /// `build_redirect_full`
+    pub const DEFAULT_REDIRECT_MAX_AGE: Duration = Duration::from_secs(300);
+
+    /// Default cap on the length of a URL produced by template expansion
+    pub const DEFAULT_MAX_EXPANDED_URL_LENGTH: usize = 2048;
+
+    /// Most days `access_timeseries` returns per call, about ten years
+    #[cfg(feature = "chrono")]
+    pub const MAX_TIMESERIES_DAYS: i64 = 3660;
+
+    /// Time `shorten_url_checked` waits for the target to respond
+    #[cfg(feature = "validation")]
+    pub const REACHABILITY_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Most candidates `suggest_available_codes` checks per call
+    const MAX_SUGGESTION_CANDIDATES: usize = 100;
+
+    /// Collision probability used by `birthday_bound`
+    const BIRTHDAY_COLLISION_PROBABILITY: f64 = 0.01;
 
     /// Create a new URL shortener service
     ///
@@ -56,7 +119,341 @@ where
         Self {
             repository,
             id_generator,
+            clock: Arc::new(SystemClock),
+            metrics: Arc::new(NoopMetrics),
+            unknown_placeholders: UnknownPlaceholderPolicy::default(),
+            read_only: false,
+            case_insensitive: false,
+            checksums: false,
+            reserved_codes: HashSet::new(),
+            domain_blocklist: Vec::new(),
+            domain_allowlist: Vec::new(),
+            prefix: None,
+            preferred_code_length: None,
+            max_generation_attempts: Self::DEFAULT_MAX_GENERATION_ATTEMPTS,
+            default_expiry: None,
+            default_destination: None,
+            canonical_output: false,
+            redirect_kind: RedirectKind::Temporary,
+            redirect_max_age: Self::DEFAULT_REDIRECT_MAX_AGE,
+            max_expanded_url_length: Self::DEFAULT_MAX_EXPANDED_URL_LENGTH,
+            created_recently: RollingCounter::new(),
+            resolved_recently: RollingCounter::new(),
+            recent_requests: RecentRequests::new(
+                Self::DEFAULT_IDEMPOTENCY_TTL,
+                Self::DEFAULT_IDEMPOTENCY_CAPACITY,
+            ),
+        }
+    }
+
+    /// Start configuring a service with a builder
+    ///
+    /// See `UrlShortenerServiceBuilder`.
+    pub fn builder(
+        repository: Arc<R>,
+        id_generator: Arc<G>,
+    ) -> UrlShortenerServiceBuilder<R, G> {
+        UrlShortenerServiceBuilder::new(repository, id_generator)
+    }
+
+    /// Put the service in read-only mode
+    ///
+    /// All mutating operations, including `resolve_short_code` (which records
+    /// accesses), return `UrlShortenerError::ReadOnly`. Read operations such as
+    /// `get_statistics` and `list_all` keep working. Intended for replicas that
+    /// must never write.
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Check whether the service is in read-only mode
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Match short codes case-insensitively
+    ///
+    /// When enabled, codes are lowercased before they are stored or looked
+    /// up, so `AbC123` finds a code stored as `abc123`. Generated codes are
+    /// lowercased too, which shrinks the keyspace of mixed-case generators.
+    /// Codes stored with uppercase letters before enabling this become
+    /// unreachable. Disabled by default.
+    pub fn with_case_insensitive(mut self, enabled: bool) -> Self {
+        self.case_insensitive = enabled;
+        self
+    }
+
+    /// End every short code of this service with a check character
+    ///
+    /// Generated and custom codes get a check character appended (see
+    /// `ShortCode::with_checksum`), so they are one character longer than
+    /// the generator or caller made them. The resolve methods reject a code
+    /// whose check character doesn't match with
+    /// `UrlShortenerError::ChecksumMismatch`, without a repository lookup, so
+    /// a mistyped link fails fast instead of resolving to another link.
+    /// Codes stored before enabling this stop resolving. With
+    /// `with_case_insensitive`, the check character is compared ignoring
+    /// case. Disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::service::UrlShortenerService;
+    /// # use url_shortener::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+    /// # use url_shortener::domain::OriginalUrl;
+    /// # use std::sync::Arc;
+    /// # let repository = Arc::new(InMemoryUrlRepository::new());
+    /// # let id_generator = Arc::new(RandomIdGenerator::new());
+    /// let service = UrlShortenerService::new(repository, id_generator).with_checksums(true);
+    ///
+    /// let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// let shortened = service.shorten_url(url).unwrap();
+    /// assert_eq!(shortened.short_code().as_str().len(), 7);
+    /// assert!(shortened.short_code().verify_checksum());
+    /// ```
+    pub fn with_checksums(mut self, enabled: bool) -> Self {
+        self.checksums = enabled;
+        self
+    }
+
+    /// Reserve short codes so they can't be claimed
+    ///
+    /// `shorten_url_with_code` rejects a reserved code with
+    /// `UrlShortenerError::ReservedShortCode`, and generated codes that are
+    /// reserved are skipped. Useful for keeping codes like `admin` or `api`
+    /// free for routes. Matching follows `with_case_insensitive`.
+    pub fn with_reserved_codes(mut self, codes: Vec<String>) -> Self {
+        self.reserved_codes = codes.into_iter().collect();
+        self
+    }
+
+    /// Refuse to shorten URLs on the given domains
+    ///
+    /// A domain also blocks its subdomains, so `example.com` blocks
+    /// `www.example.com`. Matching is case-insensitive. Blocked URLs are
+    /// rejected with `UrlShortenerError::DomainNotAllowed` by the shortening
+    /// methods and `update_target`, before anything is stored. Entries that
+    /// are already stored keep resolving.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::service::UrlShortenerService;
+    /// # use url_shortener::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+    /// # use url_shortener::domain::OriginalUrl;
+    /// # use std::sync::Arc;
+    /// # let repository = Arc::new(InMemoryUrlRepository::new());
+    /// # let id_generator = Arc::new(RandomIdGenerator::new());
+    /// let service = UrlShortenerService::new(repository, id_generator)
+    ///     .with_domain_blocklist(vec!["malware.test".to_string()]);
+    ///
+    /// let url = OriginalUrl::new("https://cdn.malware.test/x".to_string()).unwrap();
+    /// assert!(service.shorten_url(url).is_err());
+    /// ```
+    pub fn with_domain_blocklist(mut self, domains: Vec<String>) -> Self {
+        self.domain_blocklist = normalize_domains(domains);
+        self
+    }
+
+    /// Only shorten URLs on the given domains
+    ///
+    /// A domain also allows its subdomains, and matching is case-insensitive,
+    /// as for `with_domain_blocklist`. URLs whose host is an IP address have
+    /// no domain and are rejected. The blocklist still applies to allowed
+    /// domains. An empty list, the default, allows every domain.
+    pub fn with_domain_allowlist(mut self, domains: Vec<String>) -> Self {
+        self.domain_allowlist = normalize_domains(domains);
+        self
+    }
+
+    /// Ask the generator for codes of `length` characters
+    ///
+    /// Generated codes come from `IdGenerator::generate_short_code_with_length`
+    /// instead of the generator's own length, so the length stays the same
+    /// when the generator is swapped. Generators that don't support it make
+    /// shortening fail with `UrlShortenerError::Unsupported`. `None` restores
+    /// the generator's own length.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::InvalidShortCode` if `length` is outside
+    /// `ShortCode::MIN_LENGTH..=ShortCode::MAX_LENGTH`
+    pub fn with_preferred_code_length(mut self, length: Option<usize>) -> Result<Self> {
+        if let Some(length) = length {
+            check_code_length(length)?;
         }
+
+        self.preferred_code_length = length;
+        Ok(self)
+    }
+
+    /// Set how many codes are tried at each length before giving up on it
+    ///
+    /// After this many collisions, the generator is asked for codes one
+    /// character longer, or generation fails with `IdGenerationFailed` if it
+    /// can't vary the length. Raise it for crowded keyspaces, lower it to fail
+    /// fast. Defaults to `DEFAULT_MAX_GENERATION_ATTEMPTS`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::InvalidConfiguration` if `attempts` is 0
+    pub fn with_max_generation_attempts(mut self, attempts: usize) -> Result<Self> {
+        check_generation_attempts(attempts)?;
+        self.max_generation_attempts = attempts;
+        Ok(self)
+    }
+
+    /// Namespace every short code of this service under `prefix`
+    ///
+    /// Generated and custom codes are stored as `{prefix}-{code}`, so tenants
+    /// sharing a repository can't collide. Codes passed to lookups may be
+    /// given with or without the prefix; codes under another prefix are
+    /// reported as not found. Reserved codes are matched without the prefix.
+    /// The prefix counts towards `ShortCode::MAX_LENGTH`, leaving less room
+    /// for the rest of the code.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::InvalidShortCode` if the prefix is empty,
+    /// not alphanumeric, or too long to leave room for a
+    /// `ShortCode::MIN_LENGTH` code after it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::service::UrlShortenerService;
+    /// # use url_shortener::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+    /// # use url_shortener::domain::{OriginalUrl, ShortCode};
+    /// # use std::sync::Arc;
+    /// # let repository = Arc::new(InMemoryUrlRepository::new());
+    /// # let id_generator = Arc::new(RandomIdGenerator::with_length(4));
+    /// let service = UrlShortenerService::new(repository, id_generator)
+    ///     .with_prefix("acme".to_string())
+    ///     .unwrap();
+    ///
+    /// let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// let code = ShortCode::new("promo".to_string()).unwrap();
+    /// let shortened = service.shorten_url_with_code(url, code.clone()).unwrap();
+    /// assert_eq!(shortened.short_code().as_str(), "acme-promo");
+    /// assert!(service.resolve_short_code(&code).is_ok());
+    /// ```
+    pub fn with_prefix(mut self, prefix: String) -> Result<Self> {
+        if prefix.is_empty() || !prefix.chars().all(|c| c.is_alphanumeric()) {
+            return Err(UrlShortenerError::InvalidShortCode(
+                format!("Prefix '{}' must be non-empty and alphanumeric", prefix)
+            ));
+        }
+
+        if prefix.len() + 1 + ShortCode::MIN_LENGTH > ShortCode::MAX_LENGTH {
+            return Err(UrlShortenerError::InvalidShortCode(format!(
+                "Prefix '{}' leaves no room for a code within {} characters",
+                prefix,
+                ShortCode::MAX_LENGTH
+            )));
+        }
+
+        self.prefix = Some(prefix);
+        Ok(self)
+    }
+
+    /// Expire links created without an explicit expiry after `ttl`
+    ///
+    /// Applies to `shorten_url`, `shorten_batch`, `shorten_url_scheduled` and
+    /// `shorten_url_with_code`; `shorten_url_with_expiry` keeps its own ttl.
+    /// The expiry counts from the creation time given by the service clock.
+    /// `None`, the default, creates links that never expire.
+    pub fn with_default_expiry(mut self, ttl: Option<Duration>) -> Self {
+        self.default_expiry = ttl;
+        self
+    }
+
+    /// Configure how request ids are remembered by `resolve_idempotent`
+    ///
+    /// A request id is forgotten once it is older than `ttl`, or when more
+    /// than `capacity` ids are held, oldest first. Defaults to
+    /// `DEFAULT_IDEMPOTENCY_TTL` and `DEFAULT_IDEMPOTENCY_CAPACITY`.
+    pub fn with_idempotency_window(mut self, ttl: Duration, capacity: usize) -> Self {
+        self.recent_requests = RecentRequests::new(ttl, capacity);
+        self
+    }
+
+    /// Cap the length of URLs produced by `resolve_short_code_templated`
+    ///
+    /// Protects downstream systems from oversized URLs after placeholders are
+    /// filled in. Defaults to `DEFAULT_MAX_EXPANDED_URL_LENGTH`.
+    pub fn with_max_expanded_url_length(mut self, max: usize) -> Self {
+        self.max_expanded_url_length = max;
+        self
+    }
+
+    /// Return canonical URLs from `resolve_short_code`
+    ///
+    /// When enabled, resolved URLs have default ports stripped and hosts
+    /// lowercased via `OriginalUrl::canonical`. This only affects URLs with
+    /// schemes beyond http and https, which are stored canonical already. The
+    /// stored value is unchanged. Disabled by default.
+    pub fn with_canonical_output(mut self, enabled: bool) -> Self {
+        self.canonical_output = enabled;
+        self
+    }
+
+    /// Set the destination for requests that carry no short code
+    ///
+    /// This is configuration only; nothing is stored in the repository.
+    pub fn with_default_destination(mut self, url: OriginalUrl) -> Self {
+        self.default_destination = Some(url);
+        self
+    }
+
+    /// Get the destination for requests that carry no short code
+    ///
+    /// Returns `None` if no default destination is configured.
+    pub fn resolve_default(&self) -> Option<OriginalUrl> {
+        self.default_destination.clone()
+    }
+
+    /// Set the kind of redirect `build_redirect_full` answers with
+    ///
+    /// Defaults to `RedirectKind::Temporary`, so every visit reaches the
+    /// shortener and is counted.
+    pub fn with_redirect_kind(mut self, kind: RedirectKind) -> Self {
+        self.redirect_kind = kind;
+        self
+    }
+
+    /// Set the `Cache-Control` max-age of the redirects from
+    /// `build_redirect_full`
+    ///
+    /// Links that expire sooner get their remaining lifetime instead.
+    /// Defaults to `DEFAULT_REDIRECT_MAX_AGE`.
+    pub fn with_redirect_max_age(mut self, max_age: Duration) -> Self {
+        self.redirect_max_age = max_age;
+        self
+    }
+
+    /// Use a custom clock for time-dependent operations
+    ///
+    /// Defaults to `SystemClock`.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Report shortens and resolves to a metrics backend
+    ///
+    /// Defaults to `NoopMetrics`. See `Metrics` for the reported events.
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Configure how `resolve_short_code_templated` treats unknown placeholders
+    ///
+    /// Defaults to `UnknownPlaceholderPolicy::Keep`.
+    pub fn with_unknown_placeholder_policy(mut self, policy: UnknownPlaceholderPolicy) -> Self {
+        self.unknown_placeholders = policy;
+        self
     }
 
     /// Shorten a URL with an auto-generated short code
@@ -65,6 +462,7 @@ where
     ///
     /// Returns an error if:
     /// - The URL is invalid
+    /// - The URL's domain is not allowed
     /// - A unique short code cannot be generated
     /// - The repository operation fails
     ///
@@ -82,28 +480,66 @@ where
     /// let shortened = service.shorten_url(url).unwrap();
     /// println!("Short code: {}", shortened.short_code());
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(short_code, outcome))
+    )]
     pub fn shorten_url(&self, original_url: OriginalUrl) -> Result<ShortenedUrl> {
+        let result = self.shorten_generated(original_url);
+        #[cfg(feature = "tracing")]
+        trace::record_shortened(&result);
+        result
+    }
+
+    /// Shorten a URL after checking that it responds
+    ///
+    /// Sends a `HEAD` request to the URL and only shortens it if the answer
+    /// is a 2xx or 3xx status, catching typos before a link is handed out.
+    /// Blocks the calling thread for up to `REACHABILITY_TIMEOUT`, and must
+    /// not be called from within an async runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::UrlUnreachable` if the request fails or
+    /// the server answers with a 4xx or 5xx status, and otherwise the same
+    /// errors as `shorten_url`
+    #[cfg(feature = "validation")]
+    pub fn shorten_url_checked(&self, original_url: OriginalUrl) -> Result<ShortenedUrl> {
+        super::reachability::check_reachable(&original_url, Self::REACHABILITY_TIMEOUT)?;
+        self.shorten_url(original_url)
+    }
+
+    /// Shorten a URL with a generated short code, without tracing
+    fn shorten_generated(&self, original_url: OriginalUrl) -> Result<ShortenedUrl> {
+        self.ensure_writable()?;
+        self.ensure_domain_allowed(&original_url)?;
+
         // Try to generate a unique short code
-        let short_code = self.generate_unique_short_code()?;
+        let short_code = self.generate_unique_short_code(&original_url)?;
 
         // Create the domain entity
         let id = self.id_generator.generate_id();
-        let shortened_url = ShortenedUrl::new(id, short_code, original_url);
+        let shortened_url = self.new_entry(id, short_code, original_url);
 
         // Persist it
         self.repository.save(shortened_url.clone())?;
+        self.record_created(self.clock.now());
 
         Ok(shortened_url)
     }
 
-    /// Shorten a URL with a custom short code
+    /// Shorten a URL, reusing an existing entry for the same original URL
+    ///
+    /// If the repository already holds an entry pointing at exactly
+    /// `original_url`, that entry is returned unchanged. Otherwise this
+    /// behaves like `shorten_url`. Only entries in this service's prefix that
+    /// still resolve are reused; disabled, expired, not yet active or used up
+    /// entries get a new code next to them.
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - The URL is invalid
-    /// - The short code is invalid
-    /// - The short code is already in use
+    /// - A unique short code cannot be generated
     /// - The repository operation fails
     ///
     /// # Examples
@@ -111,47 +547,58 @@ where
     /// ```
     /// # use url_shortener::service::UrlShortenerService;
     /// # use url_shortener::adapters::{InMemoryUrlRepository, RandomIdGenerator};
-    /// # use url_shortener::domain::{OriginalUrl, ShortCode};
+    /// # use url_shortener::domain::OriginalUrl;
     /// # use std::sync::Arc;
     /// # let repository = Arc::new(InMemoryUrlRepository::new());
     /// # let id_generator = Arc::new(RandomIdGenerator::new());
     /// # let service = UrlShortenerService::new(repository, id_generator);
     /// let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
-    /// let code = ShortCode::new("custom".to_string()).unwrap();
-    /// let shortened = service.shorten_url_with_code(url, code).unwrap();
-    /// assert_eq!(shortened.short_code().as_str(), "custom");
+    /// let first = service.shorten_url_idempotent(url.clone()).unwrap();
+    /// let second = service.shorten_url_idempotent(url).unwrap();
+    /// assert_eq!(first.short_code(), second.short_code());
     /// ```
-    pub fn shorten_url_with_code(
-        &self,
-        original_url: OriginalUrl,
-        short_code: ShortCode,
-    ) -> Result<ShortenedUrl> {
-        // Check if code already exists
-        if self.repository.exists(&short_code)? {
-            return Err(UrlShortenerError::ShortCodeAlreadyExists(
-                short_code.as_str().to_string()
-            ));
+    pub fn shorten_url_idempotent(&self, original_url: OriginalUrl) -> Result<ShortenedUrl> {
+        self.ensure_writable()?;
+        self.ensure_domain_allowed(&original_url)?;
+
+        if let Some(existing) = self.find_reusable(&original_url)? {
+            return Ok(existing);
         }
 
-        // Create the domain entity
-        let id = self.id_generator.generate_id();
-        let shortened_url = ShortenedUrl::new(id, short_code, original_url);
+        self.shorten_url(original_url)
+    }
 
-        // Persist it
-        self.repository.save(shortened_url.clone())?;
+    /// Find an entry for `original_url` that `shorten_url_idempotent` can
+    /// hand out
+    fn find_reusable(&self, original_url: &OriginalUrl) -> Result<Option<ShortenedUrl>> {
+        let now = self.clock.now();
+        let reusable = |entry: &ShortenedUrl| {
+            self.in_namespace(entry.short_code()) && ensure_resolvable(entry, now).is_ok()
+        };
 
-        Ok(shortened_url)
+        match self.repository.find_by_original_url(original_url)? {
+            None => return Ok(None),
+            Some(entry) if reusable(&entry) => return Ok(Some(entry)),
+            Some(_) => {}
+        }
+
+        // The repository picked an entry of another tenant or one that no
+        // longer resolves, so look through every entry for the URL
+        let candidates = match original_url.domain() {
+            Some(domain) => self.repository.find_by_domain(domain)?,
+            None => self.repository.list_all()?,
+        };
+        Ok(candidates
+            .into_iter()
+            .find(|entry| entry.original_url() == original_url && reusable(entry)))
     }
 
-    /// Resolve a short code to its original URL
-    ///
-    /// This operation also records the access in the access counter.
-    ///
-    /// # Errors
+    /// Shorten several URLs, each with an auto-generated short code
     ///
-    /// Returns an error if:
-    /// - The short code doesn't exist
-    /// - The repository operation fails
+    /// Each URL is handled independently. The returned vector has one result
+    /// per input, in the same order, so a failure doesn't abort the batch.
+    /// Entries are persisted through `UrlRepository::save_batch`, which lets
+    /// the repository take its write lock once for the whole batch.
     ///
     /// # Examples
     ///
@@ -163,202 +610,4300 @@ where
     /// # let repository = Arc::new(InMemoryUrlRepository::new());
     /// # let id_generator = Arc::new(RandomIdGenerator::new());
     /// # let service = UrlShortenerService::new(repository, id_generator);
-    /// # let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
-    /// # let shortened = service.shorten_url(url).unwrap();
-    /// let original = service.resolve_short_code(shortened.short_code()).unwrap();
-    /// assert_eq!(original.as_str(), "https://example.com/");
+    /// let urls = vec![
+    ///     OriginalUrl::new("https://example.com/a".to_string()).unwrap(),
+    ///     OriginalUrl::new("https://example.com/b".to_string()).unwrap(),
+    /// ];
+    /// let results = service.shorten_batch(urls);
+    /// assert!(results.iter().all(|r| r.is_ok()));
     /// ```
-    pub fn resolve_short_code(&self, short_code: &ShortCode) -> Result<OriginalUrl> {
-        // Find the shortened URL
-        let mut shortened_url = self.repository.find_by_short_code(short_code)?;
+    pub fn shorten_batch(&self, urls: Vec<OriginalUrl>) -> Vec<Result<ShortenedUrl>> {
+        if self.read_only {
+            return urls.iter().map(|_| Err(UrlShortenerError::ReadOnly)).collect();
+        }
 
-        // Record the access
-        shortened_url.record_access();
+        let mut results: Vec<Result<ShortenedUrl>> = urls.into_iter()
+            .map(|original_url| {
+                self.ensure_domain_allowed(&original_url)?;
+                let short_code = self.generate_unique_short_code(&original_url)?;
+                let id = self.id_generator.generate_id();
+                Ok(self.new_entry(id, short_code, original_url))
+            })
+            .collect();
 
-        // Update in repository
-        self.repository.update(shortened_url.clone())?;
+        let pending: Vec<ShortenedUrl> = results.iter()
+            .filter_map(|result| result.as_ref().ok().cloned())
+            .collect();
+        let mut saved = self.repository.save_batch(pending).into_iter();
 
-        Ok(shortened_url.original_url().clone())
+        for result in results.iter_mut().filter(|result| result.is_ok()) {
+            match saved.next() {
+                Some(Ok(())) => self.record_created(self.clock.now()),
+                Some(Err(e)) => *result = Err(e),
+                None => *result = Err(UrlShortenerError::repository(
+                    "Repository returned fewer results than entries saved"
+                )),
+            }
+        }
+
+        results
     }
 
-    /// Get statistics for a short code
+    /// Shorten a URL that only starts resolving at `active_from`
     ///
-    /// Returns the ShortenedUrl entity which includes access count and metadata.
+    /// Until then `resolve_short_code` returns `NotYetActive` without
+    /// recording an access.
     ///
     /// # Errors
     ///
-    /// Returns an error if the short code doesn't exist
-    pub fn get_statistics(&self, short_code: &ShortCode) -> Result<ShortenedUrl> {
-        self.repository.find_by_short_code(short_code)
+    /// Returns an error if:
+    /// - A unique short code cannot be generated
+    /// - The repository operation fails
+    pub fn shorten_url_scheduled(
+        &self,
+        original_url: OriginalUrl,
+        active_from: SystemTime,
+    ) -> Result<ShortenedUrl> {
+        self.ensure_writable()?;
+        self.ensure_domain_allowed(&original_url)?;
+
+        let short_code = self.generate_unique_short_code(&original_url)?;
+
+        let id = self.id_generator.generate_id();
+        let shortened_url = self.new_entry(id, short_code, original_url)
+            .with_active_from(active_from);
+
+        self.repository.save(shortened_url.clone())?;
+        self.record_created(self.clock.now());
+
+        Ok(shortened_url)
     }
 
-    /// Delete a shortened URL
+    /// Shorten a URL that stops resolving `ttl` after it is created
+    ///
+    /// Once expired, `resolve_short_code` returns `ShortCodeExpired` without
+    /// recording an access. Expired entries are kept, so they still show up
+    /// in `get_statistics`. A `ttl` too large to represent, such as
+    /// `Duration::MAX`, never expires.
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - The short code doesn't exist
+    /// - A unique short code cannot be generated
     /// - The repository operation fails
-    pub fn delete_short_code(&self, short_code: &ShortCode) -> Result<()> {
-        self.repository.delete(short_code)
-    }
-
-    /// List all shortened URLs
-    ///
-    /// Useful for admin interfaces or testing
-    pub fn list_all(&self) -> Result<Vec<ShortenedUrl>> {
-        self.repository.list_all()
-    }
+    pub fn shorten_url_with_expiry(
+        &self,
+        original_url: OriginalUrl,
+        ttl: Duration,
+    ) -> Result<ShortenedUrl> {
+        self.ensure_writable()?;
+        self.ensure_domain_allowed(&original_url)?;
 
-    /// Generate a unique short code
-    ///
-    /// Attempts multiple times to avoid collisions
-    fn generate_unique_short_code(&self) -> Result<ShortCode> {
-        for attempt in 0..Self::MAX_GENERATION_ATTEMPTS {
-            let code = self.id_generator.generate_short_code()?;
+        let short_code = self.generate_unique_short_code(&original_url)?;
 
-            if !self.repository.exists(&code)? {
-                return Ok(code);
-            }
+        let id = self.id_generator.generate_id();
+        let now = self.clock.now();
+        let shortened_url = ShortenedUrl::with_created_at(id, short_code, original_url, now)
+            .with_expiry(ttl);
 
-            if attempt == Self::MAX_GENERATION_ATTEMPTS - 1 {
-                return Err(UrlShortenerError::IdGenerationFailed(
-                    Self::MAX_GENERATION_ATTEMPTS
-                ));
-            }
-        }
+        self.repository.save(shortened_url.clone())?;
+        self.record_created(now);
 
-        unreachable!()
+        Ok(shortened_url)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+    /// Shorten a URL that can be resolved at most `max_accesses` times
+    ///
+    /// Afterwards `resolve_short_code` returns `AccessLimitReached`. Pass 1
+    /// for a one-time link. The limit holds under concurrent resolves as
+    /// long as the repository increments access counts atomically.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A unique short code cannot be generated
+    /// - The repository operation fails
+    pub fn shorten_url_limited(
+        &self,
+        original_url: OriginalUrl,
+        max_accesses: u64,
+    ) -> Result<ShortenedUrl> {
+        self.ensure_writable()?;
+        self.ensure_domain_allowed(&original_url)?;
 
-    fn create_service() -> UrlShortenerService<InMemoryUrlRepository, RandomIdGenerator> {
-        let repository = Arc::new(InMemoryUrlRepository::new());
-        let id_generator = Arc::new(RandomIdGenerator::new());
-        UrlShortenerService::new(repository, id_generator)
-    }
+        let short_code = self.generate_unique_short_code(&original_url)?;
 
-    #[test]
-    fn test_shorten_url() {
-        let service = create_service();
-        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let id = self.id_generator.generate_id();
+        let shortened_url = self.new_entry(id, short_code, original_url)
+            .with_max_accesses(max_accesses);
 
-        let result = service.shorten_url(url);
-        assert!(result.is_ok());
+        self.repository.save(shortened_url.clone())?;
+        self.record_created(self.clock.now());
 
-        let shortened = result.unwrap();
-        assert_eq!(shortened.original_url().as_str(), "https://example.com/");
+        Ok(shortened_url)
     }
 
-    #[test]
+    /// Shorten a URL with a custom short code
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The URL is invalid
+    /// - The URL's domain is not allowed
+    /// - The short code is invalid, or too long for a check character when
+    ///   `with_checksums` is enabled
+    /// - The short code is reserved
+    /// - The short code is already in use
+    /// - The repository operation fails
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::service::UrlShortenerService;
+    /// # use url_shortener::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+    /// # use url_shortener::domain::{OriginalUrl, ShortCode};
+    /// # use std::sync::Arc;
+    /// # let repository = Arc::new(InMemoryUrlRepository::new());
+    /// # let id_generator = Arc::new(RandomIdGenerator::new());
+    /// # let service = UrlShortenerService::new(repository, id_generator);
+    /// let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// let code = ShortCode::new("custom".to_string()).unwrap();
+    /// let shortened = service.shorten_url_with_code(url, code).unwrap();
+    /// assert_eq!(shortened.short_code().as_str(), "custom");
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(short_code = %short_code, outcome))
+    )]
+    pub fn shorten_url_with_code(
+        &self,
+        original_url: OriginalUrl,
+        short_code: ShortCode,
+    ) -> Result<ShortenedUrl> {
+        let result = self.shorten_custom(original_url, short_code);
+        #[cfg(feature = "tracing")]
+        trace::record_outcome(&result);
+        result
+    }
+
+    /// Shorten a URL with a custom short code, without tracing
+    fn shorten_custom(
+        &self,
+        original_url: OriginalUrl,
+        short_code: ShortCode,
+    ) -> Result<ShortenedUrl> {
+        self.ensure_writable()?;
+        self.ensure_domain_allowed(&original_url)?;
+
+        let short_code = self.custom_code(&short_code)?;
+
+        // Check if code already exists or is held by a reservation
+        if self.repository.exists(&short_code)?
+            || self.repository.is_reserved(&short_code, self.clock.now())?
+        {
+            return Err(UrlShortenerError::ShortCodeAlreadyExists(
+                short_code.as_str().to_string()
+            ));
+        }
+
+        self.save_custom(original_url, short_code)
+    }
+
+    /// Reserve a custom short code for `ttl`
+    ///
+    /// Until the reservation lapses, `shorten_url_with_code` and generated
+    /// codes treat the code as taken, while `commit_reservation` can still
+    /// claim it. The code is normalized and prefixed like in
+    /// `shorten_url_with_code`, and the expiry counts from the service clock.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The service is read-only
+    /// - The code is outside this service's prefix, or reserved by name
+    /// - The code is stored or already reserved (`ShortCodeAlreadyExists`)
+    /// - `ttl` reaches past the latest representable time (`InvalidConfiguration`)
+    /// - The repository doesn't support reservations (`Unsupported`)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::service::UrlShortenerService;
+    /// # use url_shortener::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+    /// # use url_shortener::domain::{OriginalUrl, ShortCode};
+    /// # use std::sync::Arc;
+    /// # use std::time::Duration;
+    /// # let repository = Arc::new(InMemoryUrlRepository::new());
+    /// # let id_generator = Arc::new(RandomIdGenerator::new());
+    /// # let service = UrlShortenerService::new(repository, id_generator);
+    /// let code = ShortCode::new("launch".to_string()).unwrap();
+    /// service.reserve_code(code.clone(), Duration::from_secs(300)).unwrap();
+    ///
+    /// let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// assert!(service.shorten_url_with_code(url.clone(), code.clone()).is_err());
+    ///
+    /// let shortened = service.commit_reservation(code, url).unwrap();
+    /// assert_eq!(shortened.short_code().as_str(), "launch");
+    /// ```
+    pub fn reserve_code(&self, short_code: ShortCode, ttl: Duration) -> Result<()> {
+        self.ensure_writable()?;
+
+        let short_code = self.custom_code(&short_code)?;
+        if self.repository.exists(&short_code)? {
+            return Err(UrlShortenerError::ShortCodeAlreadyExists(short_code.into_inner()));
+        }
+
+        let now = self.clock.now();
+        let until = now.checked_add(ttl).ok_or_else(|| UrlShortenerError::InvalidConfiguration(
+            format!("reservation TTL {:?} is too long", ttl)
+        ))?;
+        self.repository.reserve(&short_code, now, until)
+    }
+
+    /// Shorten a URL with a code reserved by `reserve_code`
+    ///
+    /// Saves the entry and drops the reservation. Reservations aren't tied to
+    /// a caller, so whoever knows the code can commit it. If the reservation
+    /// has lapsed, the code is still committed as long as it isn't stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the service is read-only, the URL's domain isn't
+    /// allowed, the code is invalid for this service or already stored, or
+    /// the repository operation fails
+    pub fn commit_reservation(
+        &self,
+        short_code: ShortCode,
+        original_url: OriginalUrl,
+    ) -> Result<ShortenedUrl> {
+        self.ensure_writable()?;
+        self.ensure_domain_allowed(&original_url)?;
+
+        let short_code = self.custom_code(&short_code)?;
+        if self.repository.exists(&short_code)? {
+            return Err(UrlShortenerError::ShortCodeAlreadyExists(short_code.into_inner()));
+        }
+
+        let shortened_url = self.save_custom(original_url, short_code)?;
+        self.repository.release_reservation(shortened_url.short_code())?;
+
+        Ok(shortened_url)
+    }
+
+    /// Scope a custom short code, reject reserved names and append the check
+    /// character
+    fn custom_code(&self, short_code: &ShortCode) -> Result<ShortCode> {
+        let short_code = self.scoped_code(short_code)?;
+
+        if self.is_reserved(&short_code) {
+            return Err(UrlShortenerError::ReservedShortCode(short_code.into_inner()));
+        }
+        self.with_check_character(short_code)
+    }
+
+    /// Create and persist an entry under a custom short code already checked
+    /// to be free
+    fn save_custom(
+        &self,
+        original_url: OriginalUrl,
+        short_code: ShortCode,
+    ) -> Result<ShortenedUrl> {
+        // Create the domain entity
+        let id = self.id_generator.generate_id();
+        let shortened_url = self.new_entry(id, short_code, original_url);
+
+        // Persist it
+        self.repository.save(shortened_url.clone())?;
+        self.record_created(self.clock.now());
+
+        Ok(shortened_url)
+    }
+
+    /// Suggest free variants of a custom short code
+    ///
+    /// Candidates append an increasing number to `desired` (`promo1`,
+    /// `promo2`, ...), shortening it where needed to stay within
+    /// `ShortCode::MAX_LENGTH`. Reserved and taken candidates are skipped.
+    /// At most `MAX_SUGGESTION_CANDIDATES` candidates are checked, so fewer
+    /// than `count` suggestions may come back. `desired` itself is never
+    /// suggested.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `desired` is outside this service's prefix, or the
+    /// repository operation fails
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::service::UrlShortenerService;
+    /// # use url_shortener::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+    /// # use url_shortener::domain::{OriginalUrl, ShortCode};
+    /// # use std::sync::Arc;
+    /// # let repository = Arc::new(InMemoryUrlRepository::new());
+    /// # let id_generator = Arc::new(RandomIdGenerator::new());
+    /// # let service = UrlShortenerService::new(repository, id_generator);
+    /// let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// let code = ShortCode::new("promo".to_string()).unwrap();
+    /// service.shorten_url_with_code(url, code.clone()).unwrap();
+    ///
+    /// let suggestions = service.suggest_available_codes(&code, 2).unwrap();
+    /// let suggestions: Vec<_> = suggestions.iter().map(|c| c.as_str()).collect();
+    /// assert_eq!(suggestions, ["promo1", "promo2"]);
+    /// ```
+    pub fn suggest_available_codes(
+        &self,
+        desired: &ShortCode,
+        count: usize,
+    ) -> Result<Vec<ShortCode>> {
+        let desired = self.scoped_code(desired)?;
+
+        // Keep a namespace prefix intact and only shorten the part after it
+        let (head, stem) = match (&self.prefix, desired.as_str().split_once(ShortCode::SEPARATOR)) {
+            (Some(_), Some((namespace, local))) => {
+                (format!("{}{}", namespace, ShortCode::SEPARATOR), local)
+            }
+            _ => (String::new(), desired.as_str()),
+        };
+
+        let mut suggestions = Vec::new();
+
+        for n in 1..=Self::MAX_SUGGESTION_CANDIDATES {
+            if suggestions.len() >= count {
+                break;
+            }
+
+            let suffix = n.to_string();
+            let room = ShortCode::MAX_LENGTH.saturating_sub(head.len() + suffix.len());
+            let end = stem
+                .char_indices()
+                .map(|(i, c)| i + c.len_utf8())
+                .take_while(|&end| end <= room)
+                .last()
+                .unwrap_or(0);
+
+            let Ok(candidate) = ShortCode::new(format!("{}{}{}", head, &stem[..end], suffix))
+            else {
+                continue;
+            };
+            let candidate = self.normalize_code(&candidate);
+
+            if candidate != desired
+                && !suggestions.contains(&candidate)
+                && self.is_available(&candidate)?
+            {
+                suggestions.push(candidate);
+            }
+        }
+
+        Ok(suggestions)
+    }
+
+    /// Resolve a short code to its original URL
+    ///
+    /// This operation also records the access in the access counter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The short code doesn't exist
+    /// - The short code has been disabled (`ShortCodeDisabled`)
+    /// - The short code is scheduled to activate later (`NotYetActive`)
+    /// - The short code is past its expiry time (`ShortCodeExpired`)
+    /// - The short code has been resolved `max_accesses` times (`AccessLimitReached`)
+    /// - The repository operation fails
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::service::UrlShortenerService;
+    /// # use url_shortener::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+    /// # use url_shortener::domain::OriginalUrl;
+    /// # use std::sync::Arc;
+    /// # let repository = Arc::new(InMemoryUrlRepository::new());
+    /// # let id_generator = Arc::new(RandomIdGenerator::new());
+    /// # let service = UrlShortenerService::new(repository, id_generator);
+    /// # let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// # let shortened = service.shorten_url(url).unwrap();
+    /// let original = service.resolve_short_code(shortened.short_code()).unwrap();
+    /// assert_eq!(original.as_str(), "https://example.com/");
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(short_code = %short_code, outcome))
+    )]
+    pub fn resolve_short_code(&self, short_code: &ShortCode) -> Result<OriginalUrl> {
+        let result = self
+            .resolve_with(short_code, self.clock.now(), |url| Ok(url.clone()))
+            .map(|(_, resolved)| resolved);
+        #[cfg(feature = "tracing")]
+        trace::record_outcome(&result);
+        result
+    }
+
+    /// Resolve a short code, counting the access towards `referrer`
+    ///
+    /// Behaves like `resolve_short_code` and then adds the access to the
+    /// entry's per-referrer counts; `None` counts as a direct access. See
+    /// `ShortenedUrl::record_referrer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as `resolve_short_code`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::service::UrlShortenerService;
+    /// # use url_shortener::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+    /// # use url_shortener::domain::OriginalUrl;
+    /// # use std::sync::Arc;
+    /// # let repository = Arc::new(InMemoryUrlRepository::new());
+    /// # let id_generator = Arc::new(RandomIdGenerator::new());
+    /// # let service = UrlShortenerService::new(repository, id_generator);
+    /// # let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// # let shortened = service.shorten_url(url).unwrap();
+    /// let code = shortened.short_code();
+    /// service.resolve_short_code_with_referrer(code, Some("twitter".to_string())).unwrap();
+    ///
+    /// let stats = service.get_statistics(code).unwrap();
+    /// assert_eq!(stats.referrer_counts().get("twitter"), Some(&1));
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(short_code = %short_code, outcome))
+    )]
+    pub fn resolve_short_code_with_referrer(
+        &self,
+        short_code: &ShortCode,
+        referrer: Option<String>,
+    ) -> Result<OriginalUrl> {
+        let result = self
+            .resolve_with(short_code, self.clock.now(), |url| Ok(url.clone()))
+            .and_then(|(entry, resolved)| {
+                self.repository.record_referrer(entry.short_code(), referrer)?;
+                Ok(resolved)
+            });
+        #[cfg(feature = "tracing")]
+        trace::record_outcome(&result);
+        result
+    }
+
+    /// Resolve a short code and expand placeholders in its destination
+    ///
+    /// Behaves like `resolve_short_code` and replaces `{code}` with the short
+    /// code and `{ts}` with the current Unix timestamp. The stored URL is left
+    /// untouched. Unknown placeholders are handled according to the configured
+    /// `UnknownPlaceholderPolicy`. The access is only recorded if expansion
+    /// succeeds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The short code doesn't exist
+    /// - The expanded URL is invalid, longer than the configured maximum, or
+    ///   contains an unknown placeholder under `UnknownPlaceholderPolicy::Reject`
+    /// - The repository operation fails
+    pub fn resolve_short_code_templated(&self, short_code: &ShortCode) -> Result<OriginalUrl> {
+        let now = self.clock.now();
+        let (_, resolved) = self.resolve_with(short_code, now, |url| {
+            let expanded = template::expand(url, short_code, now, self.unknown_placeholders)?;
+
+            if expanded.as_str().len() > self.max_expanded_url_length {
+                return Err(UrlShortenerError::InvalidUrl(format!(
+                    "Expanded URL exceeds the maximum length of {} characters",
+                    self.max_expanded_url_length
+                )));
+            }
+
+            Ok(expanded)
+        })?;
+        Ok(resolved)
+    }
+
+    /// Resolve a short code, counting each request id at most once
+    ///
+    /// Retries of the same request (for example a double-fired webhook) carry
+    /// the same `request_id`; within the idempotency window only the first
+    /// call records an access, while every call returns the destination.
+    /// Request ids are kept in a bounded in-memory cache, see
+    /// `with_idempotency_window`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as `resolve_short_code`
+    pub fn resolve_idempotent(
+        &self,
+        short_code: &ShortCode,
+        request_id: &str,
+    ) -> Result<OriginalUrl> {
+        self.ensure_writable()?;
+
+        let key = format!("{}\0{}", self.normalize_code(short_code), request_id);
+        if !self.recent_requests.insert(&key, self.clock.now()) {
+            return self.resolve_without_tracking(short_code);
+        }
+
+        self.resolve_short_code(short_code).inspect_err(|_| {
+            // Let a retry of a failed resolve count
+            self.recent_requests.remove(&key);
+        })
+    }
+
+    /// Resolve a short code without recording an access
+    ///
+    /// A pure read: the access count and last access time are left alone and
+    /// nothing is written to the repository, so it also works on a read-only
+    /// service. Meant for health checks and other monitoring traffic that
+    /// shouldn't show up in analytics.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as `resolve_short_code`, except that
+    /// repository write failures can't occur
+    pub fn resolve_without_tracking(&self, short_code: &ShortCode) -> Result<OriginalUrl> {
+        let shortened_url = self.find_for_resolve(short_code)?;
+
+        ensure_resolvable(&shortened_url, self.clock.now())?;
+        self.metrics.on_resolve_hit();
+
+        if self.canonical_output {
+            return Ok(shortened_url.original_url().canonical());
+        }
+
+        Ok(shortened_url.original_url().clone())
+    }
+
+    /// Resolve a short code to a redirect with cache hints
+    ///
+    /// Behaves like `resolve_short_code` with `now` as the current time, and
+    /// describes the response: the status of the configured `RedirectKind`,
+    /// the destination as `location`, a `Cache-Control` max-age of the
+    /// configured default capped to the link's remaining lifetime, and the
+    /// link's expiry time for an `Expires` header.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as `resolve_short_code`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::service::UrlShortenerService;
+    /// # use url_shortener::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+    /// # use url_shortener::domain::OriginalUrl;
+    /// # use std::sync::Arc;
+    /// # use std::time::{Duration, SystemTime};
+    /// # let repository = Arc::new(InMemoryUrlRepository::new());
+    /// # let id_generator = Arc::new(RandomIdGenerator::new());
+    /// # let service = UrlShortenerService::new(repository, id_generator);
+    /// let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// let shortened = service.shorten_url_with_expiry(url, Duration::from_secs(60)).unwrap();
+    ///
+    /// let response = service.build_redirect_full(shortened.short_code(), SystemTime::now())
+    ///     .unwrap();
+    /// assert_eq!(response.status(), 302);
+    /// assert_eq!(response.location(), "https://example.com/");
+    /// assert!(response.cache_control_seconds() <= 60);
+    /// assert_eq!(response.expires_at(), shortened.expires_at());
+    /// ```
+    pub fn build_redirect_full(
+        &self,
+        short_code: &ShortCode,
+        now: SystemTime,
+    ) -> Result<RedirectResponse> {
+        let (entry, location) = self.resolve_with(short_code, now, |url| Ok(url.clone()))?;
+
+        let mut max_age = self.redirect_max_age;
+        if let Some(expires_at) = entry.expires_at() {
+            // Resolvable entries expire after `now`
+            max_age = max_age.min(expires_at.duration_since(now).unwrap_or_default());
+        }
+
+        Ok(RedirectResponse::new(
+            self.redirect_kind.status_code(),
+            location.as_str().to_string(),
+            max_age.as_secs(),
+            entry.expires_at(),
+        ))
+    }
+
+    /// Resolve a short code, rendering the destination with `render`
+    ///
+    /// The access is recorded at `now`, only after `render` succeeds.
+    /// Returns the stored entry along with the rendered URL.
+    fn resolve_with<F>(
+        &self,
+        short_code: &ShortCode,
+        now: SystemTime,
+        render: F,
+    ) -> Result<(ShortenedUrl, OriginalUrl)>
+    where
+        F: FnOnce(&OriginalUrl) -> Result<OriginalUrl>,
+    {
+        self.ensure_writable()?;
+
+        // Find the shortened URL
+        let shortened_url = self.find_for_resolve(short_code)?;
+
+        ensure_resolvable(&shortened_url, now)?;
+
+        let mut resolved = render(shortened_url.original_url())?;
+        if self.canonical_output {
+            resolved = resolved.canonical();
+        }
+
+        // Record the access in a single repository operation, so concurrent
+        // resolves don't lose increments
+        self.repository.increment_access_at(shortened_url.short_code(), now)?;
+        self.resolved_recently.record(now);
+        self.metrics.on_resolve_hit();
+
+        Ok((shortened_url, resolved))
+    }
+
+    /// Find the entry to resolve, reporting unknown codes as misses
+    fn find_for_resolve(&self, short_code: &ShortCode) -> Result<ShortenedUrl> {
+        self.lookup_code(short_code)
+            .and_then(|code| self.ensure_checksum(code))
+            .and_then(|code| self.repository.find_by_short_code(&code))
+            .inspect_err(|e| {
+                if matches!(e, UrlShortenerError::ShortCodeNotFound(_)) {
+                    self.metrics.on_resolve_miss();
+                }
+            })
+    }
+
+    /// Record a link created through this service
+    fn record_created(&self, now: SystemTime) {
+        self.created_recently.record(now);
+        self.metrics.on_shorten();
+    }
+
+    /// Count links created through this service within `window`
+    ///
+    /// Counts are kept in process memory, bucketed by minute, and reset on
+    /// restart. Windows are rounded up to whole minutes and clamped to the
+    /// last hour.
+    pub fn created_last(&self, window: Duration) -> u64 {
+        self.created_recently.count_last(window, self.clock.now())
+    }
+
+    /// Count successful resolves through this service within `window`
+    ///
+    /// Counts are kept in process memory, bucketed by minute, and reset on
+    /// restart. Windows are rounded up to whole minutes and clamped to the
+    /// last hour.
+    pub fn resolved_last(&self, window: Duration) -> u64 {
+        self.resolved_recently.count_last(window, self.clock.now())
+    }
+
+    /// Get statistics for a short code
+    ///
+    /// Returns the ShortenedUrl entity which includes access count and metadata.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the short code doesn't exist
+    pub fn get_statistics(&self, short_code: &ShortCode) -> Result<ShortenedUrl> {
+        self.repository.find_by_short_code(&self.lookup_code(short_code)?)
+    }
+
+    /// Render a QR code for the short link of `short_code`
+    ///
+    /// The link is `{base_url}/{code}`; a trailing slash on `base_url` is
+    /// ignored. The stored code is used, so with a prefix configured the
+    /// link carries the full code.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::ShortCodeNotFound` if the code doesn't
+    /// exist, or `UrlShortenerError::InvalidUrl` if the link is too long to
+    /// fit in a QR code
+    #[cfg(feature = "qr")]
+    pub fn qr_code_svg(&self, short_code: &ShortCode, base_url: &str) -> Result<String> {
+        let shortened_url = self.get_statistics(short_code)?;
+        let link =
+            crate::domain::ShortUrl::new(base_url.to_string(), shortened_url.short_code().clone());
+        super::qr::render_svg(&link.to_string())
+    }
+
+    /// Delete a shortened URL
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The short code doesn't exist
+    /// - The repository operation fails
+    pub fn delete_short_code(&self, short_code: &ShortCode) -> Result<()> {
+        self.ensure_writable()?;
+        self.repository.delete(&self.lookup_code(short_code)?)
+    }
+
+    /// Delete several shortened URLs, reporting a result per code
+    ///
+    /// Results are in the order of `codes`. A missing code reports
+    /// `ShortCodeNotFound` without stopping the others from being deleted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::service::UrlShortenerService;
+    /// # use url_shortener::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+    /// # use url_shortener::domain::{OriginalUrl, ShortCode};
+    /// # use std::sync::Arc;
+    /// # let repository = Arc::new(InMemoryUrlRepository::new());
+    /// # let id_generator = Arc::new(RandomIdGenerator::new());
+    /// # let service = UrlShortenerService::new(repository, id_generator);
+    /// let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// let code = service.shorten_url(url).unwrap().short_code().clone();
+    /// let missing = ShortCode::new("missing".to_string()).unwrap();
+    ///
+    /// let results = service.delete_many(&[code, missing]);
+    /// assert!(results[0].is_ok());
+    /// assert!(results[1].is_err());
+    /// ```
+    pub fn delete_many(&self, codes: &[ShortCode]) -> Vec<Result<()>> {
+        if self.read_only {
+            return codes.iter().map(|_| Err(UrlShortenerError::ReadOnly)).collect();
+        }
+
+        let mut results: Vec<Result<ShortCode>> =
+            codes.iter().map(|code| self.lookup_code(code)).collect();
+
+        let pending: Vec<ShortCode> = results.iter()
+            .filter_map(|result| result.as_ref().ok().cloned())
+            .collect();
+        let mut deleted = self.repository.delete_many(&pending).into_iter();
+
+        for result in results.iter_mut().filter(|result| result.is_ok()) {
+            match deleted.next() {
+                Some(Ok(())) => {}
+                Some(Err(e)) => *result = Err(e),
+                None => *result = Err(UrlShortenerError::repository(
+                    "Repository returned fewer results than codes deleted"
+                )),
+            }
+        }
+
+        results.into_iter().map(|result| result.map(|_| ())).collect()
+    }
+
+    /// Disable a short code without deleting it
+    ///
+    /// Resolving a disabled code fails with `ShortCodeDisabled` and records
+    /// no access. The entry keeps its statistics and still shows up in
+    /// `get_statistics` and `list_all`. Disabling a disabled code is a no-op.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The short code doesn't exist
+    /// - The repository operation fails
+    pub fn disable_short_code(&self, short_code: &ShortCode) -> Result<()> {
+        self.set_enabled(short_code, false)
+    }
+
+    /// Re-enable a short code disabled with `disable_short_code`
+    ///
+    /// Enabling an enabled code is a no-op.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The short code doesn't exist
+    /// - The repository operation fails
+    pub fn enable_short_code(&self, short_code: &ShortCode) -> Result<()> {
+        self.set_enabled(short_code, true)
+    }
+
+    fn set_enabled(&self, short_code: &ShortCode, enabled: bool) -> Result<()> {
+        self.ensure_writable()?;
+
+        let short_code = self.lookup_code(short_code)?;
+        let mut shortened_url = self.repository.find_by_short_code(&short_code)?;
+        if shortened_url.is_enabled() == enabled {
+            return Ok(());
+        }
+
+        shortened_url.set_enabled(enabled);
+        self.repository.update(shortened_url)
+    }
+
+    /// List all shortened URLs
+    ///
+    /// Useful for admin interfaces or testing
+    pub fn list_all(&self) -> Result<Vec<ShortenedUrl>> {
+        self.repository.list_all()
+    }
+
+    /// List shortened URLs a page at a time using an offset
+    ///
+    /// Returns up to `limit` entries after skipping `offset`, ordered by
+    /// creation time and then short code. An offset past the end yields an
+    /// empty page. Inserts between pages can shift entries across page
+    /// boundaries; use `list_after` when that matters.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    pub fn list_paginated(&self, offset: usize, limit: usize) -> Result<Vec<ShortenedUrl>> {
+        self.repository.list_paginated(offset, limit)
+    }
+
+    /// List the shortened URLs created in `[start, end)`
+    ///
+    /// The range includes `start` and excludes `end`, so consecutive ranges
+    /// such as calendar months never count an entry twice. Entries are
+    /// ordered by creation time, then short code.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    pub fn created_between(
+        &self,
+        start: SystemTime,
+        end: SystemTime,
+    ) -> Result<Vec<ShortenedUrl>> {
+        self.repository.created_between(start, end)
+    }
+
+    /// List the shortened URLs pointing at `domain`, ordered by short code
+    ///
+    /// Matches the exact host, ignoring case; subdomains are listed
+    /// separately. Useful for finding every link to a flagged domain, for
+    /// example before disabling them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    pub fn find_by_domain(&self, domain: &str) -> Result<Vec<ShortenedUrl>> {
+        self.repository.find_by_domain(domain)
+    }
+
+    /// List the shortened URLs accessed between `min` and `max` times,
+    /// inclusive, ordered by short code
+    ///
+    /// With `min` and `max` of 0 this finds links that were never used;
+    /// combine it with `created_between` to find old ones worth pruning.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::service::UrlShortenerService;
+    /// # use url_shortener::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+    /// # use url_shortener::domain::OriginalUrl;
+    /// # use std::sync::Arc;
+    /// # let repository = Arc::new(InMemoryUrlRepository::new());
+    /// # let id_generator = Arc::new(RandomIdGenerator::new());
+    /// # let service = UrlShortenerService::new(repository, id_generator);
+    /// # let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// let unused = service.shorten_url(url.clone()).unwrap();
+    /// let used = service.shorten_url(url).unwrap();
+    /// service.resolve_short_code(used.short_code()).unwrap();
+    ///
+    /// let never_clicked = service.find_by_access_count_range(0, 0).unwrap();
+    /// assert_eq!(never_clicked, [unused]);
+    /// ```
+    pub fn find_by_access_count_range(&self, min: u64, max: u64) -> Result<Vec<ShortenedUrl>> {
+        self.repository.find_by_access_count_range(min, max)
+    }
+
+    /// List the most accessed shortened URLs, up to `limit` of them
+    ///
+    /// Entries are ordered by access count, highest first. Ties go to the
+    /// older entry, then to the lower short code. Works with any repository
+    /// by scanning `list_all`; only the top `limit` entries are sorted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    pub fn most_accessed(&self, limit: usize) -> Result<Vec<ShortenedUrl>> {
+        let ranking = |a: &ShortenedUrl, b: &ShortenedUrl| {
+            b.access_count()
+                .cmp(&a.access_count())
+                .then_with(|| a.created_at().cmp(&b.created_at()))
+                .then_with(|| a.short_code().cmp(b.short_code()))
+        };
+
+        let mut urls = self.repository.list_all()?;
+        if limit < urls.len() {
+            urls.select_nth_unstable_by(limit, ranking);
+            urls.truncate(limit);
+        }
+        urls.sort_by(ranking);
+
+        Ok(urls)
+    }
+
+    /// List the trending shortened URLs with their scores, up to `limit`
+    ///
+    /// Scores come from `ShortenedUrl::trending_score_at` at the current
+    /// time, so recent accesses weigh more than old ones, halving in weight
+    /// every `half_life`. Unlike `most_accessed`, a link that was popular
+    /// long ago ranks below one that is busy now. Entries are ordered by
+    /// score, highest first, with ties going to the lower short code.
+    /// Entries without accesses in their per-day histogram are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    pub fn trending(
+        &self,
+        half_life: Duration,
+        limit: usize,
+    ) -> Result<Vec<(ShortenedUrl, f64)>> {
+        let now = self.clock.now();
+
+        let mut scored: Vec<(ShortenedUrl, f64)> = self.repository.list_all()?
+            .into_iter()
+            .map(|url| {
+                let score = url.trending_score_at(now, half_life);
+                (url, score)
+            })
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+        scored.sort_by(|(a, a_score), (b, b_score)| {
+            b_score.total_cmp(a_score).then_with(|| a.short_code().cmp(b.short_code()))
+        });
+        scored.truncate(limit);
+
+        Ok(scored)
+    }
+
+    /// Delete every shortened URL
+    ///
+    /// Mainly useful for resetting shared state between tests.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    pub fn clear_all(&self) -> Result<()> {
+        self.ensure_writable()?;
+        self.repository.clear()
+    }
+
+    /// Delete every expired shortened URL, returning how many were deleted
+    ///
+    /// Expired entries otherwise stay in the repository, still showing up in
+    /// `get_statistics`. Meant to be called from a periodic task. Covers the
+    /// whole repository, including entries under other prefixes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    pub fn prune_expired(&self) -> Result<usize> {
+        self.ensure_writable()?;
+        self.repository.delete_expired(self.clock.now())
+    }
+
+    /// Count the stored shortened URLs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    pub fn count(&self) -> Result<usize> {
+        self.repository.count()
+    }
+
+    /// List shortened URLs a page at a time using a cursor
+    ///
+    /// Returns up to `limit` entries whose short code sorts after `cursor`
+    /// (or from the start when `cursor` is `None`), ordered by short code,
+    /// together with the cursor for the next page. The next cursor is `None`
+    /// once the last page has been returned.
+    ///
+    /// Unlike offset pagination, inserts between pages never cause entries to
+    /// be skipped or returned twice.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::InvalidConfiguration` if `limit` is 0,
+    /// since an empty page can't advance the cursor, or an error if the
+    /// repository operation fails
+    pub fn list_after(
+        &self,
+        cursor: Option<ShortCode>,
+        limit: usize,
+    ) -> Result<(Vec<ShortenedUrl>, Option<ShortCode>)> {
+        if limit == 0 {
+            return Err(UrlShortenerError::InvalidConfiguration(
+                "page limit must be at least 1".to_string()
+            ));
+        }
+
+        let mut remaining: Vec<ShortenedUrl> = self.repository.list_all()?
+            .into_iter()
+            .filter(|url| cursor.as_ref().is_none_or(|c| url.short_code() > c))
+            .collect();
+        remaining.sort_by(|a, b| a.short_code().cmp(b.short_code()));
+
+        let has_more = remaining.len() > limit;
+        remaining.truncate(limit);
+
+        let next = if has_more {
+            remaining.last().map(|url| url.short_code().clone())
+        } else {
+            None
+        };
+
+        Ok((remaining, next))
+    }
+
+    /// Select short codes and destinations matching a filter
+    ///
+    /// Returns the entries satisfying every predicate in `filter`, ordered by
+    /// short code and truncated to `limit` if given. No accesses are recorded.
+    /// See `SelectFilter` for the supported predicates.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    pub fn select(
+        &self,
+        filter: SelectFilter,
+        limit: Option<usize>,
+    ) -> Result<Vec<(ShortCode, OriginalUrl)>> {
+        let mut selected: Vec<(ShortCode, OriginalUrl)> = self.repository.list_all()?
+            .into_iter()
+            .filter(|url| filter.matches(url))
+            .map(|url| (url.short_code().clone(), url.original_url().clone()))
+            .collect();
+        selected.sort_by(|a, b| a.0.cmp(&b.0));
+
+        if let Some(limit) = limit {
+            selected.truncate(limit);
+        }
+
+        Ok(selected)
+    }
+
+    /// Collect every short code with its destination for cache warming
+    ///
+    /// This is a pure read: unlike `resolve_short_code`, no access is recorded,
+    /// so pre-populating an edge cache doesn't inflate click counts.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    pub fn warm_cache(&self) -> Result<Vec<(ShortCode, OriginalUrl)>> {
+        Ok(self.repository.list_all()?
+            .into_iter()
+            .map(|url| (url.short_code().clone(), url.original_url().clone()))
+            .collect())
+    }
+
+    /// Collect the distinct hosts targeted by all shortened URLs
+    ///
+    /// Useful for security review and for bootstrapping a host allowlist.
+    /// URLs without a host are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    pub fn distinct_hosts(&self) -> Result<BTreeSet<String>> {
+        Ok(self.repository.list_all()?
+            .iter()
+            .filter_map(|url| url.original_url().host_str().map(str::to_string))
+            .collect())
+    }
+
+    /// Export all shortened URLs as CSV
+    ///
+    /// The first line is a header naming the columns `short_code`,
+    /// `original_url`, `created_at` and `access_count`; one row per entry
+    /// follows, ordered by short code. `created_at` is in Unix seconds.
+    /// Fields containing commas, quotes or line breaks are quoted as described
+    /// in RFC 4180.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    pub fn export_csv(&self) -> Result<String> {
+        let mut urls = self.repository.list_all()?;
+        urls.sort_by(|a, b| a.short_code().cmp(b.short_code()));
+
+        let mut out = String::new();
+        csv::write_record(&mut out, &CSV_HEADER);
+        for url in &urls {
+            let created_at = url.created_at()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs());
+            csv::write_record(&mut out, &[
+                url.short_code().as_str(),
+                url.original_url().as_str(),
+                &created_at.to_string(),
+                &url.access_count().to_string(),
+            ]);
+        }
+
+        Ok(out)
+    }
+
+    /// Import shortened URLs from CSV
+    ///
+    /// Reads the format written by `export_csv`: rows of `short_code`,
+    /// `original_url` and optionally `created_at` (Unix seconds) and
+    /// `access_count`. The header line and blank lines are skipped. A missing
+    /// creation time defaults to now and a missing access count to zero.
+    /// Imported entries get fresh ids.
+    ///
+    /// Rows are validated like `shorten_url_with_code` input: codes are
+    /// scoped to the service's prefix and get a check character if checksums
+    /// are enabled, and URLs must pass the domain lists. Malformed or invalid
+    /// rows, including reserved codes and disallowed domains, are counted as
+    /// invalid, and rows whose short code is already taken or held by a
+    /// reservation are skipped; neither stops the import. See `ImportReport`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the service is read-only or a repository operation
+    /// fails. Rows imported before the failure are kept.
+    pub fn import_csv(&self, csv: &str) -> Result<ImportReport> {
+        self.ensure_writable()?;
+
+        let now = self.clock.now();
+        let mut report = ImportReport::default();
+        for (index, record) in csv::parse(csv).into_iter().enumerate() {
+            let Some(fields) = record.fields else {
+                report.record_invalid(record.line);
+                continue;
+            };
+
+            let blank = fields.len() == 1 && fields[0].is_empty();
+            if blank || (index == 0 && fields == CSV_HEADER) {
+                continue;
+            }
+
+            let Some(shortened_url) = self.entry_from_csv(&fields, now) else {
+                report.record_invalid(record.line);
+                continue;
+            };
+            if self.repository.is_reserved(shortened_url.short_code(), now)? {
+                report.record_duplicate(record.line);
+                continue;
+            }
+
+            match self.repository.save(shortened_url) {
+                Ok(()) => {
+                    self.record_created(now);
+                    report.record_imported();
+                }
+                Err(UrlShortenerError::ShortCodeAlreadyExists(_)) => {
+                    report.record_duplicate(record.line);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Serialize every shortened URL to a JSON array, ordered by short code
+    ///
+    /// Unlike `export_csv`, the snapshot keeps everything about each entry,
+    /// including its id, tags, notes and access history, so `restore` can
+    /// bring the repository back to the same state. Times are kept to the
+    /// second.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    #[cfg(feature = "serde")]
+    pub fn snapshot(&self) -> Result<String> {
+        let mut urls = self.repository.list_all()?;
+        urls.sort_by(|a, b| a.short_code().cmp(b.short_code()));
+
+        serde_json::to_string(&urls).map_err(|e| {
+            UrlShortenerError::repository_with_source("Failed to serialize snapshot", e)
+        })
+    }
+
+    /// Re-insert the shortened URLs of a `snapshot`, returning how many were
+    /// written
+    ///
+    /// Entries whose short code already exists are replaced if `overwrite` is
+    /// set and skipped otherwise. Entries are stored as they are in the
+    /// snapshot, bypassing the service's prefix, reserved codes and domain
+    /// lists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the service is read-only, `json` isn't a valid
+    /// snapshot, or a repository operation fails. Entries restored before the
+    /// failure are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use url_shortener::service::UrlShortenerService;
+    /// # use url_shortener::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+    /// # use url_shortener::domain::OriginalUrl;
+    /// # use std::sync::Arc;
+    /// # let repository = Arc::new(InMemoryUrlRepository::new());
+    /// # let id_generator = Arc::new(RandomIdGenerator::new());
+    /// # let service = UrlShortenerService::new(repository, id_generator);
+    /// let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+    /// service.shorten_url(url).unwrap();
+    ///
+    /// let backup = service.snapshot().unwrap();
+    /// service.clear_all().unwrap();
+    /// assert_eq!(service.restore(&backup, false).unwrap(), 1);
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn restore(&self, json: &str, overwrite: bool) -> Result<usize> {
+        self.ensure_writable()?;
+
+        let urls: Vec<ShortenedUrl> = serde_json::from_str(json).map_err(|e| {
+            UrlShortenerError::repository_with_source("Invalid snapshot", e)
+        })?;
+
+        let mut restored = 0;
+        for url in urls {
+            match self.repository.save(url.clone()) {
+                Ok(()) => restored += 1,
+                Err(UrlShortenerError::ShortCodeAlreadyExists(_)) if overwrite => {
+                    self.repository.update(url)?;
+                    restored += 1;
+                }
+                Err(UrlShortenerError::ShortCodeAlreadyExists(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(restored)
+    }
+
+    /// Re-issue a short code under the current generator policy
+    ///
+    /// Generates a new code (for example after increasing the configured code
+    /// length) pointing at the same destination, with the access count,
+    /// creation time and notes copied over. The old code is kept and keeps
+    /// resolving, acting as an alias. Returns the new code.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The short code doesn't exist
+    /// - A unique short code cannot be generated
+    /// - The repository operation fails
+    pub fn migrate_code_length(&self, short_code: &ShortCode) -> Result<ShortCode> {
+        self.ensure_writable()?;
+
+        let existing = self.repository.find_by_short_code(&self.lookup_code(short_code)?)?;
+        let new_code = self.generate_unique_short_code(existing.original_url())?;
+        let id = self.id_generator.generate_id();
+
+        self.repository.save(existing.reissue(id, new_code.clone()))?;
+
+        Ok(new_code)
+    }
+
+    /// Swap the destinations of two short codes
+    ///
+    /// Both codes keep their id, creation time and access count; only the
+    /// original URLs are exchanged. The swap is delegated to the repository,
+    /// which writes both entries or, if the second write fails, neither.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShortCodeNotFound` if either code doesn't exist, in which case
+    /// neither entry is modified.
+    pub fn swap_destinations(&self, a: &ShortCode, b: &ShortCode) -> Result<()> {
+        self.ensure_writable()?;
+        self.repository.swap_original_urls(&self.lookup_code(a)?, &self.lookup_code(b)?)
+    }
+
+    /// Point an existing short code at a new destination
+    ///
+    /// The id, creation time and access count are preserved, so a printed
+    /// link stays stable while its target changes. Returns the updated entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The short code doesn't exist
+    /// - The repository operation fails
+    pub fn update_target(
+        &self,
+        short_code: &ShortCode,
+        new_url: OriginalUrl,
+    ) -> Result<ShortenedUrl> {
+        self.ensure_writable()?;
+        self.ensure_domain_allowed(&new_url)?;
+
+        let mut shortened_url = self.repository.find_by_short_code(&self.lookup_code(short_code)?)?;
+        shortened_url.retarget(new_url);
+        self.repository.update(shortened_url.clone())?;
+
+        Ok(shortened_url)
+    }
+
+    /// Count links by the UTC hour of day they were created in
+    ///
+    /// Slot `h` of the result holds the number of links created between
+    /// `h:00` and `h:59` UTC. Useful for spotting bot-driven bursts.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    #[cfg(feature = "chrono")]
+    pub fn creation_hour_histogram(&self) -> Result<[u64; 24]> {
+        self.creation_hour_histogram_in(&chrono::Utc)
+    }
+
+    /// Count links by the hour of day they were created in, in a given timezone
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    #[cfg(feature = "chrono")]
+    pub fn creation_hour_histogram_in<Tz: chrono::TimeZone>(&self, tz: &Tz) -> Result<[u64; 24]> {
+        use chrono::{DateTime, Timelike, Utc};
+
+        let mut histogram = [0u64; 24];
+        for shortened_url in self.repository.list_all()? {
+            let created_at = DateTime::<Utc>::from(shortened_url.created_at()).with_timezone(tz);
+            histogram[created_at.hour() as usize] += 1;
+        }
+
+        Ok(histogram)
+    }
+
+    /// Export the daily access counts of a short code as a dense time series
+    ///
+    /// Returns one `(date, count)` pair per UTC day from `from` to `to`
+    /// inclusive, with zero for days without accesses, ready for charting.
+    /// Days older than the entry's `history_days` window read as zero. No
+    /// access is recorded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - `from` is after `to` (`InvalidConfiguration`)
+    /// - The range spans more than `MAX_TIMESERIES_DAYS` days
+    ///   (`InvalidConfiguration`)
+    /// - The short code doesn't exist
+    /// - The repository operation fails
+    #[cfg(feature = "chrono")]
+    pub fn access_timeseries(
+        &self,
+        code: &ShortCode,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<Vec<(chrono::NaiveDate, u64)>> {
+        if from > to {
+            return Err(UrlShortenerError::InvalidConfiguration(format!(
+                "time series starts on {} after it ends on {}",
+                from,
+                to
+            )));
+        }
+        let span = to.signed_duration_since(from).num_days() + 1;
+        if span > Self::MAX_TIMESERIES_DAYS {
+            return Err(UrlShortenerError::InvalidConfiguration(format!(
+                "time series spans {} days, more than the maximum of {}",
+                span,
+                Self::MAX_TIMESERIES_DAYS
+            )));
+        }
+
+        let shortened_url = self.repository.find_by_short_code(&self.lookup_code(code)?)?;
+        let days = shortened_url.access_counts_by_day();
+        let epoch = chrono::DateTime::UNIX_EPOCH.date_naive();
+
+        Ok(from
+            .iter_days()
+            .take_while(|date| *date <= to)
+            .map(|date| {
+                let day = date.signed_duration_since(epoch).num_days();
+                (date, days.get(&day).copied().unwrap_or(0))
+            })
+            .collect())
+    }
+
+    /// Compute the Gini coefficient of access counts across all links
+    ///
+    /// Returns a value between 0 (clicks spread evenly over all links) and
+    /// close to 1 (all clicks on a single link). An empty store, or one where
+    /// no link has been accessed, returns 0.0.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    pub fn access_gini(&self) -> Result<f64> {
+        let mut counts: Vec<u64> = self.repository.list_all()?
+            .iter()
+            .map(|url| url.access_count())
+            .collect();
+        counts.sort_unstable();
+
+        let n = counts.len() as f64;
+        let mut total = 0.0;
+        let mut weighted = 0.0;
+        for (i, &count) in counts.iter().enumerate() {
+            total += count as f64;
+            weighted += (i as f64 + 1.0) * count as f64;
+        }
+
+        if total == 0.0 {
+            return Ok(0.0);
+        }
+
+        Ok((2.0 * weighted) / (n * total) - (n + 1.0) / n)
+    }
+
+    /// Attach a note to a short code
+    ///
+    /// The note is timestamped with the service clock.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The short code doesn't exist
+    /// - The note is invalid or the notes limit is reached
+    /// - The repository operation fails
+    pub fn add_note(&self, short_code: &ShortCode, author: String, text: String) -> Result<()> {
+        self.ensure_writable()?;
+
+        let mut shortened_url = self.repository.find_by_short_code(&self.lookup_code(short_code)?)?;
+        shortened_url.add_note(author, text, self.clock.now())?;
+        self.repository.update(shortened_url)
+    }
+
+    /// List the notes attached to a short code, oldest first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the short code doesn't exist
+    pub fn list_notes(&self, short_code: &ShortCode) -> Result<Vec<Note>> {
+        let shortened_url = self.repository.find_by_short_code(&self.lookup_code(short_code)?)?;
+        Ok(shortened_url.notes().to_vec())
+    }
+
+    /// Produce a tamper-evident token for a short code
+    ///
+    /// The token has the form `{code}.{signature}`, where the signature is an
+    /// HMAC-SHA256 of the code keyed with `secret`, encoded as unpadded
+    /// base64url. Use `verify_signed_link` to check it. With a prefix, the
+    /// stored, prefixed code is signed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShortCodeNotFound` if the code doesn't exist
+    #[cfg(feature = "signing")]
+    pub fn signed_link(&self, short_code: &ShortCode, secret: &[u8]) -> Result<String> {
+        let code = self.lookup_code(short_code)?;
+        if !self.repository.exists(&code)? {
+            return Err(UrlShortenerError::ShortCodeNotFound(short_code.as_str().to_string()));
+        }
+
+        Ok(super::signing::sign(&code, secret))
+    }
+
+    /// Verify a token from `signed_link` and return its short code
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::InvalidSignature` if the token is malformed
+    /// or its signature doesn't match
+    #[cfg(feature = "signing")]
+    pub fn verify_signed_link(&self, token: &str, secret: &[u8]) -> Result<ShortCode> {
+        super::signing::verify(token, secret)
+    }
+
+    /// Estimate how many more links fit before collisions become likely
+    ///
+    /// Uses the birthday bound `n ≈ sqrt(2 * N * ln(1 / (1 - p)))` for the
+    /// generator's keyspace `N` and a 1% collision probability `p`, minus the
+    /// links already stored. This is roughly `sqrt(N)` and is a signal for
+    /// when to grow the short code length.
+    ///
+    /// Returns `None` if the generator can't report its keyspace.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository operation fails
+    pub fn birthday_bound(&self) -> Result<Option<u64>> {
+        let Some(keyspace) = self.id_generator.keyspace() else {
+            return Ok(None);
+        };
+
+        let p = Self::BIRTHDAY_COLLISION_PROBABILITY;
+        let total = (2.0 * keyspace as f64 * (1.0 / (1.0 - p)).ln()).sqrt() as u64;
+        let stored = self.repository.count()? as u64;
+
+        Ok(Some(total.saturating_sub(stored)))
+    }
+
+    /// Fail with `ReadOnly` if the service must not write
+    fn ensure_writable(&self) -> Result<()> {
+        if self.read_only {
+            return Err(UrlShortenerError::ReadOnly);
+        }
+        Ok(())
+    }
+
+    /// Fail with `DomainNotAllowed` if `url` is blocked or not allowlisted
+    fn ensure_domain_allowed(&self, url: &OriginalUrl) -> Result<()> {
+        let domain = url.domain().map(|domain| domain.trim_end_matches('.').to_lowercase());
+        let listed = |domains: &[String]| {
+            domain.as_deref().is_some_and(|domain| domains.iter().any(|d| is_within(domain, d)))
+        };
+
+        let allowed = !listed(&self.domain_blocklist)
+            && (self.domain_allowlist.is_empty() || listed(&self.domain_allowlist));
+        if !allowed {
+            return Err(UrlShortenerError::DomainNotAllowed(
+                url.host_str().unwrap_or_default().to_string()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Create an entry, applying the default expiry if one is configured
+    fn new_entry(
+        &self,
+        id: UrlId,
+        short_code: ShortCode,
+        original_url: OriginalUrl,
+    ) -> ShortenedUrl {
+        match self.default_expiry {
+            Some(ttl) => {
+                ShortenedUrl::with_created_at(id, short_code, original_url, self.clock.now())
+                    .with_expiry(ttl)
+            }
+            None => ShortenedUrl::new(id, short_code, original_url),
+        }
+    }
+
+    /// Build an entry from the fields of a CSV row, or `None` if invalid
+    fn entry_from_csv(&self, fields: &[String], now: SystemTime) -> Option<ShortenedUrl> {
+        let [code, target, optional @ ..] = fields else {
+            return None;
+        };
+        if optional.len() > 2 {
+            return None;
+        }
+
+        let short_code = self.custom_code(&ShortCode::new(code.clone()).ok()?).ok()?;
+        let original_url = OriginalUrl::new(target.clone()).ok()?;
+        self.ensure_domain_allowed(&original_url).ok()?;
+
+        let created_at = match optional.first().filter(|secs| !secs.is_empty()) {
+            Some(secs) => UNIX_EPOCH.checked_add(Duration::from_secs(secs.parse().ok()?))?,
+            None => now,
+        };
+        let access_count = match optional.get(1).filter(|count| !count.is_empty()) {
+            Some(count) => count.parse().ok()?,
+            None => 0,
+        };
+
+        let id = self.id_generator.generate_id();
+        Some(ShortenedUrl::with_created_at(id, short_code, original_url, created_at)
+            .with_access_count(access_count))
+    }
+
+    /// Lowercase a short code if the service matches codes case-insensitively
+    fn normalize_code(&self, short_code: &ShortCode) -> ShortCode {
+        if !self.case_insensitive {
+            return short_code.clone();
+        }
+
+        // Lowercasing can turn a non-ASCII code invalid; it then only
+        // matches itself
+        ShortCode::new_case_insensitive(short_code.as_str().to_string())
+            .unwrap_or_else(|_| short_code.clone())
+    }
+
+    /// Normalize a short code and place it in the service's namespace
+    ///
+    /// Codes without a separator get the prefix added; codes already under
+    /// the prefix are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns `UrlShortenerError::InvalidShortCode` if the code is under
+    /// another prefix, or too long once prefixed
+    fn scoped_code(&self, short_code: &ShortCode) -> Result<ShortCode> {
+        let Some(prefix) = &self.prefix else {
+            return Ok(self.normalize_code(short_code));
+        };
+
+        let code = match short_code.as_str().split_once(ShortCode::SEPARATOR) {
+            None => ShortCode::new(format!("{}{}{}", prefix, ShortCode::SEPARATOR, short_code))?,
+            Some((namespace, _)) if self.same_prefix(namespace, prefix) => short_code.clone(),
+            Some(_) => {
+                return Err(UrlShortenerError::InvalidShortCode(format!(
+                    "'{}' is outside the '{}' namespace",
+                    short_code,
+                    prefix
+                )));
+            }
+        };
+
+        Ok(self.normalize_code(&code))
+    }
+
+    /// Append a check character if checksums are enabled
+    ///
+    /// The result is normalized again, since the check character may be
+    /// uppercase.
+    fn with_check_character(&self, short_code: ShortCode) -> Result<ShortCode> {
+        if !self.checksums {
+            return Ok(short_code);
+        }
+
+        Ok(self.normalize_code(&short_code.with_checksum()?))
+    }
+
+    /// Fail with `ChecksumMismatch` if checksums are enabled and `short_code`
+    /// doesn't end with its check character
+    fn ensure_checksum(&self, short_code: ShortCode) -> Result<ShortCode> {
+        if !self.checksums {
+            return Ok(short_code);
+        }
+
+        let code = short_code.as_str();
+        let valid = match code.char_indices().last() {
+            Some((index, last)) if self.case_insensitive => {
+                ShortCode::check_character(&code[..index])
+                    .is_some_and(|check| check.eq_ignore_ascii_case(&last))
+            }
+            _ => short_code.verify_checksum(),
+        };
+
+        if !valid {
+            return Err(UrlShortenerError::ChecksumMismatch(short_code.into_inner()));
+        }
+        Ok(short_code)
+    }
+
+    /// Like `scoped_code`, reporting codes that can't be ours as not found
+    fn lookup_code(&self, short_code: &ShortCode) -> Result<ShortCode> {
+        self.scoped_code(short_code)
+            .map_err(|_| UrlShortenerError::ShortCodeNotFound(short_code.as_str().to_string()))
+    }
+
+    /// Check whether a stored short code belongs to this service's prefix
+    fn in_namespace(&self, short_code: &ShortCode) -> bool {
+        self.scoped_code(short_code).is_ok_and(|scoped| &scoped == short_code)
+    }
+
+    fn same_prefix(&self, namespace: &str, prefix: &str) -> bool {
+        if self.case_insensitive {
+            namespace.to_lowercase() == prefix.to_lowercase()
+        } else {
+            namespace == prefix
+        }
+    }
+
+    /// Check whether a short code is reserved
+    ///
+    /// With a prefix, the part after it is checked.
+    fn is_reserved(&self, short_code: &ShortCode) -> bool {
+        let code = match &self.prefix {
+            Some(_) => short_code
+                .as_str()
+                .split_once(ShortCode::SEPARATOR)
+                .map_or(short_code.as_str(), |(_, local)| local),
+            None => short_code.as_str(),
+        };
+
+        if !self.case_insensitive {
+            return self.reserved_codes.contains(code);
+        }
+
+        let code = code.to_lowercase();
+        self.reserved_codes.iter().any(|reserved| reserved.to_lowercase() == code)
+    }
+
+    /// Generate a unique short code for `url`
+    ///
+    /// Attempts multiple times to avoid collisions. Deterministic generators
+    /// return the same code on every attempt, so a collision fails right away
+    /// in practice. After `max_generation_attempts` collisions, asks the
+    /// generator for codes one character longer at a time, as long as they
+    /// fit in `ShortCode::MAX_LENGTH` with the prefix and check character.
+    /// Generators that can't vary the length fail at that point.
+    fn generate_unique_short_code(&self, url: &OriginalUrl) -> Result<ShortCode> {
+        // Lengths below are of the generator's codes, before the prefix and
+        // check character are added
+        let mut length = 0;
+        for _ in 0..self.max_generation_attempts {
+            let code = match self.preferred_code_length {
+                Some(length) => self.id_generator.generate_short_code_with_length(length)?,
+                None => self.id_generator.generate_short_code_for(url)?,
+            };
+            length = code.as_str().len();
+            let code = self.with_check_character(self.scoped_code(&code)?)?;
+
+            if self.is_available(&code)? {
+                return Ok(code);
+            }
+        }
+
+        // The keyspace at this length is crowded; try longer codes
+        let overhead = self.prefix.as_ref().map_or(0, |prefix| prefix.len() + 1)
+            + usize::from(self.checksums);
+        let mut attempts = self.max_generation_attempts;
+        for length in (length + 1)..=ShortCode::MAX_LENGTH.saturating_sub(overhead) {
+            for _ in 0..self.max_generation_attempts {
+                let code = match self.id_generator.generate_short_code_with_length(length) {
+                    Ok(code) => self.with_check_character(self.scoped_code(&code)?)?,
+                    Err(UrlShortenerError::Unsupported(_)) => {
+                        return Err(UrlShortenerError::IdGenerationFailed(attempts));
+                    }
+                    Err(e) => return Err(e),
+                };
+                attempts += 1;
+
+                if self.is_available(&code)? {
+                    return Ok(code);
+                }
+            }
+        }
+
+        Err(UrlShortenerError::IdGenerationFailed(attempts))
+    }
+
+    /// Check whether a generated code may be handed out
+    fn is_available(&self, code: &ShortCode) -> Result<bool> {
+        Ok(!self.is_reserved(code)
+            && !self.repository.exists(code)?
+            && !self.repository.is_reserved(code, self.clock.now())?)
+    }
+}
+
+/// Lowercase domains and drop trailing dots, for comparison with URL domains
+fn normalize_domains(domains: Vec<String>) -> Vec<String> {
+    domains
+        .into_iter()
+        .map(|domain| domain.trim_end_matches('.').to_lowercase())
+        .collect()
+}
+
+/// Check whether `domain` is `parent` or one of its subdomains
+fn is_within(domain: &str, parent: &str) -> bool {
+    domain
+        .strip_suffix(parent)
+        .is_some_and(|rest| rest.is_empty() || rest.ends_with('.'))
+}
+
+/// Check that at least one attempt is allowed to generate a short code
+pub(super) fn check_generation_attempts(attempts: usize) -> Result<()> {
+    if attempts == 0 {
+        return Err(UrlShortenerError::InvalidConfiguration(
+            "At least one generation attempt is required".to_string()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check that `length` is a valid short code length
+pub(super) fn check_code_length(length: usize) -> Result<()> {
+    if !(ShortCode::MIN_LENGTH..=ShortCode::MAX_LENGTH).contains(&length) {
+        return Err(UrlShortenerError::InvalidShortCode(format!(
+            "Length {} is outside {}..={}",
+            length,
+            ShortCode::MIN_LENGTH,
+            ShortCode::MAX_LENGTH
+        )));
+    }
+
+    Ok(())
+}
+
+/// Columns written by `export_csv` and read by `import_csv`
+const CSV_HEADER: [&str; 4] = ["short_code", "original_url", "created_at", "access_count"];
+
+/// Check that a stored entry may be resolved at `now`
+///
+/// Shared with the async service so both report the same errors.
+pub(super) fn ensure_resolvable(shortened_url: &ShortenedUrl, now: SystemTime) -> Result<()> {
+    let code = shortened_url.short_code().as_str();
+
+    if !shortened_url.is_enabled() {
+        return Err(UrlShortenerError::ShortCodeDisabled(code.to_string()));
+    }
+
+    if !shortened_url.is_active_at(now) {
+        return Err(UrlShortenerError::NotYetActive(code.to_string()));
+    }
+
+    if shortened_url.is_expired_at(now) {
+        return Err(UrlShortenerError::ShortCodeExpired(code.to_string()));
+    }
+
+    if shortened_url.is_access_limit_reached() {
+        return Err(UrlShortenerError::AccessLimitReached(code.to_string()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::{InMemoryUrlRepository, ManualClock, RandomIdGenerator};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn create_service() -> UrlShortenerService<InMemoryUrlRepository, RandomIdGenerator> {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(RandomIdGenerator::new());
+        UrlShortenerService::new(repository, id_generator)
+    }
+
+    #[test]
+    fn test_shorten_url() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+
+        let result = service.shorten_url(url);
+        assert!(result.is_ok());
+
+        let shortened = result.unwrap();
+        assert_eq!(shortened.original_url().as_str(), "https://example.com/");
+    }
+
+    #[test]
     fn test_shorten_url_with_custom_code() {
         let service = create_service();
         let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
-        let code = ShortCode::new("custom".to_string()).unwrap();
+        let code = ShortCode::new("custom".to_string()).unwrap();
+
+        let result = service.shorten_url_with_code(url, code);
+        assert!(result.is_ok());
+
+        let shortened = result.unwrap();
+        assert_eq!(shortened.short_code().as_str(), "custom");
+    }
+
+    #[test]
+    fn test_custom_code_already_exists() {
+        let service = create_service();
+        let url1 = OriginalUrl::new("https://example1.com".to_string()).unwrap();
+        let url2 = OriginalUrl::new("https://example2.com".to_string()).unwrap();
+        let code = ShortCode::new("dupe12".to_string()).unwrap();
+
+        service.shorten_url_with_code(url1, code.clone()).unwrap();
+
+        let result = service.shorten_url_with_code(url2, code);
+        assert!(result.is_err());
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeAlreadyExists(_))));
+    }
+
+    #[test]
+    fn test_resolve_short_code() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com/test".to_string()).unwrap();
+        let shortened = service.shorten_url(url).unwrap();
+
+        let resolved = service.resolve_short_code(shortened.short_code()).unwrap();
+        assert_eq!(resolved.as_str(), "https://example.com/test");
+    }
+
+    #[test]
+    fn test_resolve_increments_access_count() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let shortened = service.shorten_url(url).unwrap();
+        let code = shortened.short_code().clone();
+
+        // Initial access count should be 0
+        let stats = service.get_statistics(&code).unwrap();
+        assert_eq!(stats.access_count(), 0);
+
+        // Resolve once
+        service.resolve_short_code(&code).unwrap();
+
+        // Access count should be 1
+        let stats = service.get_statistics(&code).unwrap();
+        assert_eq!(stats.access_count(), 1);
+
+        // Resolve again
+        service.resolve_short_code(&code).unwrap();
+
+        // Access count should be 2
+        let stats = service.get_statistics(&code).unwrap();
+        assert_eq!(stats.access_count(), 2);
+    }
+
+    #[test]
+    fn test_resolve_with_referrer() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let code = service.shorten_url(url).unwrap().short_code().clone();
+
+        for referrer in [Some("twitter"), Some("newsletter"), None, Some("twitter"), None] {
+            let referrer = referrer.map(str::to_string);
+            service.resolve_short_code_with_referrer(&code, referrer).unwrap();
+        }
+        service.resolve_short_code(&code).unwrap();
+
+        let stats = service.get_statistics(&code).unwrap();
+        assert_eq!(stats.access_count(), 6);
+        let counts = stats.referrer_counts();
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts.get("twitter"), Some(&2));
+        assert_eq!(counts.get("newsletter"), Some(&1));
+        assert_eq!(counts.get(ShortenedUrl::DIRECT_REFERRER), Some(&2));
+
+        // Unknown codes fail like in resolve_short_code
+        let missing = ShortCode::new("missing1".to_string()).unwrap();
+        let result = service.resolve_short_code_with_referrer(&missing, None);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+    }
+
+    #[test]
+    fn test_resolve_nonexistent_code() {
+        let service = create_service();
+        let code = ShortCode::new("notfound".to_string()).unwrap();
+
+        let result = service.resolve_short_code(&code);
+        assert!(result.is_err());
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+    }
+
+    #[test]
+    fn test_delete_short_code() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let shortened = service.shorten_url(url).unwrap();
+        let code = shortened.short_code().clone();
+
+        // Should exist
+        assert!(service.get_statistics(&code).is_ok());
+
+        // Delete it
+        service.delete_short_code(&code).unwrap();
+
+        // Should no longer exist
+        assert!(service.get_statistics(&code).is_err());
+    }
+
+    #[test]
+    fn test_disable_and_enable_short_code() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let shortened = service.shorten_url(url.clone()).unwrap();
+        let code = shortened.short_code().clone();
+        service.resolve_short_code(&code).unwrap();
+
+        service.disable_short_code(&code).unwrap();
+        service.disable_short_code(&code).unwrap();
+
+        let result = service.resolve_short_code(&code);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeDisabled(_))));
+
+        // The entry and its stats are still visible, and the blocked resolve isn't counted
+        let stats = service.get_statistics(&code).unwrap();
+        assert!(!stats.is_enabled());
+        assert_eq!(stats.access_count(), 1);
+        assert_eq!(service.list_all().unwrap().len(), 1);
+
+        service.enable_short_code(&code).unwrap();
+        assert_eq!(service.resolve_short_code(&code).unwrap(), url);
+        assert_eq!(service.get_statistics(&code).unwrap().access_count(), 2);
+    }
+
+    #[test]
+    fn test_concurrent_resolves_count_every_access() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let code = service.shorten_url(url).unwrap().short_code().clone();
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for _ in 0..100 {
+                        service.resolve_short_code(&code).unwrap();
+                    }
+                });
+            }
+        });
+
+        assert_eq!(service.get_statistics(&code).unwrap().access_count(), 800);
+    }
+
+    #[test]
+    fn test_prune_expired() {
+        let start = UNIX_EPOCH + Duration::from_secs(10_000);
+        let clock = Arc::new(ManualClock::new(start));
+        let service = create_service().with_clock(clock.clone());
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+
+        let short = service.shorten_url_with_expiry(url.clone(), Duration::from_secs(60)).unwrap();
+        let long = service.shorten_url_with_expiry(url.clone(), Duration::from_secs(600)).unwrap();
+        let forever = service.shorten_url(url).unwrap();
+        assert_eq!(service.prune_expired().unwrap(), 0);
+
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(service.prune_expired().unwrap(), 1);
+        assert!(matches!(
+            service.get_statistics(short.short_code()),
+            Err(UrlShortenerError::ShortCodeNotFound(_))
+        ));
+        assert!(service.get_statistics(long.short_code()).is_ok());
+        assert!(service.get_statistics(forever.short_code()).is_ok());
+
+        clock.advance(Duration::from_secs(3_600));
+        assert_eq!(service.prune_expired().unwrap(), 1);
+        assert_eq!(service.count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_delete_many() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let first = service.shorten_url(url.clone()).unwrap().short_code().clone();
+        let second = service.shorten_url(url.clone()).unwrap().short_code().clone();
+        let kept = service.shorten_url(url).unwrap().short_code().clone();
+        let missing = ShortCode::new("missing".to_string()).unwrap();
+
+        let results = service.delete_many(&[first.clone(), missing, second.clone()]);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(UrlShortenerError::ShortCodeNotFound(_))));
+        assert!(results[2].is_ok());
+
+        let remaining: Vec<_> = service.list_all().unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].short_code(), &kept);
+
+        // Deleting again reports every code as missing
+        let results = service.delete_many(&[first, second]);
+        assert!(results.iter().all(|r| matches!(r, Err(UrlShortenerError::ShortCodeNotFound(_)))));
+        assert!(service.delete_many(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_one_time_link() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let shortened = service.shorten_url_limited(url.clone(), 1).unwrap();
+        let code = shortened.short_code().clone();
+        assert_eq!(shortened.max_accesses(), Some(1));
+
+        assert_eq!(service.resolve_short_code(&code).unwrap(), url);
+        for _ in 0..2 {
+            assert!(matches!(
+                service.resolve_short_code(&code),
+                Err(UrlShortenerError::AccessLimitReached(_))
+            ));
+        }
+        assert_eq!(service.get_statistics(&code).unwrap().access_count(), 1);
+    }
+
+    #[test]
+    fn test_unlimited_link_keeps_resolving() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let shortened = service.shorten_url(url.clone()).unwrap();
+        assert_eq!(shortened.max_accesses(), None);
+
+        for _ in 0..50 {
+            assert_eq!(service.resolve_short_code(shortened.short_code()).unwrap(), url);
+        }
+    }
+
+    #[test]
+    fn test_concurrent_resolves_respect_access_limit() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let code = service.shorten_url_limited(url, 10).unwrap().short_code().clone();
+        let resolved = std::sync::atomic::AtomicU64::new(0);
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for _ in 0..5 {
+                        match service.resolve_short_code(&code) {
+                            Ok(_) => {
+                                resolved.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                            }
+                            Err(UrlShortenerError::AccessLimitReached(_)) => {}
+                            Err(e) => panic!("unexpected error: {}", e),
+                        }
+                    }
+                });
+            }
+        });
+
+        assert_eq!(resolved.into_inner(), 10);
+        assert_eq!(service.get_statistics(&code).unwrap().access_count(), 10);
+    }
+
+    #[test]
+    fn test_resolve_without_tracking() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let code = service.shorten_url(url.clone()).unwrap().short_code().clone();
+        service.resolve_short_code(&code).unwrap();
+        let before = service.get_statistics(&code).unwrap();
+
+        for _ in 0..3 {
+            assert_eq!(service.resolve_without_tracking(&code).unwrap(), url);
+        }
+
+        let after = service.get_statistics(&code).unwrap();
+        assert_eq!(after.access_count(), 1);
+        assert_eq!(after.last_accessed_at(), before.last_accessed_at());
+
+        service.resolve_short_code(&code).unwrap();
+        assert_eq!(service.get_statistics(&code).unwrap().access_count(), 2);
+
+        let missing = ShortCode::new("missing1".to_string()).unwrap();
+        let result = service.resolve_without_tracking(&missing);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+    }
+
+    #[test]
+    fn test_disable_missing_code() {
+        let service = create_service();
+        let code = ShortCode::new("missing1".to_string()).unwrap();
+
+        let result = service.disable_short_code(&code);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+    }
+
+    #[test]
+    fn test_list_all() {
+        let service = create_service();
+
+        let urls = service.list_all().unwrap();
+        assert_eq!(urls.len(), 0);
+
+        let url1 = OriginalUrl::new("https://example1.com".to_string()).unwrap();
+        let url2 = OriginalUrl::new("https://example2.com".to_string()).unwrap();
+
+        service.shorten_url(url1).unwrap();
+        service.shorten_url(url2).unwrap();
+
+        let urls = service.list_all().unwrap();
+        assert_eq!(urls.len(), 2);
+    }
+
+    #[test]
+    fn test_created_between_monthly_window() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let service = UrlShortenerService::new(
+            repository.clone(),
+            Arc::new(RandomIdGenerator::new()),
+        );
+
+        // 2024-01-01, 2024-02-01 and 2024-03-01 UTC
+        let jan = UNIX_EPOCH + Duration::from_secs(1_704_067_200);
+        let feb = UNIX_EPOCH + Duration::from_secs(1_706_745_600);
+        let mar = UNIX_EPOCH + Duration::from_secs(1_709_251_200);
+        let entries = [
+            ("before01", jan - Duration::from_secs(1)),
+            ("febstart", feb),
+            ("janstart", jan),
+            ("janlate1", feb - Duration::from_secs(1)),
+            ("marstart", mar),
+        ];
+        for (code, created_at) in entries {
+            repository.save(ShortenedUrl::with_created_at(
+                UrlId::new(code.to_string()),
+                ShortCode::new(code.to_string()).unwrap(),
+                OriginalUrl::new("https://example.com".to_string()).unwrap(),
+                created_at,
+            )).unwrap();
+        }
+
+        let codes = |start, end| -> Vec<String> {
+            service.created_between(start, end).unwrap()
+                .iter()
+                .map(|url| url.short_code().as_str().to_string())
+                .collect()
+        };
+
+        assert_eq!(codes(jan, feb), ["janstart", "janlate1"]);
+        assert_eq!(codes(feb, mar), ["febstart"]);
+        assert!(codes(mar + Duration::from_secs(1), mar + Duration::from_secs(60)).is_empty());
+    }
+
+    #[test]
+    fn test_most_accessed() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let service = UrlShortenerService::new(
+            repository.clone(),
+            Arc::new(RandomIdGenerator::new()),
+        );
+
+        // (code, creation time, resolves)
+        let entries = [("quiet001", 10, 1), ("busy0001", 20, 5), ("tied0002", 40, 3),
+            ("tied0001", 30, 3), ("never001", 50, 0)];
+        for (code, secs, _) in entries {
+            repository.save(ShortenedUrl::with_created_at(
+                UrlId::new(code.to_string()),
+                ShortCode::new(code.to_string()).unwrap(),
+                OriginalUrl::new("https://example.com".to_string()).unwrap(),
+                UNIX_EPOCH + Duration::from_secs(secs),
+            )).unwrap();
+        }
+        for (code, _, resolves) in entries {
+            let code = ShortCode::new(code.to_string()).unwrap();
+            for _ in 0..resolves {
+                service.resolve_short_code(&code).unwrap();
+            }
+        }
+
+        let codes = |limit| -> Vec<String> {
+            service.most_accessed(limit).unwrap()
+                .iter()
+                .map(|url| url.short_code().as_str().to_string())
+                .collect()
+        };
+
+        // Equal counts are ordered oldest first
+        assert_eq!(codes(10), ["busy0001", "tied0001", "tied0002", "quiet001", "never001"]);
+        assert_eq!(codes(3), ["busy0001", "tied0001", "tied0002"]);
+        assert_eq!(codes(1), ["busy0001"]);
+        assert!(codes(0).is_empty());
+    }
+
+    #[test]
+    fn test_trending_favors_recent_activity() {
+        let day = |d: u64| UNIX_EPOCH + Duration::from_secs(d * 86_400);
+        let clock = Arc::new(ManualClock::new(day(100)));
+        let service = create_service().with_clock(clock.clone());
+        let shorten = |code: &str| {
+            let url = OriginalUrl::new(format!("https://example.com/{code}")).unwrap();
+            let code = ShortCode::new(code.to_string()).unwrap();
+            service.shorten_url_with_code(url, code).unwrap().short_code().clone()
+        };
+        let veteran = shorten("veteran1");
+        let newcomer = shorten("newcomer");
+        let idle = shorten("idle0001");
+
+        for _ in 0..50 {
+            service.resolve_short_code(&veteran).unwrap();
+        }
+        clock.set(day(130));
+        for _ in 0..5 {
+            service.resolve_short_code(&newcomer).unwrap();
+        }
+
+        let week = Duration::from_secs(7 * 86_400);
+        let trending = service.trending(week, 10).unwrap();
+        let codes: Vec<&ShortCode> = trending.iter().map(|(url, _)| url.short_code()).collect();
+        assert_eq!(codes, [&newcomer, &veteran]);
+        assert!(!codes.contains(&&idle));
+
+        // The veteran has more accesses in total but they are four weeks old
+        assert_eq!(trending[0].1, 5.0);
+        assert!(trending[1].1 < 5.0 && trending[1].1 > 0.0);
+        assert_eq!(trending[1].0.access_count(), 50);
+
+        assert_eq!(service.trending(week, 1).unwrap().len(), 1);
+        assert!(service.trending(week, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_list_paginated_pages() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let service = UrlShortenerService::new(
+            repository.clone(),
+            Arc::new(RandomIdGenerator::new()),
+        );
+
+        // Two entries share a creation time and are ordered by short code
+        let entries = [("page0000", 10), ("page0001", 20), ("page000b", 30), ("page000a", 30),
+            ("page0003", 40)];
+        for (code, secs) in entries {
+            repository.save(ShortenedUrl::with_created_at(
+                UrlId::new(code.to_string()),
+                ShortCode::new(code.to_string()).unwrap(),
+                OriginalUrl::new("https://example.com".to_string()).unwrap(),
+                UNIX_EPOCH + Duration::from_secs(secs),
+            )).unwrap();
+        }
+
+        let codes = |offset, limit| -> Vec<String> {
+            service.list_paginated(offset, limit).unwrap()
+                .iter()
+                .map(|url| url.short_code().as_str().to_string())
+                .collect()
+        };
+
+        assert_eq!(codes(0, 2), ["page0000", "page0001"]);
+        assert_eq!(codes(2, 2), ["page000a", "page000b"]);
+
+        // Partial last page
+        assert_eq!(codes(4, 2), ["page0003"]);
+
+        // Offset beyond the end
+        assert!(codes(5, 2).is_empty());
+        assert!(codes(100, 2).is_empty());
+    }
+
+    #[test]
+    fn test_count_tracks_inserts_and_deletes() {
+        let service = create_service();
+        assert_eq!(service.count().unwrap(), 0);
+
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let first = service.shorten_url(url.clone()).unwrap();
+        service.shorten_url(url.clone()).unwrap();
+        service.shorten_url(url).unwrap();
+        assert_eq!(service.count().unwrap(), 3);
+
+        service.delete_short_code(first.short_code()).unwrap();
+        assert_eq!(service.count().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let service = UrlShortenerService::new(
+            repository.clone(),
+            Arc::new(RandomIdGenerator::new()),
+        );
+        let entries = [
+            ("plain123", "https://example.com/page", 1_000, 3),
+            ("comma123", "https://example.com/search?q=a,b", 2_000, 0),
+        ];
+        for (code, target, secs, accesses) in entries {
+            repository.save(ShortenedUrl::with_created_at(
+                UrlId::new(code.to_string()),
+                ShortCode::new(code.to_string()).unwrap(),
+                OriginalUrl::new(target.to_string()).unwrap(),
+                UNIX_EPOCH + Duration::from_secs(secs),
+            ).with_access_count(accesses)).unwrap();
+        }
+
+        let csv = service.export_csv().unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines, [
+            "short_code,original_url,created_at,access_count",
+            "comma123,\"https://example.com/search?q=a,b\",2000,0",
+            "plain123,https://example.com/page,1000,3",
+        ]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_snapshot_restore_round_trip() {
+        let service = create_service();
+        for target in ["https://example.com/a", "https://example.com/b"] {
+            let shortened = service.shorten_url(OriginalUrl::new(target.to_string()).unwrap());
+            let code = shortened.unwrap().short_code().clone();
+            service.resolve_short_code(&code).unwrap();
+            service.resolve_short_code(&code).unwrap();
+        }
+        let code = ShortCode::new("noted1".to_string()).unwrap();
+        let url = OriginalUrl::new("https://example.com/c".to_string()).unwrap();
+        service.shorten_url_with_code(url, code.clone()).unwrap();
+        service.add_note(&code, "ops".to_string(), "Launch link".to_string()).unwrap();
+
+        let mut before = service.list_all().unwrap();
+        let snapshot = service.snapshot().unwrap();
+        service.clear_all().unwrap();
+
+        assert_eq!(service.restore(&snapshot, false).unwrap(), 3);
+        assert_eq!(service.snapshot().unwrap(), snapshot);
+
+        let mut after = service.list_all().unwrap();
+        before.sort_by(|a, b| a.short_code().cmp(b.short_code()));
+        after.sort_by(|a, b| a.short_code().cmp(b.short_code()));
+        for (restored, original) in after.iter().zip(&before) {
+            assert!(restored.same_mapping(original));
+            assert_eq!(restored.id(), original.id());
+            assert_eq!(restored.access_count(), original.access_count());
+            assert_eq!(restored.notes().len(), original.notes().len());
+        }
+        assert_eq!(after.len(), 3);
+        assert_eq!(after.iter().map(|url| url.access_count()).sum::<u64>(), 4);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_restore_existing_codes() {
+        let service = create_service();
+        let code = ShortCode::new("keep12".to_string()).unwrap();
+        let url = OriginalUrl::new("https://example.com/old".to_string()).unwrap();
+        service.shorten_url_with_code(url, code.clone()).unwrap();
+        let snapshot = service.snapshot().unwrap();
+
+        let new_url = OriginalUrl::new("https://example.com/new".to_string()).unwrap();
+        service.update_target(&code, new_url.clone()).unwrap();
+
+        // Skipped without overwrite
+        assert_eq!(service.restore(&snapshot, false).unwrap(), 0);
+        assert_eq!(service.get_statistics(&code).unwrap().original_url(), &new_url);
+
+        assert_eq!(service.restore(&snapshot, true).unwrap(), 1);
+        let restored = service.get_statistics(&code).unwrap();
+        assert_eq!(restored.original_url().as_str(), "https://example.com/old");
+
+        let result = service.restore("not json", true);
+        assert!(matches!(result, Err(UrlShortenerError::RepositoryError { .. })));
+        let result = service.read_only().restore(&snapshot, true);
+        assert!(matches!(result, Err(UrlShortenerError::ReadOnly)));
+    }
+
+    #[test]
+    fn test_import_csv_report() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        service.shorten_url_with_code(url, ShortCode::new("taken1".to_string()).unwrap()).unwrap();
+
+        let csv = "short_code,original_url,created_at,access_count\n\
+                   good01,https://example.com/a,1000,5\n\
+                   taken1,https://example.com/b,,\n\
+                   bad,https://example.com/c\n\
+                   good02,not a url\n\
+                   \n\
+                   good03,\"https://example.com/search?q=a,b\"\n\
+                   good01,https://example.com/d\n\
+                   good04,https://example.com/e,soon\n\
+                   good05,\"https://example.com/f\n";
+
+        let report = service.import_csv(csv).unwrap();
+        assert_eq!(report.imported(), 2);
+        assert_eq!(report.skipped_duplicates(), 2);
+        assert_eq!(report.duplicate_lines(), [3, 8]);
+        assert_eq!(report.invalid(), 4);
+        assert_eq!(report.invalid_lines(), [4, 5, 9, 10]);
+
+        let imported = service.get_statistics(&ShortCode::new("good01".to_string()).unwrap())
+            .unwrap();
+        assert_eq!(imported.original_url().as_str(), "https://example.com/a");
+        assert_eq!(imported.created_at(), UNIX_EPOCH + Duration::from_secs(1000));
+        assert_eq!(imported.access_count(), 5);
+
+        let quoted = service.get_statistics(&ShortCode::new("good03".to_string()).unwrap())
+            .unwrap();
+        assert_eq!(quoted.original_url().as_str(), "https://example.com/search?q=a,b");
+    }
+
+    #[test]
+    fn test_import_csv_applies_service_policies() {
+        let service = create_service()
+            .with_domain_blocklist(vec!["blocked.example".to_string()])
+            .with_reserved_codes(vec!["admin".to_string()]);
+        let held = ShortCode::new("held01".to_string()).unwrap();
+        service.reserve_code(held.clone(), Duration::from_secs(300)).unwrap();
+
+        let csv = "block1,https://blocked.example/a\n\
+                   block2,https://sub.blocked.example/b\n\
+                   admin,https://example.com/c\n\
+                   held01,https://example.com/d\n\
+                   fine01,https://example.com/e\n";
+
+        let report = service.import_csv(csv).unwrap();
+        assert_eq!(report.imported(), 1);
+        assert_eq!(report.invalid_lines(), [1, 2, 3]);
+        assert_eq!(report.duplicate_lines(), [4]);
+        assert!(matches!(
+            service.get_statistics(&held),
+            Err(UrlShortenerError::ShortCodeNotFound(_))
+        ));
+        assert_eq!(service.created_last(Duration::from_secs(60)), 1);
+
+        // Codes get a check character, like in shorten_url_with_code
+        let service = create_service().with_checksums(true);
+        let report = service.import_csv("fine01,https://example.com/e\n").unwrap();
+        assert_eq!(report.imported(), 1);
+        let code = service.list_all().unwrap()[0].short_code().clone();
+        assert!(code.as_str().starts_with("fine01") && code.verify_checksum());
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let source = create_service();
+        let url = OriginalUrl::new("https://example.com/x?a=1,2".to_string()).unwrap();
+        for _ in 0..3 {
+            let shortened = source.shorten_url(url.clone()).unwrap();
+            source.resolve_short_code(shortened.short_code()).unwrap();
+        }
+
+        let target = create_service();
+        let report = target.import_csv(&source.export_csv().unwrap()).unwrap();
+        assert_eq!(report.imported(), 3);
+        assert_eq!(report.skipped_duplicates() + report.invalid(), 0);
+        assert_eq!(target.export_csv().unwrap(), source.export_csv().unwrap());
+    }
+
+    #[test]
+    fn test_clear_all() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let service = UrlShortenerService::new(
+            repository.clone(),
+            Arc::new(RandomIdGenerator::new()),
+        );
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        for _ in 0..3 {
+            service.shorten_url(url.clone()).unwrap();
+        }
+
+        let replica = UrlShortenerService::new(
+            repository,
+            Arc::new(RandomIdGenerator::new()),
+        ).read_only();
+        assert!(matches!(replica.clear_all(), Err(UrlShortenerError::ReadOnly)));
+
+        service.clear_all().unwrap();
+        assert_eq!(service.count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_swap_destinations() {
+        let service = create_service();
+        let url_a = OriginalUrl::new("https://a.example.com".to_string()).unwrap();
+        let url_b = OriginalUrl::new("https://b.example.com".to_string()).unwrap();
+        let a = service.shorten_url(url_a).unwrap();
+        let b = service.shorten_url(url_b).unwrap();
+
+        service.resolve_short_code(a.short_code()).unwrap();
+        service.resolve_short_code(a.short_code()).unwrap();
+
+        service.swap_destinations(a.short_code(), b.short_code()).unwrap();
+
+        let stats_a = service.get_statistics(a.short_code()).unwrap();
+        let stats_b = service.get_statistics(b.short_code()).unwrap();
+        assert_eq!(stats_a.original_url().as_str(), "https://b.example.com/");
+        assert_eq!(stats_b.original_url().as_str(), "https://a.example.com/");
+        assert_eq!(stats_a.id(), a.id());
+        assert_eq!(stats_b.id(), b.id());
+        assert_eq!(stats_a.access_count(), 2);
+        assert_eq!(stats_b.access_count(), 0);
+    }
+
+    #[test]
+    fn test_swap_destinations_missing_code() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://a.example.com".to_string()).unwrap();
+        let a = service.shorten_url(url).unwrap();
+        let missing = ShortCode::new("missing1".to_string()).unwrap();
+
+        let result = service.swap_destinations(a.short_code(), &missing);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+
+        let stats_a = service.get_statistics(a.short_code()).unwrap();
+        assert_eq!(stats_a.original_url().as_str(), "https://a.example.com/");
+    }
+
+    #[test]
+    fn test_swap_destinations_rolls_back_failed_write() {
+        /// Uses the default `swap_original_urls`, failing updates to "bbbb"
+        struct FailsSecondWrite(InMemoryUrlRepository);
+
+        impl UrlRepository for FailsSecondWrite {
+            fn save(&self, url: ShortenedUrl) -> Result<()> {
+                self.0.save(url)
+            }
+            fn find_by_short_code(&self, code: &ShortCode) -> Result<ShortenedUrl> {
+                self.0.find_by_short_code(code)
+            }
+            fn update(&self, url: ShortenedUrl) -> Result<()> {
+                if url.short_code().as_str() == "bbbb" {
+                    return Err(UrlShortenerError::StorageUnavailable("down".to_string()));
+                }
+                self.0.update(url)
+            }
+            fn exists(&self, code: &ShortCode) -> Result<bool> {
+                self.0.exists(code)
+            }
+            fn delete(&self, code: &ShortCode) -> Result<()> {
+                self.0.delete(code)
+            }
+            fn list_all(&self) -> Result<Vec<ShortenedUrl>> {
+                self.0.list_all()
+            }
+        }
+
+        let service = UrlShortenerService::new(
+            Arc::new(FailsSecondWrite(InMemoryUrlRepository::new())),
+            Arc::new(RandomIdGenerator::new()),
+        );
+        let entries = [("aaaa", "https://a.example.com"), ("bbbb", "https://b.example.com")];
+        for (code, target) in entries {
+            let url = OriginalUrl::new(target.to_string()).unwrap();
+            service.shorten_url_with_code(url, ShortCode::new(code.to_string()).unwrap()).unwrap();
+        }
+        let a = ShortCode::new("aaaa".to_string()).unwrap();
+        let b = ShortCode::new("bbbb".to_string()).unwrap();
+
+        let result = service.swap_destinations(&a, &b);
+        assert!(matches!(result, Err(UrlShortenerError::StorageUnavailable(_))));
+
+        let stats_a = service.get_statistics(&a).unwrap();
+        assert_eq!(stats_a.original_url().as_str(), "https://a.example.com/");
+    }
+
+    #[test]
+    fn test_case_insensitive_lookup() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(RandomIdGenerator::new());
+        let service = UrlShortenerService::new(repository.clone(), id_generator.clone())
+            .with_case_insensitive(true);
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let stored = ShortCode::new("abc123".to_string()).unwrap();
+        let typed = ShortCode::new("AbC123".to_string()).unwrap();
+
+        service.shorten_url_with_code(url.clone(), stored).unwrap();
+        assert_eq!(service.resolve_short_code(&typed).unwrap(), url);
+        assert_eq!(service.get_statistics(&typed).unwrap().access_count(), 1);
+
+        let result = service.shorten_url_with_code(url.clone(), typed.clone());
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeAlreadyExists(_))));
+
+        let generated = service.shorten_url(url).unwrap();
+        let code = generated.short_code().as_str();
+        assert_eq!(code, code.to_lowercase());
+
+        service.delete_short_code(&typed).unwrap();
+        assert_eq!(repository.list_all().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_case_insensitive_mutations() {
+        let service = create_service().with_case_insensitive(true);
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let other = OriginalUrl::new("https://other.example.com".to_string()).unwrap();
+        service.shorten_url_with_code(url, ShortCode::new("promo".to_string()).unwrap()).unwrap();
+        service.shorten_url_with_code(other, ShortCode::new("sale".to_string()).unwrap()).unwrap();
+        let promo = ShortCode::new("PROMO".to_string()).unwrap();
+        let sale = ShortCode::new("SaLe".to_string()).unwrap();
+
+        let target = OriginalUrl::new("https://new.example.com".to_string()).unwrap();
+        let updated = service.update_target(&promo, target).unwrap();
+        assert_eq!(updated.short_code().as_str(), "promo");
+
+        service.swap_destinations(&promo, &sale).unwrap();
+        let swapped = service.get_statistics(&sale).unwrap();
+        assert_eq!(swapped.original_url().as_str(), "https://new.example.com/");
+
+        service.add_note(&promo, "ops".to_string(), "checked".to_string()).unwrap();
+        assert_eq!(service.list_notes(&promo).unwrap().len(), 1);
+        assert!(service.migrate_code_length(&sale).is_ok());
+    }
+
+    #[test]
+    fn test_case_sensitive_by_default() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let stored = ShortCode::new("abc123".to_string()).unwrap();
+        let typed = ShortCode::new("AbC123".to_string()).unwrap();
+
+        service.shorten_url_with_code(url.clone(), stored).unwrap();
+        let result = service.resolve_short_code(&typed);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+
+        service.shorten_url_with_code(url, typed).unwrap();
+    }
+
+    #[test]
+    fn test_update_target_preserves_stats() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://old.example.com".to_string()).unwrap();
+        let shortened = service.shorten_url(url).unwrap();
+        service.resolve_short_code(shortened.short_code()).unwrap();
+
+        let new_url = OriginalUrl::new("https://new.example.com".to_string()).unwrap();
+        let updated = service.update_target(shortened.short_code(), new_url.clone()).unwrap();
+        assert_eq!(updated.original_url(), &new_url);
+
+        let stats = service.get_statistics(shortened.short_code()).unwrap();
+        assert_eq!(stats.id(), shortened.id());
+        assert_eq!(stats.created_at(), shortened.created_at());
+        assert_eq!(stats.access_count(), 1);
+
+        let resolved = service.resolve_short_code(shortened.short_code()).unwrap();
+        assert_eq!(resolved, new_url);
+    }
+
+    #[test]
+    fn test_update_target_missing_code() {
+        let service = create_service();
+        let missing = ShortCode::new("missing1".to_string()).unwrap();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+
+        let result = service.update_target(&missing, url);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+    }
+
+    #[test]
+    fn test_resolve_templated_expands_code() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com/track?c={code}".to_string()).unwrap();
+        let code = ShortCode::new("beacon1".to_string()).unwrap();
+        service.shorten_url_with_code(url, code.clone()).unwrap();
+
+        let resolved = service.resolve_short_code_templated(&code).unwrap();
+        assert_eq!(resolved.as_str(), "https://example.com/track?c=beacon1");
+
+        // The stored destination keeps its placeholder
+        let stats = service.get_statistics(&code).unwrap();
+        assert_eq!(stats.original_url().as_str(), "https://example.com/track?c={code}");
+        assert_eq!(stats.access_count(), 1);
+    }
+
+    #[test]
+    fn test_resolve_templated_without_placeholders() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com/plain".to_string()).unwrap();
+        let shortened = service.shorten_url(url).unwrap();
+
+        let resolved = service.resolve_short_code_templated(shortened.short_code()).unwrap();
+        assert_eq!(resolved.as_str(), "https://example.com/plain");
+    }
+
+    #[test]
+    fn test_resolve_templated_rejects_unknown_placeholder() {
+        let service = create_service()
+            .with_unknown_placeholder_policy(UnknownPlaceholderPolicy::Reject);
+        let url = OriginalUrl::new("https://example.com/?x={nope}".to_string()).unwrap();
+        let shortened = service.shorten_url(url).unwrap();
+
+        let result = service.resolve_short_code_templated(shortened.short_code());
+        assert!(matches!(result, Err(UrlShortenerError::InvalidUrl(_))));
+    }
+
+    #[test]
+    fn test_read_only_rejects_mutations() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(RandomIdGenerator::new());
+        let writer = UrlShortenerService::new(repository.clone(), id_generator.clone());
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let existing = writer.shorten_url(url.clone()).unwrap();
+        let other = writer.shorten_url(url.clone()).unwrap();
+
+        let replica = UrlShortenerService::new(repository, id_generator).read_only();
+        assert!(replica.is_read_only());
+        let code = existing.short_code();
+
+        assert!(matches!(replica.shorten_url(url.clone()), Err(UrlShortenerError::ReadOnly)));
+        let custom = ShortCode::new("custom".to_string()).unwrap();
+        assert!(matches!(
+            replica.shorten_url_with_code(url, custom),
+            Err(UrlShortenerError::ReadOnly)
+        ));
+        assert!(matches!(replica.resolve_short_code(code), Err(UrlShortenerError::ReadOnly)));
+        assert!(matches!(replica.delete_short_code(code), Err(UrlShortenerError::ReadOnly)));
+        assert!(matches!(
+            replica.swap_destinations(code, other.short_code()),
+            Err(UrlShortenerError::ReadOnly)
+        ));
+
+        // Nothing was written through the replica
+        assert_eq!(writer.get_statistics(code).unwrap().access_count(), 0);
+        assert_eq!(writer.list_all().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_read_only_allows_reads() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(RandomIdGenerator::new());
+        let writer = UrlShortenerService::new(repository.clone(), id_generator.clone());
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let existing = writer.shorten_url(url).unwrap();
+
+        let replica = UrlShortenerService::new(repository, id_generator).read_only();
+
+        let stats = replica.get_statistics(existing.short_code()).unwrap();
+        assert_eq!(stats.short_code(), existing.short_code());
+        assert_eq!(replica.list_all().unwrap().len(), 1);
+    }
+
+    /// Generator with a small fixed keyspace, delegating generation
+    struct SmallKeyspaceGenerator {
+        keyspace: Option<u64>,
+    }
+
+    impl IdGenerator for SmallKeyspaceGenerator {
+        fn generate_id(&self) -> UrlId {
+            RandomIdGenerator::new().generate_id()
+        }
+
+        fn generate_short_code(&self) -> Result<ShortCode> {
+            RandomIdGenerator::new().generate_short_code()
+        }
+
+        fn keyspace(&self) -> Option<u64> {
+            self.keyspace
+        }
+    }
+
+    #[test]
+    fn test_shorten_url_idempotent_reuses_code() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com/same".to_string()).unwrap();
+
+        let first = service.shorten_url_idempotent(url.clone()).unwrap();
+        let second = service.shorten_url_idempotent(url.clone()).unwrap();
+        assert_eq!(first.short_code(), second.short_code());
+        assert_eq!(first.id(), second.id());
+        assert_eq!(service.list_all().unwrap().len(), 1);
+
+        // A plain shorten_url still mints a new code
+        let third = service.shorten_url(url).unwrap();
+        assert_ne!(third.short_code(), first.short_code());
+    }
+
+    #[test]
+    fn test_shorten_url_idempotent_stays_in_prefix() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let tenant = |prefix: &str| {
+            UrlShortenerService::new(repository.clone(), Arc::new(RandomIdGenerator::new()))
+                .with_prefix(prefix.to_string())
+                .unwrap()
+        };
+        let (acme, beta) = (tenant("acme"), tenant("beta"));
+        let url = OriginalUrl::new("https://example.com/shared".to_string()).unwrap();
+
+        let a = acme.shorten_url_idempotent(url.clone()).unwrap();
+        let b = beta.shorten_url_idempotent(url.clone()).unwrap();
+        assert!(a.short_code().as_str().starts_with("acme-"));
+        assert!(b.short_code().as_str().starts_with("beta-"));
+
+        // Each tenant keeps getting its own entry back
+        for _ in 0..5 {
+            assert_eq!(acme.shorten_url_idempotent(url.clone()).unwrap(), a);
+            assert_eq!(beta.shorten_url_idempotent(url.clone()).unwrap(), b);
+        }
+        assert_eq!(repository.list_all().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_shorten_url_idempotent_skips_unresolvable_entries() {
+        let clock = Arc::new(ManualClock::new(UNIX_EPOCH + Duration::from_secs(1_000)));
+        let service = create_service().with_clock(clock.clone());
+        let url = OriginalUrl::new("https://example.com/stale".to_string()).unwrap();
+
+        let disabled = service.shorten_url_idempotent(url.clone()).unwrap();
+        service.disable_short_code(disabled.short_code()).unwrap();
+        let fresh = service.shorten_url_idempotent(url.clone()).unwrap();
+        assert_ne!(fresh.short_code(), disabled.short_code());
+        service.disable_short_code(fresh.short_code()).unwrap();
+
+        let expiring = service.shorten_url_with_expiry(url.clone(), Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(service.shorten_url_idempotent(url.clone()).unwrap(), expiring);
+        clock.advance(Duration::from_secs(61));
+        let replacement = service.shorten_url_idempotent(url).unwrap();
+        assert_ne!(replacement.short_code(), expiring.short_code());
+        assert_eq!(service.list_all().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_shorten_url_idempotent_distinct_urls() {
+        let service = create_service();
+        let a = OriginalUrl::new("https://example.com/a".to_string()).unwrap();
+        let b = OriginalUrl::new("https://example.com/b".to_string()).unwrap();
+
+        let first = service.shorten_url_idempotent(a).unwrap();
+        let second = service.shorten_url_idempotent(b).unwrap();
+        assert_ne!(first.short_code(), second.short_code());
+        assert_eq!(service.list_all().unwrap().len(), 2);
+    }
+
+    #[cfg(feature = "hash")]
+    #[test]
+    fn test_shorten_url_uses_url_derived_code() {
+        use crate::adapters::HashIdGenerator;
+
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(HashIdGenerator::new());
+        let service = UrlShortenerService::new(repository, id_generator.clone());
+
+        let url = OriginalUrl::new("https://example.com/cached".to_string()).unwrap();
+        let expected = id_generator.generate_short_code_for(&url).unwrap();
+
+        let shortened = service.shorten_url(url.clone()).unwrap();
+        assert_eq!(shortened.short_code(), &expected);
+
+        let again = service.shorten_url_idempotent(url.clone()).unwrap();
+        assert_eq!(again.short_code(), &expected);
+
+        let result = service.shorten_url(url);
+        assert!(matches!(result, Err(UrlShortenerError::IdGenerationFailed(_))));
+    }
+
+    /// Always proposes the same code, but can produce random longer ones
+    struct CrowdedGenerator {
+        longer: RandomIdGenerator,
+        lengths: std::sync::Mutex<Vec<usize>>,
+    }
+
+    impl IdGenerator for CrowdedGenerator {
+        fn generate_id(&self) -> UrlId {
+            self.longer.generate_id()
+        }
+
+        fn generate_short_code(&self) -> Result<ShortCode> {
+            ShortCode::new("full".to_string())
+        }
+
+        fn generate_short_code_with_length(&self, length: usize) -> Result<ShortCode> {
+            self.lengths.lock().unwrap().push(length);
+            self.longer.generate_short_code_with_length(length)
+        }
+    }
+
+    #[test]
+    fn test_generation_escalates_length_when_keyspace_is_full() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(CrowdedGenerator {
+            longer: RandomIdGenerator::new(),
+            lengths: std::sync::Mutex::new(Vec::new()),
+        });
+        let service = UrlShortenerService::new(repository, id_generator.clone());
+
+        // The generator's whole keyspace at its own length is one code
+        let first = service
+            .shorten_url(OriginalUrl::new("https://example.com/1".to_string()).unwrap())
+            .unwrap();
+        assert_eq!(first.short_code().as_str(), "full");
+
+        let second = service
+            .shorten_url(OriginalUrl::new("https://example.com/2".to_string()).unwrap())
+            .unwrap();
+        assert_eq!(second.short_code().as_str().len(), 5);
+        assert_eq!(*id_generator.lengths.lock().unwrap(), [5]);
+    }
+
+    #[test]
+    fn test_generation_escalation_ignores_prefix_length() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(CrowdedGenerator {
+            longer: RandomIdGenerator::new(),
+            lengths: std::sync::Mutex::new(Vec::new()),
+        });
+        let service = UrlShortenerService::new(repository, id_generator.clone())
+            .with_prefix("acme".to_string())
+            .unwrap();
+
+        let first = service
+            .shorten_url(OriginalUrl::new("https://example.com/1".to_string()).unwrap())
+            .unwrap();
+        assert_eq!(first.short_code().as_str(), "acme-full");
+
+        // Widening starts from the generator's own length, not "acme-full"
+        let second = service
+            .shorten_url(OriginalUrl::new("https://example.com/2".to_string()).unwrap())
+            .unwrap();
+        assert_eq!(second.short_code().as_str().len(), "acme-".len() + 5);
+        assert_eq!(*id_generator.lengths.lock().unwrap(), [5]);
+    }
+
+    #[test]
+    fn test_generation_escalation_ignores_check_character() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(CrowdedGenerator {
+            longer: RandomIdGenerator::new(),
+            lengths: std::sync::Mutex::new(Vec::new()),
+        });
+        let service = UrlShortenerService::new(repository, id_generator.clone())
+            .with_checksums(true);
+
+        let first = service
+            .shorten_url(OriginalUrl::new("https://example.com/1".to_string()).unwrap())
+            .unwrap();
+        assert_eq!(first.short_code().as_str().len(), 5);
+
+        // One character wider than "full", plus the check character
+        let second = service
+            .shorten_url(OriginalUrl::new("https://example.com/2".to_string()).unwrap())
+            .unwrap();
+        assert_eq!(second.short_code().as_str().len(), 6);
+        assert_eq!(*id_generator.lengths.lock().unwrap(), [5]);
+        assert!(service.resolve_short_code(second.short_code()).is_ok());
+    }
+
+    /// Proposes the same code whatever length is asked for
+    struct StuckGenerator {
+        lengths: std::sync::Mutex<Vec<usize>>,
+    }
+
+    impl IdGenerator for StuckGenerator {
+        fn generate_id(&self) -> UrlId {
+            UrlId::new("stuck".to_string())
+        }
+
+        fn generate_short_code(&self) -> Result<ShortCode> {
+            ShortCode::new("full".to_string())
+        }
+
+        fn generate_short_code_with_length(&self, length: usize) -> Result<ShortCode> {
+            self.lengths.lock().unwrap().push(length);
+            self.generate_short_code()
+        }
+    }
+
+    #[test]
+    fn test_generation_escalation_leaves_room_for_prefix_and_check_character() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(StuckGenerator { lengths: std::sync::Mutex::new(Vec::new()) });
+        let service = UrlShortenerService::new(repository, id_generator.clone())
+            .with_prefix("acme".to_string())
+            .unwrap()
+            .with_checksums(true)
+            .with_max_generation_attempts(1)
+            .unwrap();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        service.shorten_url(url.clone()).unwrap();
+
+        // "acme-" and the check character leave 6 of the 12 characters
+        let result = service.shorten_url(url);
+        assert!(matches!(result, Err(UrlShortenerError::IdGenerationFailed(3))));
+        assert_eq!(*id_generator.lengths.lock().unwrap(), [5, 6]);
+    }
+
+    #[test]
+    fn test_generation_fails_without_length_support() {
+        // Every proposal collides with a taken code, and the generator can't widen
+        let script = vec!["taken".to_string(); 10];
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(ScriptedGenerator::new(script));
+        let service = UrlShortenerService::new(repository, id_generator);
+
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let taken = ShortCode::new("taken".to_string()).unwrap();
+        service.shorten_url_with_code(url.clone(), taken).unwrap();
+
+        let result = service.shorten_url(url);
+        assert!(matches!(result, Err(UrlShortenerError::IdGenerationFailed(10))));
+    }
+
+    /// Hands out short codes from a fixed script
+    struct ScriptedGenerator {
+        codes: std::sync::Mutex<std::collections::VecDeque<String>>,
+    }
+
+    impl ScriptedGenerator {
+        fn new(codes: Vec<String>) -> Self {
+            Self { codes: std::sync::Mutex::new(codes.into()) }
+        }
+    }
+
+    impl IdGenerator for ScriptedGenerator {
+        fn generate_id(&self) -> UrlId {
+            RandomIdGenerator::new().generate_id()
+        }
+
+        fn generate_short_code(&self) -> Result<ShortCode> {
+            let code = self.codes.lock().unwrap().pop_front().expect("script exhausted");
+            ShortCode::new(code)
+        }
+    }
+
+    #[test]
+    fn test_reservation_blocks_other_claims() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let code = ShortCode::new("launch".to_string()).unwrap();
+
+        service.reserve_code(code.clone(), Duration::from_secs(300)).unwrap();
+
+        let result = service.shorten_url_with_code(url.clone(), code.clone());
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeAlreadyExists(_))));
+        let result = service.reserve_code(code.clone(), Duration::from_secs(300));
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeAlreadyExists(_))));
+        assert!(service.list_all().unwrap().is_empty());
+
+        let shortened = service.commit_reservation(code.clone(), url.clone()).unwrap();
+        assert_eq!(service.resolve_short_code(shortened.short_code()).unwrap(), url);
+
+        // Once committed, the code is stored rather than reserved
+        let result = service.commit_reservation(code.clone(), url.clone());
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeAlreadyExists(_))));
+        let result = service.reserve_code(code, Duration::from_secs(300));
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeAlreadyExists(_))));
+    }
+
+    #[test]
+    fn test_reservation_ttl_overflow_rejected() {
+        let service = create_service();
+        let code = ShortCode::new("launch".to_string()).unwrap();
+
+        let result = service.reserve_code(code.clone(), Duration::MAX);
+        assert!(matches!(result, Err(UrlShortenerError::InvalidConfiguration(_))));
+        service.reserve_code(code, Duration::from_secs(300)).unwrap();
+    }
+
+    #[test]
+    fn test_expired_reservation_frees_the_code() {
+        let clock = Arc::new(ManualClock::new(UNIX_EPOCH + Duration::from_secs(1_000)));
+        let service = create_service().with_clock(clock.clone());
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let code = ShortCode::new("launch".to_string()).unwrap();
+
+        service.reserve_code(code.clone(), Duration::from_secs(60)).unwrap();
+        clock.advance(Duration::from_secs(59));
+        assert!(service.shorten_url_with_code(url.clone(), code.clone()).is_err());
+
+        clock.advance(Duration::from_secs(1));
+        let shortened = service.shorten_url_with_code(url, code).unwrap();
+        assert_eq!(shortened.short_code().as_str(), "launch");
+    }
+
+    #[test]
+    fn test_generated_codes_skip_reservations() {
+        let script = vec!["taken1".to_string(), "free12".to_string()];
+        let service = UrlShortenerService::new(
+            Arc::new(InMemoryUrlRepository::new()),
+            Arc::new(ScriptedGenerator::new(script)),
+        );
+        let taken = ShortCode::new("taken1".to_string()).unwrap();
+        service.reserve_code(taken, Duration::from_secs(60)).unwrap();
+
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let shortened = service.shorten_url(url).unwrap();
+        assert_eq!(shortened.short_code().as_str(), "free12");
+    }
+
+    #[test]
+    fn test_reserved_custom_code_rejected() {
+        let service = create_service()
+            .with_reserved_codes(vec!["admin".to_string(), "login".to_string()]);
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+
+        let result = service
+            .shorten_url_with_code(url.clone(), ShortCode::new("admin".to_string()).unwrap());
+        assert!(matches!(result, Err(UrlShortenerError::ReservedShortCode(_))));
+        assert!(service.list_all().unwrap().is_empty());
+
+        // Matching is case-sensitive unless configured otherwise
+        service
+            .shorten_url_with_code(url.clone(), ShortCode::new("Login".to_string()).unwrap())
+            .unwrap();
+        let shortened = service
+            .shorten_url_with_code(url, ShortCode::new("promo".to_string()).unwrap())
+            .unwrap();
+        assert_eq!(shortened.short_code().as_str(), "promo");
+    }
+
+    #[test]
+    fn test_reserved_codes_respect_case_insensitivity() {
+        let service = create_service()
+            .with_case_insensitive(true)
+            .with_reserved_codes(vec!["Admin".to_string()]);
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+
+        let result =
+            service.shorten_url_with_code(url, ShortCode::new("ADMIN".to_string()).unwrap());
+        assert!(matches!(result, Err(UrlShortenerError::ReservedShortCode(_))));
+    }
+
+    #[test]
+    fn test_domain_blocklist() {
+        let service = create_service()
+            .with_domain_blocklist(vec!["Malware.test".to_string()]);
+        let url = |s: &str| OriginalUrl::new(s.to_string()).unwrap();
+
+        let result = service.shorten_url(url("https://malware.test/payload"));
+        assert!(matches!(result, Err(UrlShortenerError::DomainNotAllowed(_))));
+
+        // Subdomains are blocked too, regardless of case
+        let code = ShortCode::new("promo".to_string()).unwrap();
+        let result = service.shorten_url_with_code(url("https://CDN.malware.test/"), code);
+        assert!(matches!(result, Err(UrlShortenerError::DomainNotAllowed(_))));
+        assert!(service.list_all().unwrap().is_empty());
+
+        // Only whole labels match
+        let shortened = service.shorten_url(url("https://notmalware.test/")).unwrap();
+
+        let result = service.update_target(shortened.short_code(), url("https://malware.test/"));
+        assert!(matches!(result, Err(UrlShortenerError::DomainNotAllowed(_))));
+        let stored = service.get_statistics(shortened.short_code()).unwrap();
+        assert_eq!(stored.original_url().as_str(), "https://notmalware.test/");
+    }
+
+    #[test]
+    fn test_find_by_domain() {
+        let service = create_service();
+        for target in [
+            "https://flagged.example/a",
+            "https://FLAGGED.example/b",
+            "https://ok.example/",
+        ] {
+            service.shorten_url(OriginalUrl::new(target.to_string()).unwrap()).unwrap();
+        }
+
+        let found = service.find_by_domain("Flagged.Example").unwrap();
+        assert_eq!(found.len(), 2);
+        assert!(found.iter().all(|url| url.original_url().domain() == Some("flagged.example")));
+        assert!(found.windows(2).all(|pair| pair[0].short_code() < pair[1].short_code()));
+
+        assert!(service.find_by_domain("sub.flagged.example").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_find_by_access_count_range() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+
+        let mut codes = Vec::new();
+        for accesses in [0, 2, 5, 0] {
+            let code = service.shorten_url(url.clone()).unwrap().short_code().clone();
+            for _ in 0..accesses {
+                service.resolve_short_code(&code).unwrap();
+            }
+            codes.push(code);
+        }
+
+        let found = |min: u64, max: u64| -> Vec<ShortCode> {
+            service.find_by_access_count_range(min, max).unwrap()
+                .iter()
+                .map(|url| url.short_code().clone())
+                .collect()
+        };
+        let mut never_clicked = vec![codes[0].clone(), codes[3].clone()];
+        never_clicked.sort();
+        assert_eq!(found(0, 0), never_clicked);
+        assert_eq!(found(1, 4), [codes[1].clone()]);
+        assert_eq!(found(2, 5).len(), 2);
+        assert!(found(6, u64::MAX).is_empty());
+    }
+
+    #[test]
+    fn test_domain_allowlist() {
+        let service = create_service()
+            .with_domain_allowlist(vec!["example.com".to_string()])
+            .with_domain_blocklist(vec!["private.example.com".to_string()]);
+        let url = |s: &str| OriginalUrl::new(s.to_string()).unwrap();
+
+        assert!(service.shorten_url(url("https://example.com/a")).is_ok());
+        assert!(service.shorten_url(url("https://docs.EXAMPLE.com/b")).is_ok());
+
+        for rejected in [
+            "https://example.org/",
+            "https://badexample.com/",
+            "https://private.example.com/",
+            "http://127.0.0.1/",
+        ] {
+            let result = service.shorten_url(url(rejected));
+            assert!(matches!(result, Err(UrlShortenerError::DomainNotAllowed(_))), "{rejected}");
+        }
+
+        let urls = vec![url("https://example.com/c"), url("https://a.io/")];
+        let results = service.shorten_batch(urls);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(UrlShortenerError::DomainNotAllowed(_))));
+        assert_eq!(service.count().unwrap(), 3);
+    }
+
+    /// Replace the character at `index` with the next one in the alphabet
+    fn corrupt(code: &ShortCode, index: usize) -> ShortCode {
+        let mut chars: Vec<char> = code.as_str().chars().collect();
+        chars[index] = match chars[index] {
+            'z' => 'a',
+            'Z' => 'A',
+            '9' => '0',
+            c => (c as u8 + 1) as char,
+        };
+        ShortCode::new(chars.into_iter().collect()).unwrap()
+    }
+
+    #[test]
+    fn test_checksummed_codes() {
+        let service = create_service().with_checksums(true);
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+
+        let generated = service.shorten_url(url.clone()).unwrap();
+        assert_eq!(generated.short_code().as_str().len(), 7);
+        assert_eq!(service.resolve_short_code(generated.short_code()).unwrap(), url);
+
+        let custom = service
+            .shorten_url_with_code(url.clone(), ShortCode::new("promo".to_string()).unwrap())
+            .unwrap();
+        assert!(custom.short_code().as_str().starts_with("promo"));
+        assert_eq!(service.resolve_short_code(custom.short_code()).unwrap(), url);
+
+        // The unchecked code is caught as well as a corrupted one
+        let unchecked = ShortCode::new("promo".to_string()).unwrap();
+        let result = service.resolve_short_code(&unchecked);
+        assert!(matches!(result, Err(UrlShortenerError::ChecksumMismatch(_))));
+
+        for index in 0..generated.short_code().as_str().len() {
+            let typo = corrupt(generated.short_code(), index);
+            let result = service.resolve_without_tracking(&typo);
+            assert!(matches!(result, Err(UrlShortenerError::ChecksumMismatch(_))), "{typo}");
+        }
+        assert_eq!(service.get_statistics(generated.short_code()).unwrap().access_count(), 1);
+    }
+
+    #[test]
+    fn test_checksummed_codes_with_prefix_and_case_insensitivity() {
+        let service = create_service()
+            .with_checksums(true)
+            .with_case_insensitive(true)
+            .with_prefix("acme".to_string())
+            .unwrap();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+
+        let shortened = service.shorten_url(url.clone()).unwrap();
+        let code = shortened.short_code().as_str();
+        assert!(code.starts_with("acme-"));
+        assert_eq!(code, code.to_lowercase());
+
+        let upper = ShortCode::new(code.to_uppercase()).unwrap();
+        assert_eq!(service.resolve_short_code(&upper).unwrap(), url);
+
+        let local = ShortCode::new(code["acme-".len()..].to_string()).unwrap();
+        assert_eq!(service.resolve_short_code(&local).unwrap(), url);
+
+        let typo = corrupt(shortened.short_code(), 6);
+        let result = service.resolve_short_code(&typo);
+        assert!(matches!(result, Err(UrlShortenerError::ChecksumMismatch(_))));
+    }
+
+    #[test]
+    fn test_prefixed_codes() {
+        let service = create_service().with_prefix("acme".to_string()).unwrap();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+
+        let generated = service.shorten_url(url.clone()).unwrap();
+        let (prefix, local) = generated.short_code().as_str().split_once('-').unwrap();
+        assert_eq!(prefix, "acme");
+        assert_eq!(local.len(), 6);
+
+        let custom = service
+            .shorten_url_with_code(url, ShortCode::new("promo".to_string()).unwrap())
+            .unwrap();
+        assert_eq!(custom.short_code().as_str(), "acme-promo");
+
+        // Lookups accept the code with or without the prefix
+        for code in ["promo", "acme-promo"] {
+            let code = ShortCode::new(code.to_string()).unwrap();
+            assert_eq!(service.get_statistics(&code).unwrap().short_code(), custom.short_code());
+            assert!(service.resolve_short_code(&code).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_prefixed_codes_accepted_by_every_method() {
+        let service = create_service().with_prefix("acme".to_string()).unwrap();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let promo = ShortCode::new("promo".to_string()).unwrap();
+        let sale = ShortCode::new("sale".to_string()).unwrap();
+        service.shorten_url_with_code(url, promo.clone()).unwrap();
+        let other = OriginalUrl::new("https://other.example.com".to_string()).unwrap();
+        service.shorten_url_with_code(other, sale.clone()).unwrap();
+
+        service.add_note(&promo, "ops".to_string(), "launch".to_string()).unwrap();
+        assert_eq!(service.list_notes(&promo).unwrap()[0].text(), "launch");
+
+        let target = OriginalUrl::new("https://new.example.com".to_string()).unwrap();
+        let updated = service.update_target(&promo, target).unwrap();
+        assert_eq!(updated.short_code().as_str(), "acme-promo");
+
+        service.swap_destinations(&promo, &sale).unwrap();
+        let swapped = service.get_statistics(&sale).unwrap();
+        assert_eq!(swapped.original_url().as_str(), "https://new.example.com/");
+
+        let migrated = service.migrate_code_length(&promo).unwrap();
+        assert!(migrated.as_str().starts_with("acme-"));
+        let original = service.get_statistics(&migrated).unwrap().original_url().clone();
+        assert_eq!(original.as_str(), "https://other.example.com/");
+    }
+
+    #[test]
+    fn test_prefixes_isolate_tenants() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(RandomIdGenerator::new());
+        let acme = UrlShortenerService::new(repository.clone(), id_generator.clone())
+            .with_prefix("acme".to_string())
+            .unwrap();
+        let other = UrlShortenerService::new(repository, id_generator)
+            .with_prefix("other".to_string())
+            .unwrap();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let code = ShortCode::new("promo".to_string()).unwrap();
+
+        acme.shorten_url_with_code(url.clone(), code.clone()).unwrap();
+        other.shorten_url_with_code(url.clone(), code.clone()).unwrap();
+        assert_eq!(acme.list_all().unwrap().len(), 2);
+
+        let foreign = ShortCode::new("other-promo".to_string()).unwrap();
+        assert!(matches!(
+            acme.resolve_short_code(&foreign),
+            Err(UrlShortenerError::ShortCodeNotFound(_))
+        ));
+        assert!(matches!(
+            acme.delete_short_code(&foreign),
+            Err(UrlShortenerError::ShortCodeNotFound(_))
+        ));
+        assert!(matches!(
+            acme.shorten_url_with_code(url, ShortCode::new("other-new".to_string()).unwrap()),
+            Err(UrlShortenerError::InvalidShortCode(_))
+        ));
+
+        acme.delete_short_code(&code).unwrap();
+        assert!(other.resolve_short_code(&code).is_ok());
+    }
+
+    #[test]
+    fn test_prefix_with_reserved_codes() {
+        let service = create_service()
+            .with_prefix("acme".to_string())
+            .unwrap()
+            .with_reserved_codes(vec!["admin".to_string()]);
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+
+        for code in ["admin", "acme-admin"] {
+            let result = service
+                .shorten_url_with_code(url.clone(), ShortCode::new(code.to_string()).unwrap());
+            assert!(matches!(result, Err(UrlShortenerError::ReservedShortCode(_))));
+        }
+    }
+
+    #[test]
+    fn test_invalid_prefix_rejected() {
+        for prefix in ["", "ac-me", "ac_me", "toolongprefix"] {
+            let result = create_service().with_prefix(prefix.to_string());
+            assert!(
+                matches!(result, Err(UrlShortenerError::InvalidShortCode(_))),
+                "{prefix:?} should be rejected"
+            );
+        }
+        assert!(create_service().with_prefix("abcdefg".to_string()).is_ok());
+    }
+
+    #[test]
+    fn test_suggest_available_codes() {
+        let service = create_service().with_reserved_codes(vec!["promo2".to_string()]);
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        for code in ["promo", "promo1"] {
+            service
+                .shorten_url_with_code(url.clone(), ShortCode::new(code.to_string()).unwrap())
+                .unwrap();
+        }
+
+        let desired = ShortCode::new("promo".to_string()).unwrap();
+        let suggestions = service.suggest_available_codes(&desired, 3).unwrap();
+        let codes: Vec<_> = suggestions.iter().map(|c| c.as_str()).collect();
+        assert_eq!(codes, ["promo3", "promo4", "promo5"]);
+
+        for code in &suggestions {
+            assert!(ShortCode::is_valid(code.as_str()));
+            service.shorten_url_with_code(url.clone(), code.clone()).unwrap();
+        }
+        assert!(service.suggest_available_codes(&desired, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_suggestions_stay_within_max_length() {
+        let service = create_service().with_prefix("acme".to_string()).unwrap();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let desired = ShortCode::new("summer2".to_string()).unwrap();
+        service.shorten_url_with_code(url, desired.clone()).unwrap();
+
+        let suggestions = service.suggest_available_codes(&desired, 12).unwrap();
+        assert_eq!(suggestions.len(), 12);
+        assert_eq!(suggestions[0].as_str(), "acme-summer1");
+        assert_eq!(suggestions[1].as_str(), "acme-summer3");
+        assert_eq!(suggestions[11].as_str(), "acme-summe13");
+        assert!(suggestions.iter().all(|c| c.as_str().len() <= ShortCode::MAX_LENGTH));
+    }
+
+    #[test]
+    fn test_preferred_code_length() {
+        let service = create_service().with_preferred_code_length(Some(8)).unwrap();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+
+        for _ in 0..10 {
+            assert_eq!(service.shorten_url(url.clone()).unwrap().short_code().as_str().len(), 8);
+        }
+
+        for length in [ShortCode::MIN_LENGTH - 1, ShortCode::MAX_LENGTH + 1] {
+            let result = create_service().with_preferred_code_length(Some(length));
+            assert!(matches!(result, Err(UrlShortenerError::InvalidShortCode(_))));
+        }
+    }
+
+    #[test]
+    fn test_preferred_code_length_needs_generator_support() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let generator = Arc::new(ScriptedGenerator::new(vec!["fixed1".to_string()]));
+        let service = UrlShortenerService::new(repository, generator)
+            .with_preferred_code_length(Some(8))
+            .unwrap();
+
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let result = service.shorten_url(url);
+        assert!(matches!(result, Err(UrlShortenerError::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_collision_retry_with_seeded_generator() {
+        use rand::SeedableRng;
+        use rand::rngs::StdRng;
+
+        let seeded = || RandomIdGenerator::new().with_rng(StdRng::seed_from_u64(42));
+        // Replays the draws the service makes below
+        let preview = seeded();
+        preview.generate_id();
+        let first = preview.generate_short_code().unwrap();
+        let second = preview.generate_short_code().unwrap();
+
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let service = UrlShortenerService::new(repository, Arc::new(seeded()));
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        service.shorten_url_with_code(url.clone(), first.clone()).unwrap();
+
+        // The service draws `first`, finds it taken, and retries
+        let shortened = service.shorten_url(url).unwrap();
+        assert_eq!(shortened.short_code(), &second);
+    }
+
+    #[test]
+    fn test_generator_skips_reserved_codes() {
+        let script = vec!["admin".to_string(), "gen12345".to_string()];
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(ScriptedGenerator::new(script));
+        let service = UrlShortenerService::new(repository, id_generator)
+            .with_reserved_codes(vec!["admin".to_string()]);
+
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let shortened = service.shorten_url(url).unwrap();
+        assert_eq!(shortened.short_code().as_str(), "gen12345");
+    }
+
+    #[test]
+    fn test_shorten_batch_isolates_failures() {
+        // The second URL only ever draws the code already claimed below
+        let mut script = vec!["batch001".to_string()];
+        script.extend(std::iter::repeat_n("taken".to_string(), 10));
+        script.push("batch002".to_string());
+
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(ScriptedGenerator::new(script));
+        let service = UrlShortenerService::new(repository.clone(), id_generator);
+
+        let url = OriginalUrl::new("https://example.com/custom".to_string()).unwrap();
+        let taken = ShortCode::new("taken".to_string()).unwrap();
+        service.shorten_url_with_code(url, taken).unwrap();
+
+        let urls: Vec<OriginalUrl> = (0..3)
+            .map(|i| OriginalUrl::new(format!("https://example.com/{}", i)).unwrap())
+            .collect();
+        let results = service.shorten_batch(urls);
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().short_code().as_str(), "batch001");
+        assert!(matches!(results[1], Err(UrlShortenerError::IdGenerationFailed(_))));
+        assert_eq!(results[2].as_ref().unwrap().short_code().as_str(), "batch002");
+
+        // Successful entries are persisted despite the failure in between
+        assert_eq!(repository.len(), 3);
+        let code = ShortCode::new("batch002".to_string()).unwrap();
+        let resolved = service.resolve_short_code(&code).unwrap();
+        assert_eq!(resolved.as_str(), "https://example.com/2");
+        assert_eq!(service.created_last(Duration::from_secs(60)), 3);
+    }
+
+    #[test]
+    fn test_shorten_batch_read_only() {
+        let service = create_service().read_only();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+
+        let results = service.shorten_batch(vec![url.clone(), url]);
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| matches!(r, Err(UrlShortenerError::ReadOnly))));
+    }
+
+    #[test]
+    fn test_birthday_bound() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(SmallKeyspaceGenerator { keyspace: Some(1_000_000) });
+        let service = UrlShortenerService::new(repository, id_generator);
+
+        // sqrt(2 * 1_000_000 * ln(1 / 0.99)) ≈ 141.78
+        assert_eq!(service.birthday_bound().unwrap(), Some(141));
+
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        service.shorten_url(url).unwrap();
+        assert_eq!(service.birthday_bound().unwrap(), Some(140));
+    }
+
+    #[test]
+    fn test_birthday_bound_random_generator() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(RandomIdGenerator::with_length(4));
+        let service = UrlShortenerService::new(repository, id_generator);
+
+        // sqrt(2 * 62^4 * ln(1 / 0.99)) ≈ 544.99
+        assert_eq!(service.birthday_bound().unwrap(), Some(544));
+    }
+
+    #[test]
+    fn test_birthday_bound_unknown_keyspace() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(SmallKeyspaceGenerator { keyspace: None });
+        let service = UrlShortenerService::new(repository, id_generator);
+
+        assert_eq!(service.birthday_bound().unwrap(), None);
+    }
+
+    #[test]
+    fn test_add_and_list_notes() {
+        let clock = Arc::new(ManualClock::new(UNIX_EPOCH + Duration::from_secs(1_000)));
+        let service = create_service().with_clock(clock.clone());
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let shortened = service.shorten_url(url).unwrap();
+        let code = shortened.short_code();
+
+        service.add_note(code, "alice".to_string(), "First".to_string()).unwrap();
+        clock.advance(Duration::from_secs(60));
+        service.add_note(code, "bob".to_string(), "Second".to_string()).unwrap();
+
+        let notes = service.list_notes(code).unwrap();
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].author(), "alice");
+        assert_eq!(notes[0].text(), "First");
+        assert_eq!(notes[0].at(), UNIX_EPOCH + Duration::from_secs(1_000));
+        assert_eq!(notes[1].author(), "bob");
+        assert_eq!(notes[1].text(), "Second");
+        assert_eq!(notes[1].at(), UNIX_EPOCH + Duration::from_secs(1_060));
+    }
+
+    #[test]
+    fn test_add_note_enforces_limit() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let shortened = service.shorten_url(url).unwrap();
+        let code = shortened.short_code();
 
-        let result = service.shorten_url_with_code(url, code);
-        assert!(result.is_ok());
+        for i in 0..ShortenedUrl::MAX_NOTES {
+            service.add_note(code, "alice".to_string(), format!("note {}", i)).unwrap();
+        }
 
-        let shortened = result.unwrap();
-        assert_eq!(shortened.short_code().as_str(), "custom");
+        let result = service.add_note(code, "alice".to_string(), "overflow".to_string());
+        assert!(matches!(result, Err(UrlShortenerError::InvalidNote(_))));
+        assert_eq!(service.list_notes(code).unwrap().len(), ShortenedUrl::MAX_NOTES);
     }
 
     #[test]
-    fn test_custom_code_already_exists() {
+    fn test_add_note_unknown_code() {
         let service = create_service();
-        let url1 = OriginalUrl::new("https://example1.com".to_string()).unwrap();
-        let url2 = OriginalUrl::new("https://example2.com".to_string()).unwrap();
-        let code = ShortCode::new("dupe12".to_string()).unwrap();
+        let code = ShortCode::new("missing1".to_string()).unwrap();
 
-        service.shorten_url_with_code(url1, code.clone()).unwrap();
+        let result = service.add_note(&code, "alice".to_string(), "text".to_string());
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+    }
 
-        let result = service.shorten_url_with_code(url2, code);
-        assert!(result.is_err());
-        assert!(matches!(result, Err(UrlShortenerError::ShortCodeAlreadyExists(_))));
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_creation_hour_histogram() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let id_generator = Arc::new(RandomIdGenerator::new());
+        let service = UrlShortenerService::new(repository.clone(), id_generator);
+
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        // 1970-01-02 at 03:15, 03:45 and 22:00 UTC
+        let day = 86_400;
+        let times = [day + 3 * 3600 + 900, day + 3 * 3600 + 2700, day + 22 * 3600];
+        for (i, secs) in times.iter().enumerate() {
+            let code = ShortCode::new(format!("hour{}", i)).unwrap();
+            repository.save(ShortenedUrl::with_created_at(
+                UrlId::new(format!("id{}", i)),
+                code,
+                url.clone(),
+                UNIX_EPOCH + Duration::from_secs(*secs),
+            )).unwrap();
+        }
+
+        let histogram = service.creation_hour_histogram().unwrap();
+        assert_eq!(histogram[3], 2);
+        assert_eq!(histogram[22], 1);
+        assert_eq!(histogram.iter().sum::<u64>(), 3);
+
+        // Shifted two hours east, 22:00 UTC falls on midnight
+        let cet = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+        let histogram = service.creation_hour_histogram_in(&cet).unwrap();
+        assert_eq!(histogram[5], 2);
+        assert_eq!(histogram[0], 1);
     }
 
+    #[cfg(feature = "chrono")]
     #[test]
-    fn test_resolve_short_code() {
+    fn test_access_timeseries_zero_fills_gaps() {
+        use chrono::NaiveDate;
+
+        let clock = Arc::new(ManualClock::new(UNIX_EPOCH + Duration::from_secs(2 * 86_400)));
+        let service = create_service().with_clock(clock.clone());
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let code = service.shorten_url(url).unwrap().short_code().clone();
+
+        // Two accesses on 1970-01-03 and one on 1970-01-05
+        service.resolve_short_code(&code).unwrap();
+        service.resolve_short_code(&code).unwrap();
+        clock.advance(Duration::from_secs(2 * 86_400));
+        service.resolve_short_code(&code).unwrap();
+
+        let date = |day| NaiveDate::from_ymd_opt(1970, 1, day).unwrap();
+        let series = service.access_timeseries(&code, date(1), date(6)).unwrap();
+        assert_eq!(
+            series,
+            [(date(1), 0), (date(2), 0), (date(3), 2), (date(4), 0), (date(5), 1), (date(6), 0)]
+        );
+        assert_eq!(service.access_timeseries(&code, date(3), date(3)).unwrap(), [(date(3), 2)]);
+
+        let result = service.access_timeseries(&code, date(6), date(1));
+        assert!(matches!(result, Err(UrlShortenerError::InvalidConfiguration(_))));
+        type Service = UrlShortenerService<InMemoryUrlRepository, RandomIdGenerator>;
+        let max = Service::MAX_TIMESERIES_DAYS;
+        let last = date(1) + chrono::Days::new(max as u64 - 1);
+        let series = service.access_timeseries(&code, date(1), last).unwrap();
+        assert_eq!(series.len() as i64, max);
+        let result = service.access_timeseries(&code, date(1), last + chrono::Days::new(1));
+        assert!(matches!(result, Err(UrlShortenerError::InvalidConfiguration(_))));
+        let result = service.access_timeseries(&code, NaiveDate::MIN, NaiveDate::MAX);
+        assert!(matches!(result, Err(UrlShortenerError::InvalidConfiguration(_))));
+        let missing = ShortCode::new("missing1".to_string()).unwrap();
+        let result = service.access_timeseries(&missing, date(1), date(6));
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+    }
+
+    #[test]
+    fn test_resolve_default() {
         let service = create_service();
-        let url = OriginalUrl::new("https://example.com/test".to_string()).unwrap();
+        assert_eq!(service.resolve_default(), None);
+
+        let landing = OriginalUrl::new("https://example.com/welcome".to_string()).unwrap();
+        let service = service.with_default_destination(landing.clone());
+        assert_eq!(service.resolve_default(), Some(landing));
+    }
+
+    #[test]
+    fn test_access_gini_empty() {
+        let service = create_service();
+        assert_eq!(service.access_gini().unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_access_gini_even_distribution() {
+        let service = create_service();
+        for i in 0..4 {
+            let url = OriginalUrl::new(format!("https://example{}.com", i)).unwrap();
+            let shortened = service.shorten_url(url).unwrap();
+            for _ in 0..3 {
+                service.resolve_short_code(shortened.short_code()).unwrap();
+            }
+        }
+
+        assert!(service.access_gini().unwrap().abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_access_gini_skewed_distribution() {
+        let service = create_service();
+        let mut codes = Vec::new();
+        for i in 0..50 {
+            let url = OriginalUrl::new(format!("https://example{}.com", i)).unwrap();
+            codes.push(service.shorten_url(url).unwrap().short_code().clone());
+        }
+        for _ in 0..10 {
+            service.resolve_short_code(&codes[0]).unwrap();
+        }
+
+        // All clicks on one of n links gives (n - 1) / n
+        let gini = service.access_gini().unwrap();
+        assert!((gini - 49.0 / 50.0).abs() < 1e-9, "gini was {}", gini);
+    }
+
+    #[test]
+    fn test_scheduled_activation() {
+        let launch = UNIX_EPOCH + Duration::from_secs(10_000);
+        let clock = Arc::new(ManualClock::new(launch - Duration::from_secs(60)));
+        let service = create_service().with_clock(clock.clone());
+
+        let url = OriginalUrl::new("https://example.com/launch".to_string()).unwrap();
+        let shortened = service.shorten_url_scheduled(url, launch).unwrap();
+        let code = shortened.short_code();
+
+        let result = service.resolve_short_code(code);
+        assert!(matches!(result, Err(UrlShortenerError::NotYetActive(_))));
+        assert_eq!(service.get_statistics(code).unwrap().access_count(), 0);
+
+        clock.set(launch);
+        let resolved = service.resolve_short_code(code).unwrap();
+        assert_eq!(resolved.as_str(), "https://example.com/launch");
+        assert_eq!(service.get_statistics(code).unwrap().access_count(), 1);
+    }
+
+    #[test]
+    fn test_expiry() {
+        let start = UNIX_EPOCH + Duration::from_secs(10_000);
+        let clock = Arc::new(ManualClock::new(start));
+        let service = create_service().with_clock(clock.clone());
+
+        let url = OriginalUrl::new("https://example.com/sale".to_string()).unwrap();
+        let shortened = service.shorten_url_with_expiry(url, Duration::from_secs(60)).unwrap();
+        let code = shortened.short_code();
+        assert_eq!(shortened.expires_at(), Some(start + Duration::from_secs(60)));
+
+        clock.advance(Duration::from_secs(59));
+        service.resolve_short_code(code).unwrap();
+
+        clock.advance(Duration::from_secs(1));
+        let result = service.resolve_short_code(code);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeExpired(_))));
+
+        // Expired entries remain inspectable
+        let stats = service.get_statistics(code).unwrap();
+        assert_eq!(stats.access_count(), 1);
+        assert!(stats.is_expired_at(clock.now()));
+
+        let url = OriginalUrl::new("https://example.com/forever".to_string()).unwrap();
+        let forever = service.shorten_url_with_expiry(url, Duration::MAX).unwrap();
+        assert_eq!(forever.expires_at(), None);
+        assert!(service.resolve_short_code(forever.short_code()).is_ok());
+    }
+
+    #[test]
+    fn test_non_expiring_urls_unaffected() {
+        let clock = Arc::new(ManualClock::new(UNIX_EPOCH + Duration::from_secs(10_000)));
+        let service = create_service().with_clock(clock.clone());
+
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let shortened = service.shorten_url(url).unwrap();
+        assert_eq!(shortened.expires_at(), None);
+
+        clock.advance(Duration::from_secs(365 * 24 * 3600));
+        service.resolve_short_code(shortened.short_code()).unwrap();
+    }
+
+    #[test]
+    fn test_statistics_report_last_access() {
+        let start = UNIX_EPOCH + Duration::from_secs(10_000);
+        let clock = Arc::new(ManualClock::new(start));
+        let service = create_service().with_clock(clock.clone());
+
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let shortened = service.shorten_url(url).unwrap();
+        let code = shortened.short_code();
+        assert_eq!(service.get_statistics(code).unwrap().last_accessed_at(), None);
+
+        service.resolve_short_code(code).unwrap();
+        clock.advance(Duration::from_secs(90));
+        service.resolve_short_code(code).unwrap();
+
+        let stats = service.get_statistics(code).unwrap();
+        assert_eq!(stats.access_count(), 2);
+        assert_eq!(stats.last_accessed_at(), Some(start + Duration::from_secs(90)));
+    }
+
+    #[test]
+    fn test_warm_cache_does_not_count() {
+        let service = create_service();
+        let mut expected = Vec::new();
+        for i in 0..3 {
+            let url = OriginalUrl::new(format!("https://example{}.com", i)).unwrap();
+            let shortened = service.shorten_url(url).unwrap();
+            expected.push((shortened.short_code().clone(), shortened.original_url().clone()));
+        }
+
+        let mut pairs = service.warm_cache().unwrap();
+        pairs.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+        expected.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+        assert_eq!(pairs, expected);
+
+        for url in service.list_all().unwrap() {
+            assert_eq!(url.access_count(), 0);
+        }
+    }
+
+    #[test]
+    fn test_build_redirect_full_without_expiry() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com/page".to_string()).unwrap();
         let shortened = service.shorten_url(url).unwrap();
 
+        let response = service.build_redirect_full(shortened.short_code(), SystemTime::now())
+            .unwrap();
+        assert_eq!(response.status(), 302);
+        assert_eq!(response.location(), "https://example.com/page");
+        // DEFAULT_REDIRECT_MAX_AGE
+        assert_eq!(response.cache_control_seconds(), 300);
+        assert_eq!(response.expires_at(), None);
+
+        // The access is counted like a resolve
+        let stats = service.get_statistics(shortened.short_code()).unwrap();
+        assert_eq!(stats.access_count(), 1);
+
+        let service = service
+            .with_redirect_kind(RedirectKind::Permanent)
+            .with_redirect_max_age(Duration::from_secs(86_400));
+        let response = service.build_redirect_full(shortened.short_code(), SystemTime::now())
+            .unwrap();
+        assert_eq!(response.status(), 301);
+        assert_eq!(response.cache_control_seconds(), 86_400);
+    }
+
+    #[test]
+    fn test_build_redirect_full_capped_to_remaining_lifetime() {
+        let start = UNIX_EPOCH + Duration::from_secs(1_000);
+        let clock = Arc::new(ManualClock::new(start));
+        let service = create_service().with_clock(clock);
+        let url = OriginalUrl::new("https://example.com/sale".to_string()).unwrap();
+        let shortened = service.shorten_url_with_expiry(url, Duration::from_secs(60)).unwrap();
+        let code = shortened.short_code();
+
+        let response = service.build_redirect_full(code, start + Duration::from_secs(20)).unwrap();
+        assert_eq!(response.cache_control_seconds(), 40);
+        assert_eq!(response.expires_at(), Some(start + Duration::from_secs(60)));
+
+        // A default shorter than the remaining lifetime still applies
+        let service = service.with_redirect_max_age(Duration::from_secs(10));
+        let response = service.build_redirect_full(code, start + Duration::from_secs(20)).unwrap();
+        assert_eq!(response.cache_control_seconds(), 10);
+
+        let result = service.build_redirect_full(code, start + Duration::from_secs(60));
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeExpired(_))));
+    }
+
+    #[test]
+    fn test_canonical_output() {
+        let raw = "ssh://Git.Example.com:22/repo".to_string();
+        let url = OriginalUrl::new_with_schemes(raw, &["ssh"]).unwrap();
+
+        let service = create_service();
+        let shortened = service.shorten_url(url.clone()).unwrap();
         let resolved = service.resolve_short_code(shortened.short_code()).unwrap();
-        assert_eq!(resolved.as_str(), "https://example.com/test");
+        assert_eq!(resolved, url);
+
+        let service = create_service().with_canonical_output(true);
+        let shortened = service.shorten_url(url.clone()).unwrap();
+        let resolved = service.resolve_short_code(shortened.short_code()).unwrap();
+        assert_eq!(resolved.as_str(), "ssh://git.example.com/repo");
+        let resolved = service.resolve_without_tracking(shortened.short_code()).unwrap();
+        assert_eq!(resolved.as_str(), "ssh://git.example.com/repo");
+
+        let stats = service.get_statistics(shortened.short_code()).unwrap();
+        assert_eq!(stats.original_url(), &url);
     }
 
     #[test]
-    fn test_resolve_increments_access_count() {
+    fn test_list_after_pages_without_gaps_or_duplicates() {
+        let service = create_service();
+        for i in 0..7 {
+            let url = OriginalUrl::new(format!("https://example{}.com", i)).unwrap();
+            let code = ShortCode::new(format!("code{}", i * 2)).unwrap();
+            service.shorten_url_with_code(url, code).unwrap();
+        }
+
+        let mut seen = Vec::new();
+        let (page, cursor) = service.list_after(None, 3).unwrap();
+        assert_eq!(page.len(), 3);
+        seen.extend(page.into_iter().map(|url| url.short_code().clone()));
+
+        // Insert one entry before and one after the cursor between pages
+        let url = OriginalUrl::new("https://late.example.com".to_string()).unwrap();
+        service.shorten_url_with_code(url.clone(), ShortCode::new("code1".to_string()).unwrap()).unwrap();
+        service.shorten_url_with_code(url, ShortCode::new("code9".to_string()).unwrap()).unwrap();
+
+        let mut cursor = cursor;
+        while cursor.is_some() {
+            let (page, next) = service.list_after(cursor, 3).unwrap();
+            seen.extend(page.into_iter().map(|url| url.short_code().clone()));
+            cursor = next;
+        }
+
+        let codes: Vec<&str> = seen.iter().map(|c| c.as_str()).collect();
+        assert_eq!(
+            codes,
+            vec!["code0", "code10", "code12", "code2", "code4", "code6", "code8", "code9"]
+        );
+    }
+
+    #[test]
+    fn test_list_after_last_page_has_no_cursor() {
+        let service = create_service();
+        for i in 0..2 {
+            let url = OriginalUrl::new(format!("https://example{}.com", i)).unwrap();
+            service.shorten_url(url).unwrap();
+        }
+
+        let (page, cursor) = service.list_after(None, 2).unwrap();
+        assert_eq!(page.len(), 2);
+        assert_eq!(cursor, None);
+
+        let (page, cursor) = service.list_after(None, 10).unwrap();
+        assert_eq!(page.len(), 2);
+        assert_eq!(cursor, None);
+    }
+
+    #[test]
+    fn test_list_after_rejects_zero_limit() {
         let service = create_service();
         let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
         let shortened = service.shorten_url(url).unwrap();
-        let code = shortened.short_code().clone();
 
-        // Initial access count should be 0
-        let stats = service.get_statistics(&code).unwrap();
-        assert_eq!(stats.access_count(), 0);
+        // An empty page with no cursor would read as the end of the listing
+        for cursor in [None, Some(ShortCode::new("aaaa".to_string()).unwrap())] {
+            let result = service.list_after(cursor, 0);
+            assert!(matches!(result, Err(UrlShortenerError::InvalidConfiguration(_))));
+        }
+        assert_eq!(service.list_after(None, 1).unwrap().0[0].short_code(), shortened.short_code());
+    }
 
-        // Resolve once
-        service.resolve_short_code(&code).unwrap();
+    #[cfg(feature = "signing")]
+    #[test]
+    fn test_signed_link_round_trip() {
+        let service = create_service();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let shortened = service.shorten_url(url).unwrap();
 
-        // Access count should be 1
-        let stats = service.get_statistics(&code).unwrap();
-        assert_eq!(stats.access_count(), 1);
+        let token = service.signed_link(shortened.short_code(), b"secret").unwrap();
+        let verified = service.verify_signed_link(&token, b"secret").unwrap();
+        assert_eq!(&verified, shortened.short_code());
+    }
 
-        // Resolve again
-        service.resolve_short_code(&code).unwrap();
+    #[cfg(feature = "signing")]
+    #[test]
+    fn test_signed_link_with_prefix() {
+        let service = create_service().with_prefix("acme".to_string()).unwrap();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let code = ShortCode::new("promo".to_string()).unwrap();
+        service.shorten_url_with_code(url, code.clone()).unwrap();
 
-        // Access count should be 2
-        let stats = service.get_statistics(&code).unwrap();
-        assert_eq!(stats.access_count(), 2);
+        let token = service.signed_link(&code, b"secret").unwrap();
+        let verified = service.verify_signed_link(&token, b"secret").unwrap();
+        assert_eq!(verified.as_str(), "acme-promo");
+        assert!(service.resolve_short_code(&verified).is_ok());
     }
 
+    #[cfg(feature = "signing")]
     #[test]
-    fn test_resolve_nonexistent_code() {
+    fn test_signed_link_tampered() {
         let service = create_service();
-        let code = ShortCode::new("notfound".to_string()).unwrap();
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let code = ShortCode::new("promo1".to_string()).unwrap();
+        service.shorten_url_with_code(url, code.clone()).unwrap();
 
-        let result = service.resolve_short_code(&code);
-        assert!(result.is_err());
-        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+        let token = service.signed_link(&code, b"secret").unwrap();
+        let tampered = token.replacen("promo1", "promo2", 1);
+
+        let result = service.verify_signed_link(&tampered, b"secret");
+        assert!(matches!(result, Err(UrlShortenerError::InvalidSignature)));
     }
 
     #[test]
-    fn test_delete_short_code() {
+    fn test_resolve_templated_within_length_cap() {
+        let service = create_service().with_max_expanded_url_length(40);
+        let url = OriginalUrl::new("https://example.com/?c={code}".to_string()).unwrap();
+        let code = ShortCode::new("abcd".to_string()).unwrap();
+        service.shorten_url_with_code(url, code.clone()).unwrap();
+
+        let resolved = service.resolve_short_code_templated(&code).unwrap();
+        assert_eq!(resolved.as_str(), "https://example.com/?c=abcd");
+    }
+
+    #[test]
+    fn test_resolve_templated_exceeding_length_cap() {
+        let service = create_service().with_max_expanded_url_length(40);
+        let url = OriginalUrl::new("https://example.com/?a={code}&b={code}".to_string()).unwrap();
+        let code = ShortCode::new("abcdefghijkl".to_string()).unwrap();
+        service.shorten_url_with_code(url, code.clone()).unwrap();
+
+        let result = service.resolve_short_code_templated(&code);
+        assert!(matches!(result, Err(UrlShortenerError::InvalidUrl(_))));
+
+        // A rejected resolve is not counted
+        assert_eq!(service.get_statistics(&code).unwrap().access_count(), 0);
+    }
+
+    #[test]
+    fn test_distinct_hosts() {
+        let service = create_service();
+        for target in [
+            "https://a.example.com/one",
+            "https://a.example.com/two",
+            "https://b.example.com/",
+            "http://10.0.0.1/",
+        ] {
+            service.shorten_url(OriginalUrl::new(target.to_string()).unwrap()).unwrap();
+        }
+
+        let hosts = service.distinct_hosts().unwrap();
+        let hosts: Vec<&str> = hosts.iter().map(String::as_str).collect();
+        assert_eq!(hosts, vec!["10.0.0.1", "a.example.com", "b.example.com"]);
+    }
+
+    #[test]
+    fn test_rolling_window_counts() {
+        let clock = Arc::new(ManualClock::new(UNIX_EPOCH + Duration::from_secs(3_600_000)));
+        let service = create_service().with_clock(clock.clone());
+        let hour = Duration::from_secs(3600);
+
+        let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
+        let first = service.shorten_url(url.clone()).unwrap();
+        service.shorten_url(url.clone()).unwrap();
+        service.resolve_short_code(first.short_code()).unwrap();
+
+        clock.advance(Duration::from_secs(30 * 60));
+        service.shorten_url(url).unwrap();
+        service.resolve_short_code(first.short_code()).unwrap();
+        service.resolve_short_code(first.short_code()).unwrap();
+
+        assert_eq!(service.created_last(hour), 3);
+        assert_eq!(service.resolved_last(hour), 3);
+        assert_eq!(service.created_last(Duration::from_secs(60)), 1);
+        assert_eq!(service.resolved_last(Duration::from_secs(60)), 2);
+
+        // The first burst falls out of the hour window
+        clock.advance(Duration::from_secs(31 * 60));
+        assert_eq!(service.created_last(hour), 1);
+        assert_eq!(service.resolved_last(hour), 2);
+
+        clock.advance(hour);
+        assert_eq!(service.created_last(hour), 0);
+        assert_eq!(service.resolved_last(hour), 0);
+    }
+
+    #[test]
+    fn test_rolling_window_ignores_failed_resolves() {
+        let service = create_service();
+        let code = ShortCode::new("missing1".to_string()).unwrap();
+
+        assert!(service.resolve_short_code(&code).is_err());
+        assert_eq!(service.resolved_last(Duration::from_secs(3600)), 0);
+    }
+
+    #[test]
+    fn test_resolve_idempotent_counts_once() {
         let service = create_service();
+        let url = OriginalUrl::new("https://example.com/hook".to_string()).unwrap();
+        let shortened = service.shorten_url(url).unwrap();
+        let code = shortened.short_code();
+
+        let first = service.resolve_idempotent(code, "req-1").unwrap();
+        let retry = service.resolve_idempotent(code, "req-1").unwrap();
+        assert_eq!(first.as_str(), "https://example.com/hook");
+        assert_eq!(retry, first);
+        assert_eq!(service.get_statistics(code).unwrap().access_count(), 1);
+
+        service.resolve_idempotent(code, "req-2").unwrap();
+        assert_eq!(service.get_statistics(code).unwrap().access_count(), 2);
+    }
+
+    #[test]
+    fn test_resolve_idempotent_window_expires() {
+        let clock = Arc::new(ManualClock::new(UNIX_EPOCH + Duration::from_secs(1_000)));
+        let service = create_service()
+            .with_clock(clock.clone())
+            .with_idempotency_window(Duration::from_secs(60), 100);
         let url = OriginalUrl::new("https://example.com".to_string()).unwrap();
         let shortened = service.shorten_url(url).unwrap();
-        let code = shortened.short_code().clone();
+        let code = shortened.short_code();
 
-        // Should exist
-        assert!(service.get_statistics(&code).is_ok());
+        service.resolve_idempotent(code, "req-1").unwrap();
+        clock.advance(Duration::from_secs(61));
+        service.resolve_idempotent(code, "req-1").unwrap();
 
-        // Delete it
-        service.delete_short_code(&code).unwrap();
+        assert_eq!(service.get_statistics(code).unwrap().access_count(), 2);
+    }
 
-        // Should no longer exist
-        assert!(service.get_statistics(&code).is_err());
+    #[test]
+    fn test_migrate_code_length() {
+        let repository = Arc::new(InMemoryUrlRepository::new());
+        let old_service = UrlShortenerService::new(
+            repository.clone(),
+            Arc::new(RandomIdGenerator::with_length(6)),
+        );
+        let url = OriginalUrl::new("https://example.com/campaign".to_string()).unwrap();
+        let old = old_service.shorten_url(url).unwrap();
+        old_service.resolve_short_code(old.short_code()).unwrap();
+        old_service.resolve_short_code(old.short_code()).unwrap();
+
+        let service = UrlShortenerService::new(
+            repository,
+            Arc::new(RandomIdGenerator::with_length(8)),
+        );
+        let new_code = service.migrate_code_length(old.short_code()).unwrap();
+        assert_eq!(new_code.as_str().len(), 8);
+
+        let migrated = service.get_statistics(&new_code).unwrap();
+        assert_eq!(migrated.access_count(), 2);
+        assert_eq!(migrated.created_at(), old.created_at());
+
+        let via_old = service.resolve_short_code(old.short_code()).unwrap();
+        let via_new = service.resolve_short_code(&new_code).unwrap();
+        assert_eq!(via_old, via_new);
+        assert_eq!(via_new.as_str(), "https://example.com/campaign");
     }
 
     #[test]
-    fn test_list_all() {
+    fn test_migrate_code_length_missing_code() {
         let service = create_service();
+        let code = ShortCode::new("missing1".to_string()).unwrap();
 
-        let urls = service.list_all().unwrap();
-        assert_eq!(urls.len(), 0);
+        let result = service.migrate_code_length(&code);
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+        assert!(service.list_all().unwrap().is_empty());
+    }
 
-        let url1 = OriginalUrl::new("https://example1.com".to_string()).unwrap();
-        let url2 = OriginalUrl::new("https://example2.com".to_string()).unwrap();
+    #[test]
+    fn test_select_combines_filters_with_limit() {
+        let service = create_service();
+        let targets = [
+            ("sela", "https://popular.com/a", 3),
+            ("selb", "https://popular.com/b", 5),
+            ("selc", "http://popular.com/c", 5),
+            ("seld", "https://other.com/d", 5),
+            ("sele", "https://popular.com/e", 1),
+            ("self", "https://POPULAR.com/f", 4),
+        ];
+        for (code, target, resolves) in targets {
+            let code = ShortCode::new(code.to_string()).unwrap();
+            let url = OriginalUrl::new(target.to_string()).unwrap();
+            service.shorten_url_with_code(url, code.clone()).unwrap();
+            for _ in 0..resolves {
+                service.resolve_short_code(&code).unwrap();
+            }
+        }
 
-        service.shorten_url(url1).unwrap();
-        service.shorten_url(url2).unwrap();
+        let filter = SelectFilter::new().min_access_count(3).host("popular.com");
+        let selected = service.select(filter.clone(), None).unwrap();
+        let codes: Vec<&str> = selected.iter().map(|(c, _)| c.as_str()).collect();
+        assert_eq!(codes, vec!["sela", "selb", "selc", "self"]);
 
-        let urls = service.list_all().unwrap();
-        assert_eq!(urls.len(), 2);
+        let selected = service.select(filter.scheme("https"), Some(2)).unwrap();
+        let codes: Vec<&str> = selected.iter().map(|(c, _)| c.as_str()).collect();
+        assert_eq!(codes, vec!["sela", "selb"]);
+        assert_eq!(selected[1].1.as_str(), "https://popular.com/b");
+    }
+
+    #[test]
+    fn test_select_empty_filter_matches_all() {
+        let service = create_service();
+        for i in 0..3 {
+            let url = OriginalUrl::new(format!("https://example{}.com", i)).unwrap();
+            service.shorten_url(url).unwrap();
+        }
+
+        assert_eq!(service.select(SelectFilter::new(), None).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_select_not_expired() {
+        let clock = Arc::new(ManualClock::new(UNIX_EPOCH + Duration::from_secs(10_000)));
+        let service = create_service().with_clock(clock.clone());
+
+        let url = OriginalUrl::new("https://example.com/short".to_string()).unwrap();
+        service.shorten_url_with_expiry(url, Duration::from_secs(60)).unwrap();
+        let url = OriginalUrl::new("https://example.com/long".to_string()).unwrap();
+        let kept = service.shorten_url_with_expiry(url, Duration::from_secs(3600)).unwrap();
+        let url = OriginalUrl::new("https://example.com/forever".to_string()).unwrap();
+        let forever = service.shorten_url(url).unwrap();
+
+        clock.advance(Duration::from_secs(120));
+        let selected = service.select(SelectFilter::new().not_expired(clock.now()), None).unwrap();
+
+        let mut codes: Vec<ShortCode> = selected.into_iter().map(|(c, _)| c).collect();
+        let mut expected = vec![kept.short_code().clone(), forever.short_code().clone()];
+        codes.sort();
+        expected.sort();
+        assert_eq!(codes, expected);
     }
 }
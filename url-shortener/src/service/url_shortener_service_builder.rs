@@ -0,0 +1,341 @@
+//! Builder for UrlShortenerService
+//!
+//! Collects the optional behaviors of the service in one place.
+
+use crate::error::Result;
+use crate::ports::{IdGenerator, Metrics, UrlRepository};
+use super::UrlShortenerService;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Builder for `UrlShortenerService`
+///
+/// Every setting defaults to the behavior of `UrlShortenerService::new`.
+/// Settings not covered here can still be applied to the built service with
+/// its `with_*` methods.
+///
+/// # Examples
+///
+/// ```
+/// use url_shortener::service::UrlShortenerServiceBuilder;
+/// use url_shortener::adapters::{InMemoryUrlRepository, RandomIdGenerator};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// let service = UrlShortenerServiceBuilder::new(
+///     Arc::new(InMemoryUrlRepository::new()),
+///     Arc::new(RandomIdGenerator::new()),
+/// )
+/// .case_insensitive(true)
+/// .reserved_codes(vec!["admin".to_string(), "api".to_string()])
+/// .default_expiry(Some(Duration::from_secs(30 * 24 * 60 * 60)))
+/// .build()
+/// .unwrap();
+/// ```
+pub struct UrlShortenerServiceBuilder<R, G>
+where
+    R: UrlRepository,
+    G: IdGenerator,
+{
+    repository: Arc<R>,
+    id_generator: Arc<G>,
+    case_insensitive: bool,
+    reserved_codes: Vec<String>,
+    default_expiry: Option<Duration>,
+    metrics: Option<Arc<dyn Metrics>>,
+    preferred_code_length: Option<usize>,
+    max_generation_attempts: usize,
+}
+
+impl<R, G> UrlShortenerServiceBuilder<R, G>
+where
+    R: UrlRepository,
+    G: IdGenerator,
+{
+    /// Start building a service over the given ports
+    pub fn new(repository: Arc<R>, id_generator: Arc<G>) -> Self {
+        Self {
+            repository,
+            id_generator,
+            case_insensitive: false,
+            reserved_codes: Vec::new(),
+            default_expiry: None,
+            metrics: None,
+            preferred_code_length: None,
+            max_generation_attempts: UrlShortenerService::<R, G>::DEFAULT_MAX_GENERATION_ATTEMPTS,
+        }
+    }
+
+    /// Match short codes case-insensitively
+    ///
+    /// See `UrlShortenerService::with_case_insensitive`.
+    pub fn case_insensitive(mut self, enabled: bool) -> Self {
+        self.case_insensitive = enabled;
+        self
+    }
+
+    /// Reserve short codes so they can't be claimed
+    ///
+    /// See `UrlShortenerService::with_reserved_codes`.
+    pub fn reserved_codes(mut self, codes: Vec<String>) -> Self {
+        self.reserved_codes = codes;
+        self
+    }
+
+    /// Expire links created without an explicit expiry
+    ///
+    /// See `UrlShortenerService::with_default_expiry`.
+    pub fn default_expiry(mut self, ttl: Option<Duration>) -> Self {
+        self.default_expiry = ttl;
+        self
+    }
+
+    /// Report shortens and resolves to a metrics backend
+    ///
+    /// See `UrlShortenerService::with_metrics`.
+    pub fn metrics(mut self, metrics: Option<Arc<dyn Metrics>>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Generate codes of `length` characters, whatever the generator
+    ///
+    /// See `UrlShortenerService::with_preferred_code_length`. The length is
+    /// validated by `build`.
+    pub fn preferred_code_length(mut self, length: Option<usize>) -> Self {
+        self.preferred_code_length = length;
+        self
+    }
+
+    /// Try this many codes at each length before giving up on it
+    ///
+    /// See `UrlShortenerService::with_max_generation_attempts`. The count is
+    /// validated by `build`.
+    pub fn max_generation_attempts(mut self, attempts: usize) -> Self {
+        self.max_generation_attempts = attempts;
+        self
+    }
+
+    /// Build the service
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The preferred code length is outside
+    ///   `ShortCode::MIN_LENGTH..=ShortCode::MAX_LENGTH` (`InvalidShortCode`)
+    /// - The number of generation attempts is 0 (`InvalidConfiguration`)
+    pub fn build(self) -> Result<UrlShortenerService<R, G>> {
+        let service = UrlShortenerService::new(self.repository, self.id_generator)
+            .with_case_insensitive(self.case_insensitive)
+            .with_reserved_codes(self.reserved_codes)
+            .with_default_expiry(self.default_expiry)
+            .with_preferred_code_length(self.preferred_code_length)?
+            .with_max_generation_attempts(self.max_generation_attempts)?;
+
+        Ok(match self.metrics {
+            Some(metrics) => service.with_metrics(metrics),
+            None => service,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::{InMemoryUrlRepository, ManualClock, RandomIdGenerator};
+    use crate::domain::{OriginalUrl, ShortCode, ShortenedUrl};
+    use crate::error::UrlShortenerError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::UNIX_EPOCH;
+
+    fn builder() -> UrlShortenerServiceBuilder<InMemoryUrlRepository, RandomIdGenerator> {
+        UrlShortenerServiceBuilder::new(
+            Arc::new(InMemoryUrlRepository::new()),
+            Arc::new(RandomIdGenerator::new()),
+        )
+    }
+
+    fn example_url() -> OriginalUrl {
+        OriginalUrl::new("https://example.com".to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_defaults_match_new() {
+        let service = builder().build().unwrap();
+        let shortened = service
+            .shorten_url_with_code(example_url(), ShortCode::new("abc123".to_string()).unwrap())
+            .unwrap();
+
+        assert_eq!(shortened.expires_at(), None);
+        let result = service.resolve_short_code(&ShortCode::new("ABC123".to_string()).unwrap());
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeNotFound(_))));
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        let service = builder().case_insensitive(true).build().unwrap();
+        service
+            .shorten_url_with_code(example_url(), ShortCode::new("abc123".to_string()).unwrap())
+            .unwrap();
+
+        let resolved = service
+            .resolve_short_code(&ShortCode::new("AbC123".to_string()).unwrap())
+            .unwrap();
+        assert_eq!(resolved, example_url());
+    }
+
+    #[test]
+    fn test_reserved_codes() {
+        let service = builder().reserved_codes(vec!["admin".to_string()]).build().unwrap();
+
+        let result = service
+            .shorten_url_with_code(example_url(), ShortCode::new("admin".to_string()).unwrap());
+        assert!(matches!(result, Err(UrlShortenerError::ReservedShortCode(_))));
+
+        service
+            .shorten_url_with_code(example_url(), ShortCode::new("promo".to_string()).unwrap())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_default_expiry() {
+        let start = UNIX_EPOCH + Duration::from_secs(10_000);
+        let clock = Arc::new(ManualClock::new(start));
+        let service = builder()
+            .default_expiry(Some(Duration::from_secs(60)))
+            .build()
+            .unwrap()
+            .with_clock(clock.clone());
+
+        let shortened = service.shorten_url(example_url()).unwrap();
+        assert_eq!(shortened.expires_at(), Some(start + Duration::from_secs(60)));
+        service.resolve_short_code(shortened.short_code()).unwrap();
+
+        clock.advance(Duration::from_secs(60));
+        let result = service.resolve_short_code(shortened.short_code());
+        assert!(matches!(result, Err(UrlShortenerError::ShortCodeExpired(_))));
+
+        // An explicit ttl takes precedence
+        let explicit = service
+            .shorten_url_with_expiry(example_url(), Duration::from_secs(3600))
+            .unwrap();
+        assert_eq!(explicit.expires_at(), Some(start + Duration::from_secs(60 + 3600)));
+    }
+
+    #[test]
+    fn test_preferred_code_length() {
+        let service = builder().preferred_code_length(Some(8)).build().unwrap();
+
+        for _ in 0..10 {
+            let shortened = service.shorten_url(example_url()).unwrap();
+            assert_eq!(shortened.short_code().as_str().len(), 8);
+        }
+
+        let result = builder().preferred_code_length(Some(ShortCode::MAX_LENGTH + 1)).build();
+        assert!(matches!(result, Err(UrlShortenerError::InvalidShortCode(_))));
+        let result = builder().preferred_code_length(Some(ShortCode::MIN_LENGTH - 1)).build();
+        assert!(matches!(result, Err(UrlShortenerError::InvalidShortCode(_))));
+    }
+
+    #[test]
+    fn test_max_generation_attempts() {
+        /// Reports every short code as taken, counting the checks
+        struct AlwaysTaken(InMemoryUrlRepository, AtomicUsize);
+
+        impl UrlRepository for AlwaysTaken {
+            fn save(&self, url: ShortenedUrl) -> Result<()> {
+                self.0.save(url)
+            }
+            fn find_by_short_code(&self, code: &ShortCode) -> Result<ShortenedUrl> {
+                self.0.find_by_short_code(code)
+            }
+            fn update(&self, url: ShortenedUrl) -> Result<()> {
+                self.0.update(url)
+            }
+            fn exists(&self, _: &ShortCode) -> Result<bool> {
+                self.1.fetch_add(1, Ordering::SeqCst);
+                Ok(true)
+            }
+            fn delete(&self, code: &ShortCode) -> Result<()> {
+                self.0.delete(code)
+            }
+            fn list_all(&self) -> Result<Vec<ShortenedUrl>> {
+                self.0.list_all()
+            }
+        }
+
+        // Codes of the maximum length can't be widened, so generation stops
+        // after the attempts at that length
+        let repository = Arc::new(AlwaysTaken(InMemoryUrlRepository::new(), AtomicUsize::new(0)));
+        let service = UrlShortenerServiceBuilder::new(
+            repository.clone(),
+            Arc::new(RandomIdGenerator::with_length(ShortCode::MAX_LENGTH)),
+        )
+        .max_generation_attempts(1)
+        .build()
+        .unwrap();
+
+        let result = service.shorten_url(example_url());
+        assert!(matches!(result, Err(UrlShortenerError::IdGenerationFailed(1))));
+        assert_eq!(repository.1.load(Ordering::SeqCst), 1);
+
+        let result = builder().max_generation_attempts(0).build();
+        assert!(matches!(result, Err(UrlShortenerError::InvalidConfiguration(_))));
+    }
+
+    #[derive(Default)]
+    struct RecordingMetrics {
+        shortens: AtomicUsize,
+        hits: AtomicUsize,
+        misses: AtomicUsize,
+    }
+
+    impl RecordingMetrics {
+        fn counts(&self) -> (usize, usize, usize) {
+            (
+                self.shortens.load(Ordering::SeqCst),
+                self.hits.load(Ordering::SeqCst),
+                self.misses.load(Ordering::SeqCst),
+            )
+        }
+    }
+
+    impl Metrics for RecordingMetrics {
+        fn on_shorten(&self) {
+            self.shortens.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn on_resolve_hit(&self) {
+            self.hits.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn on_resolve_miss(&self) {
+            self.misses.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_metrics_count_operations() {
+        let metrics = Arc::new(RecordingMetrics::default());
+        let service = builder().metrics(Some(metrics.clone())).build().unwrap();
+
+        let first = service.shorten_url(example_url()).unwrap();
+        let code = ShortCode::new("promo".to_string()).unwrap();
+        service.shorten_url_with_code(example_url(), code.clone()).unwrap();
+        assert_eq!(metrics.counts(), (2, 0, 0));
+
+        service.resolve_short_code(first.short_code()).unwrap();
+        service.resolve_idempotent(&code, "req-1").unwrap();
+        // A retried request still resolves
+        service.resolve_idempotent(&code, "req-1").unwrap();
+        assert_eq!(metrics.counts(), (2, 3, 0));
+
+        let missing = ShortCode::new("nope42".to_string()).unwrap();
+        assert!(service.resolve_short_code(&missing).is_err());
+        assert!(service.resolve_idempotent(&missing, "req-2").is_err());
+
+        // A rejected shorten isn't counted
+        assert!(service.shorten_url_with_code(example_url(), code).is_err());
+        assert_eq!(metrics.counts(), (2, 3, 2));
+    }
+}